@@ -0,0 +1,36 @@
+/* The C interface to ascii-love's renderer, from src/ffi.rs.
+ *
+ * Build the library with `cargo build --release --features ffi`, then link against
+ * target/release/libascii_love.a (or the shared library) and this header. */
+
+#ifndef ASCII_LOVE_H
+#define ASCII_LOVE_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct AlRenderer AlRenderer;
+
+/* Makes a renderer that draws the heart `width` columns wide and `height` rows tall. */
+AlRenderer *al_renderer_new(uint32_t width, uint32_t height);
+
+/* Frees a renderer from al_renderer_new. NULL is ignored. */
+void al_renderer_free(AlRenderer *renderer);
+
+/* Draws the heart turned `a` radians around the X axis and `b` around the Z axis, as rows of
+ * UTF-8 text each ending in a newline.
+ *
+ * Like snprintf, returns the length of the whole frame in bytes, not counting the NUL at the
+ * end, and only writes it to `buffer` if there's room for all of it and the NUL. Call it with
+ * a NULL buffer first to find out how big a buffer to allocate. */
+size_t al_render_frame(AlRenderer *renderer, double a, double b, char *buffer, size_t size);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
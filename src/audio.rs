@@ -0,0 +1,239 @@
+//! How loud some sound is, for pulsing in time with music. Only built with the `audio` feature.
+//!
+//! Sound comes from a WAV file, followed along in real time, or from raw 16-bit little-endian
+//! samples streamed on standard input, such as from `arecord -f S16_LE` or `parec`.
+
+use std::fmt;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+/// Where the sound comes from.
+pub enum Audio {
+    File(Envelope),
+    Live(Listener),
+}
+
+impl Audio {
+    /// Opens a WAV file, or listens to standard input if `path` is `-`.
+    pub fn open(path: &Path) -> Result<Audio, AudioError> {
+        if path == Path::new("-") {
+            return Ok(Audio::Live(Listener::spawn(io::stdin())));
+        }
+        Envelope::load(path).map(Audio::File)
+    }
+
+    /// How loud it is `seconds` after starting, from 0 for silence to 1 for the loudest.
+    pub fn level(&self, seconds: f64) -> f64 {
+        match self {
+            Audio::File(envelope) => envelope.at(seconds),
+            Audio::Live(listener) => listener.level(),
+        }
+    }
+}
+
+/// How loud a recording is over time.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Envelope {
+    /// The loudness of each slice of the recording, scaled so the loudest is 1.
+    levels: Vec<f64>,
+}
+
+impl Envelope {
+    /// How many slices each second of sound is split into; about one per frame.
+    const SLICES_PER_SECOND: f64 = 30.0;
+
+    pub fn load(path: &Path) -> Result<Envelope, AudioError> {
+        let bytes = fs::read(path).map_err(|error| AudioError::Io(path.to_owned(), error))?;
+        let (rate, samples) = wav(&bytes)?;
+        Ok(Envelope::new(&samples, rate))
+    }
+
+    /// Measures samples taken `rate` times a second. Samples from every channel count alike.
+    pub fn new(samples: &[f64], rate: f64) -> Envelope {
+        let slice = ((rate / Envelope::SLICES_PER_SECOND) as usize).max(1);
+        let mut levels: Vec<f64> = samples.chunks(slice).map(rms).collect();
+        let loudest = levels.iter().copied().fold(0.0, f64::max);
+        if loudest > 0.0 {
+            levels.iter_mut().for_each(|level| *level /= loudest);
+        }
+        Envelope { levels }
+    }
+
+    /// The loudness `seconds` into the recording, or silence once it has finished.
+    pub fn at(&self, seconds: f64) -> f64 {
+        let slice = (seconds.max(0.0) * Envelope::SLICES_PER_SECOND) as usize;
+        self.levels.get(slice).copied().unwrap_or(0.0)
+    }
+}
+
+/// Follows a live stream of samples on another thread.
+pub struct Listener {
+    /// The latest level, as the bits of an `f64`.
+    level: Arc<AtomicU64>,
+}
+
+impl Listener {
+    /// Samples per measurement: about a frame's worth at 44.1 kHz.
+    const SLICE: usize = 1470;
+    /// How much of the loudest level so far is kept each measurement, so that the level adapts
+    /// when the music gets quieter.
+    const DECAY: f64 = 0.999;
+
+    /// Reads signed 16-bit little-endian samples from `input` until it ends.
+    pub fn spawn(mut input: impl Read + Send + 'static) -> Listener {
+        let level = Arc::new(AtomicU64::new(0.0f64.to_bits()));
+        let shared = Arc::clone(&level);
+        thread::spawn(move || {
+            let mut buffer = vec![0; Listener::SLICE * 2];
+            let mut loudest: f64 = 0.0;
+            while input.read_exact(&mut buffer).is_ok() {
+                let samples: Vec<f64> = buffer
+                    .chunks_exact(2)
+                    .map(|pair| f64::from(i16::from_le_bytes([pair[0], pair[1]])) / 32768.0)
+                    .collect();
+                let current = rms(&samples);
+                loudest = (loudest * Listener::DECAY).max(current);
+                let level = if loudest > 0.0 {
+                    current / loudest
+                } else {
+                    0.0
+                };
+                shared.store(level.to_bits(), Ordering::Relaxed);
+            }
+            shared.store(0.0f64.to_bits(), Ordering::Relaxed);
+        });
+        Listener { level }
+    }
+
+    pub fn level(&self) -> f64 {
+        f64::from_bits(self.level.load(Ordering::Relaxed))
+    }
+}
+
+/// The root mean square of the samples: their average loudness.
+fn rms(samples: &[f64]) -> f64 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    (samples.iter().map(|s| s * s).sum::<f64>() / samples.len() as f64).sqrt()
+}
+
+/// Decodes a WAV file into its sample rate (counting every channel) and samples from −1 to 1.
+fn wav(bytes: &[u8]) -> Result<(f64, Vec<f64>), AudioError> {
+    let invalid = |message: &str| AudioError::Invalid(message.to_owned());
+    if bytes.len() < 12 || &bytes[..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err(invalid("not a WAV file"));
+    }
+    let u16_at = |data: &[u8], i: usize| u16::from_le_bytes([data[i], data[i + 1]]);
+
+    let mut format = None;
+    let mut rest = &bytes[12..];
+    while rest.len() >= 8 {
+        let kind = &rest[..4];
+        let length = u32::from_le_bytes([rest[4], rest[5], rest[6], rest[7]]) as usize;
+        let data = rest.get(8..8 + length).unwrap_or(&rest[8..]);
+        // Chunks are padded to an even length.
+        rest = rest.get(8 + length + length % 2..).unwrap_or_default();
+        match kind {
+            b"fmt " if data.len() >= 16 => {
+                let mut encoding = u16_at(data, 0);
+                // The extensible format keeps the real encoding further in.
+                if encoding == 0xfffe && data.len() >= 26 {
+                    encoding = u16_at(data, 24);
+                }
+                let channels = u16_at(data, 2);
+                let rate = u32::from_le_bytes([data[4], data[5], data[6], data[7]]);
+                let bits = u16_at(data, 14);
+                format = Some((encoding, channels, rate, bits));
+            }
+            b"data" => {
+                let (encoding, channels, rate, bits) =
+                    format.ok_or_else(|| invalid("WAV data comes before its format"))?;
+                let decode: fn(&[u8]) -> f64 = match (encoding, bits) {
+                    (1, 8) => |s| (f64::from(s[0]) - 128.0) / 128.0,
+                    (1, 16) => |s| f64::from(i16::from_le_bytes([s[0], s[1]])) / 32768.0,
+                    (1, 24) => {
+                        |s| f64::from(i32::from_le_bytes([0, s[0], s[1], s[2]])) / 2147483648.0
+                    }
+                    (1, 32) => {
+                        |s| f64::from(i32::from_le_bytes([s[0], s[1], s[2], s[3]])) / 2147483648.0
+                    }
+                    (3, 32) => |s| f64::from(f32::from_le_bytes([s[0], s[1], s[2], s[3]])),
+                    _ => {
+                        return Err(AudioError::Unsupported(format!(
+                            "WAV encoding {encoding} with {bits}-bit samples isn't supported"
+                        )))
+                    }
+                };
+                let samples = data
+                    .chunks_exact(usize::from(bits / 8))
+                    .map(decode)
+                    .collect();
+                return Ok((f64::from(rate) * f64::from(channels.max(1)), samples));
+            }
+            _ => {}
+        }
+    }
+    Err(invalid("WAV file has no data"))
+}
+
+/// Why sound couldn't be loaded.
+#[derive(Debug)]
+pub enum AudioError {
+    Io(PathBuf, io::Error),
+    /// The file is a kind of WAV that can't be decoded.
+    Unsupported(String),
+    /// The file is malformed.
+    Invalid(String),
+}
+
+impl fmt::Display for AudioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AudioError::Io(path, error) => write!(f, "{}: {error}", path.display()),
+            AudioError::Unsupported(message) | AudioError::Invalid(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for AudioError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AudioError::Io(_, error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn follows_loudness_over_time() {
+        // A second of silence, then a second of a square wave, at 60 samples a second.
+        let samples: Vec<f64> = (0..120)
+            .map(|i| if i < 60 { 0.0 } else { 0.5 * (-1.0f64).powi(i) })
+            .collect();
+        let envelope = Envelope::new(&samples, 60.0);
+        assert_eq!(0.0, envelope.at(0.5));
+        assert_eq!(1.0, envelope.at(1.5));
+        assert_eq!(0.0, envelope.at(3.0));
+    }
+
+    #[test]
+    fn decodes_16_bit_wav() {
+        let mut wav_file = b"RIFF\0\0\0\0WAVEfmt \x10\0\0\0".to_vec();
+        // PCM, one channel, 8000 Hz, 16000 bytes a second, 2 bytes a frame, 16 bits.
+        wav_file.extend([1, 0, 1, 0, 0x40, 0x1f, 0, 0, 0x80, 0x3e, 0, 0, 2, 0, 16, 0]);
+        wav_file.extend(b"data\x04\0\0\0\x00\x40\x00\xc0");
+        let (rate, samples) = wav(&wav_file).unwrap();
+        assert_eq!(8000.0, rate);
+        assert_eq!(vec![0.5, -0.5], samples);
+        assert!(wav(b"RIFF\0\0\0\0WAVE").is_err());
+    }
+}
@@ -0,0 +1,252 @@
+//! The `auto` theme, which changes with the local time and date: warm colours around sunset, cool
+//! ones at night, and a look of its own on occasions like Valentine's Day and Halloween.
+//!
+//! Occasions are a table, which the configuration file can add to:
+//!
+//! ```toml
+//! theme = "auto"
+//!
+//! [[occasion]]
+//! name = "st-patricks-day"
+//! date = "03-17"
+//! theme = "matrix"
+//! shape = "torus"
+//! ```
+
+use crate::clock;
+use crate::config::Config;
+use crate::scene::Shape;
+use crate::theme::Theme;
+
+/// What `--theme` and `theme` take to pick the theme this way.
+pub const NAME: &str = "auto";
+
+/// A built-in occasion, before it's turned into an [`Occasion`].
+struct BuiltIn {
+    name: &'static str,
+    month: u32,
+    day: u32,
+    theme: &'static str,
+    shape: Option<&'static str>,
+}
+
+/// The occasions there are without any in the configuration file.
+const OCCASIONS: [BuiltIn; 2] = [
+    BuiltIn {
+        name: "valentines-day",
+        month: 2,
+        day: 14,
+        theme: "valentine",
+        shape: Some("heart"),
+    },
+    BuiltIn {
+        name: "halloween",
+        month: 10,
+        day: 31,
+        theme: "halloween",
+        shape: Some("sphere"),
+    },
+];
+
+/// About when the sun sets in each month, as the hour on the clock, in the middle of the northern
+/// hemisphere with the clocks put forward for summer.
+const SUNSET: [u32; 12] = [17, 17, 18, 20, 20, 21, 21, 20, 19, 18, 17, 16];
+
+/// The hour night ends.
+const DAWN: u32 = 6;
+
+/// A local date and time, to the minute.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Moment {
+    /// From 1 for January.
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+}
+
+impl Moment {
+    pub fn now() -> Moment {
+        let tm = clock::local_time();
+        Moment {
+            month: (tm.tm_mon + 1) as u32,
+            day: tm.tm_mday as u32,
+            hour: tm.tm_hour as u32,
+            minute: tm.tm_min as u32,
+        }
+    }
+}
+
+/// A day of the year with a look of its own.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Occasion {
+    pub name: String,
+    /// From 1 for January.
+    pub month: u32,
+    pub day: u32,
+    /// The theme for the day, or `None` to keep the one for the time of day.
+    pub theme: Option<Theme>,
+    /// What to draw every object as, or `None` to draw them as they are.
+    pub shape: Option<Shape>,
+}
+
+impl Occasion {
+    /// The built-in occasions.
+    pub fn built_in() -> impl Iterator<Item = Occasion> {
+        OCCASIONS.iter().map(|occasion| Occasion {
+            name: occasion.name.to_owned(),
+            month: occasion.month,
+            day: occasion.day,
+            theme: Some(
+                occasion
+                    .theme
+                    .parse()
+                    .expect("built-in occasions use built-in themes"),
+            ),
+            shape: (occasion.shape).map(|shape| {
+                shape
+                    .parse()
+                    .expect("built-in occasions use built-in shapes")
+            }),
+        })
+    }
+}
+
+/// Reads a date without a year, like `10-31`, as the month and the day.
+pub fn parse_date(text: &str) -> Result<(u32, u32), String> {
+    const DAYS: [u32; 12] = [31, 29, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+    let (month, day) = text
+        .split_once('-')
+        .and_then(|(month, day)| Some((month.parse::<u32>().ok()?, day.parse::<u32>().ok()?)))
+        .ok_or_else(|| format!("expected a month and day like \"10-31\", found {text:?}"))?;
+    match DAYS.get(month.wrapping_sub(1) as usize) {
+        None => Err(format!("no month {month}")),
+        Some(&days) if day == 0 || day > days => Err(format!("no day {day} in month {month}")),
+        Some(_) => Ok((month, day)),
+    }
+}
+
+/// The theme, and perhaps the shape, picked for a moment.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Look {
+    /// The occasion, or the time of day: `day`, `sunset`, or `night`.
+    pub name: String,
+    pub theme: Theme,
+    pub shape: Option<Shape>,
+}
+
+impl Look {
+    /// The look for `moment`: the first of `occasions` on that day, then the built-in ones,
+    /// and otherwise the time of day.
+    pub fn at(moment: Moment, occasions: &[Occasion]) -> Look {
+        let (name, theme) = time_of_day(moment);
+        let theme: Theme = theme.parse().expect("the time of day uses built-in themes");
+        let occasion = (occasions.iter().cloned())
+            .chain(Occasion::built_in())
+            .find(|occasion| (occasion.month, occasion.day) == (moment.month, moment.day));
+        match occasion {
+            Some(occasion) => Look {
+                name: occasion.name,
+                theme: occasion.theme.unwrap_or(theme),
+                shape: occasion.shape,
+            },
+            None => Look {
+                name: name.to_owned(),
+                theme,
+                shape: None,
+            },
+        }
+    }
+
+    /// Changes the theme to this look's, and the shape of every object if it has one.
+    pub fn apply(&self, config: &mut Config) {
+        config.theme = self.theme.clone();
+        if let Some(shape) = &self.shape {
+            for object in &mut config.objects {
+                object.shape = shape.clone();
+            }
+        }
+    }
+}
+
+/// What time of day it is at `moment`, and the theme for it: the hour either side of sunset is
+/// warm, and the night after it, until dawn, is cool.
+fn time_of_day(moment: Moment) -> (&'static str, &'static str) {
+    let sunset = SUNSET[(moment.month.clamp(1, 12) - 1) as usize];
+    match moment.hour {
+        hour if hour + 1 >= sunset && hour <= sunset => ("sunset", "fire"),
+        hour if hour > sunset || hour < DAWN => ("night", "ocean"),
+        _ => ("day", "valentine"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(month: u32, day: u32, hour: u32) -> Moment {
+        Moment {
+            month,
+            day,
+            hour,
+            minute: 0,
+        }
+    }
+
+    #[test]
+    fn looks_follow_the_sun() {
+        let name = |moment| Look::at(moment, &[]).name;
+        assert_eq!("day", name(at(6, 21, 12)));
+        assert_eq!("sunset", name(at(6, 21, 20)));
+        assert_eq!("sunset", name(at(6, 21, 21)));
+        assert_eq!("night", name(at(6, 21, 22)));
+        assert_eq!("night", name(at(6, 21, 3)));
+        assert_eq!("day", name(at(6, 21, 6)));
+        // The sun sets earlier in winter.
+        assert_eq!("sunset", name(at(12, 21, 16)));
+        assert_eq!("night", name(at(12, 21, 18)));
+        let look = Look::at(at(12, 21, 16), &[]);
+        assert_eq!(("fire", None), (look.theme.name.as_str(), look.shape));
+    }
+
+    #[test]
+    fn occasions_come_first() {
+        let look = Look::at(at(10, 31, 21), &[]);
+        assert_eq!(
+            ("halloween", "halloween", Some("sphere")),
+            (
+                look.name.as_str(),
+                look.theme.name.as_str(),
+                look.shape.as_ref().map(Shape::name)
+            )
+        );
+        let mut config = Config::default();
+        look.apply(&mut config);
+        assert_eq!("sphere", config.objects[0].shape.name());
+
+        // Occasions from the configuration file come before the built-in ones, and can keep the
+        // time of day's theme.
+        let own = Occasion {
+            name: "spooky".to_owned(),
+            month: 10,
+            day: 31,
+            theme: None,
+            shape: None,
+        };
+        let look = Look::at(at(10, 31, 21), &[own]);
+        assert_eq!(
+            ("spooky", "ocean", None),
+            (look.name.as_str(), look.theme.name.as_str(), look.shape)
+        );
+        assert_eq!("valentine", Look::at(at(2, 14, 23), &[]).theme.name);
+    }
+
+    #[test]
+    fn parses_dates() {
+        assert_eq!(Ok((10, 31)), parse_date("10-31"));
+        assert_eq!(Ok((2, 29)), parse_date("2-29"));
+        for text in ["10/31", "13-01", "00-10", "04-31", "02-00", "halloween"] {
+            assert!(parse_date(text).is_err(), "{text}");
+        }
+    }
+}
@@ -0,0 +1,169 @@
+//! What's drawn behind the objects.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::camera::Camera;
+use crate::color;
+use crate::math::{Real, Vec3};
+use crate::random::Rng;
+use crate::scene::Particle;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Background {
+    /// Empty space.
+    #[default]
+    None,
+    /// Stars streaming towards the viewer.
+    Stars,
+    /// Dim, swirling plasma in the empty space around the objects.
+    Plasma,
+}
+
+impl Background {
+    pub const ALL: [Background; 3] = [Background::None, Background::Stars, Background::Plasma];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Background::None => "none",
+            Background::Stars => "stars",
+            Background::Plasma => "plasma",
+        }
+    }
+}
+
+impl fmt::Display for Background {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Background {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Background, String> {
+        Background::ALL
+            .into_iter()
+            .find(|background| background.name() == name)
+            .ok_or_else(|| format!("unknown background {name:?}"))
+    }
+}
+
+/// Stars flying towards the camera. They're kept in camera space, so they stream past however the
+/// camera moves.
+#[derive(Clone, Debug)]
+pub struct Starfield {
+    stars: Vec<Vec3>,
+    rng: Rng,
+}
+
+impl Starfield {
+    pub const COUNT: usize = 200;
+
+    /// Stars never come closer than this, so they stay behind the objects.
+    pub const NEAR: Real = 100.0;
+
+    /// Where new stars appear.
+    pub const FAR: Real = 400.0;
+
+    /// World units per second.
+    pub const SPEED: Real = 60.0;
+
+    pub fn new(seed: u64) -> Starfield {
+        let mut rng = Rng::new(seed);
+        let stars = (0..Starfield::COUNT)
+            .map(|_| {
+                let depth =
+                    Starfield::NEAR + (Starfield::FAR - Starfield::NEAR) * rng.next_f64() as Real;
+                star(&mut rng, depth)
+            })
+            .collect();
+        Starfield { stars, rng }
+    }
+
+    /// Moves the stars on by `dt` seconds, replacing ones that have gone past with new ones in
+    /// the distance.
+    pub fn update(&mut self, dt: f64) {
+        for star in &mut self.stars {
+            star.z -= Starfield::SPEED * dt as Real;
+            let out_of_view = star.x.abs() > star.z / 2.0 || star.y.abs() > star.z / 2.0;
+            if star.z < Starfield::NEAR || out_of_view {
+                *star = self::star(&mut self.rng, Starfield::FAR);
+            }
+        }
+    }
+
+    /// The stars as seen by `camera`, brighter the closer they are.
+    pub fn particles(&self, camera: &Camera) -> Vec<Particle> {
+        self.stars
+            .iter()
+            .map(|&star| {
+                let glyph = match (star.z - Starfield::NEAR) / (Starfield::FAR - Starfield::NEAR) {
+                    near if near < 0.2 => '*',
+                    mid if mid < 0.5 => '+',
+                    _ => '.',
+                };
+                Particle {
+                    position: camera.to_world(star),
+                    glyph,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Characters for plasma, from its troughs to its peaks: faint ones the objects aren't shaded
+/// with, so that they stand out from it even without colours.
+const PLASMA: [char; 4] = [' ', ' ', '`', '\''];
+
+/// The colour of plasma at its troughs, which turns around the colour wheel towards its peaks.
+const PLASMA_COLOR: [u8; 3] = [90, 20, 70];
+
+/// The plasma at column `x` and row `y`, `time` seconds in: a character and its colour. It's a
+/// sum of sine waves rolling across the screen in different directions, at different speeds.
+pub fn plasma(x: usize, y: usize, time: f64) -> (char, [u8; 3]) {
+    // Rows are about twice as tall as columns are wide.
+    let (x, y) = (x as f64, 2.0 * y as f64);
+    let waves = (x * 0.11 + time).sin()
+        + (y * 0.13 - time * 1.3).sin()
+        + ((x + y) * 0.07 + time * 0.7).sin()
+        + ((x * x + y * y).sqrt() * 0.09 - time * 1.1).sin();
+    // From 0 in the troughs to 1 at the peaks.
+    let level = (waves / 4.0 + 1.0) / 2.0;
+    let glyph = PLASMA[((level * PLASMA.len() as f64) as usize).min(PLASMA.len() - 1)];
+    (glyph, color::rotate_hue(PLASMA_COLOR, level * 180.0))
+}
+
+/// A star at `depth`, somewhere in view of the default zoom.
+fn star(rng: &mut Rng, depth: Real) -> Vec3 {
+    let mut across = || (rng.next_f64() - 0.5) as Real * depth;
+    Vec3::new(across(), across(), depth)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plasma_moves_and_stays_dim() {
+        let cells: Vec<_> = (0..40).map(|x| plasma(x, 3, 1.0)).collect();
+        assert!(cells.iter().any(|&(glyph, _)| glyph != ' '));
+        assert!(cells
+            .iter()
+            .all(|&(_, rgb)| rgb.into_iter().all(|c| c <= 90)));
+        let later: Vec<_> = (0..40).map(|x| plasma(x, 3, 2.0)).collect();
+        assert_ne!(cells, later);
+    }
+
+    #[test]
+    fn stars_stay_behind_the_objects() {
+        let mut starfield = Starfield::new(3);
+        for _ in 0..100 {
+            starfield.update(0.1);
+            assert!(starfield
+                .stars
+                .iter()
+                .all(|star| (Starfield::NEAR..=Starfield::FAR).contains(&star.z)));
+        }
+    }
+}
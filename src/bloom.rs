@@ -0,0 +1,83 @@
+//! A soft glow around the brightest parts of a frame, as if they were too bright to look at.
+
+/// How much of a bright cell's level reaches the cells right next to it.
+const BLEED: f64 = 0.6;
+
+/// How much brighter than the rest of the ramp a cell has to be to glow, as a fraction of the
+/// way up it.
+const THRESHOLD: f64 = 0.75;
+
+/// Spreads the brightest characters of `frame` up to `radius` cells into their neighbours, as
+/// characters further down `ramp` the further they go. Cells already brighter than the glow, and
+/// characters that aren't on the ramp, are left alone. Rows are about twice as tall as columns
+/// are wide, so the glow spreads half as many rows.
+pub fn bloom(frame: &mut [Vec<char>], ramp: &[char], radius: usize) {
+    // How far up the ramp each cell is, counting empty cells as 0 and the first character as 1.
+    let level = |c: char| match c {
+        ' ' => Some(0),
+        c => ramp.iter().position(|&r| r == c).map(|i| i + 1),
+    };
+    let levels: Vec<Vec<Option<usize>>> = (frame.iter())
+        .map(|row| row.iter().map(|&c| level(c)).collect())
+        .collect();
+    let bright = |level: usize| level as f64 > THRESHOLD * ramp.len() as f64;
+    let (rows, reach) = (radius / 2, radius as f64 + 1.0);
+
+    for (y, row) in frame.iter_mut().enumerate() {
+        for (x, c) in row.iter_mut().enumerate() {
+            let Some(own) = levels[y][x] else {
+                continue;
+            };
+            let mut glow: f64 = 0.0;
+            let top = y.saturating_sub(rows);
+            for (ny, near) in (top..).zip(&levels[top..=(y + rows).min(levels.len() - 1)]) {
+                let left = x.saturating_sub(radius);
+                for (nx, source) in (left..).zip(&near[left..=(x + radius).min(near.len() - 1)]) {
+                    let Some(source) = source.filter(|&level| bright(level)) else {
+                        continue;
+                    };
+                    let (dx, dy) = (nx.abs_diff(x) as f64, 2.0 * ny.abs_diff(y) as f64);
+                    let falloff = 1.0 - dx.hypot(dy) / reach;
+                    glow = glow.max(source as f64 * BLEED * falloff);
+                }
+            }
+            let glow = (glow as usize).min(ramp.len());
+            if glow > own {
+                *c = ramp[glow - 1];
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bright_cells_glow_into_their_neighbours() {
+        let ramp = ['.', ':', '=', '#', '@'];
+        let mut frame = vec![
+            vec![' ', ' ', ' ', ' ', ' '],
+            vec![' ', ' ', '@', ' ', '.'],
+            vec!['x', ' ', ' ', ' ', ' '],
+        ];
+        bloom(&mut frame, &ramp, 2);
+        // 5 steps up the ramp, 60% of that next to it, and less further away, but never over
+        // anything brighter or not on the ramp.
+        assert_eq!(
+            vec![
+                vec![' ', ' ', '.', ' ', ' '],
+                vec!['.', ':', '@', ':', '.'],
+                vec!['x', ' ', '.', ' ', ' '],
+            ],
+            frame
+        );
+    }
+
+    #[test]
+    fn dim_cells_dont_glow() {
+        let mut frame = vec![vec![' ', '=', ' ']];
+        bloom(&mut frame, &['.', ':', '=', '#', '@'], 2);
+        assert_eq!(vec![vec![' ', '=', ' ']], frame);
+    }
+}
@@ -0,0 +1,122 @@
+//! Frames kept to be shown again, for --cache. An animation that does nothing but turn comes back
+//! around to where it started, so once it's been drawn all the way round, the frames it takes
+//! can be shown again instead of drawn.
+
+use std::f64::consts::TAU;
+
+/// How far from where they started the angles can end up after a loop, in radians, and still be
+/// nudged into coming all the way around: a fraction of a frame's turning, too little to see.
+const TOLERANCE: f64 = 0.02;
+
+/// The most frames a loop can take.
+pub const LONGEST: usize = 5000;
+
+/// How many frames it takes for angles turning `steps` radians a frame to come back around at
+/// the same time, and the steps nudged so that they come exactly back around, or `None` if they
+/// don't come close within [`LONGEST`] frames. Steps of nothing stay that way.
+pub fn looped(steps: &[f64]) -> Option<(usize, Vec<f64>)> {
+    let turns = |frames: usize, step: f64| (frames as f64 * step / TAU).round();
+    let frames = (1..=LONGEST).find(|&frames| {
+        (steps.iter()).all(|&step| {
+            let turned = frames as f64 * step;
+            (turned - turns(frames, step) * TAU).abs() <= TOLERANCE
+        })
+    })?;
+    let nudged = (steps.iter())
+        .map(|&step| turns(frames, step) * TAU / frames as f64)
+        .collect();
+    Some((frames, nudged))
+}
+
+/// The frames of a loop, kept as they're drawn the first time round while what they were drawn
+/// for, `K`, stays the same, like the size of the screen. Frames stop being kept once they'd add
+/// up to more than a budget of bytes.
+#[derive(Clone, Debug)]
+pub struct Cache<K> {
+    key: Option<K>,
+    frames: Vec<Option<String>>,
+    size: usize,
+    budget: usize,
+}
+
+impl<K: PartialEq> Cache<K> {
+    pub fn new(length: usize, budget: usize) -> Cache<K> {
+        Cache {
+            key: None,
+            frames: vec![None; length],
+            size: 0,
+            budget,
+        }
+    }
+
+    /// How many frames there are in the loop.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// The frame `position` frames into the loop, if it's been kept for `key`. Frames kept for
+    /// anything else are thrown away.
+    pub fn get(&mut self, key: K, position: usize) -> Option<&str> {
+        if self.key.as_ref() != Some(&key) {
+            self.frames.fill(None);
+            self.size = 0;
+            self.key = Some(key);
+        }
+        self.frames.get(position)?.as_deref()
+    }
+
+    /// Keeps `frame` as the one `position` frames into the loop, for the key it was last asked
+    /// for with, if there's room for it.
+    pub fn keep(&mut self, position: usize, frame: &str) {
+        if self.size + frame.len() > self.budget {
+            return;
+        }
+        if let Some(kept @ None) = self.frames.get_mut(position) {
+            self.size += frame.len();
+            *kept = Some(frame.to_owned());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn turning_comes_back_around() {
+        let (frames, steps) = looped(&[0.05, 0.04, 0.0]).unwrap();
+        assert_eq!(628, frames);
+        for (step, nudged) in [0.05, 0.04, 0.0].into_iter().zip(&steps) {
+            assert!((step - nudged).abs() < 1e-4);
+            let turns = frames as f64 * nudged / TAU;
+            assert!((turns - turns.round()).abs() < 1e-9);
+        }
+        assert_eq!(Some((1, vec![0.0])), looped(&[0.0]));
+        let (frames, steps) = looped(&[TAU / 4.0]).unwrap();
+        assert_eq!((4, TAU / 4.0), (frames, steps[0]));
+        assert_eq!(None, looped(&[0.05, 0.05 * 2f64.sqrt()]));
+    }
+
+    #[test]
+    fn frames_are_kept_for_the_same_screen() {
+        let mut cache = Cache::new(3, 10);
+        assert_eq!(None, cache.get((80, 24), 0));
+        cache.keep(0, "heart");
+        cache.keep(0, "other");
+        // Too many bytes, and too far into the loop.
+        cache.keep(1, "second");
+        cache.keep(7, "beyond");
+        assert_eq!(Some("heart"), cache.get((80, 24), 0));
+        assert_eq!(None, cache.get((80, 24), 1));
+        // A new screen starts over.
+        assert_eq!(None, cache.get((100, 30), 0));
+        cache.keep(1, "second");
+        assert_eq!(Some("second"), cache.get((100, 30), 1));
+        assert_eq!(None, cache.get((80, 24), 0));
+        assert_eq!(3, cache.len());
+    }
+}
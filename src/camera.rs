@@ -0,0 +1,315 @@
+//! Where the scene is viewed from, and how that changes over time.
+
+use crate::easing::Easing;
+use crate::math::{Mat3, Real, Vec3};
+use crate::timeline::{wrap_time, Key, Lerp, Track};
+
+/// A camera looking from `position` towards `target`, with +Y pointing up.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Camera {
+    pub position: Vec3,
+    pub target: Vec3,
+}
+
+impl Default for Camera {
+    /// Looks at the origin from 70 units away, which is where the heart has always been drawn.
+    fn default() -> Camera {
+        Camera {
+            position: Vec3::new(0.0, 0.0, -70.0),
+            target: Vec3::default(),
+        }
+    }
+}
+
+impl Camera {
+    /// Transforms a point from world space to camera space, where the camera sits at the origin
+    /// looking down +Z.
+    pub fn to_view(&self, point: Vec3) -> Vec3 {
+        self.to_view_direction(point - self.position)
+    }
+
+    /// Like [`Camera::to_view`], but for directions (such as normals), which ignore position.
+    pub fn to_view_direction(&self, direction: Vec3) -> Vec3 {
+        self.view() * direction
+    }
+
+    /// The inverse of [`Camera::to_view`]: transforms a point from camera space to world space.
+    pub fn to_world(&self, point: Vec3) -> Vec3 {
+        self.position + self.to_world_direction(point)
+    }
+
+    /// Like [`Camera::to_world`], but for directions.
+    pub fn to_world_direction(&self, direction: Vec3) -> Vec3 {
+        self.view().transpose() * direction
+    }
+
+    /// How far the camera is from what it's looking at.
+    pub fn focus(&self) -> Real {
+        (self.target - self.position).length()
+    }
+
+    /// The camera moved `distance` to its right (or left, if negative), still looking at the same
+    /// target, like one of a pair of eyes.
+    pub fn shifted(&self, distance: Real) -> Camera {
+        Camera {
+            position: self.position + self.to_world_direction(Vec3::X * distance),
+            target: self.target,
+        }
+    }
+
+    /// Rotates world directions into camera space. Its rows are the camera's right, up, and
+    /// forward directions.
+    fn view(&self) -> Mat3 {
+        let forward = (self.target - self.position).normalized();
+        let mut right = Vec3::Y.cross(forward);
+        if right.length() < 1e-9 {
+            // Looking straight up or down; any horizontal direction will do.
+            right = Vec3::X;
+        }
+        let right = right.normalized();
+        let up = forward.cross(right);
+        Mat3::from_rows(right, up, forward)
+    }
+}
+
+/// A camera pose described as an orbit around the target.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Orbit {
+    pub target: Vec3,
+    /// Distance from the camera to the target.
+    pub distance: Real,
+    /// Degrees around the Y axis, starting from behind the viewer (−Z).
+    pub azimuth: Real,
+    /// Degrees above the horizontal plane through the target.
+    pub elevation: Real,
+}
+
+impl Default for Orbit {
+    fn default() -> Orbit {
+        Orbit {
+            target: Vec3::default(),
+            distance: 70.0,
+            azimuth: 0.0,
+            elevation: 0.0,
+        }
+    }
+}
+
+impl Orbit {
+    pub fn camera(&self) -> Camera {
+        let (azimuth, elevation) = (self.azimuth.to_radians(), self.elevation.to_radians());
+        let offset = Vec3::new(
+            -azimuth.sin() * elevation.cos(),
+            elevation.sin(),
+            -azimuth.cos() * elevation.cos(),
+        );
+        Camera {
+            position: self.target + offset * self.distance,
+            target: self.target,
+        }
+    }
+}
+
+impl Lerp for Orbit {
+    fn lerp(self, other: Orbit, t: f64) -> Orbit {
+        Orbit {
+            target: self.target.lerp(other.target, t),
+            distance: self.distance.lerp(other.distance, t),
+            azimuth: self.azimuth.lerp(other.azimuth, t),
+            elevation: self.elevation.lerp(other.elevation, t),
+        }
+    }
+}
+
+/// A camera pose at a point in time, described as an orbit around the target.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Keyframe {
+    /// Seconds since the start of the animation.
+    pub time: f64,
+    pub target: Vec3,
+    /// Distance from the camera to the target.
+    pub distance: Real,
+    /// Degrees around the Y axis, starting from behind the viewer (−Z).
+    pub azimuth: Real,
+    /// Degrees above the horizontal plane through the target.
+    pub elevation: Real,
+    /// How to move from the previous keyframe to this one.
+    pub easing: Easing,
+}
+
+impl Default for Keyframe {
+    fn default() -> Keyframe {
+        Keyframe::at(0.0, Orbit::default())
+    }
+}
+
+impl Keyframe {
+    /// The camera at `orbit`, `time` seconds in, moved to in a straight line.
+    pub fn at(time: f64, orbit: Orbit) -> Keyframe {
+        Keyframe {
+            time,
+            target: orbit.target,
+            distance: orbit.distance,
+            azimuth: orbit.azimuth,
+            elevation: orbit.elevation,
+            easing: Easing::Linear,
+        }
+    }
+
+    pub fn orbit(&self) -> Orbit {
+        Orbit {
+            target: self.target,
+            distance: self.distance,
+            azimuth: self.azimuth,
+            elevation: self.elevation,
+        }
+    }
+
+    pub fn camera(&self) -> Camera {
+        self.orbit().camera()
+    }
+}
+
+/// Animates the camera by interpolating between keyframes.
+///
+/// Orbit parameters are interpolated rather than positions, so an azimuth going from 0 to 360
+/// sweeps a full circle around the target instead of standing still.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CameraPath {
+    /// Each keyframe's orbit, at its time and with its easing.
+    track: Track<Orbit>,
+    /// Whether to start over after the last keyframe, rather than holding it.
+    pub looping: bool,
+}
+
+impl CameraPath {
+    /// Creates a path through the given keyframes, which are sorted by time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are no keyframes.
+    pub fn new(keyframes: Vec<Keyframe>, looping: bool) -> CameraPath {
+        assert!(!keyframes.is_empty(), "a camera path needs keyframes");
+        let track = Track::new(
+            (keyframes.into_iter())
+                .map(|keyframe| Key {
+                    time: keyframe.time,
+                    value: keyframe.orbit(),
+                    easing: keyframe.easing,
+                })
+                .collect(),
+        );
+        CameraPath { track, looping }
+    }
+
+    /// The keyframes, in order of time.
+    pub fn keyframes(&self) -> impl Iterator<Item = Keyframe> + '_ {
+        self.track.keys().iter().map(|key| Keyframe {
+            easing: key.easing,
+            ..Keyframe::at(key.time, key.value)
+        })
+    }
+
+    /// Where the camera is `time` seconds into the animation.
+    pub fn at(&self, time: f64) -> Camera {
+        let time = if self.looping {
+            wrap_time(time, self.track.start_time(), self.track.end_time())
+        } else {
+            time
+        };
+        self.track.at(time).camera()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::TOLERANCE;
+
+    fn assert_close(expected: Vec3, actual: Vec3) {
+        let error = (expected - actual).length();
+        assert!(error < TOLERANCE, "expected {expected:?}, got {actual:?}");
+    }
+
+    #[test]
+    fn default_camera_matches_classic_projection() {
+        let camera = Camera::default();
+        assert_close(
+            Vec3::new(1.0, 2.0, 73.0),
+            camera.to_view(Vec3::new(1.0, 2.0, 3.0)),
+        );
+        assert_close(Orbit::default().camera().position, camera.position);
+    }
+
+    #[test]
+    fn to_world_undoes_to_view() {
+        let camera = Orbit {
+            azimuth: 30.0,
+            elevation: 20.0,
+            ..Orbit::default()
+        }
+        .camera();
+        let point = Vec3::new(4.0, -5.0, 6.0);
+        assert_close(point, camera.to_world(camera.to_view(point)));
+    }
+
+    #[test]
+    fn path_interpolates_orbit() {
+        let path = CameraPath::new(
+            vec![
+                Keyframe::default(),
+                Keyframe {
+                    time: 10.0,
+                    distance: 50.0,
+                    azimuth: 180.0,
+                    ..Keyframe::default()
+                },
+            ],
+            false,
+        );
+
+        // Halfway around the orbit, and halfway through the dolly.
+        assert_close(Vec3::new(-60.0, 0.0, 0.0), path.at(5.0).position);
+        // Holds the last keyframe.
+        assert_close(Vec3::new(0.0, 0.0, 50.0), path.at(20.0).position);
+    }
+
+    #[test]
+    fn looping_path_wraps_around() {
+        let path = CameraPath::new(
+            vec![
+                Keyframe {
+                    time: 10.0,
+                    distance: 30.0,
+                    ..Keyframe::default()
+                },
+                Keyframe::default(),
+            ],
+            true,
+        );
+        // Sorted by time, whatever order they came in.
+        assert_eq!(
+            Some(0.0),
+            path.keyframes().next().map(|keyframe| keyframe.time)
+        );
+        assert_close(path.at(2.5).position, path.at(12.5).position);
+    }
+
+    #[test]
+    fn easing_shapes_the_segment() {
+        let path = CameraPath::new(
+            vec![
+                Keyframe::default(),
+                Keyframe {
+                    time: 10.0,
+                    distance: 30.0,
+                    easing: Easing::EaseInCubic,
+                    ..Keyframe::default()
+                },
+            ],
+            false,
+        );
+        // An eighth of the way through the dolly, rather than halfway.
+        assert_close(Vec3::new(0.0, 0.0, -65.0), path.at(5.0).position);
+    }
+}
@@ -0,0 +1,849 @@
+//! Command-line option parsing.
+
+use std::env;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use ascii_love::auto;
+use ascii_love::background::Background;
+use ascii_love::color::{self, ColorMode};
+use ascii_love::config::{Config, ConfigError};
+use ascii_love::demo::Demo;
+use ascii_love::dither::Dither;
+use ascii_love::heightmap::Heightmap;
+use ascii_love::image::Image;
+use ascii_love::light::Light;
+use ascii_love::math::{Real, Vec3};
+use ascii_love::mesh::{Mesh, Normals};
+use ascii_love::motion::Lissajous;
+use ascii_love::overlay::{Marquee, Message, Placement};
+use ascii_love::playlist::Playlist;
+use ascii_love::preset::Preset;
+use ascii_love::random::{self, Rng};
+use ascii_love::render::{DebugView, Style};
+use ascii_love::scene::Shape;
+use ascii_love::shading::Shading;
+use ascii_love::stereo::Stereo;
+use ascii_love::surface::{Expr, Surface};
+use ascii_love::theme::{Colorblind, Coloring, Theme};
+use ascii_love::viewport::{Align, Margin};
+
+pub const USAGE: &str = "\
+Usage: ascii-love [OPTIONS]
+       ascii-love image PATH [OPTIONS]  (see ascii-love image --help)
+       ascii-love completions SHELL     (bash, zsh, or fish)
+
+Options:
+  --config PATH   read settings from PATH [default: ~/.config/ascii-love/config.toml], and
+                  again on SIGHUP
+  --watch         read the settings and --theme-file again whenever they're changed
+  --cache         keep the frames of one turn and show them over and over instead of drawing
+                  them again, which takes next to no CPU, when turning is all that changes
+  --preset NAME   start from a ready-made scene: duet
+  --model PATH    draw the model in PATH (.obj, .stl, .ply) instead of the heart
+  --normals MODE  smooth or flat shading for --model [default: smooth]
+  --heightmap PATH
+                  draw the greyscale image in PATH (.png, .pgm, .ppm) as terrain
+  --fx EXPR, --fy EXPR, --fz EXPR
+                  draw the surface at these coordinates for u from 0 to 2π and v from 0 to π,
+                  like --fx \"10*sin(v)*cos(u)\" --fy \"10*cos(v)\" --fz \"10*sin(v)*sin(u)\"
+  --random        start with a random shape, look, axis, and orientation
+  --seed N        randomize with this seed, to repeat an earlier --random run
+  --spin-x RAD    rotation around the X axis per frame [default: 0.05]
+  --spin-y RAD    rotation around the Y axis per frame [default: 0.04]
+  --spin-z RAD    rotation around the Z axis per frame [default: 0]
+  --axis X,Y,Z    spin around this axis instead of tumbling
+  --speed RAD     rotation around --axis per frame [default: 0.05]
+  --demo          show off a different look every few seconds, saying how to get it
+  --cycle TIME    take turns showing each shape for TIME, like 10s or 2m
+  --shapes LIST   shapes for --cycle, like heart,torus [default: heart,sphere,torus]
+  --tile N        draw N copies side by side, taking turns with the --split shapes
+  --tile-phase DEG
+                  turn each tile DEG degrees further around than the one before
+  --insets        small top and side views without perspective, in the corners
+  --split LIST    show shapes side by side, each in its own view, like heart,torus
+  --background BG
+                  none, stars streaming past, or swirling plasma [default: none]
+  --bg-char C     fill the empty space with this character, like '.'
+  --bg-color HEX  colour the whole screen, like '#200010'
+  --card          draw a greeting-card border around the animation
+  --clock         show the time under the heart, in blocky 3D digits
+  --screensaver   take over the screen, dimmed, and quit on any key press or mouse movement
+  -v, --verbose   report resizes, how long frames take to draw, and the terminal's
+                  capabilities on standard error
+  --log-file PATH
+                  report them to the file at PATH instead
+  --export PATH   save the animation as a picture at PATH instead of drawing it: a PNG, or
+                  an animated PNG with --frames
+  --control PATH  take commands from scripts on a Unix socket at PATH, a line each: pause,
+                  resume, set-shape NAME, set-theme NAME, set SETTING VALUE, or screenshot
+  --serve ADDRESS
+                  stream the animation over HTTP instead of drawing it, for curl to play, at
+                  ADDRESS, like 8080 or 0.0.0.0:8080; /?width=W&height=H is fitted to that
+                  size, and /frame.txt is a single frame
+  --screenshot-dir DIR
+                  save screenshots in DIR when s is pressed [default: .]
+  --checksum      print a checksum of each frame and of them all instead of drawing them, on
+                  a screen exactly --width by --height, to check it draws the same everywhere
+  --frames N      how many frames --export saves or --checksum checks, a pause apart
+                  [default: 1]
+  --width N       draw N columns wide instead of fitting the terminal
+  --height N      draw N rows tall instead of fitting the terminal
+  --align PLACE   where to put a smaller animation: center, top, bottom, left, right,
+                  top-left, top-right, bottom-left, or bottom-right [default: center]
+  --margin N      leave N empty cells around the animation [default: 0]
+  --message TEXT  show TEXT with the animation
+  --message-at PLACE
+                  top, center, or bottom [default: center]
+  --message-under
+                  show the message behind the heart instead of over it
+  --marquee TEXT  scroll TEXT along the bottom row
+  --marquee-speed N
+                  cells per second to scroll the marquee; negative scrolls right [default: 8]
+  --sparkles N    emit N sparkles per second from the surface (try 40)
+  --trail N       leave fading trails, keeping N of each frame's brightness (try 0.8)
+  --bloom N       let the brightest surfaces glow N cells around them (try 2)
+  --bounce        bounce around the screen at half its size, or --width and --height if
+                  they're smaller
+  --lissajous A,B
+                  loop around the screen like that, swinging A times across to B times down,
+                  at the same size as --bounce
+  --crt           dark scanlines and jittering rows, like an old monitor
+  --phosphor      everything in shades of monochrome green
+  --bpm N         pulse with a heartbeat at N beats per minute (try 72)
+  --audio PATH    pulse with how loud the WAV file in PATH is, played from the start, or with
+                  raw 16-bit samples on standard input if PATH is - (needs the audio feature)
+  --orbit RAD     revolve the objects around the middle per frame [default: 0]
+  --fps N         draw N frames a second, which also changes how fast everything turns
+                  [default: 22]
+  --render STYLE  surface, points for sparse particles, raymarch, or raytrace for shadows
+                  on a floor [default: surface]
+  --bevel N       round off shapes by N when raymarching (try 1)
+  --blend N       melt objects together within N of each other when raymarching (try 8)
+  --shading MODE  smooth or toon [default: smooth]
+  --dither MODE   none, floyd-steinberg, or bayer [default: none]
+  --debug VIEW    draw what the renderer works with instead: depth for the depth buffer, in
+                  shades of grey. Press z to switch to it and back while it's running
+  --theme NAME    characters and colours to draw with: valentine, matrix, fire, ocean, mono,
+                  halloween, or auto to change with the time of day and the date
+  --theme-file PATH
+                  read the characters and colours from a theme file
+  --rainbow       roll the colours around the rainbow, best with truecolor terminals
+  --color-by MODE
+                  luminance, depth for warm near surfaces and cool far ones, or both to
+                  darken those colours where it's dim [default: luminance]
+  --colorblind KIND
+                  draw in colours that deuteranopia, protanopia, or tritanopia can tell apart,
+                  dark to bright, whatever the theme
+  --emoji         draw the brightest surfaces with 💖, at half the horizontal resolution
+  --ascii-only    only draw ASCII characters, as happens anyway when the locale isn't UTF-8
+  --high-contrast
+                  draw only solid blocks and empty space, in bold, for legibility
+  --reduced-motion
+                  spin slowly, without pulsing or sparkling, at fewer frames a second; also
+                  turned on by setting $ASCII_LOVE_REDUCED_MOTION
+  --stereo MODE   anaglyph, in red and cyan for 3D glasses, or magic-eye for a random-dot
+                  stereogram to view wall-eyed
+  --interlace     redraw odd and even rows on alternate frames, for slow connections
+  --shininess N   add specular highlights; higher is sharper (try 32)
+  --light-dir X,Y,Z
+                  light the heart from this direction, relative to the viewer [default: 0,0,-1]
+  --ambient N     brightness of surfaces facing away from the light, 0 to 1 [default: 0]
+  --point-light X,Y,Z
+                  light the heart with a bulb at this position, relative to the viewer
+  --cull          skip surfaces facing away from the camera (for closed surfaces)
+  --no-cull       draw surfaces facing away from the camera [default]
+  -h, --help      print this help and exit
+
+While it's running, press : to change a setting, like :theme fire or :fps 30, then Enter;
+y to copy the frame to the clipboard, or Y with its colours; or s to save a screenshot.";
+
+pub const IMAGE_USAGE: &str = "\
+Usage: ascii-love image PATH [OPTIONS]
+
+Draws the image in PATH (.png, .pgm, .ppm) with the same characters as the animation.
+
+Options:
+  --width N       draw N characters wide [default: fit the terminal, or 80]
+  --color MODE    none, 16, 256, or truecolor to keep the image's colours [default: none]
+  --shading MODE  smooth or toon [default: smooth]
+  --dither MODE   none, floyd-steinberg, or bayer [default: none]
+  -h, --help      print this help and exit";
+
+/// Setting this to anything but 0 turns on --reduced-motion.
+const REDUCED_MOTION: &str = "ASCII_LOVE_REDUCED_MOTION";
+
+/// What --screensaver dims the screen to, behind the animation.
+const SCREENSAVER_BACKGROUND: [u8; 3] = [8, 8, 12];
+
+/// How big models are drawn, across their largest dimension. About the width of the heart.
+const MODEL_SIZE: Real = 30.0;
+
+/// Settings chosen on the command line.
+pub struct Options {
+    /// Read settings from this file instead of the default location.
+    pub config_path: Option<PathBuf>,
+    /// Flags that override settings from the configuration file.
+    overrides: Vec<Override>,
+    /// Randomize the starting scene with this seed, before applying flags.
+    pub seed: Option<u64>,
+    /// Pulse with the loudness of this WAV file, or `-` for standard input.
+    pub audio: Option<PathBuf>,
+    /// Take over the screen until a key is pressed or the mouse moves.
+    pub screensaver: bool,
+    /// Read the configuration again whenever it or a theme file changes.
+    pub watch: bool,
+    /// Draw one turn, then show the same frames again instead of drawing them.
+    pub cache: bool,
+    /// Themes read from files with --theme-file.
+    theme_files: Vec<PathBuf>,
+    /// Report what's happening on standard error.
+    pub verbose: bool,
+    /// Report what's happening to this file instead.
+    pub log_file: Option<PathBuf>,
+    /// Save frames to this file as a PNG instead of drawing them on the terminal.
+    pub export: Option<PathBuf>,
+    /// Listen for commands on a Unix socket at this path.
+    pub control: Option<PathBuf>,
+    /// Serve the animation over HTTP at this address, instead of drawing it.
+    pub serve: Option<String>,
+    /// Save screenshots here, when s is pressed.
+    pub screenshot_dir: PathBuf,
+    /// Print checksums of frames instead of drawing them on the terminal.
+    pub checksum: bool,
+    /// How many frames to save or check.
+    pub frames: usize,
+    pub help: bool,
+}
+
+type Override = Box<dyn Fn(&mut Config) + Send + Sync>;
+
+/// Why the command line can't be used.
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    /// The flags don't make sense, which the usage can help with.
+    Usage(String),
+    /// A file named by a flag, like --model, couldn't be loaded.
+    Load(String),
+}
+
+impl From<String> for ParseError {
+    fn from(message: String) -> ParseError {
+        ParseError::Usage(message)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Usage(message) | ParseError::Load(message) => f.write_str(message),
+        }
+    }
+}
+
+impl Options {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Options, ParseError> {
+        let mut options = Options {
+            config_path: None,
+            overrides: Vec::new(),
+            seed: None,
+            audio: None,
+            screensaver: false,
+            watch: false,
+            cache: false,
+            theme_files: Vec::new(),
+            verbose: false,
+            log_file: None,
+            export: None,
+            control: None,
+            serve: None,
+            screenshot_dir: PathBuf::from("."),
+            checksum: false,
+            frames: 1,
+            help: false,
+        };
+        let mut args = args.into_iter();
+        // Loaded once every flag has been read, since --normals changes how.
+        let mut model: Option<PathBuf> = None;
+        let mut normals = Normals::default();
+        let mut surface: [Option<Expr>; 3] = [None, None, None];
+
+        while let Some(arg) = args.next() {
+            // Accept both `--flag value` and `--flag=value`.
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => {
+                    (flag.to_owned(), Some(value.to_owned()))
+                }
+                _ => (arg, None),
+            };
+            let mut value = || {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| format!("{flag} requires a value"))
+            };
+
+            match flag.as_str() {
+                "--config" => options.config_path = Some(PathBuf::from(value()?)),
+                "--preset" => {
+                    let preset: Preset = value()?.parse()?;
+                    options.set(move |config| preset.apply(config));
+                }
+                "--random" => {
+                    options.seed.get_or_insert_with(Rng::entropy);
+                }
+                "--seed" => {
+                    let text = value()?;
+                    let seed = text
+                        .trim()
+                        .parse()
+                        .map_err(|_| format!("--seed expects a whole number, got {text:?}"))?;
+                    options.seed = Some(seed);
+                }
+                "--model" => model = Some(PathBuf::from(value()?)),
+                "--heightmap" => {
+                    let path = PathBuf::from(value()?);
+                    let image = Image::load(&path)
+                        .map_err(|error| ParseError::Load(format!("--heightmap: {error}")))?;
+                    let heightmap = Arc::new(Heightmap::new(image));
+                    options.set(move |config| {
+                        for object in &mut config.objects {
+                            object.shape = Shape::Heightmap(Arc::clone(&heightmap));
+                        }
+                    });
+                }
+                "--normals" => normals = value()?.parse()?,
+                "--fx" => surface[0] = Some(parse_expr(&flag, &value()?)?),
+                "--fy" => surface[1] = Some(parse_expr(&flag, &value()?)?),
+                "--fz" => surface[2] = Some(parse_expr(&flag, &value()?)?),
+                "--spin-x" => {
+                    let spin = parse_f64(&flag, &value()?)?;
+                    options.set(move |config| config.spin.x = spin as Real);
+                }
+                "--spin-y" => {
+                    let spin = parse_f64(&flag, &value()?)?;
+                    options.set(move |config| config.spin.y = spin as Real);
+                }
+                "--spin-z" => {
+                    let spin = parse_f64(&flag, &value()?)?;
+                    options.set(move |config| config.spin.z = spin as Real);
+                }
+                "--axis" => {
+                    let axis = parse_vec3(&value()?)?;
+                    if axis.length() == 0.0 {
+                        return Err(ParseError::Usage(
+                            "--axis must not be the zero vector".to_owned(),
+                        ));
+                    }
+                    options.set(move |config| config.axis = Some(axis));
+                }
+                "--light-dir" => {
+                    let direction = parse_vec3(&value()?)?;
+                    if direction.length() == 0.0 {
+                        return Err(ParseError::Usage(
+                            "--light-dir must not be the zero vector".to_owned(),
+                        ));
+                    }
+                    options.set(move |config| {
+                        config.lighting.lights = vec![Light::directional(direction)]
+                    });
+                }
+                "--ambient" => {
+                    let ambient = parse_f64(&flag, &value()?)?;
+                    if !(0.0..=1.0).contains(&ambient) {
+                        return Err(ParseError::Usage(
+                            "--ambient must be between 0 and 1".to_owned(),
+                        ));
+                    }
+                    options.set(move |config| config.lighting.ambient = ambient as Real);
+                }
+                "--point-light" => {
+                    let position = parse_vec3(&value()?)?;
+                    options
+                        .set(move |config| config.lighting.lights = vec![Light::point(position)]);
+                }
+                "--render" => {
+                    let style: Style = value()?.parse()?;
+                    options.set(move |config| config.render = style);
+                }
+                "--shading" => {
+                    let shading: Shading = value()?.parse()?;
+                    options.set(move |config| config.shading = shading);
+                }
+                "--dither" => {
+                    let dither: Dither = value()?.parse()?;
+                    options.set(move |config| config.dither = dither);
+                }
+                "--theme" => match value()?.as_str() {
+                    auto::NAME => options.set(|config| config.auto = true),
+                    name => {
+                        let theme: Theme = name.parse()?;
+                        options.set(move |config| {
+                            config.theme = theme.clone();
+                            config.auto = false;
+                        });
+                    }
+                },
+                "--theme-file" => {
+                    let path = PathBuf::from(value()?);
+                    let theme = Theme::load(&path)
+                        .map_err(|error| ParseError::Load(format!("--theme-file: {error}")))?;
+                    options.theme_files.push(path.clone());
+                    // Read again each time, for --watch and SIGHUP. If it can't be read any more,
+                    // it's drawn as it was to begin with.
+                    options.set(move |config| {
+                        config.theme = Theme::load(&path).unwrap_or_else(|_| theme.clone());
+                        config.auto = false;
+                    });
+                }
+                "--rainbow" => options.set(|config| config.rainbow = true),
+                "--emoji" => options.set(|config| config.emoji = true),
+                "--ascii-only" => options.set(|config| config.ascii_only = true),
+                "--high-contrast" => options.set(|config| config.high_contrast = true),
+                "--reduced-motion" => options.set(|config| config.reduced_motion = true),
+                "--color-by" => {
+                    let color_by: Coloring = value()?.parse()?;
+                    options.set(move |config| config.color_by = color_by);
+                }
+                "--colorblind" => {
+                    let kind: Colorblind = value()?.parse()?;
+                    options.set(move |config| config.colorblind = Some(kind));
+                }
+                "--stereo" => {
+                    let stereo: Stereo = value()?.parse()?;
+                    options.set(move |config| config.stereo = Some(stereo));
+                }
+                "--interlace" => options.set(|config| config.interlace = true),
+                "--shininess" => {
+                    let shininess = parse_f64(&flag, &value()?)?;
+                    if shininess < 0.0 {
+                        return Err(ParseError::Usage(
+                            "--shininess must not be negative".to_owned(),
+                        ));
+                    }
+                    options.set(move |config| config.lighting.shininess = Some(shininess as Real));
+                }
+                "--speed" => {
+                    let speed = parse_f64(&flag, &value()?)?;
+                    options.set(move |config| config.speed = speed);
+                }
+                "--debug" => {
+                    let view: DebugView = value()?.parse()?;
+                    options.set(move |config| config.debug = Some(view));
+                }
+                "--demo" => options.set(|config| config.demo = Some(Demo::default())),
+                "--cycle" => {
+                    let every = parse_duration(&flag, &value()?)?;
+                    options.set(move |config| {
+                        config
+                            .cycle
+                            .get_or_insert_with(|| Playlist::new(every))
+                            .every = every
+                    });
+                }
+                "--shapes" => {
+                    let shapes = value()?
+                        .split(',')
+                        .map(|name| name.trim().parse())
+                        .collect::<Result<Vec<Shape>, _>>()?;
+                    options.set(move |config| {
+                        config
+                            .cycle
+                            .get_or_insert_with(|| Playlist::new(Playlist::DEFAULT_EVERY))
+                            .shapes = shapes.clone()
+                    });
+                }
+                "--tile" => {
+                    let tile = parse_size(&flag, &value()?)?;
+                    options.set(move |config| config.tile = Some(tile));
+                }
+                "--tile-phase" => {
+                    let phase = parse_f64(&flag, &value()?)?;
+                    options.set(move |config| config.tile_phase = phase);
+                }
+                "--insets" => options.set(|config| config.insets = true),
+                "--split" => {
+                    let shapes = value()?
+                        .split(',')
+                        .map(|name| name.trim().parse())
+                        .collect::<Result<Vec<Shape>, _>>()?;
+                    options.set(move |config| config.split = shapes.clone());
+                }
+                "--background" => {
+                    let background: Background = value()?.parse()?;
+                    options.set(move |config| config.background = background);
+                }
+                "--bg-char" => {
+                    let value = value()?;
+                    let mut chars = value.chars();
+                    let (Some(bg_char), None) = (chars.next(), chars.next()) else {
+                        return Err(ParseError::Usage(format!(
+                            "--bg-char: expected a single character, got {value:?}"
+                        )));
+                    };
+                    options.set(move |config| config.bg_char = bg_char);
+                }
+                "--bg-color" => {
+                    let bg_color = color::parse_hex(&value()?)
+                        .map_err(|error| format!("--bg-color: {error}"))?;
+                    options.set(move |config| config.bg_color = Some(bg_color));
+                }
+                "--card" => options.set(|config| config.card = true),
+                "--clock" => options.set(|config| config.clock = true),
+                "--screensaver" => options.screensaver = true,
+                "--watch" => options.watch = true,
+                "--cache" => options.cache = true,
+                "-v" | "--verbose" => options.verbose = true,
+                "--log-file" => options.log_file = Some(PathBuf::from(value()?)),
+                "--export" => options.export = Some(PathBuf::from(value()?)),
+                "--control" => options.control = Some(PathBuf::from(value()?)),
+                "--serve" => {
+                    // Just a port is one on this machine, rather than open to everyone.
+                    let address = value()?;
+                    options.serve = Some(match address.parse::<u16>() {
+                        Ok(port) => format!("127.0.0.1:{port}"),
+                        Err(_) => address,
+                    });
+                }
+                "--screenshot-dir" => options.screenshot_dir = PathBuf::from(value()?),
+                "--checksum" => options.checksum = true,
+                "--frames" => options.frames = parse_size(&flag, &value()?)?,
+                "--width" => {
+                    let width = parse_size(&flag, &value()?)?;
+                    options.set(move |config| config.width = Some(width));
+                }
+                "--height" => {
+                    let height = parse_size(&flag, &value()?)?;
+                    options.set(move |config| config.height = Some(height));
+                }
+                "--align" => {
+                    let align: Align = value()?.parse()?;
+                    options.set(move |config| config.align = align);
+                }
+                "--margin" => {
+                    let text = value()?;
+                    let margin = text
+                        .trim()
+                        .parse()
+                        .map_err(|_| format!("--margin expects a whole number, got {text:?}"))?;
+                    options.set(move |config| config.margin = Margin::uniform(margin));
+                }
+                "--message" => {
+                    let text = value()?;
+                    options.set(move |config| {
+                        config.message.get_or_insert_with(Message::default).text = text.clone()
+                    });
+                }
+                "--message-at" => {
+                    let placement: Placement = value()?.parse()?;
+                    options.set(move |config| {
+                        config
+                            .message
+                            .get_or_insert_with(Message::default)
+                            .placement = placement
+                    });
+                }
+                "--message-under" => options
+                    .set(|config| config.message.get_or_insert_with(Message::default).under = true),
+                "--marquee" => {
+                    let text = value()?;
+                    options.set(move |config| match &mut config.marquee {
+                        Some(marquee) => marquee.text = text.clone(),
+                        None => config.marquee = Some(Marquee::new(text.clone())),
+                    });
+                }
+                "--marquee-speed" => {
+                    let speed = parse_f64(&flag, &value()?)?;
+                    options.set(move |config| {
+                        config
+                            .marquee
+                            .get_or_insert_with(|| Marquee::new(String::new()))
+                            .speed = speed
+                    });
+                }
+                "--sparkles" => {
+                    let sparkles = parse_f64(&flag, &value()?)?;
+                    if sparkles < 0.0 {
+                        return Err(ParseError::Usage(
+                            "--sparkles must not be negative".to_owned(),
+                        ));
+                    }
+                    options.set(move |config| config.sparkles = Some(sparkles));
+                }
+                "--trail" => {
+                    let trail = parse_f64(&flag, &value()?)?;
+                    if !(0.0..1.0).contains(&trail) {
+                        return Err(ParseError::Usage(
+                            "--trail must be at least 0 and less than 1".to_owned(),
+                        ));
+                    }
+                    options.set(move |config| config.trail = Some(trail));
+                }
+                "--bounce" => options.set(|config| config.bounce = true),
+                "--lissajous" => {
+                    let curve: Lissajous = value()?.parse()?;
+                    options.set(move |config| match &mut config.lissajous {
+                        Some(path) => (path.across, path.down) = (curve.across, curve.down),
+                        None => config.lissajous = Some(curve),
+                    });
+                }
+                "--crt" => options.set(|config| config.crt = true),
+                "--phosphor" => options.set(|config| config.phosphor = true),
+                "--bloom" => {
+                    let bloom = parse_size(&flag, &value()?)?;
+                    options.set(move |config| config.bloom = Some(bloom));
+                }
+                "--bpm" => {
+                    let bpm = parse_f64(&flag, &value()?)?;
+                    if bpm <= 0.0 {
+                        return Err(ParseError::Usage("--bpm must be positive".to_owned()));
+                    }
+                    options.set(move |config| config.bpm = Some(bpm));
+                }
+                "--audio" => {
+                    let path = PathBuf::from(value()?);
+                    if !cfg!(feature = "audio") {
+                        return Err(ParseError::Usage(
+                            "--audio needs ascii-love built with --features audio".to_owned(),
+                        ));
+                    }
+                    options.audio = Some(path);
+                }
+                "--bevel" => {
+                    let bevel = parse_f64(&flag, &value()?)?;
+                    if bevel < 0.0 {
+                        return Err(ParseError::Usage("--bevel must not be negative".to_owned()));
+                    }
+                    options.set(move |config| config.bevel = bevel);
+                }
+                "--blend" => {
+                    let blend = parse_f64(&flag, &value()?)?;
+                    if blend < 0.0 {
+                        return Err(ParseError::Usage("--blend must not be negative".to_owned()));
+                    }
+                    options.set(move |config| config.blend = blend);
+                }
+                "--orbit" => {
+                    let orbit = parse_f64(&flag, &value()?)?;
+                    options.set(move |config| config.orbit = orbit);
+                }
+                "--fps" => {
+                    let fps = parse_f64(&flag, &value()?)?;
+                    if !(0.01..=1000.0).contains(&fps) {
+                        return Err(ParseError::Usage(
+                            "--fps must be between 0.01 and 1000".to_owned(),
+                        ));
+                    }
+                    options.set(move |config| config.fps = Some(fps));
+                }
+                "--cull" => options.set(|config| config.cull = true),
+                "--no-cull" => options.set(|config| config.cull = false),
+                "-h" | "--help" => options.help = true,
+                _ => return Err(ParseError::Usage(format!("unknown option: {flag}"))),
+            }
+        }
+
+        if options.screensaver && options.audio.as_deref() == Some(Path::new("-")) {
+            return Err(ParseError::Usage(
+                "--screensaver and --audio - both need standard input".to_owned(),
+            ));
+        }
+
+        match surface {
+            [Some(x), Some(y), Some(z)] => {
+                let surface = Arc::new(Surface { x, y, z });
+                options.set(move |config| {
+                    for object in &mut config.objects {
+                        object.shape = Shape::Surface(Arc::clone(&surface));
+                    }
+                });
+            }
+            [None, None, None] => {}
+            _ => {
+                return Err(ParseError::Usage(
+                    "--fx, --fy, and --fz go together".to_owned(),
+                ))
+            }
+        }
+
+        if let Some(path) = model {
+            let mesh =
+                Mesh::load(&path).map_err(|error| ParseError::Load(format!("--model: {error}")))?;
+            let mesh = Arc::new(mesh.with_normals(normals).fit(MODEL_SIZE));
+            options.set(move |config| {
+                for object in &mut config.objects {
+                    object.shape = Shape::Mesh(Arc::clone(&mesh));
+                }
+                // Point clouds have nothing to fill in between their points.
+                if mesh.is_point_cloud() {
+                    config.render = Style::Points;
+                }
+            });
+        }
+
+        Ok(options)
+    }
+
+    /// Whether frames are drawn for --export or --checksum instead of on the terminal.
+    pub fn headless(&self) -> bool {
+        self.export.is_some() || self.checksum
+    }
+
+    /// Whether frames are drawn on the terminal, rather than for --export, --checksum, or --serve.
+    pub fn on_terminal(&self) -> bool {
+        !self.headless() && self.serve.is_none()
+    }
+
+    /// The files the configuration is read from: the configuration file, whether or not it's
+    /// there yet, and any theme files.
+    pub fn files(&self) -> Vec<PathBuf> {
+        let config = self.config_path.clone().or_else(Config::default_path);
+        config.into_iter().chain(self.theme_files.clone()).collect()
+    }
+
+    /// Loads the configuration file, if there is one, and applies flags on top of it.
+    pub fn config(&self) -> Result<Config, ConfigError> {
+        let mut config = match &self.config_path {
+            Some(path) => Config::load(path)?,
+            None => match Config::default_path() {
+                Some(path) if path.exists() => Config::load(&path)?,
+                _ => Config::default(),
+            },
+        };
+        if let Some(seed) = self.seed {
+            random::randomize(&mut config, seed);
+        }
+        if env::var_os(REDUCED_MOTION).is_some_and(|value| !value.is_empty() && value != "0") {
+            config.reduced_motion = true;
+        }
+        for apply in &self.overrides {
+            apply(&mut config);
+        }
+        if config.reduced_motion {
+            config.reduce_motion();
+        }
+        // The screensaver dims the screen behind the animation, unless it's coloured already.
+        if self.screensaver {
+            config.bg_color = (config.bg_color)
+                .or(config.theme.background)
+                .or(Some(SCREENSAVER_BACKGROUND));
+        }
+        Ok(config)
+    }
+
+    fn set(&mut self, apply: impl Fn(&mut Config) + Send + Sync + 'static) {
+        self.overrides.push(Box::new(apply));
+    }
+}
+
+/// Settings for drawing a still image, chosen on the command line.
+pub struct ImageOptions {
+    pub path: PathBuf,
+    /// How many characters wide to draw, or `None` to fit the terminal.
+    pub width: Option<usize>,
+    pub color: ColorMode,
+    pub shading: Shading,
+    pub dither: Dither,
+    pub help: bool,
+}
+
+impl ImageOptions {
+    /// Parses the arguments after `image`.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<ImageOptions, String> {
+        let mut path = None;
+        let mut options = ImageOptions {
+            path: PathBuf::new(),
+            width: None,
+            color: ColorMode::default(),
+            shading: Shading::default(),
+            dither: Dither::default(),
+            help: false,
+        };
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => {
+                    (flag.to_owned(), Some(value.to_owned()))
+                }
+                _ => (arg, None),
+            };
+            let mut value = || {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| format!("{flag} requires a value"))
+            };
+
+            match flag.as_str() {
+                "--width" => options.width = Some(parse_size(&flag, &value()?)?),
+                "--color" => options.color = value()?.parse()?,
+                "--shading" => options.shading = value()?.parse()?,
+                "--dither" => options.dither = value()?.parse()?,
+                "-h" | "--help" => options.help = true,
+                _ if flag.starts_with('-') => return Err(format!("unknown option: {flag}")),
+                _ if path.is_some() => return Err(format!("unexpected argument: {flag}")),
+                _ => path = Some(PathBuf::from(flag)),
+            }
+        }
+        match path {
+            Some(path) => options.path = path,
+            None if options.help => {}
+            None => return Err("image needs the path of an image to draw".to_owned()),
+        }
+        Ok(options)
+    }
+}
+
+fn parse_f64(flag: &str, text: &str) -> Result<f64, String> {
+    match text.trim().parse::<f64>() {
+        Ok(number) if number.is_finite() => Ok(number),
+        _ => Err(format!("{flag} expects a number, got {text:?}")),
+    }
+}
+
+fn parse_size(flag: &str, text: &str) -> Result<usize, String> {
+    match text.trim().parse() {
+        Ok(size) if size > 0 => Ok(size),
+        _ => Err(format!(
+            "{flag} expects a positive whole number, got {text:?}"
+        )),
+    }
+}
+
+fn parse_expr(flag: &str, text: &str) -> Result<Expr, String> {
+    text.parse().map_err(|error| format!("{flag}: {error}"))
+}
+
+/// Parses a positive number of seconds, with an optional unit: `ms`, `s`, or `m`.
+fn parse_duration(flag: &str, text: &str) -> Result<f64, String> {
+    let text = text.trim();
+    let (number, unit) = match text.find(|c: char| c.is_ascii_alphabetic()) {
+        Some(i) => text.split_at(i),
+        None => (text, "s"),
+    };
+    let scale = match unit {
+        "ms" => 0.001,
+        "s" => 1.0,
+        "m" => 60.0,
+        _ => return Err(format!("{flag} expects a duration like 10s, got {text:?}")),
+    };
+    match number.parse::<f64>() {
+        Ok(number) if number.is_finite() && number > 0.0 => Ok(number * scale),
+        _ => Err(format!("{flag} expects a duration like 10s, got {text:?}")),
+    }
+}
+
+/// Parses a comma-separated triple like `1,1,0`.
+fn parse_vec3(text: &str) -> Result<Vec3, String> {
+    let components: Vec<Real> = text
+        .split(',')
+        .map(|part| part.trim().parse::<Real>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("expected three numbers like 1,1,0, got {text:?}"))?;
+
+    match components[..] {
+        [x, y, z] if components.iter().all(|c| c.is_finite()) => Ok(Vec3::new(x, y, z)),
+        _ => Err(format!("expected three numbers like 1,1,0, got {text:?}")),
+    }
+}
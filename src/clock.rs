@@ -0,0 +1,148 @@
+//! The time of day, built out of blocks, for an ambient desk clock.
+
+use crate::math::{Real, Vec3};
+use crate::mesh::{Mesh, Triangle};
+
+/// How big each pixel of the font is. Terminal cells are about twice as tall as they are wide,
+/// so pixels are too, or the digits would be squashed into too few rows to read.
+const PIXEL: Vec3 = Vec3::new(1.2, 3.2, 2.0);
+
+/// A 3×5 pixel font, with `#` for the pixels that are filled in.
+const FONT: [(char, [&str; 5]); 11] = [
+    ('0', ["###", "# #", "# #", "# #", "###"]),
+    ('1', [" # ", "## ", " # ", " # ", "###"]),
+    ('2', ["###", "  #", "###", "#  ", "###"]),
+    ('3', ["###", "  #", " ##", "  #", "###"]),
+    ('4', ["# #", "# #", "###", "  #", "  #"]),
+    ('5', ["###", "#  ", "###", "  #", "###"]),
+    ('6', ["###", "#  ", "###", "# #", "###"]),
+    ('7', ["###", "  #", "  #", " # ", " # "]),
+    ('8', ["###", "# #", "###", "# #", "###"]),
+    ('9', ["###", "# #", "###", "  #", "###"]),
+    (':', ["   ", " # ", "   ", " # ", "   "]),
+];
+
+/// The local time, like `09:41:07`.
+pub fn now() -> String {
+    let tm = local_time();
+    format!("{:02}:{:02}:{:02}", tm.tm_hour, tm.tm_min, tm.tm_sec)
+}
+
+/// The local date and time, like `2024-02-14-094107`, which sorts in order and can go in file
+/// names.
+pub fn timestamp() -> String {
+    let tm = local_time();
+    format!(
+        "{}-{:02}-{:02}-{:02}{:02}{:02}",
+        tm.tm_year + 1900,
+        tm.tm_mon + 1,
+        tm.tm_mday,
+        tm.tm_hour,
+        tm.tm_min,
+        tm.tm_sec
+    )
+}
+
+pub(crate) fn local_time() -> libc::tm {
+    // SAFETY: `localtime_r` only writes to the `tm` it's given, which is plain data.
+    unsafe {
+        let time = libc::time(std::ptr::null_mut());
+        let mut tm: libc::tm = std::mem::zeroed();
+        libc::localtime_r(&time, &mut tm);
+        tm
+    }
+}
+
+/// A solid block for each pixel of `text`, centred on the origin and facing −Z, towards the
+/// default camera. Characters without a glyph leave a gap.
+pub fn digits(text: &str) -> Mesh {
+    let columns = text.chars().count() * 4 - 1;
+    let origin = Vec3::new(-(columns as Real - 1.0) / 2.0 * PIXEL.x, 2.0 * PIXEL.y, 0.0);
+    let mut triangles = Vec::new();
+    for (i, c) in text.chars().enumerate() {
+        let Some((_, glyph)) = FONT.iter().find(|(glyph, _)| *glyph == c) else {
+            continue;
+        };
+        for (row, line) in glyph.iter().enumerate() {
+            for (column, pixel) in line.chars().enumerate() {
+                if pixel == '#' {
+                    let offset = Vec3::new(
+                        (i * 4 + column) as Real * PIXEL.x,
+                        -(row as Real) * PIXEL.y,
+                        0.0,
+                    );
+                    block(origin + offset, &mut triangles);
+                }
+            }
+        }
+    }
+    Mesh {
+        triangles,
+        ..Mesh::default()
+    }
+}
+
+/// Adds a block the size of a pixel, centred on `centre`, with its corners going anticlockwise
+/// around each face as seen from outside.
+fn block(centre: Vec3, triangles: &mut Vec<Triangle>) {
+    let half = PIXEL * 0.5;
+    let (x, y, z) = (
+        Vec3::new(half.x, 0.0, 0.0),
+        Vec3::new(0.0, half.y, 0.0),
+        Vec3::new(0.0, 0.0, half.z),
+    );
+    // Each face's outward direction, and two edges whose cross product points the same way. The
+    // front comes first, so that it wins ties in the depth buffer where it meets the sides.
+    let faces = [
+        (-z, y, x),
+        (z, x, y),
+        (x, y, z),
+        (-x, z, y),
+        (y, z, x),
+        (-y, x, z),
+    ];
+    for (normal, u, v) in faces {
+        let middle = centre + normal;
+        let [a, b, c, d] = [
+            middle - u - v,
+            middle + u - v,
+            middle + u + v,
+            middle - u + v,
+        ];
+        for corners in [[a, b, c], [a, c, d]] {
+            triangles.push(Triangle {
+                corners,
+                normals: None,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocks_face_outwards() {
+        let mut triangles = Vec::new();
+        block(Vec3::default(), &mut triangles);
+        assert_eq!(12, triangles.len());
+        for triangle in &triangles {
+            let [a, b, c] = triangle.corners;
+            let centroid = (a + b + c) * (1.0 / 3.0);
+            assert!(triangle.face_normal().dot(centroid) > 0.0);
+        }
+        // The 1 has eight pixels and the colon two.
+        assert_eq!(10 * 12, digits("1:").triangles.len());
+    }
+
+    #[test]
+    fn formats_the_time() {
+        let time = now();
+        assert_eq!(8, time.len());
+        assert!(time.chars().all(|c| c.is_ascii_digit() || c == ':'));
+        let timestamp = timestamp();
+        assert_eq!(17, timestamp.len());
+        assert!(timestamp.chars().all(|c| c.is_ascii_digit() || c == '-'));
+    }
+}
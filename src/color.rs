@@ -0,0 +1,344 @@
+//! Colouring characters with terminal escape codes.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::width;
+
+/// Restores the terminal's own colours.
+pub const RESET: &str = "\x1b[0m";
+
+/// Draws following characters bold, until the next [`RESET`].
+const BOLD: &str = "\x1b[1m";
+
+/// Restores the terminal's own colour for characters, but not for what's behind them.
+const DEFAULT_FOREGROUND: &str = "\x1b[39m";
+
+/// A colour for each cell of a frame, or `None` for the terminal's own.
+pub type Colors = Vec<Vec<Option<[u8; 3]>>>;
+
+/// How many colours the terminal can show.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorMode {
+    /// Plain characters in the terminal's own colour.
+    #[default]
+    None,
+    /// The eight colours every colour terminal has, and their bright versions.
+    Ansi16,
+    /// The 256-colour palette most terminals support.
+    Ansi256,
+    /// Any 24-bit colour.
+    Truecolor,
+}
+
+impl ColorMode {
+    pub const ALL: [ColorMode; 4] = [
+        ColorMode::None,
+        ColorMode::Ansi16,
+        ColorMode::Ansi256,
+        ColorMode::Truecolor,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ColorMode::None => "none",
+            ColorMode::Ansi16 => "16",
+            ColorMode::Ansi256 => "256",
+            ColorMode::Truecolor => "truecolor",
+        }
+    }
+
+    /// The escape code that draws following characters in `rgb`, or as close as this mode
+    /// allows. `None` when this mode doesn't colour anything.
+    pub fn foreground(self, rgb: [u8; 3]) -> Option<String> {
+        let [r, g, b] = rgb;
+        match self {
+            ColorMode::None => None,
+            ColorMode::Ansi16 => Some(format!("\x1b[{}m", ansi16(rgb, 30, 90))),
+            ColorMode::Ansi256 => Some(format!("\x1b[38;5;{}m", ansi256(rgb))),
+            ColorMode::Truecolor => Some(format!("\x1b[38;2;{r};{g};{b}m")),
+        }
+    }
+
+    /// Like [`ColorMode::foreground`], but for behind the characters.
+    pub fn background(self, rgb: [u8; 3]) -> Option<String> {
+        let [r, g, b] = rgb;
+        match self {
+            ColorMode::None => None,
+            ColorMode::Ansi16 => Some(format!("\x1b[{}m", ansi16(rgb, 40, 100))),
+            ColorMode::Ansi256 => Some(format!("\x1b[48;5;{}m", ansi256(rgb))),
+            ColorMode::Truecolor => Some(format!("\x1b[48;2;{r};{g};{b}m")),
+        }
+    }
+}
+
+impl fmt::Display for ColorMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for ColorMode {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<ColorMode, String> {
+        ColorMode::ALL
+            .into_iter()
+            .find(|mode| mode.name() == name)
+            .ok_or_else(|| format!("unknown color mode {name:?}"))
+    }
+}
+
+/// `rgb` with its hue turned `degrees` around the colour wheel, keeping its lightness and
+/// saturation.
+pub fn rotate_hue(rgb: [u8; 3], degrees: f64) -> [u8; 3] {
+    let [r, g, b] = rgb.map(|c| f64::from(c) / 255.0);
+    let (highest, lowest) = (r.max(g).max(b), r.min(g).min(b));
+    let chroma = highest - lowest;
+    if chroma == 0.0 {
+        return rgb;
+    }
+    let hue = if highest == r {
+        (g - b) / chroma
+    } else if highest == g {
+        (b - r) / chroma + 2.0
+    } else {
+        (r - g) / chroma + 4.0
+    };
+    // Sixths of the way around, from red.
+    let hue = (hue + degrees / 60.0).rem_euclid(6.0);
+    let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+    let (r, g, b) = match hue as u8 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    [r, g, b].map(|c| ((c + lowest) * 255.0).round() as u8)
+}
+
+/// Reads a colour written like `#ff69b4`.
+pub fn parse_hex(text: &str) -> Result<[u8; 3], String> {
+    let invalid = || format!("expected a colour like \"#ff69b4\", got {text:?}");
+    let digits = text.strip_prefix('#').ok_or_else(invalid)?;
+    if digits.len() != 6 || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(invalid());
+    }
+    let channel = |i: usize| u8::from_str_radix(&digits[i..i + 2], 16).map_err(|_| invalid());
+    Ok([channel(0)?, channel(2)?, channel(4)?])
+}
+
+/// Replaces the colours in `colors` wherever `over` has one.
+pub fn paint(colors: &mut Colors, over: &Colors) {
+    for (row, over) in colors.iter_mut().zip(over) {
+        for (color, over) in row.iter_mut().zip(over) {
+            if over.is_some() {
+                *color = *over;
+            }
+        }
+    }
+}
+
+/// Joins a row of characters into a line for the terminal, each in its colour from `colors`, or
+/// in the terminal's own colour where that's `None`. The second columns of wide characters are
+/// left out, since the characters themselves fill them.
+pub fn line(chars: &[char], colors: &[Option<[u8; 3]>], mode: ColorMode) -> String {
+    line_on(chars, colors, None, false, mode)
+}
+
+/// Like [`line`], but on `background`, or the terminal's own where that's `None`, and in bold
+/// if `bold` is set, which even terminals without colours can show.
+pub fn line_on(
+    chars: &[char],
+    colors: &[Option<[u8; 3]>],
+    background: Option<[u8; 3]>,
+    bold: bool,
+    mode: ColorMode,
+) -> String {
+    let background = background.and_then(|rgb| mode.background(rgb));
+    // Going back to the terminal's own colour mustn't lose the background or boldness too.
+    let default = match background {
+        Some(_) => DEFAULT_FOREGROUND,
+        None if bold => DEFAULT_FOREGROUND,
+        None => RESET,
+    };
+    let mut line = background.clone().unwrap_or_default();
+    if bold {
+        line.push_str(BOLD);
+    }
+    let mut current = None;
+    for (i, &c) in chars.iter().enumerate() {
+        if c == width::CONTINUATION {
+            continue;
+        }
+        let escape = colors
+            .get(i)
+            .copied()
+            .flatten()
+            .and_then(|rgb| mode.foreground(rgb));
+        if escape != current {
+            line.push_str(escape.as_deref().unwrap_or(default));
+            current = escape;
+        }
+        line.push(c);
+    }
+    if current.is_some() || background.is_some() || bold {
+        line.push_str(RESET);
+    }
+    line
+}
+
+/// The colours of the 16-colour palette, as xterm shows them: the eight normal ones, then the
+/// eight bright ones.
+const ANSI16: [[u8; 3]; 16] = [
+    [0, 0, 0],
+    [205, 0, 0],
+    [0, 205, 0],
+    [205, 205, 0],
+    [0, 0, 238],
+    [205, 0, 205],
+    [0, 205, 205],
+    [229, 229, 229],
+    [127, 127, 127],
+    [255, 0, 0],
+    [0, 255, 0],
+    [255, 255, 0],
+    [92, 92, 255],
+    [255, 0, 255],
+    [0, 255, 255],
+    [255, 255, 255],
+];
+
+/// The code for the nearest colour in the 16-colour palette, counting from `normal` for the eight
+/// normal colours and from `bright` for the bright ones.
+fn ansi16(rgb: [u8; 3], normal: u8, bright: u8) -> u8 {
+    let distance = |entry: &[u8; 3]| -> i32 {
+        (0..3)
+            .map(|i| (i32::from(rgb[i]) - i32::from(entry[i])).pow(2))
+            .sum()
+    };
+    let nearest = (0..16u8)
+        .min_by_key(|&i| distance(&ANSI16[usize::from(i)]))
+        .unwrap_or(0);
+    match nearest {
+        0..=7 => normal + nearest,
+        _ => bright + nearest - 8,
+    }
+}
+
+/// The nearest entry in the 256-colour palette: either the 6×6×6 colour cube or, for colours
+/// that are nearly grey, the finer grey ramp after it.
+fn ansi256(rgb: [u8; 3]) -> u8 {
+    let [r, g, b] = rgb.map(i32::from);
+    let (lowest, highest) = (r.min(g).min(b), r.max(g).max(b));
+    if highest - lowest < 10 {
+        let grey = (r + g + b) / 3;
+        return match grey {
+            0..=3 => 16,
+            248.. => 231,
+            _ => (232 + (grey - 8).max(0) * 24 / 240) as u8,
+        };
+    }
+    // The cube's levels are 0, 95, 135, 175, 215, and 255.
+    let level = |c: i32| if c < 48 { 0 } else { ((c - 35) / 40).min(5) };
+    (16 + 36 * level(r) + 6 * level(g) + level(b)) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_nearby_palette_entries() {
+        assert_eq!(16, ansi256([0, 0, 0]));
+        assert_eq!(231, ansi256([255, 255, 255]));
+        assert_eq!(196, ansi256([255, 0, 0]));
+        assert_eq!(244, ansi256([128, 128, 128]));
+        assert_eq!(None, ColorMode::None.foreground([1, 2, 3]));
+        assert_eq!(
+            Some("\x1b[31m".to_owned()),
+            ColorMode::Ansi16.foreground([190, 20, 10])
+        );
+        assert_eq!(
+            Some("\x1b[105m".to_owned()),
+            ColorMode::Ansi16.background([255, 105, 235])
+        );
+        assert_eq!(37, ansi16([220, 220, 220], 30, 90));
+        assert_eq!(90, ansi16([128, 128, 128], 30, 90));
+        assert_eq!(Ok(ColorMode::Ansi16), "16".parse());
+        assert_eq!(
+            Some("\x1b[38;2;1;2;3m".to_owned()),
+            ColorMode::Truecolor.foreground([1, 2, 3])
+        );
+    }
+
+    #[test]
+    fn rotates_hues() {
+        assert_eq!([0, 255, 0], rotate_hue([255, 0, 0], 120.0));
+        assert_eq!([255, 0, 255], rotate_hue([255, 0, 0], -60.0));
+        assert_eq!([128, 64, 64], rotate_hue([64, 128, 128], 180.0));
+        assert_eq!([90, 90, 90], rotate_hue([90, 90, 90], 45.0));
+        assert_eq!([255, 105, 180], rotate_hue([255, 105, 180], 720.0));
+    }
+
+    #[test]
+    fn parses_hex_colours() {
+        assert_eq!(Ok([255, 105, 180]), parse_hex("#ff69b4"));
+        assert_eq!(Ok([0, 10, 171]), parse_hex("#000AaB"));
+        assert!(parse_hex("ff69b4").is_err());
+        assert!(parse_hex("#ff69b").is_err());
+        assert!(parse_hex("#ff69bé").is_err());
+    }
+
+    #[test]
+    fn paints_over_colours() {
+        let (red, blue) = (Some([255, 0, 0]), Some([0, 0, 255]));
+        let mut colors = vec![vec![red, red, None]];
+        paint(&mut colors, &vec![vec![None, blue, blue]]);
+        assert_eq!(vec![vec![red, blue, blue]], colors);
+    }
+
+    #[test]
+    fn lines_only_change_colour_when_they_need_to() {
+        let red = Some([255, 0, 0]);
+        let painted = line(
+            &['a', 'b', 'c', 'd'],
+            &[red, red, None, red],
+            ColorMode::Ansi256,
+        );
+        assert_eq!("\x1b[38;5;196mab\x1b[0mc\x1b[38;5;196md\x1b[0m", painted);
+        assert_eq!("ab", line(&['a', 'b'], &[red, red], ColorMode::None));
+        let wide = ['💖', width::CONTINUATION, 'a'];
+        assert_eq!("💖a", line(&wide, &[], ColorMode::None));
+    }
+
+    #[test]
+    fn backgrounds_last_the_whole_line() {
+        let red = Some([255, 0, 0]);
+        let painted = line_on(
+            &['a', 'b'],
+            &[red, None],
+            Some([0, 0, 0]),
+            false,
+            ColorMode::Ansi256,
+        );
+        assert_eq!("\x1b[48;5;16m\x1b[38;5;196ma\x1b[39mb\x1b[0m", painted);
+        assert_eq!(
+            "ab",
+            line_on(&['a', 'b'], &[], Some([0, 0, 0]), false, ColorMode::None)
+        );
+    }
+
+    #[test]
+    fn bold_lines_stay_bold_between_colours() {
+        let red = Some([255, 0, 0]);
+        let painted = line_on(&['a', 'b'], &[red, None], None, true, ColorMode::Ansi256);
+        assert_eq!("\x1b[1m\x1b[38;5;196ma\x1b[39mb\x1b[0m", painted);
+        assert_eq!(
+            "\x1b[1mab\x1b[0m",
+            line_on(&['a', 'b'], &[], None, true, ColorMode::None)
+        );
+    }
+}
@@ -0,0 +1,95 @@
+//! Commands typed while the animation is running, like `theme fire` or `fps 30`, which change
+//! the settings just as the same lines in the configuration file would.
+
+use crate::config::{Config, ConfigError};
+
+/// Runs `command` on `config`: a setting and its value, like `theme fire`, or a setting on its
+/// own to switch it on, like `crt`. Values are read as they would be in the configuration file,
+/// except that words don't need quotes around them. `shape NAME` draws a single object of that
+/// shape instead of the objects there were.
+///
+/// If the command can't be run, `config` is left as it was and the error says why.
+pub fn run(config: &mut Config, command: &str) -> Result<(), String> {
+    let command = command.trim();
+    let (name, value) = match command.split_once(char::is_whitespace) {
+        Some((name, value)) => (name, value.trim()),
+        None => (command, ""),
+    };
+    if name.is_empty() {
+        return Ok(());
+    }
+    // As on the command line, where settings are spelled with dashes.
+    let key = name.replace('-', "_");
+    let attempts = match (key.as_str(), value) {
+        ("shape", shape) => vec![format!("[[object]]\nshape = {}", quoted(shape))],
+        (_, "") => vec![format!("{key} = true")],
+        (_, value) => vec![
+            format!("{key} = {value}"),
+            format!("{key} = {}", quoted(value)),
+        ],
+    };
+    let mut error = None;
+    for text in attempts {
+        let mut updated = config.clone();
+        match updated.update(&text) {
+            Ok(()) => {
+                *config = updated;
+                return Ok(());
+            }
+            // Not a value as it's written, so perhaps a word.
+            Err(ConfigError::Syntax { .. }) if error.is_none() => {
+                error = Some(format!("{name}: can't read {value:?}"));
+            }
+            Err(other) => return Err(other.to_string()),
+        }
+    }
+    Err(error.unwrap_or_default())
+}
+
+/// `text` as a string in the configuration file.
+fn quoted(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::Shape;
+
+    #[test]
+    fn commands_change_settings() {
+        let mut config = Config::default();
+        run(&mut config, "theme fire").unwrap();
+        run(&mut config, "fps 30").unwrap();
+        run(&mut config, "crt").unwrap();
+        run(&mut config, "color-by depth").unwrap();
+        run(&mut config, "spin [0, 0.1, 0]").unwrap();
+        assert_eq!(
+            ("fire", Some(30.0)),
+            (config.theme.name.as_str(), config.fps)
+        );
+        assert!(config.crt);
+        assert_eq!(0.1, config.spin.y);
+        run(&mut config, "shape torus").unwrap();
+        assert_eq!(1, config.objects.len());
+        assert_eq!("torus", config.objects[0].shape.name());
+        run(&mut config, "  ").unwrap();
+    }
+
+    #[test]
+    fn failed_commands_change_nothing() {
+        let mut config = Config::default();
+        assert_eq!(
+            "fps: must be between 0.01 and 1000",
+            run(&mut config, "fps 1e-20").unwrap_err()
+        );
+        assert_eq!(
+            "fpz: unknown setting",
+            run(&mut config, "fpz 30").unwrap_err()
+        );
+        assert!(run(&mut config, "theme nope").is_err());
+        assert!(run(&mut config, "shape cube").is_err());
+        assert_eq!(Config::default(), config);
+        assert_eq!(Shape::Heart, config.objects[0].shape);
+    }
+}
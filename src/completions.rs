@@ -0,0 +1,414 @@
+//! Completions for `ascii-love completions SHELL`, worked out from the flags in the help text, so
+//! they can't fall behind it. Flags that take a name complete the names there are.
+
+use std::fmt;
+use std::str::FromStr;
+
+use ascii_love::auto;
+use ascii_love::background::Background;
+use ascii_love::color::ColorMode;
+use ascii_love::dither::Dither;
+use ascii_love::mesh::Normals;
+use ascii_love::overlay::Placement;
+use ascii_love::preset::Preset;
+use ascii_love::render::{DebugView, Style};
+use ascii_love::scene::Shape;
+use ascii_love::shading::Shading;
+use ascii_love::stereo::Stereo;
+use ascii_love::theme::{Colorblind, Coloring, Theme};
+use ascii_love::viewport::Align;
+
+use crate::cli;
+
+/// A shell to write completions for.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Shell {
+    pub const ALL: [Shell; 3] = [Shell::Bash, Shell::Zsh, Shell::Fish];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Shell::Bash => "bash",
+            Shell::Zsh => "zsh",
+            Shell::Fish => "fish",
+        }
+    }
+}
+
+impl fmt::Display for Shell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Shell {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Shell, String> {
+        Shell::ALL
+            .into_iter()
+            .find(|shell| shell.name() == name)
+            .ok_or_else(|| format!("unknown shell {name:?}"))
+    }
+}
+
+/// The subcommands, which come before any flags.
+const SUBCOMMANDS: [&str; 2] = ["image", "completions"];
+
+/// A line of the help text: the flag and any others that mean the same, like `-v, --verbose`,
+/// what it takes, and what it does.
+struct Flag {
+    names: Vec<&'static str>,
+    value: Option<Value>,
+    help: String,
+}
+
+/// What comes after a flag.
+enum Value {
+    Path,
+    /// One of these names.
+    Choice(Vec<&'static str>),
+    /// Anything else, like a number.
+    Other,
+}
+
+/// The flags in `usage`, one for each line starting with `-`, or each of the flags on it when
+/// there are several taking their own values, like `--fx EXPR, --fy EXPR, --fz EXPR`.
+fn flags(usage: &'static str) -> Vec<Flag> {
+    let mut lines = usage.lines().peekable();
+    let mut flags = Vec::new();
+    while let Some(line) = lines.next() {
+        if !line.starts_with("  -") {
+            continue;
+        }
+        let line = line.trim_start();
+        // Descriptions start two spaces after the flag, or on the next line if it's too long.
+        let (head, help) = match line.split_once("  ") {
+            Some((head, help)) => (head, help.trim()),
+            None => (line, lines.peek().map_or("", |line| line.trim())),
+        };
+        // Just the first line, without the default, which is a description's worth.
+        let help = help.split(" [default").next().unwrap_or(help);
+        let help = help.trim_end_matches(',').to_owned();
+        let parts: Vec<(&str, Option<&str>)> = (head.split(", "))
+            .map(|part| match part.split_once(' ') {
+                Some((name, value)) => (name, Some(value)),
+                None => (part, None),
+            })
+            .collect();
+        if parts.iter().all(|(_, value)| value.is_none()) {
+            flags.push(Flag {
+                names: parts.iter().map(|&(name, _)| name).collect(),
+                value: None,
+                help,
+            });
+        } else {
+            for (name, value) in parts {
+                flags.push(Flag {
+                    names: vec![name],
+                    value: value.map(|value| self::value(name, value)),
+                    help: help.clone(),
+                });
+            }
+        }
+    }
+    flags
+}
+
+/// What `flag` takes, from what the help text calls it.
+fn value(flag: &str, value: &str) -> Value {
+    fn names<T>(all: impl IntoIterator<Item = T>, name: fn(T) -> &'static str) -> Value {
+        Value::Choice(all.into_iter().map(name).collect())
+    }
+    let shapes = || Value::Choice(Shape::BUILT_IN.iter().map(Shape::name).collect());
+    match (flag, value) {
+        (_, "PATH" | "DIR") => Value::Path,
+        ("--preset", _) => names(Preset::ALL, Preset::name),
+        ("--normals", _) => names(Normals::ALL, Normals::name),
+        ("--shapes" | "--split", _) => shapes(),
+        ("--align", _) => names(Align::ALL, Align::name),
+        ("--message-at", _) => names(Placement::ALL, Placement::name),
+        ("--background", _) => names(Background::ALL, Background::name),
+        ("--render", _) => names(Style::ALL, Style::name),
+        ("--shading", _) => names(Shading::ALL, Shading::name),
+        ("--dither", _) => names(Dither::ALL, Dither::name),
+        ("--debug", _) => names(DebugView::ALL, DebugView::name),
+        ("--theme", _) => Value::Choice(Theme::built_in().chain([auto::NAME]).collect()),
+        ("--color-by", _) => names(Coloring::ALL, Coloring::name),
+        ("--colorblind", _) => names(Colorblind::ALL, Colorblind::name),
+        ("--stereo", _) => names(Stereo::ALL, Stereo::name),
+        ("--color", _) => names(ColorMode::ALL, ColorMode::name),
+        _ => Value::Other,
+    }
+}
+
+/// A completion script for `shell`, to be sourced or put where it looks for them.
+pub fn script(shell: Shell) -> String {
+    let animate = flags(cli::USAGE);
+    let image = flags(cli::IMAGE_USAGE);
+    let shells: Vec<&str> = Shell::ALL.iter().map(|shell| shell.name()).collect();
+    match shell {
+        Shell::Bash => bash(&animate, &image, &shells),
+        Shell::Zsh => zsh(&animate, &image, &shells),
+        Shell::Fish => fish(&animate, &image, &shells),
+    }
+}
+
+fn bash(animate: &[Flag], image: &[Flag], shells: &[&str]) -> String {
+    // What to offer after each flag that takes something, then the flags themselves.
+    let cases = |flags: &[Flag]| {
+        let mut cases = String::new();
+        for flag in flags {
+            let reply = match &flag.value {
+                None => continue,
+                Some(Value::Path) => "COMPREPLY=($(compgen -f -- \"$cur\"))".to_owned(),
+                Some(Value::Choice(names)) => {
+                    format!(
+                        "COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))",
+                        names.join(" ")
+                    )
+                }
+                Some(Value::Other) => "COMPREPLY=()".to_owned(),
+            };
+            cases += &format!("            {}) {reply}; return ;;\n", flag.names.join("|"));
+        }
+        let names: Vec<&str> = flags.iter().flat_map(|flag| flag.names.clone()).collect();
+        format!(
+            "        case $prev in\n{cases}        esac\n        \
+             COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))\n",
+            names.join(" ")
+        )
+    };
+    format!(
+        "_ascii_love() {{
+    local cur=${{COMP_WORDS[COMP_CWORD]}} prev=${{COMP_WORDS[COMP_CWORD-1]}}
+    case ${{COMP_WORDS[1]}} in
+    completions)
+        [[ $COMP_CWORD == 2 ]] && COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))
+        ;;
+    image)
+{}        [[ $cur == -* ]] || COMPREPLY+=($(compgen -f -- \"$cur\"))
+        ;;
+    *)
+        if [[ $COMP_CWORD == 1 && $cur != -* ]]; then
+            COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))
+            return
+        fi
+{}        ;;
+    esac
+}}
+complete -F _ascii_love ascii-love
+",
+        shells.join(" "),
+        cases(image),
+        SUBCOMMANDS.join(" "),
+        cases(animate),
+    )
+}
+
+fn zsh(animate: &[Flag], image: &[Flag], shells: &[&str]) -> String {
+    let specs = |flags: &[Flag]| {
+        let mut specs = String::new();
+        for flag in flags {
+            // Brackets end the description, and the whole spec is in single quotes.
+            let help = (flag.help.replace('\'', "'\\''"))
+                .replace('[', "\\[")
+                .replace(']', "\\]");
+            let value = match &flag.value {
+                None => String::new(),
+                Some(Value::Path) => ":path:_files".to_owned(),
+                Some(Value::Choice(names)) => format!(":name:({})", names.join(" ")),
+                Some(Value::Other) => ":value: ".to_owned(),
+            };
+            for name in &flag.names {
+                specs += &format!(" \\\n        '{name}[{help}]{value}'");
+            }
+        }
+        specs
+    };
+    format!(
+        "#compdef ascii-love
+
+_ascii_love() {{
+    case $words[2] in
+    completions)
+        _arguments '2:shell:({})'
+        ;;
+    image)
+        shift words
+        (( CURRENT-- ))
+        _arguments{} \\
+        ':path:_files'
+        ;;
+    *)
+        _arguments{} \\
+        '1::command:({})'
+        ;;
+    esac
+}}
+
+_ascii_love \"$@\"
+",
+        shells.join(" "),
+        specs(image),
+        specs(animate),
+        SUBCOMMANDS.join(" "),
+    )
+}
+
+fn fish(animate: &[Flag], image: &[Flag], shells: &[&str]) -> String {
+    let subcommands = SUBCOMMANDS.join(" ");
+    let mut script = String::from("complete -c ascii-love -f\n");
+    script += &format!(
+        "complete -c ascii-love -n __fish_use_subcommand -a '{subcommands}'\n\
+         complete -c ascii-love -n '__fish_seen_subcommand_from completions' -a '{}'\n\
+         complete -c ascii-love -n '__fish_seen_subcommand_from image' -F\n",
+        shells.join(" ")
+    );
+    let condition = format!("not __fish_seen_subcommand_from {subcommands}");
+    for (flags, condition) in [
+        (animate, condition.as_str()),
+        (image, "__fish_seen_subcommand_from image"),
+    ] {
+        for flag in flags {
+            let mut line = format!("complete -c ascii-love -n '{condition}'");
+            for name in &flag.names {
+                match name.strip_prefix("--") {
+                    Some(long) => line += &format!(" -l {long}"),
+                    None => line += &format!(" -s {}", name.trim_start_matches('-')),
+                }
+            }
+            match &flag.value {
+                None => {}
+                Some(Value::Path) => line += " -r -F",
+                Some(Value::Choice(names)) => line += &format!(" -x -a '{}'", names.join(" ")),
+                Some(Value::Other) => line += " -x",
+            }
+            let help = flag.help.replace('\\', "\\\\").replace('\'', "\\'");
+            script += &format!("{line} -d '{help}'\n");
+        }
+    }
+    script
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{ImageOptions, Options, ParseError};
+
+    /// The flags in the arms of the `match flag.as_str()` in `source`, like `"-v" | "--verbose"`.
+    fn matched(source: &str) -> Vec<&str> {
+        let mut flags = Vec::new();
+        for line in source.lines().map(str::trim) {
+            let Some((head, _)) = line.split_once(" =>") else {
+                continue;
+            };
+            let names: Vec<&str> = head.split(" | ").collect();
+            // Just literals, not the start of an error message like `"--axis must not be`.
+            let flag = |name: &&str| {
+                let name = name
+                    .strip_prefix('"')
+                    .and_then(|name| name.strip_suffix('"'));
+                name.is_some_and(|name| name.starts_with('-') && !name.contains([' ', '"']))
+            };
+            if names.iter().all(flag) {
+                flags.extend(names.into_iter().map(|name| name.trim_matches('"')));
+            }
+        }
+        flags
+    }
+
+    /// The flags `Options::parse` and `ImageOptions::parse` match on.
+    fn parsed() -> (Vec<&'static str>, Vec<&'static str>) {
+        let source = include_str!("cli.rs");
+        let (animate, image) = source.split_once("impl ImageOptions").unwrap();
+        (matched(animate), matched(image))
+    }
+
+    /// Whether `script` for `shell` completes `flag`.
+    fn offers(shell: Shell, script: &str, flag: &str) -> bool {
+        match shell {
+            Shell::Bash => script
+                .split_whitespace()
+                .any(|word| word.trim_matches('"') == flag),
+            Shell::Zsh => script.contains(&format!("'{flag}[")),
+            Shell::Fish => match flag.strip_prefix("--") {
+                Some(long) => script.contains(&format!(" -l {long} ")),
+                None => script.contains(&format!(" -s {} ", &flag[1..])),
+            },
+        }
+    }
+
+    #[test]
+    fn every_shell_completes_every_flag_there_is() {
+        let (animate, image) = parsed();
+        assert!(animate.contains(&"--verbose") && animate.contains(&"--no-cull"));
+        assert!(image.contains(&"--color"));
+        for shell in Shell::ALL {
+            let script = script(shell);
+            for flag in animate.iter().chain(&image) {
+                assert!(offers(shell, &script, flag), "{shell} doesn't offer {flag}");
+            }
+        }
+    }
+
+    #[test]
+    fn the_flags_found_are_the_ones_parsed() {
+        let unknown = |flag: &str| format!("unknown option: {flag}");
+        let (animate, image) = parsed();
+        for flag in animate {
+            let parsed = Options::parse([flag.to_owned()]);
+            assert_ne!(parsed.err(), Some(ParseError::Usage(unknown(flag))));
+        }
+        for flag in image {
+            let parsed = ImageOptions::parse([flag.to_owned()]);
+            assert_ne!(parsed.err(), Some(unknown(flag)));
+        }
+        for flag in flags(cli::USAGE).iter().flat_map(|flag| &flag.names) {
+            let parsed = Options::parse([flag.to_string()]);
+            assert_ne!(parsed.err(), Some(ParseError::Usage(unknown(flag))));
+        }
+        for flag in flags(cli::IMAGE_USAGE).iter().flat_map(|flag| &flag.names) {
+            let parsed = ImageOptions::parse([flag.to_string()]);
+            assert_ne!(parsed.err(), Some(unknown(flag)));
+        }
+    }
+
+    #[test]
+    fn the_names_offered_are_the_ones_parsed() {
+        let choices = |usage| {
+            flags(usage)
+                .into_iter()
+                .filter_map(|flag| match flag.value {
+                    Some(Value::Choice(names)) => Some((flag.names[0], names)),
+                    _ => None,
+                })
+        };
+        let animate: Vec<_> = choices(cli::USAGE).collect();
+        for flag in ["--render", "--normals", "--theme", "--shading", "--dither"] {
+            assert!(animate.iter().any(|&(name, _)| name == flag), "{flag}");
+        }
+        for (flag, names) in animate {
+            for name in names {
+                let parsed = Options::parse([flag.to_owned(), name.to_owned()]);
+                assert!(parsed.is_ok(), "{flag} {name}: {:?}", parsed.err());
+            }
+            assert!(Options::parse([flag.to_owned(), "nope".to_owned()]).is_err());
+        }
+        let image: Vec<_> = choices(cli::IMAGE_USAGE).collect();
+        assert!(image.iter().any(|&(name, _)| name == "--color"));
+        for (flag, names) in image {
+            for name in names {
+                let args = ["cat.png", flag, name].map(str::to_owned);
+                assert!(ImageOptions::parse(args).is_ok(), "{flag} {name}");
+            }
+            let args = ["cat.png", flag, "nope"].map(str::to_owned);
+            assert!(ImageOptions::parse(args).is_err());
+        }
+    }
+}
@@ -0,0 +1,1386 @@
+//! Settings loaded from a TOML configuration file.
+//!
+//! ```toml
+//! spin = [0.0, 0.02, 0.0]
+//! shininess = 32
+//! ambient = 0.15
+//! render = "surface"
+//! shading = "toon"
+//! dither = "floyd-steinberg"
+//! theme = "valentine"
+//!
+//! [camera]
+//! loop = true
+//!
+//! [[camera.keyframe]]
+//! time = 0
+//! distance = 90
+//!
+//! [[camera.keyframe]]
+//! time = 20
+//! distance = 60
+//! azimuth = 360
+//! elevation = 15
+//! easing = "ease-in-out-cubic"
+//!
+//! # A bulb up and to the right of the viewer.
+//! [[light]]
+//! type = "point"
+//! position = [30, 20, 40]
+//! range = 60
+//!
+//! # Ramp the spin up from a standstill, then zoom in.
+//! [[timeline.speed]]
+//! time = 0
+//! value = 0
+//!
+//! [[timeline.speed]]
+//! time = 3
+//! value = 1
+//! easing = "ease-out-cubic"
+//!
+//! [[timeline.zoom]]
+//! time = 3
+//! value = 1
+//!
+//! [[timeline.zoom]]
+//! time = 6
+//! value = 1.5
+//! ```
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::auto::{self, Occasion};
+use crate::background::Background;
+use crate::camera::{CameraPath, Keyframe};
+use crate::color;
+use crate::demo::Demo;
+use crate::dither::Dither;
+use crate::light::{Light, Lighting};
+use crate::math::{Quat, Real, Vec3};
+use crate::motion::Lissajous;
+use crate::overlay::{Marquee, Message};
+use crate::playlist::Playlist;
+use crate::preset::Preset;
+use crate::render::{DebugView, Style};
+use crate::scene::{Material, Object, Shape, Transform};
+use crate::shading::Shading;
+use crate::stereo::Stereo;
+use crate::theme::{Colorblind, Coloring, Theme};
+use crate::timeline::{Key, Lerp, Timeline, Track};
+use crate::toml::{self, Table, Value};
+use crate::viewport::{Align, Margin};
+
+/// Every setting that can come from a configuration file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Config {
+    /// Radians per frame to rotate around each of the X, Y, and Z axes.
+    pub spin: Vec3,
+    /// Spin around this axis instead of tumbling around X, Y, and Z.
+    pub axis: Option<Vec3>,
+    /// Radians per frame to rotate around `axis`.
+    pub speed: f64,
+    /// How the objects are turned before they start spinning.
+    pub orientation: Quat,
+    /// Animates the camera instead of keeping it still.
+    pub camera: Option<CameraPath>,
+    /// Animates other properties, like rotation speed and zoom.
+    pub timeline: Timeline,
+    /// What to draw. Each object spins around its own centre.
+    pub objects: Vec<Object>,
+    /// Takes turns showing different shapes instead of the objects' own.
+    pub cycle: Option<Playlist>,
+    pub background: Background,
+    /// Fills the empty space around the objects.
+    pub bg_char: char,
+    /// Colours the whole screen behind the characters, instead of the theme's background.
+    pub bg_color: Option<[u8; 3]>,
+    /// Draw this many columns wide, instead of filling the screen.
+    pub width: Option<usize>,
+    /// Draw this many rows tall, instead of filling the screen.
+    pub height: Option<usize>,
+    /// Where the animation goes when it's smaller than the screen.
+    pub align: Align,
+    /// Space to leave around the animation.
+    pub margin: Margin,
+    /// Draw a greeting-card border around the animation.
+    pub card: bool,
+    /// Show the time of day under the objects.
+    pub clock: bool,
+    /// Text to show with the animation.
+    pub message: Option<Message>,
+    /// Text to scroll along the bottom of the screen.
+    pub marquee: Option<Marquee>,
+    /// Emits this many sparkles per second from the objects' surfaces.
+    pub sparkles: Option<f64>,
+    /// Each frame fades into the next, keeping this much of its brightness, from 0 to 1.
+    pub trail: Option<f64>,
+    /// The brightest surfaces glow this many cells into their surroundings.
+    pub bloom: Option<usize>,
+    /// Draw dark scanlines between the rows, and jitter them, like an old monitor.
+    pub crt: bool,
+    /// Draw everything in shades of green phosphor.
+    pub phosphor: bool,
+    /// Show small views from above and from the side, without perspective, in the corners.
+    pub insets: bool,
+    /// What to draw instead of the shaded objects, to see what the renderer is working with.
+    pub debug: Option<DebugView>,
+    /// Take turns showing off different looks.
+    pub demo: Option<Demo>,
+    /// Shapes to show side by side, each in its own part of the screen, instead of one scene.
+    pub split: Vec<Shape>,
+    /// Draw this many copies side by side, for terminals much wider than they are tall.
+    pub tile: Option<usize>,
+    /// How many degrees further around each tile is turned than the one before.
+    pub tile_phase: f64,
+    /// Bounce the animation around the screen.
+    pub bounce: bool,
+    /// Move the animation around the screen along a Lissajous curve.
+    pub lissajous: Option<Lissajous>,
+    /// Pulses the objects' size in time with this many heartbeats per minute.
+    pub bpm: Option<f64>,
+    /// Radians per frame that the objects revolve around the Y axis through the origin.
+    pub orbit: f64,
+    /// How many frames to draw a second, instead of one every 45 milliseconds.
+    pub fps: Option<f64>,
+    pub lighting: Lighting,
+    pub render: Style,
+    /// Rounds off shapes by this much when raymarching.
+    pub bevel: f64,
+    /// Melts objects into each other within this distance when raymarching.
+    pub blend: f64,
+    pub shading: Shading,
+    pub dither: Dither,
+    /// The characters and colours to draw with.
+    pub theme: Theme,
+    /// Pick the theme, and on some days the shape, by the local time and date instead. See
+    /// [`auto::Look::at`].
+    pub auto: bool,
+    /// Days with looks of their own for [`Config::auto`], before the built-in ones.
+    pub occasions: Vec<Occasion>,
+    /// Turn the hue of the theme's colours over time.
+    pub rainbow: bool,
+    pub color_by: Coloring,
+    /// Draw in colours that people with this kind of colour blindness can tell apart, whatever
+    /// the theme. See [`Theme::colorblind`].
+    pub colorblind: Option<Colorblind>,
+    /// Draw the brightest surfaces with emoji, and everything two columns wide to match.
+    pub emoji: bool,
+    /// Only draw ASCII characters, even if the locale says the terminal can show others.
+    pub ascii_only: bool,
+    /// Draw with only the two ends of the ramp, in bold and the terminal's own colours.
+    pub high_contrast: bool,
+    /// Draw fewer frames a second, for people sensitive to motion. See [`Config::reduce_motion`].
+    pub reduced_motion: bool,
+    /// Draw a view for each eye.
+    pub stereo: Option<Stereo>,
+    /// Only redraw every other row each frame, alternating between the odd and even rows, to
+    /// send half as much to the terminal.
+    pub interlace: bool,
+    /// Skip surfaces facing away from the camera, which are normally hidden behind the front of
+    /// the shape anyway. Only safe for closed surfaces with outward-facing normals.
+    pub cull: bool,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            spin: Vec3::new(0.05, 0.04, 0.0),
+            axis: None,
+            speed: 0.05,
+            orientation: Quat::IDENTITY,
+            camera: None,
+            timeline: Timeline::default(),
+            objects: vec![Object::default()],
+            cycle: None,
+            background: Background::default(),
+            bg_char: ' ',
+            bg_color: None,
+            width: None,
+            height: None,
+            align: Align::default(),
+            margin: Margin::default(),
+            card: false,
+            clock: false,
+            message: None,
+            marquee: None,
+            sparkles: None,
+            trail: None,
+            bloom: None,
+            crt: false,
+            phosphor: false,
+            insets: false,
+            demo: None,
+            debug: None,
+            split: Vec::new(),
+            tile: None,
+            tile_phase: 0.0,
+            bounce: false,
+            lissajous: None,
+            bpm: None,
+            orbit: 0.0,
+            fps: None,
+            lighting: Lighting::default(),
+            render: Style::default(),
+            bevel: 0.0,
+            blend: 0.0,
+            shading: Shading::default(),
+            dither: Dither::default(),
+            theme: Theme::default(),
+            auto: false,
+            occasions: Vec::new(),
+            rainbow: false,
+            color_by: Coloring::default(),
+            colorblind: None,
+            emoji: false,
+            ascii_only: false,
+            reduced_motion: false,
+            high_contrast: false,
+            stereo: None,
+            interlace: false,
+            cull: false,
+        }
+    }
+}
+
+impl Config {
+    /// Where the configuration file lives when none is given explicitly:
+    /// `$XDG_CONFIG_HOME/ascii-love/config.toml`, falling back to `~/.config`.
+    pub fn default_path() -> Option<PathBuf> {
+        let base = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+        Some(base.join("ascii-love").join("config.toml"))
+    }
+
+    /// Tones the animation down for people sensitive to motion: the objects and marquee move a
+    /// fraction as fast, and the heartbeat, sparkles, background, rainbow, and moving around the
+    /// screen are switched off.
+    pub fn reduce_motion(&mut self) {
+        const SLOWER: f64 = 0.3;
+        self.reduced_motion = true;
+        self.spin = self.spin * SLOWER as Real;
+        self.speed *= SLOWER;
+        self.orbit *= SLOWER;
+        if let Some(marquee) = &mut self.marquee {
+            marquee.speed *= SLOWER;
+        }
+        self.calm_down();
+    }
+
+    /// The part of [`Config::reduce_motion`] that switches things off, which is safe to do again
+    /// after changing the settings, unlike slowing things down.
+    pub(crate) fn calm_down(&mut self) {
+        self.bpm = None;
+        self.sparkles = None;
+        self.background = Background::None;
+        self.rainbow = false;
+        self.bounce = false;
+        self.lissajous = None;
+    }
+
+    pub fn load(path: &Path) -> Result<Config, ConfigError> {
+        let text =
+            fs::read_to_string(path).map_err(|error| ConfigError::Io(path.to_owned(), error))?;
+        Config::parse(&text)
+    }
+
+    /// Parses a configuration file. Settings it doesn't mention keep their defaults.
+    pub fn parse(text: &str) -> Result<Config, ConfigError> {
+        let mut config = Config::default();
+        config.update(text)?;
+        Ok(config)
+    }
+
+    /// Changes the settings `text` mentions, which is written like a configuration file, and
+    /// leaves the rest as they are. A `preset` is applied before anything else.
+    pub fn update(&mut self, text: &str) -> Result<(), ConfigError> {
+        let table = toml::parse(text).map_err(|error| ConfigError::Syntax {
+            line: error.line,
+            message: error.message,
+        })?;
+        let root = Section::root(&table);
+        root.allow_keys(&[
+            "preset",
+            "spin",
+            "axis",
+            "speed",
+            "camera",
+            "timeline",
+            "object",
+            "cycle",
+            "background",
+            "bg_char",
+            "bg_color",
+            "width",
+            "height",
+            "align",
+            "margin",
+            "card",
+            "clock",
+            "message",
+            "marquee",
+            "sparkles",
+            "trail",
+            "bloom",
+            "crt",
+            "phosphor",
+            "insets",
+            "demo",
+            "debug",
+            "split",
+            "tile",
+            "tile_phase",
+            "bounce",
+            "lissajous",
+            "bpm",
+            "orbit",
+            "fps",
+            "light",
+            "shininess",
+            "ambient",
+            "render",
+            "bevel",
+            "blend",
+            "shading",
+            "dither",
+            "theme",
+            "occasion",
+            "rainbow",
+            "color_by",
+            "colorblind",
+            "emoji",
+            "ascii_only",
+            "reduced_motion",
+            "high_contrast",
+            "stereo",
+            "interlace",
+            "cull",
+        ])?;
+
+        let config = self;
+        // Everything else refines the preset.
+        if let Some(preset) = root.parse::<Preset>("preset")? {
+            preset.apply(config);
+        }
+        if let Some(spin) = root.vec3("spin")? {
+            config.spin = spin;
+        }
+        if let Some(axis) = root.vec3("axis")? {
+            if axis.length() == 0.0 {
+                return Err(root.invalid("axis", "must not be the zero vector"));
+            }
+            config.axis = Some(axis);
+        }
+        if let Some(speed) = root.f64("speed")? {
+            config.speed = speed;
+        }
+        if let Some(camera) = root.section("camera")? {
+            config.camera = Some(camera_path(&camera)?);
+        }
+        if let Some(section) = root.section("timeline")? {
+            config.timeline = timeline(&section)?;
+        }
+        if let Some(shininess) = root.real("shininess")? {
+            if shininess < 0.0 {
+                return Err(root.invalid("shininess", "must not be negative"));
+            }
+            config.lighting.shininess = Some(shininess);
+        }
+        if let Some(ambient) = root.real("ambient")? {
+            if !(0.0..=1.0).contains(&ambient) {
+                return Err(root.invalid("ambient", "must be between 0 and 1"));
+            }
+            config.lighting.ambient = ambient;
+        }
+        if let Some(render) = root.parse("render")? {
+            config.render = render;
+        }
+        for (key, value) in [("bevel", &mut config.bevel), ("blend", &mut config.blend)] {
+            if let Some(distance) = root.f64(key)? {
+                if distance < 0.0 {
+                    return Err(root.invalid(key, "must not be negative"));
+                }
+                *value = distance;
+            }
+        }
+        if let Some(shading) = root.parse("shading")? {
+            config.shading = shading;
+        }
+        if let Some(dither) = root.parse("dither")? {
+            config.dither = dither;
+        }
+        if root.str("theme")? == Some(auto::NAME) {
+            config.auto = true;
+        } else if let Some(theme) = root.parse("theme")? {
+            config.theme = theme;
+            config.auto = false;
+        }
+        if let Some(rainbow) = root.bool("rainbow")? {
+            config.rainbow = rainbow;
+        }
+        if let Some(color_by) = root.parse("color_by")? {
+            config.color_by = color_by;
+        }
+        if let Some(colorblind) = root.parse("colorblind")? {
+            config.colorblind = Some(colorblind);
+        }
+        if let Some(emoji) = root.bool("emoji")? {
+            config.emoji = emoji;
+        }
+        if let Some(ascii_only) = root.bool("ascii_only")? {
+            config.ascii_only = ascii_only;
+        }
+        if let Some(reduced_motion) = root.bool("reduced_motion")? {
+            config.reduced_motion = reduced_motion;
+        }
+        if let Some(high_contrast) = root.bool("high_contrast")? {
+            config.high_contrast = high_contrast;
+        }
+        if let Some(stereo) = root.parse("stereo")? {
+            config.stereo = Some(stereo);
+        }
+        if let Some(interlace) = root.bool("interlace")? {
+            config.interlace = interlace;
+        }
+        if let Some(cull) = root.bool("cull")? {
+            config.cull = cull;
+        }
+        if let Some(section) = root.section("cycle")? {
+            config.cycle = Some(playlist(&section)?);
+        }
+        if let Some(background) = root.parse("background")? {
+            config.background = background;
+        }
+        if let Some(bg_char) = root.char("bg_char")? {
+            config.bg_char = bg_char;
+        }
+        if let Some(bg_color) = root.color("bg_color")? {
+            config.bg_color = Some(bg_color);
+        }
+        if let Some(width) = root.size("width")? {
+            if width == 0 {
+                return Err(root.invalid("width", "must be at least 1"));
+            }
+            config.width = Some(width);
+        }
+        if let Some(height) = root.size("height")? {
+            if height == 0 {
+                return Err(root.invalid("height", "must be at least 1"));
+            }
+            config.height = Some(height);
+        }
+        if let Some(align) = root.parse("align")? {
+            config.align = align;
+        }
+        if let Some(margin) = root.size("margin")? {
+            config.margin = Margin::uniform(margin);
+        }
+        if let Some(card) = root.bool("card")? {
+            config.card = card;
+        }
+        if let Some(clock) = root.bool("clock")? {
+            config.clock = clock;
+        }
+        if let Some(section) = root.section("message")? {
+            config.message = Some(message(&section)?);
+        }
+        if let Some(section) = root.section("marquee")? {
+            config.marquee = Some(marquee(&section)?);
+        }
+        if let Some(sparkles) = root.f64("sparkles")? {
+            if sparkles < 0.0 {
+                return Err(root.invalid("sparkles", "must not be negative"));
+            }
+            config.sparkles = Some(sparkles);
+        }
+        if let Some(trail) = root.f64("trail")? {
+            if !(0.0..1.0).contains(&trail) {
+                return Err(root.invalid("trail", "must be at least 0 and less than 1"));
+            }
+            config.trail = Some(trail);
+        }
+        if let Some(bloom) = root.size("bloom")? {
+            config.bloom = Some(bloom);
+        }
+        if let Some(crt) = root.bool("crt")? {
+            config.crt = crt;
+        }
+        if let Some(phosphor) = root.bool("phosphor")? {
+            config.phosphor = phosphor;
+        }
+        if let Some(debug) = root.parse("debug")? {
+            config.debug = Some(debug);
+        }
+        if let Some(demo) = root.bool("demo")? {
+            config.demo = demo.then(Demo::default);
+        }
+        if let Some(insets) = root.bool("insets")? {
+            config.insets = insets;
+        }
+        if let Some(split) = root.list("split")? {
+            config.split = split;
+        }
+        if let Some(tile) = root.size("tile")? {
+            if tile == 0 {
+                return Err(root.invalid("tile", "must be at least 1"));
+            }
+            config.tile = Some(tile);
+        }
+        if let Some(tile_phase) = root.f64("tile_phase")? {
+            config.tile_phase = tile_phase;
+        }
+        if let Some(bounce) = root.bool("bounce")? {
+            config.bounce = bounce;
+        }
+        if let Some(section) = root.section("lissajous")? {
+            config.lissajous = Some(lissajous(&section)?);
+        }
+        if let Some(bpm) = root.f64("bpm")? {
+            if bpm <= 0.0 {
+                return Err(root.invalid("bpm", "must be positive"));
+            }
+            config.bpm = Some(bpm);
+        }
+        if let Some(orbit) = root.f64("orbit")? {
+            config.orbit = orbit;
+        }
+        if let Some(fps) = root.f64("fps")? {
+            if !(0.01..=1000.0).contains(&fps) {
+                return Err(root.invalid("fps", "must be between 0.01 and 1000"));
+            }
+            config.fps = Some(fps);
+        }
+        let objects = root.sections("object")?;
+        if !objects.is_empty() {
+            config.objects = objects.iter().map(object).collect::<Result<_, _>>()?;
+        }
+        let lights = root.sections("light")?;
+        if !lights.is_empty() {
+            config.lighting.lights = lights.iter().map(light).collect::<Result<_, _>>()?;
+        }
+        let occasions = root.sections("occasion")?;
+        if !occasions.is_empty() {
+            config.occasions = occasions.iter().map(occasion).collect::<Result<_, _>>()?;
+        }
+        Ok(())
+    }
+}
+
+fn camera_path(section: &Section) -> Result<CameraPath, ConfigError> {
+    section.allow_keys(&["loop", "keyframe"])?;
+    let looping = section.bool("loop")?.unwrap_or(true);
+
+    // Each keyframe inherits whatever it doesn't specify from the one before it.
+    let mut time = 0.0;
+    let mut previous = Keyframe::default();
+    let mut keyframes = Vec::new();
+    for keyframe in section.sections("keyframe")? {
+        keyframe.allow_keys(&[
+            "time",
+            "target",
+            "distance",
+            "azimuth",
+            "elevation",
+            "easing",
+        ])?;
+        time = keyframe.f64("time")?.unwrap_or(time);
+        let next = Keyframe {
+            time,
+            target: keyframe.vec3("target")?.unwrap_or(previous.target),
+            distance: keyframe.real("distance")?.unwrap_or(previous.distance),
+            azimuth: keyframe.real("azimuth")?.unwrap_or(previous.azimuth),
+            elevation: keyframe.real("elevation")?.unwrap_or(previous.elevation),
+            easing: keyframe.parse("easing")?.unwrap_or_default(),
+        };
+        if next.distance <= 0.0 {
+            return Err(keyframe.invalid("distance", "must be positive"));
+        }
+        keyframes.push(next);
+        previous = next;
+    }
+
+    if keyframes.is_empty() {
+        return Err(section.invalid("keyframe", "at least one keyframe is required"));
+    }
+    Ok(CameraPath::new(keyframes, looping))
+}
+
+fn occasion(section: &Section) -> Result<Occasion, ConfigError> {
+    section.allow_keys(&["name", "date", "theme", "shape"])?;
+    let date = section
+        .str("date")?
+        .ok_or_else(|| section.invalid("date", "a date like \"10-31\" is required"))?;
+    let (month, day) =
+        auto::parse_date(date).map_err(|message| section.invalid("date", &message))?;
+    Ok(Occasion {
+        name: section.str("name")?.unwrap_or(date).to_owned(),
+        month,
+        day,
+        theme: section.parse("theme")?,
+        shape: section.parse("shape")?,
+    })
+}
+
+fn object(section: &Section) -> Result<Object, ConfigError> {
+    const COMMON: [&str; 7] = [
+        "shape",
+        "position",
+        "scale",
+        "color",
+        "shininess",
+        "ramp",
+        "emissive",
+    ];
+    let positive = |key: &str, default: Real| match section.real(key)? {
+        Some(value) if value <= 0.0 => Err(section.invalid(key, "must be positive")),
+        value => Ok(value.unwrap_or(default)),
+    };
+    let shape = match section.parse("shape")?.unwrap_or_default() {
+        Shape::Heart => {
+            section.allow_keys(&COMMON)?;
+            Shape::Heart
+        }
+        Shape::Sphere { radius } => {
+            section.allow_keys(&[&COMMON[..], &["radius"]].concat())?;
+            Shape::Sphere {
+                radius: positive("radius", radius)?,
+            }
+        }
+        Shape::Torus {
+            major_radius,
+            minor_radius,
+        } => {
+            section.allow_keys(&[&COMMON[..], &["major_radius", "minor_radius"]].concat())?;
+            Shape::Torus {
+                major_radius: positive("major_radius", major_radius)?,
+                minor_radius: positive("minor_radius", minor_radius)?,
+            }
+        }
+        // Only built-in shapes have names.
+        Shape::Mesh(_) | Shape::Surface(_) | Shape::Heightmap(_) => unreachable!(),
+    };
+    let scale = positive("scale", 1.0)?;
+    let shininess = section.real("shininess")?;
+    if shininess.is_some_and(|shininess| shininess < 0.0) {
+        return Err(section.invalid("shininess", "must not be negative"));
+    }
+    Ok(Object {
+        shape,
+        transform: Transform {
+            position: section.vec3("position")?.unwrap_or_default(),
+            scale,
+            ..Transform::default()
+        },
+        material: Material {
+            color: section.color("color")?,
+            shininess,
+            ramp: section.ramp("ramp")?,
+            emissive: section.bool("emissive")?.unwrap_or(false),
+        },
+    })
+}
+
+fn message(section: &Section) -> Result<Message, ConfigError> {
+    section.allow_keys(&["text", "at", "under"])?;
+    let Some(text) = section.str("text")? else {
+        return Err(section.invalid("text", "missing"));
+    };
+    Ok(Message {
+        text: text.to_owned(),
+        placement: section.parse("at")?.unwrap_or_default(),
+        under: section.bool("under")?.unwrap_or(false),
+    })
+}
+
+fn marquee(section: &Section) -> Result<Marquee, ConfigError> {
+    section.allow_keys(&["text", "speed"])?;
+    let Some(text) = section.str("text")? else {
+        return Err(section.invalid("text", "missing"));
+    };
+    Ok(Marquee {
+        text: text.to_owned(),
+        speed: section.f64("speed")?.unwrap_or(Marquee::DEFAULT_SPEED),
+    })
+}
+
+fn lissajous(section: &Section) -> Result<Lissajous, ConfigError> {
+    section.allow_keys(&["across", "down", "phase", "period"])?;
+    let mut curve = Lissajous::default();
+    for (key, value) in [("across", &mut curve.across), ("down", &mut curve.down)] {
+        if let Some(frequency) = section.f64(key)? {
+            if frequency <= 0.0 {
+                return Err(section.invalid(key, "must be positive"));
+            }
+            *value = frequency;
+        }
+    }
+    if let Some(phase) = section.f64("phase")? {
+        curve.phase = phase.to_radians();
+    }
+    if let Some(period) = section.f64("period")? {
+        if period <= 0.0 {
+            return Err(section.invalid("period", "must be positive"));
+        }
+        curve.period = period;
+    }
+    Ok(curve)
+}
+
+fn playlist(section: &Section) -> Result<Playlist, ConfigError> {
+    section.allow_keys(&["every", "shapes"])?;
+    let mut playlist = Playlist::new(section.f64("every")?.unwrap_or(Playlist::DEFAULT_EVERY));
+    if playlist.every <= 0.0 {
+        return Err(section.invalid("every", "must be positive"));
+    }
+    if let Some(shapes) = section.list("shapes")? {
+        if shapes.is_empty() {
+            return Err(section.invalid("shapes", "at least one shape is required"));
+        }
+        playlist.shapes = shapes;
+    }
+    Ok(playlist)
+}
+
+fn light(section: &Section) -> Result<Light, ConfigError> {
+    let color = section.color("color")?.unwrap_or(Light::WHITE);
+    match section.str("type")?.unwrap_or("directional") {
+        "directional" => {
+            section.allow_keys(&["type", "direction", "color"])?;
+            let direction = section
+                .vec3("direction")?
+                .unwrap_or(Vec3::new(0.0, 0.0, -1.0));
+            if direction.length() == 0.0 {
+                return Err(section.invalid("direction", "must not be the zero vector"));
+            }
+            Ok(Light::Directional { direction, color })
+        }
+        "point" => {
+            section.allow_keys(&["type", "position", "range", "color"])?;
+            let Some(position) = section.vec3("position")? else {
+                return Err(section.invalid("position", "point lights need a position"));
+            };
+            let range = section.real("range")?.unwrap_or(Light::DEFAULT_RANGE);
+            if range <= 0.0 {
+                return Err(section.invalid("range", "must be positive"));
+            }
+            Ok(Light::Point {
+                position,
+                range,
+                color,
+            })
+        }
+        _ => Err(section.invalid("type", "expected \"directional\" or \"point\"")),
+    }
+}
+
+fn timeline(section: &Section) -> Result<Timeline, ConfigError> {
+    section.allow_keys(&["loop", "speed", "zoom", "light", "hue"])?;
+    Ok(Timeline {
+        speed: track(section, "speed", |key| key.f64("value"))?,
+        zoom: track(section, "zoom", |key| match key.f64("value")? {
+            Some(zoom) if zoom <= 0.0 => Err(key.invalid("value", "must be positive")),
+            zoom => Ok(zoom),
+        })?,
+        light: track(section, "light", |key| match key.vec3("value")? {
+            Some(light) if light.length() == 0.0 => {
+                Err(key.invalid("value", "must not be the zero vector"))
+            }
+            light => Ok(light),
+        })?,
+        hue: track(section, "hue", |key| key.f64("value"))?,
+        looping: section.bool("loop")?.unwrap_or(true),
+    })
+}
+
+/// Reads an array of `{ time, value, easing }` tables as a track. Like camera keyframes, a key
+/// without a time happens at the same time as the one before it.
+fn track<T: Lerp>(
+    section: &Section,
+    name: &str,
+    value: impl Fn(&Section) -> Result<Option<T>, ConfigError>,
+) -> Result<Option<Track<T>>, ConfigError> {
+    let mut time = 0.0;
+    let mut keys = Vec::new();
+    for key in section.sections(name)? {
+        key.allow_keys(&["time", "value", "easing"])?;
+        time = key.f64("time")?.unwrap_or(time);
+        let Some(value) = value(&key)? else {
+            return Err(key.invalid("value", "missing"));
+        };
+        keys.push(Key {
+            time,
+            value,
+            easing: key.parse("easing")?.unwrap_or_default(),
+        });
+    }
+    Ok((!keys.is_empty()).then(|| Track::new(keys)))
+}
+
+/// Why a configuration file couldn't be used.
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(PathBuf, io::Error),
+    /// The file isn't valid TOML.
+    Syntax {
+        line: usize,
+        message: String,
+    },
+    /// The file is valid TOML, but a setting has the wrong type or value.
+    Invalid {
+        key: String,
+        message: String,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(path, error) => write!(f, "{}: {error}", path.display()),
+            ConfigError::Syntax { line, message } => write!(f, "line {line}: {message}"),
+            ConfigError::Invalid { key, message } => write!(f, "{key}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Io(_, error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// A table in the configuration file, along with its dotted path for error messages.
+pub(crate) struct Section<'a> {
+    table: &'a Table,
+    path: String,
+}
+
+impl<'a> Section<'a> {
+    pub fn root(table: &'a Table) -> Section<'a> {
+        Section {
+            table,
+            path: String::new(),
+        }
+    }
+
+    pub fn allow_keys(&self, allowed: &[&str]) -> Result<(), ConfigError> {
+        match self
+            .table
+            .keys()
+            .find(|key| !allowed.contains(&key.as_str()))
+        {
+            Some(key) => Err(self.invalid(key, "unknown setting")),
+            None => Ok(()),
+        }
+    }
+
+    pub fn f64(&self, key: &str) -> Result<Option<f64>, ConfigError> {
+        self.get(key, "a number", |value| {
+            value.as_f64().filter(|n| n.is_finite())
+        })
+    }
+
+    /// A number, in the precision that geometry is worked out in.
+    pub fn real(&self, key: &str) -> Result<Option<Real>, ConfigError> {
+        self.get(key, "a number", |value| {
+            value.as_f64().map(|n| n as Real).filter(|n| n.is_finite())
+        })
+    }
+
+    /// A whole number of cells.
+    pub fn size(&self, key: &str) -> Result<Option<usize>, ConfigError> {
+        self.get(key, "a whole number", |value| match *value {
+            Value::Integer(n) => usize::try_from(n).ok(),
+            _ => None,
+        })
+    }
+
+    pub fn bool(&self, key: &str) -> Result<Option<bool>, ConfigError> {
+        self.get(key, "true or false", |value| match *value {
+            Value::Boolean(b) => Some(b),
+            _ => None,
+        })
+    }
+
+    pub fn str(&self, key: &str) -> Result<Option<&'a str>, ConfigError> {
+        self.get(key, "a string", |value| match value {
+            Value::String(s) => Some(s.as_str()),
+            _ => None,
+        })
+    }
+
+    /// A string of exactly one character.
+    pub fn char(&self, key: &str) -> Result<Option<char>, ConfigError> {
+        self.get(key, "a single character", |value| match value {
+            Value::String(s) => {
+                let mut chars = s.chars();
+                chars.next().filter(|_| chars.next().is_none())
+            }
+            _ => None,
+        })
+    }
+
+    /// A string naming one of `T`'s values, like an easing function.
+    pub fn parse<T: FromStr<Err = String>>(&self, key: &str) -> Result<Option<T>, ConfigError> {
+        match self.str(key)? {
+            Some(name) => name
+                .parse()
+                .map(Some)
+                .map_err(|message: String| self.invalid(key, &message)),
+            None => Ok(None),
+        }
+    }
+
+    /// An `[x, y, z]` array of numbers.
+    pub fn vec3(&self, key: &str) -> Result<Option<Vec3>, ConfigError> {
+        self.get(key, "an array of three numbers", |value| match value {
+            Value::Array(items) => match items[..] {
+                [ref x, ref y, ref z] => {
+                    let v = Vec3::new(
+                        x.as_f64()? as Real,
+                        y.as_f64()? as Real,
+                        z.as_f64()? as Real,
+                    );
+                    [v.x, v.y, v.z].iter().all(|n| n.is_finite()).then_some(v)
+                }
+                _ => None,
+            },
+            _ => None,
+        })
+    }
+
+    /// A colour written like `"#ff69b4"`.
+    pub fn color(&self, key: &str) -> Result<Option<[u8; 3]>, ConfigError> {
+        self.get(key, "a colour like \"#ff69b4\"", |value| match value {
+            Value::String(text) => color::parse_hex(text).ok(),
+            _ => None,
+        })
+    }
+
+    /// Characters to shade with, from darkest to brightest, written as a string of at least two.
+    pub fn ramp(&self, key: &str) -> Result<Option<Vec<char>>, ConfigError> {
+        let Some(ramp) = self.str(key)? else {
+            return Ok(None);
+        };
+        let ramp: Vec<char> = ramp.chars().collect();
+        if ramp.len() < 2 {
+            return Err(self.invalid(key, "expected at least two characters"));
+        }
+        Ok(Some(ramp))
+    }
+
+    /// An array of colours written like `"#ff69b4"`.
+    pub fn colors(&self, key: &str) -> Result<Option<Vec<[u8; 3]>>, ConfigError> {
+        self.get(
+            key,
+            "an array of colours like \"#ff69b4\"",
+            |value| match value {
+                Value::Array(items) => items
+                    .iter()
+                    .map(|item| match item {
+                        Value::String(text) => color::parse_hex(text).ok(),
+                        _ => None,
+                    })
+                    .collect(),
+                _ => None,
+            },
+        )
+    }
+
+    /// An array of strings, each naming one of `T`'s values.
+    pub fn list<T: FromStr<Err = String>>(&self, key: &str) -> Result<Option<Vec<T>>, ConfigError> {
+        let names = self.get(key, "an array of strings", |value| match value {
+            Value::Array(items) => items
+                .iter()
+                .map(|item| match item {
+                    Value::String(name) => Some(name.as_str()),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>(),
+            _ => None,
+        })?;
+        match names {
+            Some(names) => names
+                .into_iter()
+                .map(|name| {
+                    name.parse()
+                        .map_err(|message: String| self.invalid(key, &message))
+                })
+                .collect::<Result<_, _>>()
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+
+    pub fn section(&self, key: &str) -> Result<Option<Section<'a>>, ConfigError> {
+        let path = self.key_path(key);
+        self.get(key, "a table", |value| match value {
+            Value::Table(table) => Some(Section {
+                table,
+                path: path.clone(),
+            }),
+            _ => None,
+        })
+    }
+
+    /// An array of tables, like `[[camera.keyframe]]`. Missing arrays are empty.
+    pub fn sections(&self, key: &str) -> Result<Vec<Section<'a>>, ConfigError> {
+        let path = self.key_path(key);
+        let sections = self.get(key, "an array of tables", |value| match value {
+            Value::Array(items) => items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| match item {
+                    Value::Table(table) => Some(Section {
+                        table,
+                        path: format!("{path}[{i}]"),
+                    }),
+                    _ => None,
+                })
+                .collect(),
+            _ => None,
+        })?;
+        Ok(sections.unwrap_or_default())
+    }
+
+    pub fn invalid(&self, key: &str, message: &str) -> ConfigError {
+        ConfigError::Invalid {
+            key: self.key_path(key),
+            message: message.to_owned(),
+        }
+    }
+
+    fn get<T>(
+        &self,
+        key: &str,
+        expected: &str,
+        convert: impl FnOnce(&'a Value) -> Option<T>,
+    ) -> Result<Option<T>, ConfigError> {
+        let Some(value) = self.table.get(key) else {
+            return Ok(None);
+        };
+        match convert(value) {
+            Some(converted) => Ok(Some(converted)),
+            None => Err(self.invalid(
+                key,
+                &format!("expected {expected}, found {}", value.type_name()),
+            )),
+        }
+    }
+
+    fn key_path(&self, key: &str) -> String {
+        if self.path.is_empty() {
+            key.to_owned()
+        } else {
+            format!("{}.{key}", self.path)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::easing::Easing;
+
+    #[test]
+    fn empty_file_is_default() {
+        assert_eq!(Config::default(), Config::parse("").unwrap());
+    }
+
+    #[test]
+    fn keyframes_inherit_from_previous() {
+        let config = Config::parse(
+            r#"
+            [[camera.keyframe]]
+            distance = 90
+
+            [[camera.keyframe]]
+            time = 20
+            azimuth = 360
+            "#,
+        )
+        .unwrap();
+
+        let camera = config.camera.unwrap();
+        let keyframes: Vec<Keyframe> = camera.keyframes().collect();
+        assert_eq!(90.0, keyframes[1].distance);
+        assert_eq!(360.0, keyframes[1].azimuth);
+        assert!(camera.looping);
+        assert_eq!(Easing::Linear, keyframes[1].easing);
+    }
+
+    #[test]
+    fn timeline_tracks() {
+        let config = Config::parse(
+            r#"
+            [timeline]
+            loop = false
+            speed = [
+                { time = 0, value = 0 },
+                { time = 2, value = 1, easing = "ease-in-quad" },
+            ]
+            light = [{ value = [1, 0, -1] }]
+            hue = [{ time = 0, value = 0 }, { time = 4, value = 180 }]
+            "#,
+        )
+        .unwrap();
+
+        let timeline = config.timeline;
+        assert!(!timeline.looping);
+        assert!(timeline.zoom.is_none());
+        assert_eq!(0.25, timeline.at(1.0).speed);
+        assert_eq!(Some(Vec3::new(1.0, 0.0, -1.0)), timeline.at(1.0).light);
+        assert_eq!(45.0, timeline.at(1.0).hue);
+    }
+
+    #[test]
+    fn lights() {
+        let config = Config::parse(
+            r##"
+            [[light]]
+            direction = [0, 1, 0]
+
+            [[light]]
+            type = "point"
+            position = [1, 2, 3]
+            color = "#0000ff"
+            "##,
+        )
+        .unwrap();
+
+        assert_eq!(
+            vec![
+                Light::directional(Vec3::Y),
+                Light::point(Vec3::new(1.0, 2.0, 3.0)).with_color([0, 0, 255]),
+            ],
+            config.lighting.lights
+        );
+        assert!(Config::parse("[[light]]\ntype = \"point\"").is_err());
+    }
+
+    #[test]
+    fn objects() {
+        let config = Config::parse(
+            r##"
+            [[object]]
+
+            [[object]]
+            shape = "sphere"
+            radius = 2
+            position = [25, 0, 0]
+            shininess = 64
+            color = "#ff69b4"
+            ramp = "-+#"
+            emissive = true
+            "##,
+        )
+        .unwrap();
+
+        assert_eq!(Object::default(), config.objects[0]);
+        assert_eq!(Shape::Sphere { radius: 2.0 }, config.objects[1].shape);
+        assert_eq!(
+            Vec3::new(25.0, 0.0, 0.0),
+            config.objects[1].transform.position
+        );
+        assert_eq!(
+            Material {
+                color: Some([255, 105, 180]),
+                shininess: Some(64.0),
+                ramp: Some(vec!['-', '+', '#']),
+                emissive: true,
+            },
+            config.objects[1].material
+        );
+        assert!(Config::parse("[[object]]\nradius = 2").is_err());
+        assert!(Config::parse("[[object]]\nramp = \"#\"").is_err());
+    }
+
+    #[test]
+    fn cycle() {
+        let config =
+            Config::parse("cycle = { every = 4, shapes = [\"torus\", \"heart\"] }").unwrap();
+        let playlist = config.cycle.unwrap();
+        assert_eq!(4.0, playlist.every);
+        assert_eq!(Shape::Heart, playlist.shapes[1]);
+
+        let error = Config::parse("cycle = { shapes = [\"cube\"] }").unwrap_err();
+        assert_eq!("cycle.shapes: unknown shape \"cube\"", error.to_string());
+    }
+
+    #[test]
+    fn settings_refine_preset() {
+        let config = Config::parse("preset = \"duet\"\norbit = 0.1").unwrap();
+        assert_eq!(2, config.objects.len());
+        assert_eq!(0.1, config.orbit);
+    }
+
+    #[test]
+    fn reduced_motion_calms_everything_down() {
+        let mut config = Config::parse("bpm = 72\nsparkles = 40\nbackground = \"stars\"").unwrap();
+        config.reduce_motion();
+        assert!(config.reduced_motion);
+        assert!(config.spin.length() < Config::default().spin.length() / 2.0);
+        assert_eq!(
+            (None, None, Background::None),
+            (config.bpm, config.sparkles, config.background)
+        );
+    }
+
+    #[test]
+    fn trails_have_to_fade() {
+        assert_eq!(Some(0.8), Config::parse("trail = 0.8").unwrap().trail);
+        assert_eq!(Some(2), Config::parse("bloom = 2").unwrap().bloom);
+        let error = Config::parse("trail = 1").unwrap_err();
+        assert_eq!(
+            "trail: must be at least 0 and less than 1",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn split_screens() {
+        let config = Config::parse("split = [\"heart\", \"torus\"]").unwrap();
+        assert_eq!(2, config.split.len());
+        assert!(matches!(config.split[1], Shape::Torus { .. }));
+        let config = Config::parse("tile = 3\ntile_phase = 120").unwrap();
+        assert_eq!((Some(3), 120.0), (config.tile, config.tile_phase));
+        let error = Config::parse("tile = 0").unwrap_err();
+        assert_eq!("tile: must be at least 1", error.to_string());
+    }
+
+    #[test]
+    fn demo_mode() {
+        let config = Config::parse("demo = true").unwrap();
+        assert_eq!(Some(Demo::default()), config.demo);
+        assert_eq!(None, Config::parse("demo = false").unwrap().demo);
+    }
+
+    #[test]
+    fn debug_views() {
+        let config = Config::parse("debug = \"depth\"").unwrap();
+        assert_eq!(Some(DebugView::Depth), config.debug);
+        let error = Config::parse("debug = \"normals\"").unwrap_err();
+        assert_eq!("debug: unknown debug view \"normals\"", error.to_string());
+    }
+
+    #[test]
+    fn lissajous_paths() {
+        let text = "[lissajous]\nacross = 5\nphase = 180\nperiod = 30";
+        let curve = Config::parse(text).unwrap().lissajous.unwrap();
+        assert_eq!((5.0, 2.0, 30.0), (curve.across, curve.down, curve.period));
+        assert!((curve.phase - std::f64::consts::PI).abs() < 1e-12);
+        let error = Config::parse("[lissajous]\ndown = 0").unwrap_err();
+        assert_eq!("lissajous.down: must be positive", error.to_string());
+    }
+
+    #[test]
+    fn background_fill() {
+        let config = Config::parse("bg_char = \".\"\nbg_color = \"#200010\"").unwrap();
+        assert_eq!('.', config.bg_char);
+        assert_eq!(Some([32, 0, 16]), config.bg_color);
+        let error = Config::parse("bg_char = \"..\"").unwrap_err();
+        assert_eq!(
+            "bg_char: expected a single character, found a string",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn updates_keep_what_they_dont_mention() {
+        let mut config = Config::parse("theme = \"fire\"\ncrt = true").unwrap();
+        config.update("fps = 30").unwrap();
+        assert_eq!(
+            ("fire", true, Some(30.0)),
+            (config.theme.name.as_str(), config.crt, config.fps)
+        );
+        for fps in ["0", "1e-20", "1e300"] {
+            let error = config.update(&format!("fps = {fps}")).unwrap_err();
+            assert_eq!("fps: must be between 0.01 and 1000", error.to_string());
+        }
+        assert_eq!(Some(30.0), config.fps);
+        config.update("colorblind = \"protanopia\"").unwrap();
+        config.update("theme = \"ocean\"").unwrap();
+        assert_eq!(Some(Colorblind::Protanopia), config.colorblind);
+    }
+
+    #[test]
+    fn sizes_are_at_least_one_cell() {
+        let mut config = Config::parse("width = 60\nheight = 20").unwrap();
+        assert_eq!((Some(60), Some(20)), (config.width, config.height));
+        for key in ["width", "height"] {
+            let error = config.update(&format!("{key} = 0")).unwrap_err();
+            assert_eq!(format!("{key}: must be at least 1"), error.to_string());
+        }
+        assert_eq!((Some(60), Some(20)), (config.width, config.height));
+    }
+
+    #[test]
+    fn auto_themes_have_occasions() {
+        let text = r#"
+            theme = "auto"
+
+            [[occasion]]
+            name = "st-patricks-day"
+            date = "03-17"
+            theme = "matrix"
+            shape = "torus"
+
+            [[occasion]]
+            date = "12-25"
+        "#;
+        let mut config = Config::parse(text).unwrap();
+        assert!(config.auto);
+        assert_eq!(2, config.occasions.len());
+        let first = &config.occasions[0];
+        assert_eq!(
+            ("st-patricks-day", 3, 17, Some("matrix"), Some("torus")),
+            (
+                first.name.as_str(),
+                first.month,
+                first.day,
+                first.theme.as_ref().map(|theme| theme.name.as_str()),
+                first.shape.as_ref().map(Shape::name)
+            )
+        );
+        assert_eq!(
+            ("12-25", &None),
+            (
+                config.occasions[1].name.as_str(),
+                &config.occasions[1].theme
+            )
+        );
+        config.update("theme = \"fire\"").unwrap();
+        assert!(!config.auto);
+
+        for text in [
+            "[[occasion]]\ntheme = \"fire\"",
+            "[[occasion]]\ndate = \"02-30\"",
+            "[[occasion]]\ndate = \"10-31\"\ntheme = \"auto\"",
+        ] {
+            assert!(
+                matches!(Config::parse(text), Err(ConfigError::Invalid { .. })),
+                "{text}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_and_mistyped_settings() {
+        let error = Config::parse("spinn = [0, 0, 0]").unwrap_err();
+        assert_eq!("spinn: unknown setting", error.to_string());
+
+        let error = Config::parse("[[camera.keyframe]]\ntime = \"soon\"").unwrap_err();
+        assert_eq!(
+            "camera.keyframe[0].time: expected a number, found a string",
+            error.to_string()
+        );
+    }
+}
@@ -0,0 +1,120 @@
+//! An old CRT monitor's look: dark scanlines between the rows, rows that jitter sideways now and
+//! then, and the green glow of monochrome phosphor.
+
+use crate::color::Colors;
+use crate::random::Rng;
+
+/// The colour of lit phosphor.
+pub const PHOSPHOR: [u8; 3] = [64, 255, 96];
+
+/// How much of their brightness scanlines keep.
+const SCANLINE: f64 = 0.5;
+
+/// How many rows there are for each one that jitters, on average.
+const JITTER: usize = 16;
+
+/// Darkens every other row of `frame`, moving its characters halfway down `ramp` and dimming
+/// their `colors`. Characters that aren't on the ramp keep their shape, though not their colour.
+pub fn scanlines(frame: &mut [Vec<char>], colors: Option<&mut Colors>, ramp: &[char]) {
+    for row in frame.iter_mut().skip(1).step_by(2) {
+        for c in row {
+            if let Some(i) = ramp.iter().position(|r| r == c) {
+                *c = ramp[(i as f64 * SCANLINE) as usize];
+            }
+        }
+    }
+    for row in colors.into_iter().flatten().skip(1).step_by(2) {
+        for rgb in row.iter_mut().flatten() {
+            *rgb = rgb.map(|c| (f64::from(c) * SCANLINE).round() as u8);
+        }
+    }
+}
+
+/// Now and then shifts a row of `frame` a column to the left or right, along with its `colors`.
+pub fn jitter(frame: &mut [Vec<char>], colors: Option<&mut Colors>, rng: &mut Rng) {
+    let shifts: Vec<Option<bool>> = (0..frame.len())
+        .map(|_| (rng.below(JITTER) == 0).then(|| rng.below(2) == 0))
+        .collect();
+    for (row, &shift) in frame.iter_mut().zip(&shifts) {
+        shifted(row, shift, ' ');
+    }
+    for (row, &shift) in colors.into_iter().flatten().zip(&shifts) {
+        shifted(row, shift, None);
+    }
+}
+
+/// Redraws everything in shades of [`PHOSPHOR`] green, as bright as the colour it was, or fully
+/// bright where it was in the terminal's own colour.
+pub fn phosphor(frame: &[Vec<char>], colors: Option<Colors>) -> Colors {
+    let glow =
+        |brightness: u8| PHOSPHOR.map(|c| (u16::from(c) * u16::from(brightness) / 255) as u8);
+    (frame.iter().enumerate())
+        .map(|(y, row)| {
+            (row.iter().enumerate())
+                .map(|(x, &c)| {
+                    let color = colors.as_ref().and_then(|colors| colors[y][x]);
+                    match color {
+                        Some(rgb) => Some(glow(rgb.into_iter().max().unwrap_or(0))),
+                        None => (c != ' ').then_some(PHOSPHOR),
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Moves everything in `row` a place to the left, or to the right if `shift` is `Some(true)`,
+/// filling the gap with `gap`.
+fn shifted<T: Copy>(row: &mut [T], shift: Option<bool>, gap: T) {
+    if row.is_empty() {
+        return;
+    }
+    match shift {
+        Some(true) => {
+            row.rotate_right(1);
+            row[0] = gap;
+        }
+        Some(false) => {
+            row.rotate_left(1);
+            row[row.len() - 1] = gap;
+        }
+        None => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scanlines_darken_every_other_row() {
+        let ramp = ['.', ':', '=', '#', '@'];
+        let mut frame = vec![vec!['@', 'x'], vec!['@', 'x'], vec!['#', '.']];
+        let mut colors = vec![vec![Some([200, 100, 0]); 2]; 3];
+        scanlines(&mut frame, Some(&mut colors), &ramp);
+        assert_eq!(vec![vec!['@', 'x'], vec!['=', 'x'], vec!['#', '.']], frame);
+        assert_eq!(Some([100, 50, 0]), colors[1][0]);
+        assert_eq!(Some([200, 100, 0]), colors[2][1]);
+    }
+
+    #[test]
+    fn jittered_rows_move_one_column() {
+        let row: Vec<char> = "abc".chars().collect();
+        let mut frame = vec![row.clone(); 200];
+        jitter(&mut frame, None, &mut Rng::new(391));
+        let moved = frame.iter().filter(|moved| **moved != row).count();
+        assert!((1..50).contains(&moved), "{moved} rows moved");
+        for moved in &frame {
+            assert!([row.clone(), vec![' ', 'a', 'b'], vec!['b', 'c', ' ']].contains(moved));
+        }
+    }
+
+    #[test]
+    fn phosphor_is_all_green() {
+        let frame = vec![vec!['@', 'x', ' ']];
+        let colors = phosphor(&frame, Some(vec![vec![Some([255, 0, 0]), None, None]]));
+        assert_eq!(vec![vec![Some(PHOSPHOR), Some(PHOSPHOR), None]], colors);
+        let dim = phosphor(&frame, Some(vec![vec![Some([0, 0, 51]), None, None]]));
+        assert_eq!(Some([12, 51, 19]), dim[0][0]);
+    }
+}
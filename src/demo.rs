@@ -0,0 +1,185 @@
+//! A showcase of everything the animation can do, taking turns on a timer, for leaving running on
+//! a screen people walk past.
+
+use crate::background::Background;
+use crate::config::Config;
+use crate::overlay::{Message, Placement};
+use crate::playlist;
+use crate::preset::Preset;
+use crate::render::Style;
+use crate::scene::Shape;
+use crate::shading::Shading;
+use crate::theme::{Coloring, Theme};
+
+/// One turn of the demo: the flags that give the same look, shown along the bottom, and how it
+/// changes the configuration.
+struct Act {
+    flags: &'static str,
+    apply: fn(&mut Config),
+}
+
+/// Every turn of the demo, in order.
+const ACTS: [Act; 12] = [
+    Act {
+        flags: "",
+        apply: |_| {},
+    },
+    Act {
+        flags: "--theme valentine --sparkles 40",
+        apply: |config| {
+            config.theme = theme("valentine");
+            config.sparkles = Some(40.0);
+        },
+    },
+    Act {
+        flags: "--split torus --shading toon",
+        apply: |config| {
+            config.split = vec![Shape::BUILT_IN[2].clone()];
+            config.shading = Shading::Toon;
+        },
+    },
+    Act {
+        flags: "--theme fire --bloom 2 --bpm 72",
+        apply: |config| {
+            config.theme = theme("fire");
+            config.bloom = Some(2);
+            config.bpm = Some(72.0);
+        },
+    },
+    Act {
+        flags: "--preset duet --rainbow",
+        apply: |config| {
+            Preset::Duet.apply(config);
+            config.rainbow = true;
+        },
+    },
+    Act {
+        flags: "--render raymarch --blend 6 --split heart,sphere",
+        apply: |config| {
+            config.render = Style::Raymarch;
+            config.blend = 6.0;
+            config.split = vec![Shape::Heart, Shape::BUILT_IN[1].clone()];
+        },
+    },
+    Act {
+        flags: "--crt --phosphor",
+        apply: |config| {
+            config.crt = true;
+            config.phosphor = true;
+        },
+    },
+    Act {
+        flags: "--trail 0.8 --bounce",
+        apply: |config| {
+            config.trail = Some(0.8);
+            config.bounce = true;
+        },
+    },
+    Act {
+        flags: "--theme ocean --background plasma",
+        apply: |config| {
+            config.theme = theme("ocean");
+            config.background = Background::Plasma;
+        },
+    },
+    Act {
+        flags: "--color-by depth --insets",
+        apply: |config| {
+            config.color_by = Coloring::Depth;
+            config.insets = true;
+        },
+    },
+    Act {
+        flags: "--theme matrix --background stars --render points",
+        apply: |config| {
+            config.theme = theme("matrix");
+            config.background = Background::Stars;
+            config.render = Style::Points;
+        },
+    },
+    Act {
+        flags: "--tile 3 --tile-phase 120",
+        apply: |config| {
+            config.tile = Some(3);
+            config.tile_phase = 120.0;
+        },
+    },
+];
+
+/// Takes turns showing off different looks.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Demo {
+    /// How many seconds each look is shown for, including its transitions.
+    pub every: f64,
+}
+
+impl Demo {
+    pub const DEFAULT_EVERY: f64 = 8.0;
+
+    /// Which turn it is at `time`. Starts over after the last one.
+    pub fn turn(&self, time: f64) -> usize {
+        (time / self.every).floor().rem_euclid(ACTS.len() as f64) as usize
+    }
+
+    /// How much to scale the objects by at `time`, as each look grows in and shrinks away.
+    pub fn scale(&self, time: f64) -> f64 {
+        playlist::transition(time.rem_euclid(self.every), self.every)
+    }
+
+    /// `base` as it looks on `turn`, with a message along the bottom saying how to get the same
+    /// look, unless `base` already has a message of its own.
+    pub fn config(&self, base: &Config, turn: usize) -> Config {
+        let act = &ACTS[turn % ACTS.len()];
+        let mut config = base.clone();
+        (act.apply)(&mut config);
+        if config.reduced_motion {
+            config.calm_down();
+        }
+        if config.message.is_none() && !act.flags.is_empty() {
+            config.message = Some(Message {
+                text: format!("ascii-love {}", act.flags),
+                placement: Placement::Bottom,
+                under: false,
+            });
+        }
+        config
+    }
+}
+
+impl Default for Demo {
+    fn default() -> Demo {
+        Demo {
+            every: Demo::DEFAULT_EVERY,
+        }
+    }
+}
+
+/// One of the built-in themes.
+fn theme(name: &str) -> Theme {
+    name.parse().expect("the demo only uses built-in themes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn takes_turns_showing_off() {
+        let demo = Demo::default();
+        assert_eq!(0, demo.turn(1.0));
+        assert_eq!(1, demo.turn(demo.every + 1.0));
+        assert_eq!(0, demo.turn(demo.every * ACTS.len() as f64));
+        assert!(demo.scale(0.0) < 1e-9);
+        assert_eq!(1.0, demo.scale(demo.every / 2.0));
+
+        let base = Config::default();
+        assert_eq!(base, demo.config(&base, 0));
+        let fire = demo.config(&base, 3);
+        assert_eq!(("fire", Some(2)), (fire.theme.name.as_str(), fire.bloom));
+        let message = fire.message.unwrap();
+        assert_eq!("ascii-love --theme fire --bloom 2 --bpm 72", message.text);
+        for turn in 0..ACTS.len() {
+            demo.config(&base, turn);
+        }
+    }
+}
@@ -0,0 +1,210 @@
+//! What the animation says about itself with --verbose, for working out why it's slow or drawing
+//! the wrong thing. Events are written a line each as `key=value` pairs, so they're easy to read
+//! and easy to pick apart with `grep` and `awk`.
+
+use std::fmt;
+
+use crate::color::Colors;
+
+/// Something that happened, like the terminal being resized.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Event {
+    pub name: &'static str,
+    pub fields: Vec<(&'static str, String)>,
+}
+
+impl Event {
+    pub fn new(name: &'static str) -> Event {
+        Event {
+            name,
+            fields: Vec::new(),
+        }
+    }
+
+    /// Adds `key=value` to the line.
+    pub fn with(mut self, key: &'static str, value: impl fmt::Display) -> Event {
+        self.fields.push((key, value.to_string()));
+        self
+    }
+}
+
+impl fmt::Display for Event {
+    /// Like `event=resize width=80 height=24`. Values with spaces, quotes, or equals signs in
+    /// them, or nothing at all, are quoted.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "event={}", self.name)?;
+        for (key, value) in &self.fields {
+            let plain = !value.is_empty()
+                && !(value.chars()).any(|c| c.is_whitespace() || c == '"' || c == '=');
+            if plain {
+                write!(f, " {key}={value}")?;
+            } else {
+                write!(f, " {key}={value:?}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How long the frames since the last report took to draw.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FrameTimes {
+    /// How many seconds a frame can take to draw before it's counted as dropped: as long as the
+    /// pause between frames, which halves the frame rate.
+    pub budget: f64,
+    times: Vec<f64>,
+}
+
+impl FrameTimes {
+    pub fn new(budget: f64) -> FrameTimes {
+        FrameTimes {
+            budget,
+            times: Vec::new(),
+        }
+    }
+
+    /// Counts a frame that took `seconds` to draw.
+    pub fn record(&mut self, seconds: f64) {
+        self.times.push(seconds);
+    }
+
+    /// How long the frame `p` percent of the way from fastest to slowest took, or `None` before
+    /// there have been any.
+    pub fn percentile(&self, p: f64) -> Option<f64> {
+        let mut times = self.times.clone();
+        times.sort_by(f64::total_cmp);
+        // The nearest rank: the smallest time at least `p` percent of frames were as quick as.
+        let rank = (p / 100.0 * times.len() as f64).ceil() as usize;
+        times.get(rank.clamp(1, times.len().max(1)) - 1).copied()
+    }
+
+    /// How many frames took longer than the budget.
+    pub fn dropped(&self) -> usize {
+        self.times
+            .iter()
+            .filter(|&&time| time > self.budget)
+            .count()
+    }
+
+    /// An event saying how many frames there were, how long they took in milliseconds, and how
+    /// many were dropped, then starts counting again. `None` if there haven't been any.
+    pub fn report(&mut self) -> Option<Event> {
+        let milliseconds = |p| format!("{:.1}", self.percentile(p).unwrap_or(0.0) * 1000.0);
+        let event = (!self.times.is_empty()).then(|| {
+            Event::new("frames")
+                .with("count", self.times.len())
+                .with("p50_ms", milliseconds(50.0))
+                .with("p95_ms", milliseconds(95.0))
+                .with("p99_ms", milliseconds(99.0))
+                .with("dropped", self.dropped())
+        });
+        self.times.clear();
+        event
+    }
+}
+
+/// A hash that's the same on every platform, for checking that frames come out the same after
+/// changes to the renderer: 64-bit FNV-1a.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Checksum(u64);
+
+impl Checksum {
+    pub fn new() -> Checksum {
+        Checksum(0xcbf2_9ce4_8422_2325)
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(0x100_0000_01b3);
+        }
+    }
+
+    pub fn value(self) -> u64 {
+        self.0
+    }
+
+    /// The checksum of a frame: its characters a row at a time, then the colour of each one.
+    pub fn frame(cells: &[Vec<char>], colors: Option<&Colors>) -> u64 {
+        let mut checksum = Checksum::new();
+        for row in cells {
+            for c in row {
+                checksum.write(c.encode_utf8(&mut [0; 4]).as_bytes());
+            }
+            checksum.write(b"\n");
+        }
+        for color in colors.into_iter().flatten().flatten() {
+            match color {
+                Some(rgb) => checksum.write(&[&[1][..], rgb].concat()),
+                None => checksum.write(&[0]),
+            }
+        }
+        checksum.value()
+    }
+}
+
+impl Default for Checksum {
+    fn default() -> Checksum {
+        Checksum::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_are_key_value_pairs() {
+        let event = Event::new("start")
+            .with("term", "xterm")
+            .with("size", "80 x 24");
+        assert_eq!(
+            r#"event=start term=xterm size="80 x 24""#,
+            event.to_string()
+        );
+        assert_eq!(
+            r#"event=start term="""#,
+            Event::new("start").with("term", "").to_string()
+        );
+    }
+
+    #[test]
+    fn frame_times_report_percentiles() {
+        let mut times = FrameTimes::new(0.045);
+        assert_eq!((None, None), (times.percentile(50.0), times.report()));
+        for i in 1..=100 {
+            times.record(f64::from(i) / 1000.0);
+        }
+        assert_eq!(Some(0.05), times.percentile(50.0));
+        assert_eq!(Some(0.001), times.percentile(0.0));
+        assert_eq!(Some(0.1), times.percentile(100.0));
+        assert_eq!(55, times.dropped());
+        let report = times.report().unwrap().to_string();
+        assert_eq!(
+            "event=frames count=100 p50_ms=50.0 p95_ms=95.0 p99_ms=99.0 dropped=55",
+            report
+        );
+        assert_eq!(None, times.report());
+    }
+
+    #[test]
+    fn checksums_change_with_any_cell() {
+        let mut checksum = Checksum::new();
+        checksum.write(b"a");
+        assert_eq!(0xaf63_dc4c_8601_ec8c, checksum.value());
+
+        let cells = vec![vec!['#', '.'], vec!['.', ' ']];
+        let colors = vec![vec![Some([255, 0, 0]), None], vec![None, None]];
+        let frame = Checksum::frame(&cells, Some(&colors));
+        assert_eq!(
+            frame,
+            Checksum::frame(&cells.clone(), Some(&colors.clone()))
+        );
+        assert_ne!(frame, Checksum::frame(&cells, None));
+        let mut recolored = colors.clone();
+        recolored[0][0] = Some([254, 0, 0]);
+        assert_ne!(frame, Checksum::frame(&cells, Some(&recolored)));
+        // Rows are kept apart, so the same characters wrapped differently don't match.
+        let rewrapped = vec![vec!['#'], vec!['.', '.', ' ']];
+        assert_ne!(frame, Checksum::frame(&rewrapped, Some(&colors)));
+    }
+}
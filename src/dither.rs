@@ -0,0 +1,156 @@
+//! Dithering hides the banding between the few brightness levels a character ramp can show.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::math::Real;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Dither {
+    /// Every cell gets the level nearest to its brightness.
+    #[default]
+    None,
+    /// Error diffusion: each cell passes its rounding error on to its unvisited neighbours.
+    FloydSteinberg,
+    /// Ordered dithering with a 4×4 Bayer threshold matrix, which doesn't shimmer as much
+    /// between frames.
+    Bayer,
+}
+
+impl Dither {
+    pub const ALL: [Dither; 3] = [Dither::None, Dither::FloydSteinberg, Dither::Bayer];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Dither::None => "none",
+            Dither::FloydSteinberg => "floyd-steinberg",
+            Dither::Bayer => "bayer",
+        }
+    }
+}
+
+impl fmt::Display for Dither {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Dither {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Dither, String> {
+        Dither::ALL
+            .into_iter()
+            .find(|dither| dither.name() == name)
+            .ok_or_else(|| format!("unknown dithering {name:?}"))
+    }
+}
+
+const BAYER: [[Real; 4]; 4] = [
+    [0.0, 8.0, 2.0, 10.0],
+    [12.0, 4.0, 14.0, 6.0],
+    [3.0, 11.0, 1.0, 9.0],
+    [15.0, 7.0, 13.0, 5.0],
+];
+
+/// Quantizes continuous levels into whole levels between 0 and `max`.
+///
+/// Level `n` stands for everything from `n` up to (but not including) `n + 1`, so without
+/// dithering levels are simply truncated. Cells without a level are left empty, and don't take
+/// part in error diffusion.
+pub fn quantize(
+    levels: &[Vec<Option<Real>>],
+    max: usize,
+    dither: Dither,
+) -> Vec<Vec<Option<usize>>> {
+    let clamp = |level: Real| (level.floor().max(0.0) as usize).min(max);
+
+    match dither {
+        Dither::None => levels
+            .iter()
+            .map(|row| row.iter().map(|level| level.map(clamp)).collect())
+            .collect(),
+        Dither::Bayer => levels
+            .iter()
+            .enumerate()
+            .map(|(y, row)| {
+                row.iter()
+                    .enumerate()
+                    .map(|(x, level)| {
+                        let threshold = (BAYER[y % 4][x % 4] + 0.5) / 16.0 - 0.5;
+                        level.map(|level| clamp(level + threshold))
+                    })
+                    .collect()
+            })
+            .collect(),
+        Dither::FloydSteinberg => {
+            let mut levels = levels.to_vec();
+            let mut output = vec![vec![None; levels.first().map_or(0, Vec::len)]; levels.len()];
+            for y in 0..levels.len() {
+                for x in 0..levels[y].len() {
+                    let Some(level) = levels[y][x] else {
+                        continue;
+                    };
+                    let quantized = clamp(level);
+                    output[y][x] = Some(quantized);
+
+                    // The middle of the level's range is what it represents.
+                    let error = level - (quantized as Real + 0.5);
+                    let mut spread = |dx: isize, dy: usize, weight: Real| {
+                        let nx = x.wrapping_add_signed(dx);
+                        let cell = levels.get_mut(y + dy).and_then(|row| row.get_mut(nx));
+                        if let Some(Some(neighbour)) = cell {
+                            *neighbour += error * weight;
+                        }
+                    };
+                    spread(1, 0, 7.0 / 16.0);
+                    spread(-1, 1, 3.0 / 16.0);
+                    spread(0, 1, 5.0 / 16.0);
+                    spread(1, 1, 1.0 / 16.0);
+                }
+            }
+            output
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn average(output: &[Vec<Option<usize>>]) -> Real {
+        let cells: Vec<usize> = output.iter().flatten().flatten().copied().collect();
+        cells.iter().sum::<usize>() as Real / cells.len() as Real
+    }
+
+    #[test]
+    fn without_dithering_levels_are_truncated() {
+        let levels = vec![vec![Some(0.2), Some(1.9), None, Some(-3.0), Some(99.0)]];
+        assert_eq!(
+            vec![vec![Some(0), Some(1), None, Some(0), Some(11)]],
+            quantize(&levels, 11, Dither::None)
+        );
+    }
+
+    #[test]
+    fn dithering_preserves_average_brightness() {
+        // A quarter of the way from what level 4 stands for (4.5) to what level 5 does (5.5).
+        let levels = vec![vec![Some(4.75); 32]; 32];
+        for dither in [Dither::FloydSteinberg, Dither::Bayer] {
+            let output = quantize(&levels, 11, dither);
+            assert!(output
+                .iter()
+                .flatten()
+                .all(|&level| matches!(level, Some(4 | 5))));
+            let average = average(&output);
+            assert!((average - 4.25).abs() < 0.02, "{dither}: {average}");
+        }
+    }
+
+    #[test]
+    fn error_diffusion_skips_empty_cells() {
+        let levels = vec![vec![Some(0.9), None, Some(0.5)]];
+        let output = quantize(&levels, 11, Dither::FloydSteinberg);
+        assert_eq!(vec![vec![Some(0), None, Some(0)]], output);
+    }
+}
@@ -0,0 +1,238 @@
+//! Easing functions for speed ramps, pulses, and transitions.
+//!
+//! Each function maps progress `t` in `0.0..=1.0` to an eased progress, with `f(0) = 0` and
+//! `f(1) = 1`. Elastic and back easings briefly overshoot that range on purpose.
+
+use std::f64::consts::PI;
+use std::fmt;
+use std::str::FromStr;
+
+pub fn linear(t: f64) -> f64 {
+    t
+}
+
+pub fn ease_in_quad(t: f64) -> f64 {
+    t * t
+}
+
+pub fn ease_out_quad(t: f64) -> f64 {
+    1.0 - (1.0 - t) * (1.0 - t)
+}
+
+pub fn ease_in_out_quad(t: f64) -> f64 {
+    if t < 0.5 {
+        2.0 * t * t
+    } else {
+        1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+    }
+}
+
+pub fn ease_in_cubic(t: f64) -> f64 {
+    t * t * t
+}
+
+pub fn ease_out_cubic(t: f64) -> f64 {
+    1.0 - (1.0 - t).powi(3)
+}
+
+pub fn ease_in_out_cubic(t: f64) -> f64 {
+    if t < 0.5 {
+        4.0 * t * t * t
+    } else {
+        1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+    }
+}
+
+pub fn ease_in_out_sine(t: f64) -> f64 {
+    -((PI * t).cos() - 1.0) / 2.0
+}
+
+/// Pulls back slightly before moving forward.
+pub fn ease_in_back(t: f64) -> f64 {
+    const C1: f64 = 1.70158;
+    const C3: f64 = C1 + 1.0;
+    C3 * t * t * t - C1 * t * t
+}
+
+/// Overshoots the target slightly before settling.
+pub fn ease_out_back(t: f64) -> f64 {
+    1.0 - ease_in_back(1.0 - t)
+}
+
+/// Springs past the target and wobbles back into place.
+pub fn ease_out_elastic(t: f64) -> f64 {
+    const C4: f64 = 2.0 * PI / 3.0;
+    if t <= 0.0 {
+        0.0
+    } else if t >= 1.0 {
+        1.0
+    } else {
+        2f64.powf(-10.0 * t) * ((t * 10.0 - 0.75) * C4).sin() + 1.0
+    }
+}
+
+pub fn ease_in_elastic(t: f64) -> f64 {
+    1.0 - ease_out_elastic(1.0 - t)
+}
+
+/// Lands on the target like a dropped ball, bouncing a few times.
+pub fn ease_out_bounce(t: f64) -> f64 {
+    const N1: f64 = 7.5625;
+    const D1: f64 = 2.75;
+    if t < 1.0 / D1 {
+        N1 * t * t
+    } else if t < 2.0 / D1 {
+        let t = t - 1.5 / D1;
+        N1 * t * t + 0.75
+    } else if t < 2.5 / D1 {
+        let t = t - 2.25 / D1;
+        N1 * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D1;
+        N1 * t * t + 0.984375
+    }
+}
+
+pub fn ease_in_bounce(t: f64) -> f64 {
+    1.0 - ease_out_bounce(1.0 - t)
+}
+
+/// A named easing function, for choosing one in a configuration file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Easing {
+    #[default]
+    Linear,
+    EaseInQuad,
+    EaseOutQuad,
+    EaseInOutQuad,
+    EaseInCubic,
+    EaseOutCubic,
+    EaseInOutCubic,
+    EaseInOutSine,
+    EaseInBack,
+    EaseOutBack,
+    EaseInElastic,
+    EaseOutElastic,
+    EaseInBounce,
+    EaseOutBounce,
+}
+
+impl Easing {
+    pub const ALL: [Easing; 14] = [
+        Easing::Linear,
+        Easing::EaseInQuad,
+        Easing::EaseOutQuad,
+        Easing::EaseInOutQuad,
+        Easing::EaseInCubic,
+        Easing::EaseOutCubic,
+        Easing::EaseInOutCubic,
+        Easing::EaseInOutSine,
+        Easing::EaseInBack,
+        Easing::EaseOutBack,
+        Easing::EaseInElastic,
+        Easing::EaseOutElastic,
+        Easing::EaseInBounce,
+        Easing::EaseOutBounce,
+    ];
+
+    /// Eases `t`, which is clamped to `0.0..=1.0` first.
+    pub fn apply(self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        let function = match self {
+            Easing::Linear => linear,
+            Easing::EaseInQuad => ease_in_quad,
+            Easing::EaseOutQuad => ease_out_quad,
+            Easing::EaseInOutQuad => ease_in_out_quad,
+            Easing::EaseInCubic => ease_in_cubic,
+            Easing::EaseOutCubic => ease_out_cubic,
+            Easing::EaseInOutCubic => ease_in_out_cubic,
+            Easing::EaseInOutSine => ease_in_out_sine,
+            Easing::EaseInBack => ease_in_back,
+            Easing::EaseOutBack => ease_out_back,
+            Easing::EaseInElastic => ease_in_elastic,
+            Easing::EaseOutElastic => ease_out_elastic,
+            Easing::EaseInBounce => ease_in_bounce,
+            Easing::EaseOutBounce => ease_out_bounce,
+        };
+        function(t)
+    }
+
+    /// The name used in configuration files, like `ease-in-out-cubic`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Easing::Linear => "linear",
+            Easing::EaseInQuad => "ease-in-quad",
+            Easing::EaseOutQuad => "ease-out-quad",
+            Easing::EaseInOutQuad => "ease-in-out-quad",
+            Easing::EaseInCubic => "ease-in-cubic",
+            Easing::EaseOutCubic => "ease-out-cubic",
+            Easing::EaseInOutCubic => "ease-in-out-cubic",
+            Easing::EaseInOutSine => "ease-in-out-sine",
+            Easing::EaseInBack => "ease-in-back",
+            Easing::EaseOutBack => "ease-out-back",
+            Easing::EaseInElastic => "ease-in-elastic",
+            Easing::EaseOutElastic => "ease-out-elastic",
+            Easing::EaseInBounce => "ease-in-bounce",
+            Easing::EaseOutBounce => "ease-out-bounce",
+        }
+    }
+}
+
+impl fmt::Display for Easing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Easing {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Easing, String> {
+        Easing::ALL
+            .into_iter()
+            .find(|easing| easing.name() == name)
+            .ok_or_else(|| format!("unknown easing {name:?}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_easing_starts_at_zero_and_ends_at_one() {
+        for easing in Easing::ALL {
+            assert!(easing.apply(0.0).abs() < 1e-9, "{easing} at 0");
+            assert!((easing.apply(1.0) - 1.0).abs() < 1e-9, "{easing} at 1");
+        }
+    }
+
+    #[test]
+    fn in_out_easings_are_symmetric() {
+        for easing in [
+            Easing::EaseInOutQuad,
+            Easing::EaseInOutCubic,
+            Easing::EaseInOutSine,
+        ] {
+            assert!((easing.apply(0.5) - 0.5).abs() < 1e-9, "{easing}");
+            let (a, b) = (easing.apply(0.2), easing.apply(0.8));
+            assert!((a + b - 1.0).abs() < 1e-9, "{easing}");
+        }
+    }
+
+    #[test]
+    fn bounce_never_overshoots() {
+        for t in crate::ToFloatRangeIter::by(0.0..1.0, 0.01) {
+            let eased = ease_out_bounce(t);
+            assert!((0.0..=1.0).contains(&eased), "{t} -> {eased}");
+        }
+    }
+
+    #[test]
+    fn names_round_trip() {
+        for easing in Easing::ALL {
+            assert_eq!(Ok(easing), easing.name().parse());
+        }
+        assert!("ease-sideways".parse::<Easing>().is_err());
+    }
+}
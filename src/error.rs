@@ -0,0 +1,116 @@
+//! One error type for everything in the library that can fail, for programs that would rather
+//! handle every failure in one place. Each module's own error converts into it with `?`.
+
+use std::fmt;
+
+#[cfg(feature = "audio")]
+use crate::audio::AudioError;
+use crate::config::ConfigError;
+use crate::image::ImageError;
+use crate::mesh::MeshError;
+use crate::FloatRangeError;
+
+/// Anything that went wrong, by where it went wrong.
+#[derive(Debug)]
+pub enum Error {
+    Config(ConfigError),
+    Mesh(MeshError),
+    Image(ImageError),
+    #[cfg(feature = "audio")]
+    Audio(AudioError),
+    /// The terminal's terminfo entry couldn't be read.
+    Terminal(String),
+    Range(FloatRangeError),
+}
+
+/// A result whose error is [`Error`].
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Config(error) => error.fmt(f),
+            Error::Mesh(error) => error.fmt(f),
+            Error::Image(error) => error.fmt(f),
+            #[cfg(feature = "audio")]
+            Error::Audio(error) => error.fmt(f),
+            Error::Terminal(message) => write!(f, "terminfo: {message}"),
+            Error::Range(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Config(error) => Some(error),
+            Error::Mesh(error) => Some(error),
+            Error::Image(error) => Some(error),
+            #[cfg(feature = "audio")]
+            Error::Audio(error) => Some(error),
+            Error::Terminal(_) => None,
+            Error::Range(error) => Some(error),
+        }
+    }
+}
+
+impl From<ConfigError> for Error {
+    fn from(error: ConfigError) -> Error {
+        Error::Config(error)
+    }
+}
+
+impl From<MeshError> for Error {
+    fn from(error: MeshError) -> Error {
+        Error::Mesh(error)
+    }
+}
+
+impl From<ImageError> for Error {
+    fn from(error: ImageError) -> Error {
+        Error::Image(error)
+    }
+}
+
+#[cfg(feature = "audio")]
+impl From<AudioError> for Error {
+    fn from(error: AudioError) -> Error {
+        Error::Audio(error)
+    }
+}
+
+impl From<FloatRangeError> for Error {
+    fn from(error: FloatRangeError) -> Error {
+        Error::Range(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::ToFloatRangeIter;
+
+    #[test]
+    fn module_errors_convert_with_question_marks() {
+        fn load() -> Result<()> {
+            Config::parse("spin = true")?;
+            Ok(())
+        }
+        fn step() -> Result<()> {
+            (0.0..1.0).try_by(0.0)?;
+            Ok(())
+        }
+        let error = load().unwrap_err();
+        assert!(matches!(error, Error::Config(_)));
+        assert!(std::error::Error::source(&error).is_some());
+        assert!(matches!(
+            step(),
+            Err(Error::Range(FloatRangeError::Step(_)))
+        ));
+        assert_eq!(
+            "step must be a positive number, not 0",
+            step().unwrap_err().to_string()
+        );
+    }
+}
@@ -0,0 +1,117 @@
+//! A C interface to the renderer, for drawing the heart from programs in other languages. Only
+//! built with the `ffi` feature. The declarations are in `include/ascii_love.h`.
+
+use std::ptr;
+
+use crate::math::{Quat, Real, Vec3};
+use crate::render::{Frame, Settings};
+use crate::scene::{Object, Scene, Transform};
+
+/// What a C program holds on to between frames.
+pub struct AlRenderer {
+    width: usize,
+    height: usize,
+    scene: Scene,
+    settings: Settings,
+}
+
+/// Makes a renderer that draws the heart `width` columns wide and `height` rows tall. Free it
+/// with [`al_renderer_free`].
+#[no_mangle]
+pub extern "C" fn al_renderer_new(width: u32, height: u32) -> *mut AlRenderer {
+    let renderer = AlRenderer {
+        width: width as usize,
+        height: height as usize,
+        scene: Scene {
+            objects: vec![Object::default()],
+            ..Scene::default()
+        },
+        settings: Settings::default(),
+    };
+    Box::into_raw(Box::new(renderer))
+}
+
+/// Frees a renderer made by [`al_renderer_new`]. Null pointers are ignored.
+///
+/// # Safety
+///
+/// `renderer` must be null or have come from [`al_renderer_new`], and not have been freed.
+#[no_mangle]
+pub unsafe extern "C" fn al_renderer_free(renderer: *mut AlRenderer) {
+    if !renderer.is_null() {
+        drop(Box::from_raw(renderer));
+    }
+}
+
+/// Draws the heart turned `a` radians around the X axis and `b` around the Z axis, as rows of
+/// UTF-8 text each ending in a newline.
+///
+/// Like `snprintf`, returns the length of the whole frame in bytes, not counting the NUL at the
+/// end, and only writes it to `buffer` if there's room for all of it and the NUL. Returns 0 if
+/// `renderer` is null.
+///
+/// # Safety
+///
+/// `renderer` must have come from [`al_renderer_new`], and `buffer` must be null or point to at
+/// least `size` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn al_render_frame(
+    renderer: *mut AlRenderer,
+    a: f64,
+    b: f64,
+    buffer: *mut u8,
+    size: usize,
+) -> usize {
+    let Some(renderer) = renderer.as_mut() else {
+        return 0;
+    };
+    let orientation =
+        Quat::from_axis_angle(Vec3::Z, b as Real) * Quat::from_axis_angle(Vec3::X, a as Real);
+    renderer.scene.objects[0].transform = Transform {
+        orientation,
+        ..Transform::default()
+    };
+    let frame = Frame::render(
+        &renderer.scene,
+        &renderer.settings,
+        renderer.width,
+        renderer.height,
+    )
+    .to_string();
+    if !buffer.is_null() && frame.len() < size {
+        ptr::copy_nonoverlapping(frame.as_ptr(), buffer, frame.len());
+        *buffer.add(frame.len()) = 0;
+    }
+    frame.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    #[test]
+    fn renders_into_a_buffer_like_snprintf() {
+        let renderer = al_renderer_new(40, 12);
+        unsafe {
+            let needed = al_render_frame(renderer, 0.0, 0.0, ptr::null_mut(), 0);
+            assert_eq!(12 * 41, needed);
+
+            let mut small = vec![b'x'; needed];
+            al_render_frame(renderer, 0.0, 0.0, small.as_mut_ptr(), small.len());
+            assert!(small.iter().all(|&byte| byte == b'x'));
+
+            let mut buffer = vec![0; needed + 1];
+            al_render_frame(renderer, 0.0, 0.0, buffer.as_mut_ptr(), buffer.len());
+            let text = CStr::from_bytes_with_nul(&buffer)
+                .unwrap()
+                .to_str()
+                .unwrap();
+            assert_eq!(12, text.lines().count());
+            assert!(text.chars().any(|c| !c.is_whitespace()));
+
+            al_renderer_free(renderer);
+            al_renderer_free(ptr::null_mut());
+        }
+    }
+}
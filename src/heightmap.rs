@@ -0,0 +1,70 @@
+//! Terrain raised from a greyscale image, brighter pixels higher.
+
+use crate::image::Image;
+use crate::math::{Real, Vec3};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Heightmap {
+    image: Image,
+}
+
+impl Heightmap {
+    /// How wide the terrain is across its longer side, about the width of the heart.
+    const SIZE: Real = 36.0;
+    /// How far the brightest pixels rise above the darkest.
+    const HEIGHT: Real = 8.0;
+
+    pub fn new(image: Image) -> Heightmap {
+        Heightmap { image }
+    }
+
+    /// The terrain's width along X and depth along Z, keeping the image's proportions.
+    fn extent(&self) -> (Real, Real) {
+        let (width, height) = (self.image.width as Real, self.image.height as Real);
+        let longest = width.max(height);
+        (
+            Heightmap::SIZE * width / longest,
+            Heightmap::SIZE * height / longest,
+        )
+    }
+
+    fn height(&self, s: Real, t: Real) -> Real {
+        (self.image.sample(s, t) - 0.5) * Heightmap::HEIGHT
+    }
+
+    /// The point at `(s, t)`, each from 0 to 1 across the image, and the normal there. The
+    /// terrain lies flat in the XZ plane facing up +Y, with the top of the image furthest along
+    /// +Z.
+    pub fn at(&self, s: Real, t: Real) -> (Vec3, Vec3) {
+        let (width, depth) = self.extent();
+        let point = Vec3::new((s - 0.5) * width, self.height(s, t), (0.5 - t) * depth);
+
+        // The slope each way, measured a pixel to either side.
+        let ds = 1.0 / (self.image.width.max(2) - 1) as Real;
+        let dt = 1.0 / (self.image.height.max(2) - 1) as Real;
+        let dx = (self.height(s + ds, t) - self.height(s - ds, t)) / (2.0 * ds * width);
+        // Z runs against t, so the slope along Z has the opposite sign.
+        let dz = -(self.height(s, t + dt) - self.height(s, t - dt)) / (2.0 * dt * depth);
+        (point, Vec3::new(-dx, 1.0, -dz))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normals_lean_away_from_high_ground() {
+        // Bright on the left, dark on the right, so the ground slopes down towards +X.
+        let image = Image::parse(b"P2 3 3 2 2 1 0 2 1 0 2 1 0").unwrap();
+        let heightmap = Heightmap::new(image);
+        let (point, normal) = heightmap.at(0.5, 0.5);
+        assert!(point.length() < 0.1);
+        assert!(normal.x > 0.0);
+        assert!(normal.z.abs() < 1e-9);
+
+        let (corner, _) = heightmap.at(0.0, 0.0);
+        assert!((corner.y - Heightmap::HEIGHT / 2.0).abs() < 1e-9);
+        assert!(corner.x < 0.0 && corner.z > 0.0);
+    }
+}
@@ -0,0 +1,159 @@
+//! Just enough HTTP for --serve: reading what a client asks for, and answering it, a chunk at a
+//! time for answers that don't end, so that `curl` shows each frame as it comes.
+
+use std::io::{self, Read};
+
+/// The most a request can take to say what it wants, headers and all.
+const LONGEST_HEAD: usize = 8 * 1024;
+
+/// What a client asked for.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Request {
+    pub method: String,
+    /// The path asked for, without any query.
+    pub path: String,
+    /// The names and values in the query, like `width=80`, as they're written.
+    pub query: Vec<(String, String)>,
+}
+
+impl Request {
+    /// Reads the request line from the head of a request, which is all that's needed to answer
+    /// it. Headers don't matter here.
+    pub fn parse(head: &str) -> Result<Request, String> {
+        let line = head.lines().next().unwrap_or_default();
+        let mut parts = line.split(' ');
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(method), Some(target), Some(version), None)
+                if !method.is_empty() && version.starts_with("HTTP/") =>
+            {
+                let target = target.split('#').next().unwrap_or_default();
+                let (path, query) = target.split_once('?').unwrap_or((target, ""));
+                let query = (query.split('&'))
+                    .filter(|pair| !pair.is_empty())
+                    .map(|pair| {
+                        let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+                        (name.to_owned(), value.to_owned())
+                    })
+                    .collect();
+                Ok(Request {
+                    method: method.to_owned(),
+                    path: path.to_owned(),
+                    query,
+                })
+            }
+            _ => Err(format!("not a request line: {line:?}")),
+        }
+    }
+
+    /// The value of `name` in the query, if it's there.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        (self.query.iter())
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Reads from `input` up to the empty line that ends the head of a request, and returns it.
+pub fn read_head(input: &mut impl Read) -> io::Result<String> {
+    let mut head = Vec::new();
+    let mut byte = [0];
+    while !head.ends_with(b"\r\n\r\n") && !head.ends_with(b"\n\n") {
+        if head.len() == LONGEST_HEAD {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "request too long",
+            ));
+        }
+        match input.read(&mut byte)? {
+            0 => return Err(io::ErrorKind::UnexpectedEof.into()),
+            _ => head.push(byte[0]),
+        }
+    }
+    String::from_utf8(head).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+}
+
+/// The status line and headers of a response, up to the empty line after them. Without a
+/// `Content-Length` header, the body is sent in chunks.
+pub fn response(status: u16, headers: &[(&str, &str)]) -> String {
+    let mut head = format!("HTTP/1.1 {status} {}\r\n", reason(status));
+    for (name, value) in headers {
+        head += &format!("{name}: {value}\r\n");
+    }
+    if !headers
+        .iter()
+        .any(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+    {
+        head += "Transfer-Encoding: chunked\r\n";
+    }
+    head + "\r\n"
+}
+
+/// `data` as one chunk of a chunked response. An empty chunk ends the response.
+pub fn chunk(data: &[u8]) -> Vec<u8> {
+    let mut chunk = format!("{:x}\r\n", data.len()).into_bytes();
+    chunk.extend_from_slice(data);
+    chunk.extend_from_slice(b"\r\n");
+    chunk
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Unknown",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_say_what_they_want() {
+        let mut input = &b"GET /frame.txt HTTP/1.1\r\nHost: localhost\r\n\r\nleft over"[..];
+        let head = read_head(&mut input).unwrap();
+        assert_eq!(b"left over", input);
+        assert_eq!(
+            Request {
+                method: "GET".to_owned(),
+                path: "/frame.txt".to_owned(),
+                query: Vec::new(),
+            },
+            Request::parse(&head).unwrap()
+        );
+        let request = Request::parse("GET /?width=80&height=&fast HTTP/1.1\r\n\r\n").unwrap();
+        assert_eq!("/", request.path);
+        assert_eq!(
+            (Some("80"), Some(""), Some(""), None),
+            (
+                request.param("width"),
+                request.param("height"),
+                request.param("fast"),
+                request.param("slow")
+            )
+        );
+        assert!(Request::parse("GET /\r\n\r\n").is_err());
+        assert!(Request::parse("").is_err());
+        assert!(read_head(&mut &b"GET / HTTP/1.1\r\n"[..]).is_err());
+        assert!(read_head(&mut &[b'a'; LONGEST_HEAD + 1][..]).is_err());
+    }
+
+    #[test]
+    fn responses_come_in_chunks() {
+        assert_eq!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nTransfer-Encoding: chunked\r\n\r\n",
+            response(200, &[("Content-Type", "text/plain")])
+        );
+        assert_eq!(
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n",
+            response(404, &[("Content-Length", "0")])
+        );
+        assert_eq!(
+            b"1a\r\nabcdefghijklmnopqrstuvwxyz\r\n".to_vec(),
+            chunk(b"abcdefghijklmnopqrstuvwxyz")
+        );
+        assert_eq!(b"0\r\n\r\n".to_vec(), chunk(b""));
+    }
+}
@@ -0,0 +1,168 @@
+//! Pictures loaded from image files.
+
+mod deflate;
+mod inflate;
+mod png;
+mod pnm;
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::math::Real;
+
+/// An image, with each pixel's red, green, and blue from left to right and top to bottom.
+/// Transparent pixels are blended with black.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<[u8; 3]>,
+}
+
+impl Image {
+    /// Loads a PNG or a Netpbm image (PGM or PPM), choosing the format by the file's contents.
+    pub fn load(path: &Path) -> Result<Image, ImageError> {
+        let bytes = fs::read(path).map_err(|error| ImageError::Io(path.to_owned(), error))?;
+        Image::parse(&bytes)
+    }
+
+    pub fn parse(bytes: &[u8]) -> Result<Image, ImageError> {
+        let image = match bytes {
+            _ if bytes.starts_with(&png::SIGNATURE) => png::parse(bytes)?,
+            [b'P', b'2' | b'3' | b'5' | b'6', ..] => pnm::parse(bytes)?,
+            _ => {
+                return Err(ImageError::Unsupported(
+                    "only PNG, PGM, and PPM images are supported".to_owned(),
+                ))
+            }
+        };
+        if image.width == 0 || image.height == 0 {
+            return Err(ImageError::Invalid("image is empty".to_owned()));
+        }
+        Ok(image)
+    }
+
+    /// Encodes the image as a PNG file.
+    pub fn png(&self) -> Vec<u8> {
+        png::encode(std::slice::from_ref(self), 0)
+    }
+
+    /// Encodes `frames` as an animated PNG file, which loops forever, showing each frame for
+    /// `delay` milliseconds. Programs that can't animate show the first frame.
+    ///
+    /// # Panics
+    ///
+    /// If the frames aren't all the same size.
+    pub fn animated_png(frames: &[Image], delay: u16) -> Vec<u8> {
+        png::encode(frames, delay)
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 3] {
+        self.pixels[y * self.width + x]
+    }
+
+    /// How bright the pixel is, from 0 to 1.
+    pub fn luminance(&self, x: usize, y: usize) -> Real {
+        // Weighted in whole numbers, so that white comes out as exactly 1.
+        let [r, g, b] = self.pixel(x, y).map(u32::from);
+        (2126 * r + 7152 * g + 722 * b) as Real / (10_000.0 * 255.0)
+    }
+
+    /// Shrinks or stretches the image to `width` by `height`, averaging the pixels that fall in
+    /// each new one.
+    pub fn resize(&self, width: usize, height: usize) -> Image {
+        let mut pixels = Vec::with_capacity(width * height);
+        for y in 0..height {
+            let (top, bottom) = span(y, height, self.height);
+            for x in 0..width {
+                let (left, right) = span(x, width, self.width);
+                let mut sum = [0usize; 3];
+                for row in top..bottom {
+                    for column in left..right {
+                        for (total, c) in sum.iter_mut().zip(self.pixel(column, row)) {
+                            *total += usize::from(c);
+                        }
+                    }
+                }
+                let count = (bottom - top) * (right - left);
+                pixels.push(sum.map(|total| (total / count) as u8));
+            }
+        }
+        Image {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// The brightness at `(s, t)`, each from 0 to 1 across the image, blended between the
+    /// nearest pixels.
+    pub fn sample(&self, s: Real, t: Real) -> Real {
+        let x = s.clamp(0.0, 1.0) * (self.width - 1) as Real;
+        let y = t.clamp(0.0, 1.0) * (self.height - 1) as Real;
+        let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+        let (x1, y1) = ((x0 + 1).min(self.width - 1), (y0 + 1).min(self.height - 1));
+        let (fx, fy) = (x - x0 as Real, y - y0 as Real);
+        let top = self.luminance(x0, y0) * (1.0 - fx) + self.luminance(x1, y0) * fx;
+        let bottom = self.luminance(x0, y1) * (1.0 - fx) + self.luminance(x1, y1) * fx;
+        top * (1.0 - fy) + bottom * fy
+    }
+}
+
+/// The range of the `old` pixels covered by pixel `i` of `new`, always at least one pixel.
+fn span(i: usize, new: usize, old: usize) -> (usize, usize) {
+    let start = (i * old / new).min(old - 1);
+    let end = ((i + 1) * old / new).clamp(start + 1, old);
+    (start, end)
+}
+
+/// Why an image couldn't be loaded.
+#[derive(Debug)]
+pub enum ImageError {
+    Io(PathBuf, io::Error),
+    /// The file is a format, or a variant of one, that can't be loaded.
+    Unsupported(String),
+    /// The file is malformed.
+    Invalid(String),
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImageError::Io(path, error) => write!(f, "{}: {error}", path.display()),
+            ImageError::Unsupported(message) | ImageError::Invalid(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ImageError::Io(_, error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_between_pixels() {
+        let image = Image::parse(b"P2 2 1 255 0 255").unwrap();
+        assert_eq!(0.0, image.sample(0.0, 0.5));
+        assert!((image.sample(0.25, 0.0) - 0.25).abs() < 1e-12);
+        assert!((image.sample(1.0, 1.0) - 1.0).abs() < 1e-12);
+        assert!(Image::parse(b"GIF89a").is_err());
+    }
+
+    #[test]
+    fn resize_averages_pixels() {
+        let image = Image::parse(b"P2 4 2 255 0 100 200 255 0 100 200 255").unwrap();
+        assert_eq!(vec![[50; 3], [227; 3]], image.resize(2, 1).pixels);
+        assert_eq!(vec![[0; 3]; 2], image.resize(8, 1).pixels[..2]);
+    }
+}
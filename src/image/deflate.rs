@@ -0,0 +1,172 @@
+//! Compression into zlib streams, the other way from [`super::inflate`]. Repeats are found one
+//! at a time and written with DEFLATE's fixed codes, which is quick and does well enough on
+//! pictures of text, where most of each row is the same as the last.
+
+use super::inflate::{DISTANCES, LENGTHS};
+
+/// How far back repeats can be found.
+const WINDOW: usize = 32 * 1024;
+/// The longest repeat DEFLATE can copy, and the shortest worth copying.
+const LONGEST: usize = 258;
+const SHORTEST: usize = 3;
+/// How many of the places the next bytes were seen before are tried, most recent first.
+const TRIES: usize = 32;
+
+/// Compresses `data` into a zlib stream, checksum and all.
+pub fn zlib(data: &[u8]) -> Vec<u8> {
+    // Deflated with a 32K window, and a check value that makes the header a multiple of 31.
+    let mut bits = Bits::default();
+    bits.bytes.extend([0x78, 0x01]);
+    // A single final block, with fixed codes.
+    bits.put(1, 1);
+    bits.put(1, 2);
+
+    // Where each pair of bytes was last seen, and for each place, where its pair was seen before.
+    let mut last_seen = vec![usize::MAX; 1 << 16];
+    let mut before = vec![usize::MAX; data.len()];
+    let key = |i: usize| usize::from(data[i]) << 8 | usize::from(data[i + 1]);
+    let at_most = |i: usize| LONGEST.min(data.len() - i);
+    let mut i = 0;
+    while i < data.len() {
+        // The longest repeat from the places the next two bytes were seen, nearest first.
+        let mut best = (0, 0);
+        if i + SHORTEST <= data.len() {
+            let mut from = last_seen[key(i)];
+            for _ in 0..TRIES {
+                if from >= i || i - from > WINDOW || best.0 == at_most(i) {
+                    break;
+                }
+                let length = (0..at_most(i))
+                    .take_while(|&n| data[from + n] == data[i + n])
+                    .count();
+                if length > best.0 {
+                    best = (length, i - from);
+                }
+                from = before[from];
+            }
+        }
+        let step = if best.0 >= SHORTEST {
+            bits.repeat(best.0, best.1);
+            best.0
+        } else {
+            bits.literal(data[i]);
+            1
+        };
+        for j in i..(i + step).min(data.len().saturating_sub(1)) {
+            before[j] = last_seen[key(j)];
+            last_seen[key(j)] = j;
+        }
+        i += step;
+    }
+    bits.symbol(256);
+    let mut bytes = bits.finish();
+    bytes.extend(adler32(data).to_be_bytes());
+    bytes
+}
+
+/// The checksum at the end of a zlib stream.
+fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65_521;
+    let (mut a, mut b) = (1, 0);
+    // As many bytes as can be added up before `b` could overflow.
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += u32::from(byte);
+            b += a;
+        }
+        (a, b) = (a % MOD, b % MOD);
+    }
+    b << 16 | a
+}
+
+/// Bits written from the least significant end of each byte, as DEFLATE packs them.
+#[derive(Default)]
+struct Bits {
+    bytes: Vec<u8>,
+    buffer: u32,
+    count: u32,
+}
+
+impl Bits {
+    /// Writes the lowest `count` bits of `value`.
+    fn put(&mut self, value: u32, count: u32) {
+        self.buffer |= value << self.count;
+        self.count += count;
+        while self.count >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    /// Writes a Huffman code, which goes most significant bit first.
+    fn code(&mut self, code: u32, length: u32) {
+        self.put(code.reverse_bits() >> (32 - length), length);
+    }
+
+    /// Writes one of the fixed codes for literals, lengths, and the end of the block.
+    fn symbol(&mut self, symbol: u16) {
+        let symbol = u32::from(symbol);
+        match symbol {
+            0..=143 => self.code(0x30 + symbol, 8),
+            144..=255 => self.code(0x190 + symbol - 144, 9),
+            256..=279 => self.code(symbol - 256, 7),
+            _ => self.code(0xc0 + symbol - 280, 8),
+        }
+    }
+
+    fn literal(&mut self, byte: u8) {
+        self.symbol(u16::from(byte));
+    }
+
+    /// Writes a copy of the `length` bytes from `distance` back.
+    fn repeat(&mut self, length: usize, distance: usize) {
+        let (code, (base, extra)) = last_at_most(&LENGTHS, length);
+        self.symbol(257 + code as u16);
+        self.put((length - base) as u32, extra);
+        let (code, (base, extra)) = last_at_most(&DISTANCES, distance);
+        self.code(code as u32, 5);
+        self.put((distance - base) as u32, extra);
+    }
+
+    /// The bytes written, with the last one padded out.
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+/// The last code in `table` whose base is no more than `n`, with its base and extra bits.
+fn last_at_most(table: &[(u16, u32)], n: usize) -> (usize, (usize, u32)) {
+    let code = table.partition_point(|&(base, _)| usize::from(base) <= n) - 1;
+    let (base, extra) = table[code];
+    (code, (usize::from(base), extra))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::inflate;
+    use super::*;
+
+    #[test]
+    fn inflates_back_to_the_same() {
+        let mut rng = crate::random::Rng::new(411);
+        let noise: Vec<u8> = (0..5000).map(|_| rng.below(256) as u8).collect();
+        let rows: Vec<u8> = (0..40_000).map(|i| (i % 300 / 7) as u8).collect();
+        for data in [
+            &b""[..],
+            b"a",
+            b"abcabcabcabcabcd",
+            &noise,
+            &rows,
+            &[0; 100_000],
+        ] {
+            let compressed = zlib(data);
+            assert_eq!(data, inflate::zlib(&compressed).unwrap());
+        }
+        assert!(zlib(&rows).len() < rows.len() / 20);
+        assert_eq!(0x11e6_0398, adler32(b"Wikipedia"));
+    }
+}
@@ -0,0 +1,323 @@
+//! Decompression of zlib streams (RFC 1950), which wrap DEFLATE data (RFC 1951).
+
+/// Decompresses a zlib stream. The checksum at the end isn't checked.
+pub fn zlib(data: &[u8]) -> Result<Vec<u8>, String> {
+    let [cmf, flg, ..] = *data else {
+        return Err("compressed data is cut short".to_owned());
+    };
+    if cmf & 0x0f != 8 || (u16::from(cmf) << 8 | u16::from(flg)) % 31 != 0 {
+        return Err("compressed data isn't a zlib stream".to_owned());
+    }
+    if flg & 0x20 != 0 {
+        return Err("compressed data needs a preset dictionary".to_owned());
+    }
+    inflate(&data[2..])
+}
+
+/// Decompresses raw DEFLATE data.
+pub fn inflate(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut bits = Bits {
+        data,
+        position: 0,
+        buffer: 0,
+        count: 0,
+    };
+    let mut output = Vec::new();
+    loop {
+        let last = bits.take(1)? == 1;
+        match bits.take(2)? {
+            0 => stored(&mut bits, &mut output)?,
+            1 => {
+                let (lengths, distances) = fixed_tables();
+                compressed(&mut bits, &mut output, &lengths, &distances)?;
+            }
+            2 => {
+                let (lengths, distances) = dynamic_tables(&mut bits)?;
+                compressed(&mut bits, &mut output, &lengths, &distances)?;
+            }
+            _ => return Err("compressed data has an invalid block type".to_owned()),
+        }
+        if last {
+            return Ok(output);
+        }
+    }
+}
+
+/// Reads bits least significant first, as DEFLATE packs them.
+struct Bits<'a> {
+    data: &'a [u8],
+    position: usize,
+    buffer: u32,
+    count: u32,
+}
+
+impl Bits<'_> {
+    fn take(&mut self, n: u32) -> Result<u32, String> {
+        while self.count < n {
+            let byte = *self
+                .data
+                .get(self.position)
+                .ok_or("compressed data is cut short")?;
+            self.position += 1;
+            self.buffer |= u32::from(byte) << self.count;
+            self.count += 8;
+        }
+        let value = self.buffer & ((1 << n) - 1);
+        self.buffer >>= n;
+        self.count -= n;
+        Ok(value)
+    }
+
+    /// Skips to the next whole byte.
+    fn align(&mut self) {
+        self.buffer = 0;
+        self.count = 0;
+    }
+}
+
+/// A canonical Huffman code, stored as how many codes there are of each length and the symbols
+/// in code order.
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    /// Builds the code from the length of each symbol's code, or 0 for unused symbols.
+    fn new(lengths: &[u8]) -> Huffman {
+        let mut counts = [0u16; 16];
+        for &length in lengths {
+            counts[usize::from(length)] += 1;
+        }
+        counts[0] = 0;
+        let mut offsets = [0u16; 16];
+        for length in 1..16 {
+            offsets[length] = offsets[length - 1] + counts[length - 1];
+        }
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, &length) in lengths.iter().enumerate() {
+            if length != 0 {
+                symbols[usize::from(offsets[usize::from(length)])] = symbol as u16;
+                offsets[usize::from(length)] += 1;
+            }
+        }
+        Huffman { counts, symbols }
+    }
+
+    fn decode(&self, bits: &mut Bits) -> Result<u16, String> {
+        // Codes of each length follow on from the codes one bit shorter, so walk down the
+        // lengths until the code read so far falls within the range for its length.
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for &count in &self.counts[1..] {
+            code |= bits.take(1)? as i32;
+            let count = i32::from(count);
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err("compressed data has an invalid code".to_owned())
+    }
+}
+
+fn stored(bits: &mut Bits, output: &mut Vec<u8>) -> Result<(), String> {
+    bits.align();
+    let start = bits.position;
+    let header = bits
+        .data
+        .get(start..start + 4)
+        .ok_or("compressed data is cut short")?;
+    let length = usize::from(u16::from_le_bytes([header[0], header[1]]));
+    let bytes = bits
+        .data
+        .get(start + 4..start + 4 + length)
+        .ok_or("compressed data is cut short")?;
+    output.extend_from_slice(bytes);
+    bits.position = start + 4 + length;
+    Ok(())
+}
+
+/// The length codes after 256 stand for a base length plus some extra bits.
+pub(super) const LENGTHS: [(u16, u32); 29] = [
+    (3, 0),
+    (4, 0),
+    (5, 0),
+    (6, 0),
+    (7, 0),
+    (8, 0),
+    (9, 0),
+    (10, 0),
+    (11, 1),
+    (13, 1),
+    (15, 1),
+    (17, 1),
+    (19, 2),
+    (23, 2),
+    (27, 2),
+    (31, 2),
+    (35, 3),
+    (43, 3),
+    (51, 3),
+    (59, 3),
+    (67, 4),
+    (83, 4),
+    (99, 4),
+    (115, 4),
+    (131, 5),
+    (163, 5),
+    (195, 5),
+    (227, 5),
+    (258, 0),
+];
+
+/// Like [`LENGTHS`], for how far back to copy from.
+pub(super) const DISTANCES: [(u16, u32); 30] = [
+    (1, 0),
+    (2, 0),
+    (3, 0),
+    (4, 0),
+    (5, 1),
+    (7, 1),
+    (9, 2),
+    (13, 2),
+    (17, 3),
+    (25, 3),
+    (33, 4),
+    (49, 4),
+    (65, 5),
+    (97, 5),
+    (129, 6),
+    (193, 6),
+    (257, 7),
+    (385, 7),
+    (513, 8),
+    (769, 8),
+    (1025, 9),
+    (1537, 9),
+    (2049, 10),
+    (3073, 10),
+    (4097, 11),
+    (6145, 11),
+    (8193, 12),
+    (12289, 12),
+    (16385, 13),
+    (24577, 13),
+];
+
+fn compressed(
+    bits: &mut Bits,
+    output: &mut Vec<u8>,
+    lengths: &Huffman,
+    distances: &Huffman,
+) -> Result<(), String> {
+    loop {
+        let symbol = lengths.decode(bits)?;
+        match symbol {
+            0..=255 => output.push(symbol as u8),
+            256 => return Ok(()),
+            _ => {
+                let &(base, extra) = LENGTHS
+                    .get(usize::from(symbol - 257))
+                    .ok_or("compressed data has an invalid length")?;
+                let length = usize::from(base) + bits.take(extra)? as usize;
+                let &(base, extra) = DISTANCES
+                    .get(usize::from(distances.decode(bits)?))
+                    .ok_or("compressed data has an invalid distance")?;
+                let distance = usize::from(base) + bits.take(extra)? as usize;
+                if distance > output.len() {
+                    return Err("compressed data refers back too far".to_owned());
+                }
+                // Copies can overlap what they're copying, so go a byte at a time.
+                let start = output.len() - distance;
+                for i in 0..length {
+                    output.push(output[start + i]);
+                }
+            }
+        }
+    }
+}
+
+/// The codes for literals and lengths, and for distances, that blocks without their own use.
+fn fixed_tables() -> (Huffman, Huffman) {
+    let lengths: Vec<u8> = (0..288)
+        .map(|symbol| match symbol {
+            0..=143 => 8,
+            144..=255 => 9,
+            256..=279 => 7,
+            _ => 8,
+        })
+        .collect();
+    (Huffman::new(&lengths), Huffman::new(&[5; 30]))
+}
+
+/// Reads the code lengths for a block's own Huffman codes, which are themselves compressed with
+/// a Huffman code.
+fn dynamic_tables(bits: &mut Bits) -> Result<(Huffman, Huffman), String> {
+    const ORDER: [usize; 19] = [
+        16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+    ];
+    let literals = bits.take(5)? as usize + 257;
+    let distances = bits.take(5)? as usize + 1;
+    let code_lengths = bits.take(4)? as usize + 4;
+
+    let mut lengths = [0u8; 19];
+    for &i in &ORDER[..code_lengths] {
+        lengths[i] = bits.take(3)? as u8;
+    }
+    let code = Huffman::new(&lengths);
+
+    let mut lengths = Vec::with_capacity(literals + distances);
+    while lengths.len() < literals + distances {
+        let (value, repeat) = match code.decode(bits)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => {
+                let &previous = lengths.last().ok_or("compressed data repeats nothing")?;
+                (previous, 3 + bits.take(2)?)
+            }
+            17 => (0, 3 + bits.take(3)?),
+            _ => (0, 11 + bits.take(7)?),
+        };
+        lengths.extend(std::iter::repeat_n(value, repeat as usize));
+    }
+    if lengths.len() > literals + distances {
+        return Err("compressed data has too many code lengths".to_owned());
+    }
+    let distances = lengths.split_off(literals);
+    Ok((Huffman::new(&lengths), Huffman::new(&distances)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inflates_each_kind_of_block() {
+        // A stored block, from zlib at level 0.
+        let stored = [
+            0x78, 0x01, 0x01, 0x05, 0x00, 0xfa, 0xff, b'h', b'e', b'l', b'l', b'o', 0x06, 0x2c,
+            0x02, 0x15,
+        ];
+        assert_eq!(b"hello".to_vec(), zlib(&stored).unwrap());
+
+        // Fixed codes with a back-reference, from `zlib.compress(b"abcabcabcabc", 9)`.
+        let fixed = [
+            0x78, 0xda, 0x4b, 0x4c, 0x4a, 0x4e, 0x84, 0x21, 0x00, 0x1d, 0xe0, 0x04, 0x99,
+        ];
+        assert_eq!(b"abcabcabcabc".to_vec(), zlib(&fixed).unwrap());
+
+        // Codes of its own, likewise.
+        let dynamic = [
+            0x78, 0xda, 0x05, 0xc1, 0xc1, 0x11, 0x00, 0x30, 0x08, 0x02, 0xb0, 0x59, 0xe1, 0xa0,
+            0x45, 0xd9, 0xff, 0x6f, 0x82, 0xfc, 0x15, 0xe8, 0x6e, 0x85, 0x32, 0xf6, 0x88, 0x8a,
+            0xf4, 0x3a, 0x07, 0xa1, 0x8b, 0x0b, 0x28,
+        ];
+        assert_eq!(
+            b"ahgjdabekjkdakbheeidbdhddfki".to_vec(),
+            zlib(&dynamic).unwrap()
+        );
+
+        assert!(zlib(&fixed[..6]).is_err());
+    }
+}
@@ -0,0 +1,376 @@
+//! PNG images of any bit depth and colour type, as long as they aren't interlaced.
+
+use super::{deflate, inflate, Image, ImageError};
+
+pub const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+pub fn parse(bytes: &[u8]) -> Result<Image, ImageError> {
+    let invalid = |message: &str| ImageError::Invalid(message.to_owned());
+    let mut rest = bytes
+        .strip_prefix(&SIGNATURE)
+        .ok_or_else(|| invalid("not a PNG file"))?;
+
+    let mut header = None;
+    let mut palette: &[u8] = &[];
+    let mut compressed = Vec::new();
+    while !rest.is_empty() {
+        let (length, kind) = match rest {
+            [a, b, c, d, kind @ ..] if kind.len() >= 4 => {
+                (u32::from_be_bytes([*a, *b, *c, *d]) as usize, &kind[..4])
+            }
+            _ => return Err(invalid("PNG chunk is cut short")),
+        };
+        // Each chunk is its length, type, data, and a checksum, which isn't checked.
+        let data = rest
+            .get(8..8 + length)
+            .ok_or_else(|| invalid("PNG chunk is cut short"))?;
+        rest = rest.get(12 + length..).unwrap_or_default();
+        match kind {
+            b"IHDR" => header = Some(Header::parse(data)?),
+            b"PLTE" => palette = data,
+            b"IDAT" => compressed.extend_from_slice(data),
+            b"IEND" => break,
+            _ => {}
+        }
+    }
+
+    let header = header.ok_or_else(|| invalid("PNG has no header"))?;
+    let data = inflate::zlib(&compressed).map_err(ImageError::Invalid)?;
+    let rows = unfilter(&header, &data)?;
+    header.pixels(&rows, palette)
+}
+
+/// Encodes `frames` as a PNG, or as an animated PNG that loops forever if there's more than one,
+/// showing each for `delay` milliseconds. Every frame has to be the size of the first.
+pub fn encode(frames: &[Image], delay: u16) -> Vec<u8> {
+    let mut png = SIGNATURE.to_vec();
+    let mut chunk = |kind: &[u8; 4], data: &[u8]| {
+        png.extend((data.len() as u32).to_be_bytes());
+        png.extend(kind);
+        png.extend(data);
+        png.extend(crc32(kind.iter().chain(data)).to_be_bytes());
+    };
+    let Some(first) = frames.first() else {
+        return Vec::new();
+    };
+    let size = [first.width as u32, first.height as u32].map(u32::to_be_bytes);
+    // 8-bit RGB, not interlaced.
+    chunk(b"IHDR", &[&size.concat()[..], &[8, 2, 0, 0, 0]].concat());
+    let animated = frames.len() > 1;
+    if animated {
+        // How many frames, and how many times to play them, where 0 is forever.
+        chunk(
+            b"acTL",
+            &[(frames.len() as u32).to_be_bytes(), [0; 4]].concat(),
+        );
+    }
+    // Frame controls and the data after the first frame share one count.
+    let mut sequence = 0u32;
+    for (i, frame) in frames.iter().enumerate() {
+        assert_eq!((frame.width, frame.height), (first.width, first.height));
+        if animated {
+            let mut control = sequence.to_be_bytes().to_vec();
+            control.extend(size.concat());
+            // At the top left, shown for `delay` thousandths of a second, and then replaced.
+            control.extend([[0; 4], [0; 4]].concat());
+            control.extend(delay.to_be_bytes());
+            control.extend(1000u16.to_be_bytes());
+            control.extend([0, 0]);
+            chunk(b"fcTL", &control);
+            sequence += 1;
+        }
+        // Every row with no filter in front of it.
+        let rows: Vec<u8> = (frame.pixels.chunks(frame.width))
+            .flat_map(|row| std::iter::once(0).chain(row.iter().flatten().copied()))
+            .collect();
+        let data = deflate::zlib(&rows);
+        if i == 0 {
+            chunk(b"IDAT", &data);
+        } else {
+            chunk(b"fdAT", &[&sequence.to_be_bytes()[..], &data].concat());
+            sequence += 1;
+        }
+    }
+    chunk(b"IEND", &[]);
+    png
+}
+
+/// The checksum at the end of each chunk.
+fn crc32<'a>(bytes: impl IntoIterator<Item = &'a u8>) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                crc >> 1 ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+struct Header {
+    width: usize,
+    height: usize,
+    depth: u8,
+    colour: u8,
+}
+
+impl Header {
+    fn parse(data: &[u8]) -> Result<Header, ImageError> {
+        let &[w0, w1, w2, w3, h0, h1, h2, h3, depth, colour, _, _, interlace] = data else {
+            return Err(ImageError::Invalid(
+                "PNG header is the wrong size".to_owned(),
+            ));
+        };
+        if interlace != 0 {
+            return Err(ImageError::Unsupported(
+                "interlaced PNGs aren't supported".to_owned(),
+            ));
+        }
+        let header = Header {
+            width: u32::from_be_bytes([w0, w1, w2, w3]) as usize,
+            height: u32::from_be_bytes([h0, h1, h2, h3]) as usize,
+            depth,
+            colour,
+        };
+        let valid_depths: &[u8] = match colour {
+            0 => &[1, 2, 4, 8, 16],
+            3 => &[1, 2, 4, 8],
+            2 | 4 | 6 => &[8, 16],
+            _ => &[],
+        };
+        if !valid_depths.contains(&depth) {
+            return Err(ImageError::Invalid(format!(
+                "PNG has an invalid bit depth ({depth}) or colour type ({colour})"
+            )));
+        }
+        if header.size().is_none() {
+            return Err(ImageError::Invalid("PNG is too big".to_owned()));
+        }
+        Ok(header)
+    }
+
+    /// Values per pixel: grey, grey and alpha, red green and blue, and so on.
+    fn channels(&self) -> usize {
+        match self.colour {
+            2 => 3,
+            4 => 2,
+            6 => 4,
+            _ => 1,
+        }
+    }
+
+    fn bits_per_pixel(&self) -> usize {
+        self.channels() * usize::from(self.depth)
+    }
+
+    fn stride(&self) -> usize {
+        (self.width * self.bits_per_pixel()).div_ceil(8)
+    }
+
+    /// How many bytes the image takes once it's decompressed, each row with its filter in front,
+    /// or `None` if there are too many to count. [`Header::parse`] only accepts ones there aren't.
+    fn size(&self) -> Option<usize> {
+        let bits = self.width.checked_mul(self.bits_per_pixel())?;
+        self.width.checked_mul(self.height)?;
+        self.height.checked_mul(bits.div_ceil(8).checked_add(1)?)
+    }
+
+    /// Converts unfiltered rows into RGB pixels, blending any transparency with black.
+    fn pixels(&self, rows: &[Vec<u8>], palette: &[u8]) -> Result<Image, ImageError> {
+        let depth = usize::from(self.depth);
+        let max = (1u32 << depth.min(8)) - 1;
+        let mut pixels = Vec::with_capacity(self.width * self.height);
+        for row in rows {
+            // Values narrower than a byte are packed from the most significant bit down. Wider
+            // values keep only their high byte.
+            let value = |i: usize| -> u32 {
+                match depth {
+                    16 => u32::from(row[2 * i]),
+                    8 => u32::from(row[i]),
+                    _ => {
+                        let bit = i * depth;
+                        u32::from(row[bit / 8] >> (8 - depth - bit % 8)) & max
+                    }
+                }
+            };
+            let scale = |v: u32| (v * 255 / max) as u8;
+            for x in 0..self.width {
+                let i = x * self.channels();
+                let (rgb, alpha) = match self.colour {
+                    0 => ([scale(value(i)); 3], 255),
+                    4 => ([scale(value(i)); 3], value(i + 1)),
+                    2 => ([value(i), value(i + 1), value(i + 2)].map(|v| v as u8), 255),
+                    6 => (
+                        [value(i), value(i + 1), value(i + 2)].map(|v| v as u8),
+                        value(i + 3),
+                    ),
+                    _ => {
+                        let index = value(i) as usize * 3;
+                        let entry = palette.get(index..index + 3).ok_or_else(|| {
+                            ImageError::Invalid("PNG palette is too short".to_owned())
+                        })?;
+                        ([entry[0], entry[1], entry[2]], 255)
+                    }
+                };
+                pixels.push(rgb.map(|c| (u32::from(c) * alpha / 255) as u8));
+            }
+        }
+        Ok(Image {
+            width: self.width,
+            height: self.height,
+            pixels,
+        })
+    }
+}
+
+/// Undoes the filter each row was stored with, which predicts each byte from its neighbours.
+fn unfilter(header: &Header, data: &[u8]) -> Result<Vec<Vec<u8>>, ImageError> {
+    // Checked before anything is set aside for the rows, so that a header claiming to be huge
+    // can't ask for more memory than the data could ever fill.
+    if header.size().is_none_or(|size| size > data.len()) {
+        return Err(ImageError::Invalid("PNG data is cut short".to_owned()));
+    }
+    let stride = header.stride();
+    // Filters compare bytes with the same byte of the previous pixel.
+    let step = header.bits_per_pixel().div_ceil(8);
+    let mut rows: Vec<Vec<u8>> = Vec::with_capacity(header.height);
+    let mut previous = vec![0; stride];
+    for y in 0..header.height {
+        let start = y * (stride + 1);
+        let line = data
+            .get(start..start + stride + 1)
+            .ok_or_else(|| ImageError::Invalid("PNG data is cut short".to_owned()))?;
+        let (filter, line) = (line[0], &line[1..]);
+        let mut row = line.to_vec();
+        for i in 0..stride {
+            let left = if i >= step { row[i - step] } else { 0 };
+            let up = previous[i];
+            let up_left = if i >= step { previous[i - step] } else { 0 };
+            let prediction = match filter {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => ((u16::from(left) + u16::from(up)) / 2) as u8,
+                4 => paeth(left, up, up_left),
+                _ => {
+                    return Err(ImageError::Invalid(
+                        "PNG row has an unknown filter".to_owned(),
+                    ))
+                }
+            };
+            row[i] = row[i].wrapping_add(prediction);
+        }
+        previous.clone_from(&row);
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+/// Whichever of `a`, `b`, or `c` is closest to `a + b − c`.
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = i16::from(a) + i16::from(b) - i16::from(c);
+    let (pa, pb, pc) = (
+        (p - i16::from(a)).abs(),
+        (p - i16::from(b)).abs(),
+        (p - i16::from(c)).abs(),
+    );
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_greyscale() {
+        // A 2×2 8-bit greyscale image, rows filtered with Sub and then Up, from Python's zlib.
+        let mut png = SIGNATURE.to_vec();
+        let mut chunk = |kind: &[u8], data: &[u8]| {
+            png.extend((data.len() as u32).to_be_bytes());
+            png.extend(kind);
+            png.extend(data);
+            png.extend([0; 4]);
+        };
+        chunk(b"IHDR", &[0, 0, 0, 2, 0, 0, 0, 2, 8, 0, 0, 0, 0]);
+        chunk(
+            b"IDAT",
+            &[
+                0x78, 0xda, 0x63, 0xe4, 0xe2, 0x62, 0x62, 0x62, 0x00, 0x00, 0x00, 0x70, 0x00, 0x1a,
+            ],
+        );
+        chunk(b"IEND", &[]);
+
+        let image = parse(&png).unwrap();
+        assert_eq!((2, 2), (image.width, image.height));
+        assert_eq!(vec![[10; 3], [20; 3], [12; 3], [20; 3]], image.pixels);
+    }
+
+    #[test]
+    fn oversized_headers_are_invalid() {
+        let png = |width: u32, height: u32| {
+            let mut png = SIGNATURE.to_vec();
+            let mut chunk = |kind: &[u8], data: &[u8]| {
+                png.extend((data.len() as u32).to_be_bytes());
+                png.extend(kind);
+                png.extend(data);
+                png.extend([0; 4]);
+            };
+            let size = [width.to_be_bytes(), height.to_be_bytes()].concat();
+            chunk(b"IHDR", &[&size[..], &[8, 6, 0, 0, 0]].concat());
+            chunk(b"IDAT", &deflate::zlib(&[0; 16]));
+            chunk(b"IEND", &[]);
+            png
+        };
+        for (width, height) in [(0x0fff_ffff, 0x0fff_ffff), (u32::MAX, u32::MAX), (1, 1000)] {
+            assert!(
+                matches!(parse(&png(width, height)), Err(ImageError::Invalid(_))),
+                "{width}×{height}"
+            );
+        }
+        assert!(parse(&png(1, 3)).is_ok());
+    }
+
+    #[test]
+    fn encodes_what_it_decodes() {
+        let image = Image {
+            width: 3,
+            height: 2,
+            pixels: vec![
+                [255, 0, 0],
+                [0, 255, 0],
+                [0, 0, 255],
+                [1, 2, 3],
+                [0; 3],
+                [255; 3],
+            ],
+        };
+        let png = encode(std::slice::from_ref(&image), 0);
+        assert_eq!(image, parse(&png).unwrap());
+        // The checksum of the header, as any PNG decoder would work it out.
+        assert_eq!(
+            0x1216_f14d,
+            u32::from_be_bytes(png[29..33].try_into().unwrap())
+        );
+        assert_eq!(0xcbf4_3926, crc32(b"123456789"));
+
+        // The first frame of an animation is what decoders that don't animate show.
+        let black = Image {
+            pixels: vec![[0; 3]; 6],
+            ..image.clone()
+        };
+        let apng = encode(&[image.clone(), black], 100);
+        assert_eq!(image, parse(&apng).unwrap());
+        let has = |kind: &[u8]| apng.windows(4).filter(|window| *window == kind).count();
+        assert_eq!((1, 2, 1), (has(b"acTL"), has(b"fcTL"), has(b"fdAT")));
+    }
+}
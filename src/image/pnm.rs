@@ -0,0 +1,124 @@
+//! Netpbm greyscale and colour images, in text (P2 and P3) or binary (P5 and P6).
+
+use super::{Image, ImageError};
+
+pub fn parse(bytes: &[u8]) -> Result<Image, ImageError> {
+    let invalid = |message: &str| ImageError::Invalid(message.to_owned());
+    let (binary, channels) = match &bytes[..2] {
+        b"P2" => (false, 1),
+        b"P3" => (false, 3),
+        b"P5" => (true, 1),
+        _ => (true, 3),
+    };
+    let mut position = 2;
+    let mut header = [0; 3];
+    for value in &mut header {
+        *value =
+            number(bytes, &mut position).ok_or_else(|| invalid("image header is malformed"))?;
+    }
+    let [width, height, max] = header;
+    if max == 0 || max > 65535 {
+        return Err(invalid("image has an invalid maximum value"));
+    }
+
+    let size = if max < 256 { 1 } else { 2 };
+    // Each value takes at least a byte, so a header that says there are more values than bytes
+    // left is wrong, and nothing is set aside for them.
+    let count = (width.checked_mul(height))
+        .and_then(|pixels| pixels.checked_mul(channels))
+        .filter(|&count| {
+            let needed = if binary {
+                count.checked_mul(size)
+            } else {
+                Some(count)
+            };
+            needed.is_some_and(|needed| needed <= bytes.len().saturating_sub(position))
+        })
+        .ok_or_else(|| invalid("image data is cut short"))?;
+    let mut values = Vec::with_capacity(count);
+    if binary {
+        // A single whitespace character separates the header from the data.
+        let data = bytes.get(position + 1..).unwrap_or_default();
+        let data = data
+            .get(..count * size)
+            .ok_or_else(|| invalid("image data is cut short"))?;
+        values.extend(data.chunks(size).map(|value| match *value {
+            [high, low] => usize::from(u16::from_be_bytes([high, low])),
+            _ => usize::from(value[0]),
+        }));
+    } else {
+        for _ in 0..count {
+            values.push(
+                number(bytes, &mut position).ok_or_else(|| invalid("image data is cut short"))?,
+            );
+        }
+    }
+
+    let scale = |value: usize| (value.min(max) * 255 / max) as u8;
+    let pixels = values
+        .chunks(channels)
+        .map(|pixel| match *pixel {
+            [r, g, b] => [scale(r), scale(g), scale(b)],
+            _ => [scale(pixel[0]); 3],
+        })
+        .collect();
+    Ok(Image {
+        width,
+        height,
+        pixels,
+    })
+}
+
+/// Reads the next decimal number from `position` on, skipping whitespace and `#` comments.
+fn number(bytes: &[u8], position: &mut usize) -> Option<usize> {
+    loop {
+        match bytes.get(*position)? {
+            b'#' => {
+                while bytes.get(*position).is_some_and(|&b| b != b'\n') {
+                    *position += 1;
+                }
+            }
+            b if b.is_ascii_whitespace() => *position += 1,
+            _ => break,
+        }
+    }
+    let start = *position;
+    while bytes.get(*position).is_some_and(u8::is_ascii_digit) {
+        *position += 1;
+    }
+    std::str::from_utf8(&bytes[start..*position])
+        .ok()?
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_text_and_binary() {
+        let text = parse(b"P3\n# a comment\n2 1\n15\n15 0 0  0 15 0\n").unwrap();
+        assert_eq!(vec![[255, 0, 0], [0, 255, 0]], text.pixels);
+
+        let binary = parse(b"P5 2 1 255\n\x10\x20").unwrap();
+        assert_eq!(vec![[16; 3], [32; 3]], binary.pixels);
+        assert!(parse(b"P5 2 1 255\n\x10").is_err());
+    }
+
+    #[test]
+    fn oversized_headers_are_invalid() {
+        for bytes in [
+            &b"P5 4000000000 4000000000 255\n\x10\x20"[..],
+            b"P2 4000000000 4000000000 255 0 255",
+            b"P6 18446744073709551615 2 255\n",
+            b"P5 1000 1000 65535\n\x10\x20",
+        ] {
+            assert!(
+                matches!(parse(bytes), Err(ImageError::Invalid(_))),
+                "{}",
+                String::from_utf8_lossy(bytes)
+            );
+        }
+    }
+}
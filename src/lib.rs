@@ -1,47 +1,398 @@
+#[cfg(feature = "audio")]
+pub mod audio;
+pub mod auto;
+pub mod background;
+pub mod bloom;
+pub mod cache;
+pub mod camera;
+pub mod clock;
+pub mod color;
+pub mod command;
+pub mod config;
+pub mod crt;
+pub mod demo;
+pub mod diagnostics;
+pub mod dither;
+pub mod easing;
+pub mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod heightmap;
+pub mod http;
+pub mod image;
+pub mod light;
+pub mod math;
+pub mod mesh;
+pub mod motion;
+pub mod overlay;
+pub mod pace;
+pub mod playlist;
+pub mod preset;
+pub mod pulse;
+pub mod random;
+pub mod raster;
+pub mod render;
+pub mod scene;
+pub mod screenshot;
+pub mod sdf;
+pub mod shading;
+pub mod sparkle;
+pub mod stereo;
+pub mod surface;
+pub mod terminfo;
+pub mod theme;
+pub mod timeline;
+pub mod trail;
+pub mod viewport;
+pub mod width;
+
+mod toml;
+
+pub use error::{Error, Result};
+
+/// The floating-point types that ranges can step through: `f32` and `f64`.
+pub trait Float:
+    Copy
+    + PartialOrd
+    + std::fmt::Debug
+    + std::fmt::Display
+    + std::ops::Add<Output = Self>
+    + std::ops::Sub<Output = Self>
+    + std::ops::Mul<Output = Self>
+    + std::ops::Div<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+    /// How far from a whole number of steps a range can be and still count as one, allowing
+    /// for rounding.
+    const TOLERANCE: Self;
+
+    fn from_i64(n: i64) -> Self;
+    /// Rounds towards zero, saturating at the ends of `i64`.
+    fn to_i64(self) -> i64;
+    fn is_finite(self) -> bool;
+    fn floor(self) -> Self;
+    fn abs(self) -> Self;
+    fn min(self, other: Self) -> Self;
+    fn max(self, other: Self) -> Self;
+    fn next_up(self) -> Self;
+    fn next_down(self) -> Self;
+}
+
+macro_rules! impl_float {
+    ($float:ty, $tolerance:expr) => {
+        impl Float for $float {
+            const ZERO: Self = 0.0;
+            const ONE: Self = 1.0;
+            const TOLERANCE: Self = $tolerance;
+
+            fn from_i64(n: i64) -> Self {
+                n as $float
+            }
+            fn to_i64(self) -> i64 {
+                self as i64
+            }
+            fn is_finite(self) -> bool {
+                <$float>::is_finite(self)
+            }
+            fn floor(self) -> Self {
+                <$float>::floor(self)
+            }
+            fn abs(self) -> Self {
+                <$float>::abs(self)
+            }
+            fn min(self, other: Self) -> Self {
+                <$float>::min(self, other)
+            }
+            fn max(self, other: Self) -> Self {
+                <$float>::max(self, other)
+            }
+            fn next_up(self) -> Self {
+                <$float>::next_up(self)
+            }
+            fn next_down(self) -> Self {
+                <$float>::next_down(self)
+            }
+        }
+    };
+}
+
+impl_float!(f32, 1e-5);
+impl_float!(f64, 1e-9);
+
 /// Allows you to iterate from one float value to another.
 ///
 #[derive(Clone)]
-pub struct FloatRangeIter {
-    start: f64,
-    end: f64,
-    step: f64,
+pub struct FloatRangeIter<T: Float = f64> {
+    start: T,
+    end: T,
+    step: T,
     current: i64,
     size: i64,
+    /// Whether `end` itself is included.
+    inclusive: bool,
+    /// The index and value of the last value, when it's known exactly rather than by adding up
+    /// steps.
+    last: Option<(i64, T)>,
+}
+
+impl<T: Float> FloatRangeIter<T> {
+    /// Exactly `n` evenly spaced values from `start` to `end`, including both. `end` can be
+    /// less than `start`, to count down.
+    ///
+    /// # Panics
+    ///
+    /// If either end isn't finite.
+    pub fn linspace(start: T, end: T, n: usize) -> FloatRangeIter<T> {
+        if let Some(bound) = [start, end].into_iter().find(|bound| !bound.is_finite()) {
+            panic!("{}", FloatRangeError::Bound(bound));
+        }
+        let gaps = T::from_i64(n.saturating_sub(1).max(1) as i64);
+        FloatRangeIter {
+            start,
+            end,
+            step: (end - start) / gaps,
+            current: 0,
+            size: n as i64,
+            inclusive: true,
+            last: (n > 1).then_some((n as i64 - 1, end)),
+        }
+    }
+
+    /// Sweeps back and forth through the rest of the range forever, like a triangle wave: from
+    /// start to end, back to start, and so on, without repeating the values at either end.
+    pub fn ping_pong(self) -> PingPong<T> {
+        PingPong {
+            next: self.current,
+            forwards: true,
+            range: self,
+        }
+    }
+
+    /// The `i`th value from the start, always within the range. Rounding can carry the last
+    /// few values a hair past the end, so they're kept to it, and because the steps never go
+    /// backwards, earlier values can't go past the start.
+    fn value(&self, i: i64) -> T {
+        if let Some((_, last)) = self.last.filter(|&(index, _)| index == i) {
+            return last;
+        }
+        let value = self.start + self.step * T::from_i64(i);
+        let counting_down = self.step < T::ZERO;
+        let (low, high) = if counting_down {
+            (self.end, self.start)
+        } else {
+            (self.start, self.end)
+        };
+        // Also catches the NaN from multiplying an infinite step by 0.
+        let value = value.max(low).min(high);
+        if !self.inclusive && value == self.end {
+            // The end isn't in the range, but the float just before it is.
+            if counting_down {
+                self.end.next_up()
+            } else {
+                self.end.next_down()
+            }
+        } else {
+            value
+        }
+    }
+}
+
+/// Sweeps back and forth through a range. See [`FloatRangeIter::ping_pong`].
+#[derive(Clone)]
+pub struct PingPong<T: Float = f64> {
+    range: FloatRangeIter<T>,
+    /// The index of the next value.
+    next: i64,
+    forwards: bool,
+}
+
+impl<T: Float> Iterator for PingPong<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (first, last) = (self.range.current, self.range.size - 1);
+        if first > last {
+            return None;
+        }
+        let value = self.range.value(self.next);
+        // Turn around at either end. A range of one value stays put.
+        if self.forwards && self.next == last {
+            self.forwards = false;
+        } else if !self.forwards && self.next == first {
+            self.forwards = true;
+        }
+        if first < last {
+            self.next += if self.forwards { 1 } else { -1 };
+        }
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.range.len() == 0 {
+            (0, Some(0))
+        } else {
+            (usize::MAX, None)
+        }
+    }
 }
 
 /// Converts values to float ranges.
-pub trait ToFloatRangeIter {
-    fn by(self, step: f64) -> FloatRangeIter;
+pub trait ToFloatRangeIter<T: Float = f64>: Sized {
+    /// Steps through the range, or explains why it can't.
+    fn try_by(self, step: T) -> Result<FloatRangeIter<T>, FloatRangeError<T>>;
+
+    /// Exactly `n` evenly spaced values across the range, starting at its start. Inclusive
+    /// ranges end at their end, like [`FloatRangeIter::linspace`].
+    fn points(self, n: usize) -> FloatRangeIter<T>;
+
+    /// Steps through the range.
+    ///
+    /// # Panics
+    ///
+    /// If the step isn't positive, or either end of the range isn't finite.
+    fn by(self, step: T) -> FloatRangeIter<T> {
+        self.try_by(step).unwrap_or_else(|error| panic!("{error}"))
+    }
 }
 
-impl Iterator for FloatRangeIter {
-    type Item = f64;
+/// Why a range can't be stepped through.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FloatRangeError<T: Float = f64> {
+    /// Steps have to be positive and finite.
+    Step(T),
+    /// Ranges have to start and end at finite values.
+    Bound(T),
+}
+
+impl<T: Float> std::fmt::Display for FloatRangeError<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FloatRangeError::Step(step) => write!(f, "step must be a positive number, not {step}"),
+            FloatRangeError::Bound(bound) => write!(f, "range must have finite ends, not {bound}"),
+        }
+    }
+}
+
+impl<T: Float> std::error::Error for FloatRangeError<T> {}
+
+fn validate<T: Float>(start: T, end: T, step: T) -> Result<(), FloatRangeError<T>> {
+    if !(step.is_finite() && step > T::ZERO) {
+        return Err(FloatRangeError::Step(step));
+    }
+    match [start, end].into_iter().find(|bound| !bound.is_finite()) {
+        Some(bound) => Err(FloatRangeError::Bound(bound)),
+        None => Ok(()),
+    }
+}
+
+impl<T: Float> Iterator for FloatRangeIter<T> {
+    type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.current < self.size {
-            let value = self.start + self.step * (self.current as f64);
-            assert!(value >= self.start);
-            assert!(value < self.end);
+            let value = self.value(self.current);
             self.current += 1;
             Some(value)
         } else {
             None
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.len();
+        (remaining, Some(remaining))
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        // Skip straight past the first n values, without computing them.
+        let n = i64::try_from(n).unwrap_or(i64::MAX);
+        self.current = self
+            .current
+            .saturating_add(n)
+            .min(self.size.max(self.current));
+        self.next()
+    }
+}
+
+impl<T: Float> ExactSizeIterator for FloatRangeIter<T> {
+    fn len(&self) -> usize {
+        (self.size - self.current).max(0) as usize
+    }
+}
+
+/// Once finished, a range stays finished: `next` and `next_back` keep returning `None`.
+impl<T: Float> std::iter::FusedIterator for FloatRangeIter<T> {}
+
+impl<T: Float> DoubleEndedIterator for FloatRangeIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.current < self.size {
+            self.size -= 1;
+            Some(self.value(self.size))
+        } else {
+            None
+        }
+    }
 }
 
-impl ToFloatRangeIter for std::ops::Range<f64> {
-    fn by(self, step: f64) -> FloatRangeIter {
+impl<T: Float> ToFloatRangeIter<T> for std::ops::Range<T> {
+    fn try_by(self, step: T) -> Result<FloatRangeIter<T>, FloatRangeError<T>> {
         let std::ops::Range { start, end } = self;
+        validate(start, end, step)?;
         let size = (end - start) / step;
 
-        FloatRangeIter {
+        Ok(FloatRangeIter {
             start,
             end,
             step,
             current: 0,
-            size: size as i64,
-        }
+            size: size.to_i64(),
+            inclusive: false,
+            last: None,
+        })
+    }
+
+    fn points(self, n: usize) -> FloatRangeIter<T> {
+        // The end is where the next point would be.
+        let mut points = FloatRangeIter::linspace(self.start, self.end, n + 1);
+        // Ranges that end where they start have no room for any points.
+        points.size = if self.start == self.end { 0 } else { n as i64 };
+        points.inclusive = false;
+        points.last = None;
+        points
+    }
+}
+
+impl<T: Float> ToFloatRangeIter<T> for std::ops::RangeInclusive<T> {
+    fn try_by(self, step: T) -> Result<FloatRangeIter<T>, FloatRangeError<T>> {
+        let (start, end) = self.into_inner();
+        validate(start, end, step)?;
+        let steps = (end - start) / step;
+        // Whole steps that fit, allowing for rounding when the end is meant to be a multiple of
+        // the step, like 0.1 into 0.3. How close counts doesn't grow with the number of steps,
+        // which would let long ranges take steps past the end.
+        let whole = steps.floor();
+        let whole = if steps - whole >= T::ONE - T::TOLERANCE {
+            whole + T::ONE
+        } else {
+            whole
+        };
+        let size = whole + T::ONE;
+
+        Ok(FloatRangeIter {
+            start,
+            end,
+            step,
+            current: 0,
+            size: size.to_i64(),
+            inclusive: true,
+            last: None,
+        })
+    }
+
+    fn points(self, n: usize) -> FloatRangeIter<T> {
+        let (start, end) = self.into_inner();
+        FloatRangeIter::linspace(start, end, n)
     }
 }
 
@@ -55,4 +406,224 @@ mod tests {
         let result: Vec<_> = range.collect();
         assert_eq!(vec![0.0, 0.25, 0.5, 0.75], result);
     }
+
+    #[test]
+    fn reversed() {
+        let result: Vec<_> = (0.0..1.0).by(0.25).rev().collect();
+        assert_eq!(vec![0.75, 0.5, 0.25, 0.0], result);
+
+        // Both ends can be taken from until they meet in the middle.
+        let mut range = (0.0..1.0).by(0.25);
+        assert_eq!(Some(0.0), range.next());
+        assert_eq!(Some(0.75), range.next_back());
+        assert_eq!(Some(0.25), range.next());
+        assert_eq!(Some(0.5), range.next_back());
+        assert_eq!(None, range.next());
+        assert_eq!(None, range.next_back());
+    }
+
+    #[test]
+    fn knows_how_many_are_left() {
+        let mut range = (0.0..1.0).by(0.25);
+        assert_eq!(4, range.len());
+        assert_eq!((4, Some(4)), range.size_hint());
+        range.next_back();
+        assert_eq!(3, range.len());
+        assert_eq!(Some(0.5), range.nth(2));
+        assert_eq!(0, range.len());
+        assert_eq!(None, (0.0..1.0).by(0.25).nth(4));
+        assert_eq!(None, (0.0..1.0).by(0.25).nth(usize::MAX));
+        assert_eq!(0, (1.0..0.0).by(0.25).len());
+    }
+
+    #[test]
+    fn stays_finished() {
+        let mut range = (0.0..0.5).by(0.25);
+        assert_eq!(2, range.by_ref().count());
+        for _ in 0..3 {
+            assert_eq!(None, range.next());
+            assert_eq!(None, range.next_back());
+            assert_eq!(None, range.nth(1));
+            assert_eq!(0, range.len());
+        }
+
+        // Empty and backwards ranges are finished from the start.
+        let mut backwards = (1.0..0.0).by(0.25);
+        assert_eq!(None, backwards.next());
+        assert_eq!(None, backwards.next_back());
+        assert_eq!(None, backwards.next());
+    }
+
+    #[test]
+    fn inclusive() {
+        let result: Vec<_> = (0.0..=1.0).by(0.25).collect();
+        assert_eq!(vec![0.0, 0.25, 0.5, 0.75, 1.0], result);
+
+        // Steps that don't divide the range evenly stop short of the end.
+        let result: Vec<_> = (0.0..=1.0).by(0.3).collect();
+        assert_eq!(4, result.len());
+        assert!((result[3] - 0.9).abs() < 1e-12);
+
+        // Steps that do, but only up to rounding, still reach it.
+        let result: Vec<_> = (0.0..=0.3).by(0.1).collect();
+        assert_eq!(4, result.len());
+        assert_eq!(Some(&0.3), result.last());
+        assert_eq!(Some(0.3), (0.0..=0.3).by(0.1).next_back());
+
+        assert_eq!(vec![2.0], (2.0..=2.0).by(1.0).collect::<Vec<_>>());
+        assert_eq!(0, (1.0..=0.0).by(0.25).len());
+
+        // Long ranges don't gain extra steps at the end.
+        let mut range = (0.0f32..=100000.0).by(1.0);
+        assert_eq!(100001, range.len());
+        assert_eq!(
+            (Some(100000.0), Some(99999.0)),
+            (range.next_back(), range.next_back())
+        );
+        assert_eq!(1428571429, (0.0f64..=1e9).by(0.7).len());
+    }
+
+    #[test]
+    fn rejects_nonsense_steps_and_bounds() {
+        assert_eq!(
+            Err(FloatRangeError::Step(0.0)),
+            (0.0..1.0).try_by(0.0).map(|_| ())
+        );
+        assert_eq!(
+            Err(FloatRangeError::Step(-0.5)),
+            (1.0..0.0).try_by(-0.5).map(|_| ())
+        );
+        assert!(matches!(
+            (0.0..=1.0).try_by(f64::NAN),
+            Err(FloatRangeError::Step(step)) if step.is_nan()
+        ));
+        assert_eq!(
+            Err(FloatRangeError::Bound(f64::INFINITY)),
+            (0.0..f64::INFINITY).try_by(1.0).map(|_| ())
+        );
+        assert_eq!(4, (0.0..1.0).try_by(0.25).unwrap().len());
+    }
+
+    #[test]
+    #[should_panic(expected = "step must be a positive number")]
+    fn by_panics_on_zero_steps() {
+        (0.0..1.0).by(0.0);
+    }
+
+    #[test]
+    fn single_precision() {
+        let result: Vec<f32> = (0.0f32..=1.0).by(0.25).collect();
+        assert_eq!(vec![0.0, 0.25, 0.5, 0.75, 1.0], result);
+        let result: Vec<f32> = (0.0f32..=0.3).by(0.1).collect();
+        assert_eq!(4, result.len());
+        assert_eq!(
+            Err(FloatRangeError::Step(0.0f32)),
+            (0.0f32..1.0).try_by(0.0).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn evenly_spaced_points() {
+        let result: Vec<_> = FloatRangeIter::linspace(0.0, 1.0, 5).collect();
+        assert_eq!(vec![0.0, 0.25, 0.5, 0.75, 1.0], result);
+        // The ends are exact, even when the steps between them aren't.
+        let result: Vec<_> = FloatRangeIter::linspace(0.1, 0.7, 7).collect();
+        assert_eq!(7, result.len());
+        assert_eq!((Some(&0.1), Some(&0.7)), (result.first(), result.last()));
+        let mut range = FloatRangeIter::linspace(0.0, 1.0, 5);
+        assert_eq!(Some(1.0), range.next_back());
+        assert_eq!(vec![0.0, 0.25, 0.5, 0.75], range.collect::<Vec<_>>());
+
+        let result: Vec<_> = FloatRangeIter::linspace(1.0, 0.0, 3).collect();
+        assert_eq!(vec![1.0, 0.5, 0.0], result);
+        assert_eq!(
+            vec![2.0],
+            FloatRangeIter::linspace(2.0, 3.0, 1).collect::<Vec<_>>()
+        );
+        assert_eq!(0, FloatRangeIter::linspace(2.0, 3.0, 0).len());
+
+        assert_eq!(
+            vec![0.0, 0.5, 1.0],
+            (0.0..=1.0).points(3).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![0.0, 0.25, 0.5, 0.75],
+            (0.0..1.0).points(4).collect::<Vec<_>>()
+        );
+        assert_eq!(vec![1.0, 0.5], (1.0..0.0).points(2).collect::<Vec<_>>());
+        assert_eq!(0, (1.0..1.0).points(2).len());
+    }
+
+    /// Checks a handful of values from both ends and the middle of the range.
+    fn assert_within<T: Float>(range: FloatRangeIter<T>, low: T, high: T, inclusive: bool) {
+        let middle = range.len() / 2;
+        let mut values: Vec<T> = range.clone().take(3).collect();
+        values.extend(range.clone().rev().take(3));
+        values.extend(range.clone().nth(middle));
+        for value in values {
+            let above_end = if inclusive {
+                value > high
+            } else {
+                value >= high
+            };
+            assert!(
+                value >= low && !above_end,
+                "{value} is outside {low}..{high}"
+            );
+        }
+    }
+
+    #[test]
+    fn never_panics_for_finite_ranges() {
+        let mut rng = random::Rng::new(365);
+        // Numbers of every size, with the awkward ones most likely to round badly.
+        let number = |rng: &mut random::Rng| -> f64 {
+            let special = [0.0, 1.0, 0.1, f64::MAX, f64::MIN_POSITIVE, 5e-324, 1e16];
+            let magnitude = if rng.below(3) == 0 {
+                special[rng.below(special.len())]
+            } else {
+                10f64.powf(rng.range(-300.0, 300.0)) * rng.next_f64()
+            };
+            if rng.below(2) == 0 {
+                magnitude
+            } else {
+                -magnitude
+            }
+        };
+        for _ in 0..20_000 {
+            let (a, b) = (number(&mut rng), number(&mut rng));
+            let (low, high) = if a <= b { (a, b) } else { (b, a) };
+            let step = number(&mut rng).abs();
+            if step > 0.0 {
+                assert_within((low..high).by(step), low, high, false);
+                assert_within((low..=high).by(step), low, high, true);
+            }
+            let n = rng.below(10);
+            assert_within(FloatRangeIter::linspace(a, b, n), low, high, true);
+            assert_within((low..high).points(n), low, high, false);
+
+            let (low, high, step) = (low as f32, high as f32, step as f32);
+            if [low, high, step].iter().all(|n| n.is_finite()) && step > 0.0 {
+                assert_within((low..high).by(step), low, high, false);
+                assert_within((low..=high).by(step), low, high, true);
+            }
+        }
+    }
+
+    #[test]
+    fn ping_pong() {
+        let result: Vec<_> = (0.0..=1.0).by(0.5).ping_pong().take(7).collect();
+        assert_eq!(vec![0.0, 0.5, 1.0, 0.5, 0.0, 0.5, 1.0], result);
+
+        // Only what's left of the range.
+        let mut range = (0.0..1.0).by(0.25);
+        range.next();
+        range.next_back();
+        let result: Vec<_> = range.ping_pong().take(4).collect();
+        assert_eq!(vec![0.25, 0.5, 0.25, 0.5], result);
+
+        let result: Vec<_> = (2.0..=2.0).by(1.0).ping_pong().take(3).collect();
+        assert_eq!(vec![2.0, 2.0, 2.0], result);
+        assert_eq!(None, (1.0..0.0).by(1.0).ping_pong().next());
+    }
 }
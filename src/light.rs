@@ -0,0 +1,324 @@
+//! Light sources and how they illuminate a surface.
+//!
+//! Lights are positioned relative to the camera, which looks down +Z from the origin, so the
+//! lighting stays put while the heart and camera move.
+
+use crate::math::{Real, Vec3};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Light {
+    /// A light infinitely far away, like the sun.
+    Directional {
+        /// Direction from the surface towards the light.
+        direction: Vec3,
+        color: [u8; 3],
+    },
+    /// A light at a position, like a bulb, which gets dimmer with the square of the distance.
+    Point {
+        position: Vec3,
+        /// Distance at which the light has full strength. Closer surfaces are brighter.
+        range: Real,
+        color: [u8; 3],
+    },
+}
+
+impl Default for Light {
+    /// Shines from the viewer's eye, which is how the heart has always been lit.
+    fn default() -> Light {
+        Light::directional(Vec3::new(0.0, 0.0, -1.0))
+    }
+}
+
+impl Light {
+    /// The range of point lights that don't specify one.
+    pub const DEFAULT_RANGE: Real = 60.0;
+
+    /// The colour of lights that don't specify one, which leaves surfaces their own colour.
+    pub const WHITE: [u8; 3] = [255, 255, 255];
+
+    pub fn directional(direction: Vec3) -> Light {
+        Light::Directional {
+            direction,
+            color: Light::WHITE,
+        }
+    }
+
+    pub fn point(position: Vec3) -> Light {
+        Light::Point {
+            position,
+            range: Light::DEFAULT_RANGE,
+            color: Light::WHITE,
+        }
+    }
+
+    pub fn color(&self) -> [u8; 3] {
+        match *self {
+            Light::Directional { color, .. } | Light::Point { color, .. } => color,
+        }
+    }
+
+    /// The same light in `color`.
+    pub fn with_color(self, color: [u8; 3]) -> Light {
+        match self {
+            Light::Directional { direction, .. } => Light::Directional { direction, color },
+            Light::Point {
+                position, range, ..
+            } => Light::Point {
+                position,
+                range,
+                color,
+            },
+        }
+    }
+
+    /// How far `point` is from the light.
+    pub fn distance(&self, point: Vec3) -> Real {
+        match *self {
+            Light::Directional { .. } => Real::INFINITY,
+            Light::Point { position, .. } => (position - point).length(),
+        }
+    }
+
+    /// Unit vector from `point` towards the light, and how strongly the light arrives there.
+    pub fn incident(&self, point: Vec3) -> (Vec3, Real) {
+        match *self {
+            Light::Directional { direction, .. } => (direction.normalized(), 1.0),
+            Light::Point {
+                position, range, ..
+            } => {
+                let to_light = position - point;
+                let distance = to_light.length();
+                if distance == 0.0 {
+                    return (Vec3::new(0.0, 0.0, -1.0), 1.0);
+                }
+                let falloff = (range / distance).powi(2);
+                (to_light * (1.0 / distance), falloff)
+            }
+        }
+    }
+}
+
+/// Every light in the scene, and how surfaces respond to them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lighting {
+    pub lights: Vec<Light>,
+    /// Fraction of full brightness that every surface gets, even where no light reaches it.
+    pub ambient: Real,
+    /// Adds specular highlights; higher values make them smaller and sharper.
+    pub shininess: Option<Real>,
+}
+
+impl Default for Lighting {
+    fn default() -> Lighting {
+        Lighting {
+            lights: vec![Light::default()],
+            ambient: 0.0,
+            shininess: None,
+        }
+    }
+}
+
+impl Lighting {
+    /// How brightly a surface at `point` facing `normal` (a unit vector) is lit.
+    ///
+    /// Each light contributes between −1 (shining directly at the back of the surface) and 1
+    /// (directly at the front), scaled by its falloff. With a `shininess`, lights also add a
+    /// Blinn–Phong specular highlight of up to 1 where they reflect towards the viewer. Finally,
+    /// the ambient term lifts the darkest surfaces without changing the brightest ones.
+    pub fn luminance(&self, point: Vec3, normal: Vec3) -> Real {
+        self.shadowed_luminance(point, normal, |_| false)
+    }
+
+    /// Like [`Lighting::luminance`], but lights for which `shadowed` is true are blocked, and
+    /// count as if they were shining directly at the back of the surface.
+    pub fn shadowed_luminance(
+        &self,
+        point: Vec3,
+        normal: Vec3,
+        shadowed: impl Fn(&Light) -> bool,
+    ) -> Real {
+        let luma: Real = self
+            .lights
+            .iter()
+            .map(|light| {
+                let (direction, strength) = light.incident(point);
+                if normal.dot(direction) > 0.0 && shadowed(light) {
+                    return -strength;
+                }
+                self.reflected(direction, strength, point, normal)
+            })
+            .sum();
+
+        // Remap −1..1 to (−1 + 2 × ambient)..1.
+        luma + self.ambient * (1.0 - luma)
+    }
+
+    /// Whether any of the lights has a colour.
+    pub fn is_colored(&self) -> bool {
+        self.lights
+            .iter()
+            .any(|light| light.color() != Light::WHITE)
+    }
+
+    /// The colour of a surface at `point` facing `normal` whose own colour is `base`: each channel
+    /// of `base` (white if it's `None`) times the lights' colours, mixed by how much each one
+    /// lights it. `None` when neither the surface nor any of the lights has a colour, so the
+    /// terminal's own colour is left alone.
+    pub fn color(&self, base: Option<[u8; 3]>, point: Vec3, normal: Vec3) -> Option<[u8; 3]> {
+        if !self.is_colored() {
+            return base;
+        }
+        let mut mix = [0.0; 3];
+        let mut total = 0.0;
+        for light in &self.lights {
+            // Lights that don't reach the surface still tint it a little, the way the ambient
+            // term still lights it.
+            let (direction, strength) = light.incident(point);
+            let weight =
+                self.reflected(direction, strength, point, normal).max(0.0) + Real::EPSILON;
+            for (mix, channel) in mix.iter_mut().zip(light.color()) {
+                *mix += weight * Real::from(channel);
+            }
+            total += weight;
+        }
+        let base = base.unwrap_or(Light::WHITE);
+        Some([0, 1, 2].map(|i| (mix[i] / total * Real::from(base[i]) / 255.0).round() as u8))
+    }
+
+    /// How much a light arriving from `direction` with `strength` adds to the luminance of a
+    /// surface at `point` facing `normal`.
+    fn reflected(&self, direction: Vec3, strength: Real, point: Vec3, normal: Vec3) -> Real {
+        // The viewer is at the origin.
+        let to_viewer = -point.normalized();
+        let diffuse = normal.dot(direction);
+        let specular = match self.shininess {
+            Some(shininess) if diffuse > 0.0 => {
+                let halfway = (direction + to_viewer).normalized();
+                normal.dot(halfway).max(0.0).powf(shininess)
+            }
+            _ => 0.0,
+        };
+        (diffuse + specular) * strength
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::TOLERANCE;
+
+    fn lighting(lights: impl Into<Vec<Light>>, shininess: Option<Real>) -> Lighting {
+        Lighting {
+            lights: lights.into(),
+            ambient: 0.0,
+            shininess,
+        }
+    }
+
+    #[test]
+    fn default_light_comes_from_the_viewer() {
+        let facing_viewer = Vec3::new(0.0, 0.0, -1.0);
+        let lights = [Light::default()];
+        assert_eq!(
+            1.0,
+            lighting(lights, None).luminance(Vec3::new(0.0, 0.0, 70.0), facing_viewer)
+        );
+        assert_eq!(
+            -1.0,
+            lighting(lights, None).luminance(Vec3::new(0.0, 0.0, 70.0), -facing_viewer)
+        );
+    }
+
+    #[test]
+    fn shadowed_lights_leave_the_surface_dark() {
+        let facing_viewer = Vec3::new(0.0, 0.0, -1.0);
+        let lighting = lighting([Light::default(), Light::point(Vec3::default())], None);
+        let point = Vec3::new(0.0, 0.0, 60.0);
+        let luma = lighting.shadowed_luminance(point, facing_viewer, |light| {
+            matches!(light, Light::Directional { .. })
+        });
+        assert_eq!(0.0, luma);
+        assert_eq!(
+            2.0,
+            lighting.shadowed_luminance(point, facing_viewer, |_| false)
+        );
+    }
+
+    #[test]
+    fn point_light_falls_off_with_inverse_square() {
+        let light = Light::Point {
+            position: Vec3::default(),
+            range: 10.0,
+            color: Light::WHITE,
+        };
+        let normal = Vec3::new(0.0, 0.0, -1.0);
+        let near = lighting([light], None).luminance(Vec3::new(0.0, 0.0, 10.0), normal);
+        let far = lighting([light], None).luminance(Vec3::new(0.0, 0.0, 20.0), normal);
+        assert!((near - 1.0).abs() < TOLERANCE);
+        assert!((far - 0.25).abs() < TOLERANCE);
+    }
+
+    #[test]
+    fn nearer_side_is_brighter() {
+        let lights = [Light::point(Vec3::new(40.0, 0.0, 70.0))];
+        let facing_light = Vec3::new(1.0, 0.0, 0.0);
+        let near = lighting(lights, None).luminance(Vec3::new(10.0, 0.0, 70.0), facing_light);
+        let far = lighting(lights, None).luminance(Vec3::new(-10.0, 0.0, 70.0), facing_light);
+        assert!(near > far);
+    }
+
+    #[test]
+    fn specular_highlight_peaks_at_reflection() {
+        let lights = [Light::directional(Vec3::new(1.0, 0.0, -1.0))];
+        let point = Vec3::new(0.0, 0.0, 70.0);
+        // Halfway between the light and the viewer.
+        let mirror = Vec3::new(1.0, 0.0, -(2.0 as Real).sqrt() - 1.0).normalized();
+        let off_axis = Vec3::new(1.0, 0.0, -0.2).normalized();
+
+        let diffuse = |normal: Vec3| lighting(lights, None).luminance(point, normal);
+        let shiny = |normal: Vec3| lighting(lights, Some(64.0)).luminance(point, normal);
+        assert!((shiny(mirror) - diffuse(mirror) - 1.0).abs() < TOLERANCE);
+        assert!(shiny(off_axis) - diffuse(off_axis) < 0.01);
+    }
+
+    #[test]
+    fn lights_tint_surfaces_they_shine_on() {
+        let pink = Light::directional(Vec3::new(0.0, 0.0, -1.0)).with_color([255, 0, 128]);
+        let blue = Light::directional(Vec3::new(1.0, 0.0, 0.0)).with_color([0, 0, 255]);
+        let lighting = lighting([pink, blue], None);
+        let point = Vec3::new(0.0, 0.0, 70.0);
+        let facing_viewer = Vec3::new(0.0, 0.0, -1.0);
+        let facing_right = Vec3::new(1.0, 0.0, 0.0);
+        assert_eq!(
+            Some([255, 0, 128]),
+            lighting.color(None, point, facing_viewer)
+        );
+        assert_eq!(
+            Some([0, 0, 128]),
+            lighting.color(Some([255, 255, 128]), point, facing_right)
+        );
+        // Halfway between them, both lights mix.
+        let between = (facing_viewer + facing_right).normalized();
+        let [r, g, b] = lighting.color(None, point, between).unwrap();
+        assert!(r.abs_diff(128) <= 1 && g == 0 && b.abs_diff(192) <= 1);
+
+        let white = self::lighting([Light::default()], None);
+        assert_eq!(None, white.color(None, point, facing_viewer));
+        assert_eq!(
+            Some([1, 2, 3]),
+            white.color(Some([1, 2, 3]), point, facing_viewer)
+        );
+    }
+
+    #[test]
+    fn ambient_lifts_the_dark_side() {
+        let lighting = Lighting {
+            ambient: 0.25,
+            ..Lighting::default()
+        };
+        let point = Vec3::new(0.0, 0.0, 70.0);
+        let facing_viewer = Vec3::new(0.0, 0.0, -1.0);
+        assert_eq!(-0.5, lighting.luminance(point, -facing_viewer));
+        assert_eq!(1.0, lighting.luminance(point, facing_viewer));
+    }
+}
@@ -1,172 +1,1957 @@
 //! Rust version of https://github.com/T4ras123/ascii-love
 
-use std::f64::consts::PI;
+mod cli;
+mod completions;
+
+use std::borrow::Cow;
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::mem;
+use std::net::{TcpListener, TcpStream};
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::process;
 use std::sync::atomic::Ordering;
 use std::sync::atomic::{AtomicBool, AtomicUsize};
+use std::sync::{mpsc, Arc, Mutex, OnceLock};
 use std::thread;
 use std::time;
 
-use signal_hook::consts::{SIGINT, SIGTERM, SIGWINCH};
+use signal_hook::consts::{SIGHUP, SIGINT, SIGTERM, SIGWINCH};
 use signal_hook::iterator::Signals;
 
-use ascii_love::ToFloatRangeIter;
+#[cfg(feature = "audio")]
+use ascii_love::audio::Audio;
+use ascii_love::auto::{Look, Moment};
+use ascii_love::background::{self, Background, Starfield};
+use ascii_love::bloom;
+use ascii_love::cache::{self, Cache};
+use ascii_love::camera::Camera;
+use ascii_love::clock;
+use ascii_love::color::{self, Colors};
+use ascii_love::command;
+use ascii_love::config::Config;
+use ascii_love::crt;
+use ascii_love::diagnostics::{Checksum, Event, FrameTimes};
+use ascii_love::dither::Dither;
+use ascii_love::http::{self, Request};
+use ascii_love::image::Image;
+use ascii_love::light::{Light, Lighting};
+use ascii_love::math::consts::PI;
+use ascii_love::math::{Quat, Real, Vec3};
+use ascii_love::mesh::Mesh;
+use ascii_love::motion::Bounce;
+use ascii_love::overlay;
+use ascii_love::pace::Pace;
+use ascii_love::pulse;
+use ascii_love::random::Rng;
+use ascii_love::raster;
+use ascii_love::render::{self, DebugView};
+use ascii_love::scene::{Material, Object, Scene, Shape, Transform};
+use ascii_love::screenshot;
+use ascii_love::shading::{self, Shading};
+use ascii_love::sparkle::Sparkles;
+use ascii_love::stereo::{self, Stereo};
+use ascii_love::terminfo::{self, Charset, Terminal};
+use ascii_love::theme::Coloring;
+use ascii_love::timeline::{Properties, Timeline};
+use ascii_love::trail::Trail;
+use ascii_love::viewport::{self, Align, Viewport};
+use ascii_love::width;
 
-const LUMINANCE: [char; 12] = ['.', ',', '-', '~', ':', ';', '=', '!', '*', '#', '$', '@'];
+use cli::{ImageOptions, Options, ParseError};
+use completions::Shell;
 
 static SCREEN_WIDTH: AtomicUsize = AtomicUsize::new(150);
 static SCREEN_HEIGHT: AtomicUsize = AtomicUsize::new(40);
+/// How much bigger objects get at the peak of a heartbeat.
+const PULSE: f64 = 0.12;
+/// Where --clock shows the time: under the heart, clear of it as it tumbles.
+const CLOCK_POSITION: Vec3 = Vec3::new(0.0, -21.0, 0.0);
+/// How much bigger objects get at the loudest moments of --audio.
+const AUDIO_PULSE: f64 = 0.2;
+/// How fast --rainbow turns the hue, in degrees per second.
+const RAINBOW_SPEED: f64 = 90.0;
+/// How long to wait between frames, and with --reduced-motion.
+const PAUSE: time::Duration = time::Duration::from_millis(45);
+const REDUCED_MOTION_PAUSE: time::Duration = time::Duration::from_millis(150);
+/// How many times taller than wide terminal cells are.
+const CELL_ASPECT: f64 = 2.0;
+
+/// Insets are this many times smaller than the screen, across and down.
+const INSET_SIZE: usize = 4;
+
+/// How often --watch checks whether the configuration has changed.
+const WATCH_EVERY: time::Duration = time::Duration::from_millis(500);
+
+/// How long the terminal has to stay the same size after being resized before frames are drawn
+/// in full again. Until then, while someone's dragging the window, they're drawn roughly and
+/// less often, so that they keep up.
+const RESIZE_SETTLE: time::Duration = time::Duration::from_millis(300);
+/// How densely surfaces are sampled while the terminal's being resized, compared to usual.
+const RESIZE_DENSITY: Real = 0.5;
+/// The pause between frames while the terminal's being resized, at the least.
+const RESIZE_PAUSE: time::Duration = time::Duration::from_millis(100);
+
+/// How long a --control connection waits for a command to be run before giving up.
+const CONTROL_TIMEOUT: time::Duration = time::Duration::from_secs(5);
+
+/// The size frames are served at by --serve, unless --width and --height or the client say
+/// otherwise: the size terminals usually start at.
+const SERVED_SIZE: (usize, usize) = (80, 24);
+/// The biggest frames a client can ask --serve for, in columns and rows.
+const LARGEST_SERVED: (usize, usize) = (500, 200);
+/// How long --serve waits for a client to say what it wants, or to take the next frame, before
+/// giving up on it.
+const CLIENT_TIMEOUT: time::Duration = time::Duration::from_secs(30);
+
+/// The most bytes of frames --cache keeps.
+const CACHE_BUDGET: usize = 64 * 1024 * 1024;
+
+/// How often --verbose reports how long frames are taking, in seconds.
+const FRAME_REPORT: f64 = 10.0;
+
 static SHOULD_PLAY: AtomicBool = AtomicBool::new(true);
+/// Whether time stands still, with the last frame left on screen. Set over --control.
+static PAUSED: AtomicBool = AtomicBool::new(false);
+/// When the terminal was last resized, if it has been.
+static RESIZED: Mutex<Option<time::Instant>> = Mutex::new(None);
+/// Whether the configuration file has changed, and should be read again before the next frame.
+static RELOAD: AtomicBool = AtomicBool::new(false);
+/// Whether to draw the depth buffer instead of the shaded objects. Switched with the z key.
+static DEPTH_VIEW: AtomicBool = AtomicBool::new(false);
+/// The command line opened with the : key.
+static PROMPT: Mutex<Prompt> = Mutex::new(Prompt {
+    typing: None,
+    entered: Vec::new(),
+    message: None,
+});
+/// How to copy the next frame to the clipboard, once y or Y has been pressed.
+static COPY: Mutex<Option<Clipboard>> = Mutex::new(None);
+/// Whether to save the next frame in --screenshot-dir. Set with the s key.
+static SCREENSHOT: AtomicBool = AtomicBool::new(false);
+static TERMINAL: OnceLock<Terminal> = OnceLock::new();
+static LOG: OnceLock<Log> = OnceLock::new();
 
 fn main() {
-    let mut signals = Signals::new([SIGINT, SIGTERM, SIGWINCH]).unwrap();
+    let mut args = std::env::args().skip(1).peekable();
+    if args.peek().is_some_and(|arg| arg == "image") {
+        args.next();
+        return draw_image(args);
+    }
+    if args.peek().is_some_and(|arg| arg == "completions") {
+        args.next();
+        return print_completions(args);
+    }
+    let options = match Options::parse(args) {
+        Ok(options) => options,
+        Err(ParseError::Usage(message)) => {
+            eprintln!("ascii-love: {message}");
+            eprintln!("{}", cli::USAGE);
+            process::exit(2);
+        }
+        Err(ParseError::Load(message)) => {
+            eprintln!("ascii-love: {message}");
+            process::exit(1);
+        }
+    };
+    if options.help {
+        println!("{}", cli::USAGE);
+        return;
+    }
+    if options.serve.is_some() {
+        // Whoever's watching, nearly every terminal understands these.
+        let _ = TERMINAL.set(Terminal::ansi());
+    }
+    let config = match options.config() {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("ascii-love: {error}");
+            process::exit(1);
+        }
+    };
+
+    if let Err(message) = start_log(&options) {
+        eprintln!("ascii-love: {message}");
+        process::exit(1);
+    }
+
+    let mut signals = Signals::new([SIGHUP, SIGINT, SIGTERM, SIGWINCH]).unwrap();
     let handle = signals.handle();
+    let (width, height) = (config.width, config.height);
+    // Checksums can't depend on the size of the terminal they're worked out in, and served
+    // frames aren't drawn on it.
+    let measure = !options.checksum && options.serve.is_none();
     let thread = thread::spawn(move || {
         for signal in &mut signals {
             match signal {
                 SIGTERM | SIGINT => stop_animation(),
-                SIGWINCH => update_screen_dimensions(),
+                SIGHUP => RELOAD.store(true, Ordering::Relaxed),
+                SIGWINCH if !measure => {}
+                SIGWINCH => {
+                    *RESIZED.lock().unwrap() = Some(time::Instant::now());
+                    update_screen_dimensions(width, height);
+                    log(Event::new("resize")
+                        .with("width", SCREEN_WIDTH.load(Ordering::Relaxed))
+                        .with("height", SCREEN_HEIGHT.load(Ordering::Relaxed)));
+                }
                 _ => unreachable!(),
             }
         }
     });
 
-    update_screen_dimensions();
-    hide_cursor();
-    animate();
-    show_cursor();
+    let loudness = match loudness(&options) {
+        Ok(_) if config.reduced_motion => None,
+        Ok(loudness) => loudness,
+        Err(message) => {
+            eprintln!("ascii-love: {message}");
+            process::exit(1);
+        }
+    };
+
+    if options.watch {
+        watch(options.files());
+    }
+    let control = match options.control.as_deref().map(Control::start) {
+        Some(Err(error)) => {
+            eprintln!("ascii-love: --control: {error}");
+            process::exit(1);
+        }
+        control => control.and_then(Result::ok),
+    };
+    let server =
+        match (options.serve.as_deref()).map(|address| Server::start(address, width, height)) {
+            Some(Err(error)) => {
+                eprintln!("ascii-love: --serve: {error}");
+                process::exit(1);
+            }
+            server => server.and_then(Result::ok),
+        };
+
+    if measure {
+        update_screen_dimensions(width, height);
+    } else if let Some(server) = &server {
+        let (width, height) = server.size();
+        set_screen_dimensions(Some(width), Some(height));
+    } else {
+        set_screen_dimensions(width, height);
+    }
+    // Frames that aren't drawn on the terminal leave it alone.
+    let on_terminal = options.on_terminal();
+    let screensaver = (options.screensaver && on_terminal).then(Screensaver::start);
+    // Standard input isn't free for keys when the screensaver or --audio - is using it.
+    let keys =
+        (screensaver.is_none() && on_terminal && options.audio.as_deref() != Some(Path::new("-")))
+            .then(Keys::start)
+            .flatten();
+    DEPTH_VIEW.store(config.debug == Some(DebugView::Depth), Ordering::Relaxed);
+    log(Event::new("start")
+        .with("term", env::var("TERM").unwrap_or_default())
+        .with("colors", terminal().colors)
+        .with(
+            "charset",
+            match charset(&config) {
+                Charset::Ascii => "ascii",
+                Charset::Unicode => "unicode",
+            },
+        )
+        .with("width", SCREEN_WIDTH.load(Ordering::Relaxed))
+        .with("height", SCREEN_HEIGHT.load(Ordering::Relaxed))
+        .with("keys", keys.is_some()));
+    if options.headless() {
+        let delay = pause(&config).as_millis() as u16;
+        let frames = animate(config, &options, loudness.as_deref(), None);
+        let png = match frames.as_slice() {
+            [] => None,
+            [frame] => Some(frame.png()),
+            frames => Some(Image::animated_png(frames, delay)),
+        };
+        if let (Some(path), Some(png)) = (&options.export, png) {
+            if let Err(error) = fs::write(path, png) {
+                eprintln!("ascii-love: {}: {error}", path.display());
+                process::exit(1);
+            }
+        }
+    } else if let Some(server) = &server {
+        animate(config, &options, loudness.as_deref(), Some(server));
+    } else {
+        hide_cursor();
+        print!("{}", terminal().save_title);
+        animate(config, &options, loudness.as_deref(), None);
+        print!("{}", terminal().restore_title);
+        show_cursor();
+    }
+    drop(server);
+    drop(control);
+    drop(keys);
+    drop(screensaver);
+    log(Event::new("stop"));
+    if let Some(seed) = options.seed {
+        // So that a run worth keeping can be repeated.
+        eprintln!("ascii-love: random seed {seed}");
+    }
 
     handle.close();
     thread.join().unwrap();
 }
 
-fn animate() {
-    let pause = time::Duration::from_millis(45);
-    let mut a = (0.0..2.0 * PI).by(0.05).cycle();
-    let mut b = (0.0..2.0 * PI).by(0.04).cycle();
+/// Prints a completion script for the shell named in `args`.
+fn print_completions(mut args: impl Iterator<Item = String>) {
+    let shell = match (args.next(), args.next()) {
+        (Some(shell), None) => shell.parse::<Shell>(),
+        _ => Err("expected a shell: bash, zsh, or fish".to_owned()),
+    };
+    match shell {
+        Ok(shell) => print!("{}", completions::script(shell)),
+        Err(message) => {
+            eprintln!("ascii-love: {message}");
+            eprintln!("{}", cli::USAGE);
+            process::exit(2);
+        }
+    }
+}
+
+/// Prints a still image as characters, instead of animating.
+fn draw_image(args: impl Iterator<Item = String>) {
+    let options = match ImageOptions::parse(args) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("ascii-love: {message}");
+            eprintln!("{}", cli::IMAGE_USAGE);
+            process::exit(2);
+        }
+    };
+    if options.help {
+        println!("{}", cli::IMAGE_USAGE);
+        return;
+    }
+    let image = match Image::load(&options.path) {
+        Ok(image) => image,
+        Err(error) => {
+            eprintln!("ascii-love: {error}");
+            process::exit(1);
+        }
+    };
+
+    // Rows per column of the image, allowing for cells being taller than they're wide.
+    let aspect = image.height as f64 / image.width as f64 / CELL_ASPECT;
+    let width = options
+        .width
+        .unwrap_or_else(|| match terminal_dimensions() {
+            // Fit the whole image on screen without stretching it, leaving a row for the prompt.
+            Some((columns, rows)) => {
+                let fit = (rows.saturating_sub(1) as f64 / aspect) as usize;
+                columns.min(fit).min(image.width).max(1)
+            }
+            None => 80,
+        });
+    let height = ((width as f64 * aspect).round() as usize).max(1);
+
+    let image = image.resize(width, height);
+    let text = render::image(&image, options.shading, options.dither);
+    for (y, row) in text.iter().enumerate() {
+        let colors: Vec<_> = (0..width).map(|x| Some(image.pixel(x, y))).collect();
+        println!("{}", color::line(row, &colors, options.color));
+    }
+}
+
+/// How loud it is some number of seconds in, from 0 to 1.
+type Loudness = dyn Fn(f64) -> f64;
+
+/// Follows the sound chosen with --audio, if any.
+#[cfg(feature = "audio")]
+fn loudness(options: &Options) -> Result<Option<Box<Loudness>>, String> {
+    let Some(path) = &options.audio else {
+        return Ok(None);
+    };
+    let audio = Audio::open(path).map_err(|error| format!("--audio: {error}"))?;
+    Ok(Some(Box::new(move |seconds| audio.level(seconds))))
+}
+
+#[cfg(not(feature = "audio"))]
+fn loudness(_: &Options) -> Result<Option<Box<Loudness>>, String> {
+    Ok(None)
+}
+
+/// Plays the animation until it's stopped, on the terminal or for everyone watching `server`.
+/// `options` are for reading the configuration again on SIGHUP, which starts the effects over
+/// with the new settings. For --checksum and --export, frames are drawn as fast as they can be
+/// until there are enough of them, printing their checksums, or returned as pictures.
+fn animate(
+    mut base: Config,
+    options: &Options,
+    loudness: Option<&Loudness>,
+    server: Option<&Server>,
+) -> Vec<Image> {
+    let start = time::Instant::now();
+    // How long time has stood still for, which doesn't count, and when it stopped if it still is.
+    let mut paused = time::Duration::ZERO;
+    let mut stopped: Option<time::Instant> = None;
+    // Rotation angle around each axis, in radians.
+    let mut angles = Vec3::default();
+    let mut axis_angle = 0.0;
+    let mut orbit_angle = 0.0;
+    let mut effects = Effects::new(&base);
+    // Which turn of the demo is showing, and the configuration for it.
+    let mut demo: Option<(usize, Config)> = None;
+    // When --theme auto last picked a look, and the configuration with it.
+    let mut seasonal: Option<(Moment, Config)> = None;
+    let mut last_frame = 0.0;
+    let mut frames: u64 = 0;
+    // Frames skipped because drawing took too long.
+    let mut dropped: u64 = 0;
+    // How far the turning is behind the clock, and when it last caught up.
+    let mut pace = Pace::default();
+    let mut stepped = time::Instant::now();
+    // When the frame rate in the title was last worked out, and how many frames there were then.
+    let mut counted = (0.0, 0);
+    // The time shown by --clock, and the digits for it.
+    let mut face: Option<(String, Arc<Mesh>)> = None;
+    // How long frames have taken since they were last reported, and when that was.
+    let mut frame_times = LOG
+        .get()
+        .map(|_| FrameTimes::new(pause(&base).as_secs_f64()));
+    let mut reported = 0.0;
+    let output = Output::start();
+    let mut exported = Vec::new();
+    let mut checksum = Checksum::new();
+    // The frames of one turn for --cache, and how far into it the animation is. Served frames
+    // are fitted to each client, so they're always drawn.
+    let caching = options.cache && options.on_terminal();
+    let mut cache = caching.then(|| looping(&mut base)).flatten();
+    let mut position = 0;
+    // The size of the screen the last frame was drawn for.
+    let mut drawn = (
+        SCREEN_WIDTH.load(Ordering::Relaxed),
+        SCREEN_HEIGHT.load(Ordering::Relaxed),
+    );
+    if caching {
+        log(Event::new("cache").with("frames", cache.as_ref().map_or(0, Cache::len)));
+    }
 
     while SHOULD_PLAY.load(Ordering::Relaxed) {
-        let a = a.next().unwrap();
-        let b = b.next().unwrap();
-        clear_screen();
-        render_frame(a, b);
-        thread::sleep(pause);
+        let mut changed = false;
+        if RELOAD.swap(false, Ordering::Relaxed) {
+            match options.config() {
+                Ok(config) => {
+                    base = config;
+                    changed = true;
+                    log(Event::new("reload"));
+                }
+                // Carry on as before, so a typo doesn't end the animation.
+                Err(error) if LOG.get().is_some() => {
+                    log(Event::new("reload").with("error", error));
+                }
+                Err(error) => eprintln!("ascii-love: {error}"),
+            }
+        }
+        let entered = mem::take(&mut PROMPT.lock().unwrap().entered);
+        for Entered { line, reply } in entered {
+            let result = command::run(&mut base, &line);
+            if result.is_ok() {
+                changed = true;
+                log(Event::new("command").with("command", &line));
+            }
+            match (result, reply) {
+                (result, Some(reply)) => {
+                    let _ = reply.send(result);
+                }
+                (Err(error), None) => PROMPT.lock().unwrap().message = Some(error),
+                (Ok(()), None) => {}
+            }
+        }
+        if changed {
+            if caching {
+                cache = looping(&mut base);
+                position = 0;
+            }
+            effects = Effects::new(&base);
+            demo = None;
+            seasonal = None;
+            if let Some(times) = &mut frame_times {
+                times.budget = pause(&base).as_secs_f64();
+            }
+        }
+        // Served frames are drawn big enough for the biggest anyone's watching, and shrunk for
+        // everyone else.
+        if let Some((width, height)) = server.map(Server::size) {
+            let size = (
+                SCREEN_WIDTH.load(Ordering::Relaxed),
+                SCREEN_HEIGHT.load(Ordering::Relaxed),
+            );
+            if size != (width, height) {
+                set_screen_dimensions(Some(width), Some(height));
+                log(Event::new("resize")
+                    .with("width", width)
+                    .with("height", height));
+            }
+        }
+        // Frames are still drawn while paused, so that changes show, just all at the same moment.
+        match (PAUSED.load(Ordering::Relaxed), stopped) {
+            (true, None) => stopped = Some(time::Instant::now()),
+            (false, Some(since)) => {
+                paused += since.elapsed();
+                stopped = None;
+            }
+            _ => {}
+        }
+        // Exported frames are a pause apart, however long they take to draw.
+        let elapsed = match options.headless() {
+            true => frames as f64 * pause(&base).as_secs_f64(),
+            false => (stopped.unwrap_or_else(time::Instant::now) - start - paused).as_secs_f64(),
+        };
+        // Picked again each minute, which is as often as the clock changes.
+        if base.auto {
+            let moment = Moment::now();
+            if seasonal
+                .as_ref()
+                .is_none_or(|(picked, _)| *picked != moment)
+            {
+                let look = Look::at(moment, &base.occasions);
+                let mut config = base.clone();
+                look.apply(&mut config);
+                if seasonal.as_ref().is_none_or(|(_, shown)| *shown != config) {
+                    log(Event::new("look").with("name", &look.name));
+                }
+                seasonal = Some((moment, config));
+            }
+        }
+        let looked = seasonal.as_ref().map_or(&base, |(_, config)| config);
+        if let Some(showcase) = &base.demo {
+            let turn = showcase.turn(elapsed);
+            if demo.as_ref().is_none_or(|(shown, _)| *shown != turn) {
+                let config = showcase.config(looked, turn);
+                effects = Effects::new(&config);
+                demo = Some((turn, config));
+            }
+        }
+        let config = demo.as_ref().map_or(looked, |(_, config)| config);
+        let rotation = match config.axis {
+            Some(axis) => Quat::from_axis_angle(axis, axis_angle),
+            // Tumble around Y, then X, then Z.
+            None => {
+                Quat::from_axis_angle(Vec3::Z, angles.z)
+                    * Quat::from_axis_angle(Vec3::X, angles.x)
+                    * Quat::from_axis_angle(Vec3::Y, angles.y)
+            }
+        };
+        let orientation = rotation * config.orientation;
+        let camera = match &config.camera {
+            Some(path) => path.at(elapsed),
+            None => Camera::default(),
+        };
+        let properties = config.timeline.at(elapsed);
+        let pulse = match config.bpm {
+            Some(bpm) => 1.0 + PULSE * pulse::heartbeat(elapsed * bpm / 60.0),
+            None => 1.0,
+        };
+        let pulse = match loudness {
+            Some(loudness) => pulse * (1.0 + AUDIO_PULSE * loudness(elapsed)),
+            None => pulse,
+        };
+        let (shape, transition) = match &config.cycle {
+            Some(playlist) => {
+                let (shape, scale) = playlist.at(elapsed);
+                (Some(shape), scale)
+            }
+            None => (None, 1.0),
+        };
+        let transition = transition * base.demo.map_or(1.0, |demo| demo.scale(elapsed));
+        let pose = Pose {
+            orientation,
+            orbit: Quat::from_axis_angle(Vec3::Y, orbit_angle),
+            scale: (pulse * transition) as Real,
+            shape,
+        };
+        // One scene for each view, side by side. Tiles take turns with the split shapes, each
+        // turned a little further than the last.
+        let shapes: Vec<Option<Shape>> = match config.split.as_slice() {
+            [] => vec![pose.shape.clone()],
+            split => split.iter().cloned().map(Some).collect(),
+        };
+        let views = config.tile.unwrap_or(shapes.len());
+        let mut scenes: Vec<Scene> = (0..views)
+            .map(|i| {
+                let phase = (i as f64 * config.tile_phase).to_radians() as Real;
+                let pose = Pose {
+                    orientation: pose.orientation * Quat::from_axis_angle(Vec3::Y, phase),
+                    shape: shapes[i % shapes.len()].clone(),
+                    ..pose
+                };
+                scene(config, &pose, &camera, &properties)
+            })
+            .collect();
+        if config.clock {
+            let time = clock::now();
+            if face.as_ref().is_none_or(|(shown, _)| *shown != time) {
+                let digits = Arc::new(clock::digits(&time));
+                face = Some((time, digits));
+            }
+            if let Some((_, digits)) = &face {
+                let clock = Object {
+                    shape: Shape::Mesh(Arc::clone(digits)),
+                    // Only orbits, so that it stays readable.
+                    transform: Transform {
+                        position: CLOCK_POSITION,
+                        ..Transform::default()
+                    }
+                    .rotated(pose.orbit),
+                    material: Material::default(),
+                };
+                for scene in &mut scenes {
+                    scene.objects.push(clock.clone());
+                }
+            }
+        }
+        let dt = elapsed - last_frame;
+        if let Some(starfield) = &mut effects.starfield {
+            starfield.update(dt);
+            let stars = starfield.particles(&camera);
+            for scene in &mut scenes {
+                scene.particles.extend_from_slice(&stars);
+            }
+        }
+        // Sparkles only keep track of where they came from in the first view.
+        if let Some(sparkles) = &mut effects.sparkles {
+            sparkles.update(dt, &scenes[0].objects);
+            scenes[0].particles.extend(sparkles.particles());
+        }
+        if let Some(bounce) = &mut effects.bounce {
+            bounce.update(dt);
+        }
+        last_frame = elapsed;
+        let placement = match (&effects.bounce, &config.lissajous) {
+            (Some(bounce), _) => Some(bounce.position),
+            (None, Some(curve)) => Some(curve.at(elapsed)),
+            (None, None) => None,
+        };
+        // Counting the wait for the terminal to catch up, if it's fallen behind.
+        let drawing = time::Instant::now();
+        let mut frame = output.buffer();
+        let size = (
+            SCREEN_WIDTH.load(Ordering::Relaxed),
+            SCREEN_HEIGHT.load(Ordering::Relaxed),
+        );
+        // Whatever the terminal made of the last frame when it was resized is wiped away.
+        let resized = size != drawn;
+        if resized && options.on_terminal() {
+            frame.push_str(&terminal().clear);
+        }
+        drawn = size;
+        // Frames showing more than the loop does, or wanted as pictures, are drawn as usual.
+        let live = PROMPT.lock().unwrap().line().is_some()
+            || COPY.lock().unwrap().is_some()
+            || SCREENSHOT.load(Ordering::Relaxed)
+            || resized
+            || resizing();
+        let screen = (size.0, size.1, DEPTH_VIEW.load(Ordering::Relaxed));
+        let kept = (cache.as_mut().filter(|_| !live)).and_then(|cache| cache.get(screen, position));
+        if let Some(kept) = kept {
+            frame.push_str(kept);
+            output.show(frame);
+        } else {
+            let picture = render_frame(
+                config,
+                &scenes,
+                &properties,
+                placement,
+                effects.trail.as_mut(),
+                elapsed,
+                frames,
+                &mut frame,
+            );
+            if let Some(cache) = cache.as_mut().filter(|_| !live) {
+                cache.keep(position, &frame);
+            }
+            if options.headless() {
+                if options.export.is_some() {
+                    exported.push(raster::rasterize(
+                        &picture.cells,
+                        picture.colors.as_ref(),
+                        picture.background,
+                        1,
+                    ));
+                }
+                if options.checksum {
+                    let frame = Checksum::frame(&picture.cells, picture.colors.as_ref());
+                    checksum.write(&frame.to_le_bytes());
+                    println!("{frames} {frame:016x}");
+                }
+                if frames + 1 == options.frames as u64 {
+                    break;
+                }
+            } else {
+                // Sent along with the frame, so that they can't be written out at the same time.
+                if let Some(clipboard) = COPY.lock().unwrap().take() {
+                    let text = picture.text(clipboard == Clipboard::Colored);
+                    frame.push_str(&terminfo::copy_to_clipboard(&text));
+                    PROMPT.lock().unwrap().message = Some("copied the frame".to_owned());
+                }
+                if SCREENSHOT.swap(false, Ordering::Relaxed) {
+                    let message = match screenshot::save(
+                        &options.screenshot_dir,
+                        &picture.text(false),
+                        &picture.text(true),
+                    ) {
+                        Ok(path) => format!("saved {}", path.display()),
+                        Err(error) => format!("couldn't save the screenshot: {error}"),
+                    };
+                    PROMPT.lock().unwrap().message = Some(message);
+                }
+                match server {
+                    Some(server) => server.show(frame, &picture),
+                    None => output.show(frame),
+                }
+            }
+        }
+        let drew = drawing.elapsed();
+        if let Some(times) = &mut frame_times {
+            times.record(drew.as_secs_f64());
+            if elapsed - reported >= FRAME_REPORT {
+                if let Some(event) = times.report() {
+                    log(event);
+                }
+                reported = elapsed;
+            }
+        }
+        frames += 1;
+        if options.on_terminal() && elapsed - counted.0 >= 1.0 {
+            let fps = (frames - counted.1) as f64 / (elapsed - counted.0);
+            let title = title(fps, dropped, config, &pose, charset(config));
+            if let Some(title) = terminal().set_title(&title) {
+                print!("{title}");
+            }
+            counted = (elapsed, frames);
+        }
+        if !options.headless() {
+            let pause = match resizing() {
+                true => pause(config).max(RESIZE_PAUSE),
+                false => pause(config),
+            };
+            thread::sleep(Pace::wait(pause, drew));
+        }
+        // From the start of one frame to the start of the next, waiting included.
+        let took = stepped.elapsed();
+        stepped = time::Instant::now();
+        if stopped.is_some() {
+            continue;
+        }
+        // Frames there wasn't time for are turned through anyway, which keeps the objects
+        // turning at the same speed however slow the terminal is.
+        let steps = match options.headless() {
+            true => 1,
+            false => pace.step(took, pause(config)),
+        };
+        dropped += steps.saturating_sub(1) as u64;
+        if let Some(cache) = &cache {
+            position = (position + steps) % cache.len();
+        }
+
+        let speed = properties.speed as Real * steps as Real;
+        let spin = config.spin * speed;
+        angles = Vec3::new(
+            wrap_angle(angles.x + spin.x),
+            wrap_angle(angles.y + spin.y),
+            wrap_angle(angles.z + spin.z),
+        );
+        axis_angle = wrap_angle(axis_angle + config.speed as Real * speed);
+        orbit_angle = wrap_angle(orbit_angle + config.orbit as Real * speed);
+    }
+    output.finish();
+    if let Some(event) = frame_times.as_mut().and_then(FrameTimes::report) {
+        log(event);
+    }
+
+    if options.checksum {
+        println!("total {:016x}", checksum.value());
+    }
+    exported
+}
+
+/// How long to wait between frames. Reduced motion never goes any faster than its own pause.
+fn pause(config: &Config) -> time::Duration {
+    // Rates are kept within bounds when they're set, but a pause too long for a `Duration` falls
+    // back on the usual one rather than panicking.
+    let pause = (config.fps)
+        .and_then(|fps| time::Duration::try_from_secs_f64(1.0 / fps).ok())
+        .unwrap_or(PAUSE);
+    if config.reduced_motion {
+        pause.max(REDUCED_MOTION_PAUSE)
+    } else {
+        pause
+    }
+}
+
+/// For --cache, nudges how fast `config` turns so that it comes exactly back around, and returns a
+/// cache for the frames it takes. `None` if there's more than turning that changes from frame to
+/// frame, like the heartbeat or the sparkles, so that the frames would never come round again.
+fn looping(config: &mut Config) -> Option<Cache<(usize, usize, bool)>> {
+    let still = config.timeline == Timeline::default()
+        && config.camera.is_none()
+        && config.cycle.is_none()
+        && config.demo.is_none()
+        && !config.auto
+        && config.bpm.is_none()
+        && !config.clock
+        && config.marquee.is_none()
+        && config.sparkles.is_none()
+        && config.trail.is_none()
+        && config.background == Background::None
+        && !config.bounce
+        && config.lissajous.is_none()
+        && !config.rainbow
+        && !config.crt
+        && !config.interlace;
+    if !still {
+        return None;
+    }
+    let (frames, steps) = match config.axis {
+        Some(_) => cache::looped(&[config.speed, config.orbit])?,
+        None => {
+            let [x, y, z] = [config.spin.x, config.spin.y, config.spin.z].map(f64::from);
+            cache::looped(&[x, y, z, config.orbit])?
+        }
+    };
+    match config.axis {
+        Some(_) => (config.speed, config.orbit) = (steps[0], steps[1]),
+        None => {
+            config.spin = Vec3::new(steps[0] as Real, steps[1] as Real, steps[2] as Real);
+            config.orbit = steps[3];
+        }
+    }
+    Some(Cache::new(frames, CACHE_BUDGET))
+}
+
+fn wrap_angle(angle: Real) -> Real {
+    angle.rem_euclid(2.0 * PI)
+}
+
+/// Says what's showing, like `ascii-love ♥ 20fps — torus`, and how many frames have been
+/// skipped to keep up, if any.
+fn title(fps: f64, dropped: u64, config: &Config, pose: &Pose, charset: Charset) -> String {
+    let mut shapes: Vec<&str> = Vec::new();
+    for object in &config.objects {
+        let name = pose.shape.as_ref().unwrap_or(&object.shape).name();
+        if !shapes.contains(&name) {
+            shapes.push(name);
+        }
+    }
+    let shapes = shapes.join(", ");
+    let fps = match dropped {
+        0 => format!("{fps:.0}fps"),
+        dropped => format!("{fps:.0}fps, {dropped} dropped"),
+    };
+    match charset {
+        Charset::Unicode => format!("ascii-love ♥ {fps} — {shapes}"),
+        Charset::Ascii => format!("ascii-love <3 {fps} - {shapes}"),
     }
 }
 
-fn render_frame(a: f64, b: f64) {
+/// The characters there are to draw with.
+fn charset(config: &Config) -> Charset {
+    if config.ascii_only {
+        Charset::Ascii
+    } else {
+        terminal().charset
+    }
+}
+
+/// Everything the configured effects carry over from one frame to the next.
+struct Effects {
+    sparkles: Option<Sparkles>,
+    starfield: Option<Starfield>,
+    trail: Option<Trail>,
+    bounce: Option<Bounce>,
+}
+
+impl Effects {
+    fn new(config: &Config) -> Effects {
+        Effects {
+            sparkles: (config.sparkles).map(|rate| Sparkles::new(rate, Rng::entropy())),
+            starfield: match config.background {
+                Background::None | Background::Plasma => None,
+                Background::Stars => Some(Starfield::new(Rng::entropy())),
+            },
+            trail: config.trail.map(Trail::new),
+            bounce: config.bounce.then(Bounce::default),
+        }
+    }
+}
+
+/// How the configured objects have moved by the current frame.
+struct Pose {
+    /// Turns each object around its own centre.
+    orientation: Quat,
+    /// Revolves the objects around the middle.
+    orbit: Quat,
+    /// Multiplies the size of each object.
+    scale: Real,
+    /// Replaces the shape of each object.
+    shape: Option<Shape>,
+}
+
+/// The configured objects as they appear this frame.
+fn scene(config: &Config, pose: &Pose, camera: &Camera, properties: &Properties) -> Scene {
+    Scene {
+        objects: config
+            .objects
+            .iter()
+            .map(|object| {
+                let mut transform = object.transform.spun(pose.orientation).rotated(pose.orbit);
+                transform.scale *= pose.scale;
+                Object {
+                    shape: pose.shape.clone().unwrap_or_else(|| object.shape.clone()),
+                    transform,
+                    material: object.material.clone(),
+                }
+            })
+            .collect(),
+        particles: Vec::new(),
+        lighting: animated_lighting(&config.lighting, properties),
+        camera: *camera,
+    }
+}
+
+/// The configured lighting, with directional lights pointing where the timeline says.
+fn animated_lighting(lighting: &Lighting, properties: &Properties) -> Lighting {
+    let mut lighting = lighting.clone();
+    if let Some(animated) = properties.light {
+        for light in &mut lighting.lights {
+            if let Light::Directional { direction, .. } = light {
+                *direction = animated;
+            }
+        }
+    }
+    lighting
+}
+
+/// How big the animation is across or down `room` cells while it moves around them: `requested`
+/// by --width or --height if that leaves it room to move, and otherwise half the room, as when
+/// the screen is itself exactly --width by --height for --export and --checksum.
+fn moving_size(requested: Option<usize>, room: usize) -> usize {
+    requested.filter(|&size| size < room).unwrap_or(room / 2)
+}
+
+/// A frame as it's shown on the screen: a character in each cell, the colours they're in, if any,
+/// and the colour behind them all, if it isn't the terminal's own.
+struct Picture {
+    cells: Vec<Vec<char>>,
+    colors: Option<Colors>,
+    background: Option<[u8; 3]>,
+    bold: bool,
+}
+
+impl Picture {
+    /// A line of text for each row, with its colours if `colored`. Plain lines leave out the
+    /// spaces at their ends.
+    fn text(&self, colored: bool) -> String {
+        let mut text = String::new();
+        for (y, row) in self.cells.iter().enumerate() {
+            if colored {
+                let colors = self.colors.as_ref().map_or(&[][..], |colors| &colors[y]);
+                let mode = terminal().colors;
+                text += &color::line_on(row, colors, self.background, self.bold, mode);
+            } else {
+                text += width::printable(row).collect::<String>().trim_end();
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    /// The picture shrunk or stretched to fit in the middle of `columns` by `rows`, keeping its
+    /// proportions, with blank space around it.
+    fn fitted(&self, columns: usize, rows: usize) -> Picture {
+        let size = (self.cells.first().map_or(0, Vec::len), self.cells.len());
+        let view = Viewport::full(columns, rows).fitted(size.0, size.1);
+        let mut cells = vec![vec![' '; columns]; rows];
+        view.blit(
+            &mut cells,
+            &viewport::resample(&self.cells, view.width, view.height),
+        );
+        // Wide characters can lose their second column.
+        for row in &mut cells {
+            width::repair(row);
+        }
+        let colors = self.colors.as_ref().map(|colors| {
+            let mut fitted = vec![vec![None; columns]; rows];
+            view.blit(
+                &mut fitted,
+                &viewport::resample(colors, view.width, view.height),
+            );
+            fitted
+        });
+        Picture {
+            cells,
+            colors,
+            ..*self
+        }
+    }
+}
+
+/// Draws the next frame into `out`, as it's written to the terminal, from the top left, and
+/// returns it as it looks.
+#[allow(clippy::too_many_arguments)]
+fn render_frame(
+    config: &Config,
+    scenes: &[Scene],
+    properties: &Properties,
+    placement: Option<(f64, f64)>,
+    trail: Option<&mut Trail>,
+    elapsed: f64,
+    frame_number: u64,
+    out: &mut String,
+) -> Picture {
     let screen_width = SCREEN_WIDTH.load(Ordering::Relaxed);
     let screen_height = SCREEN_HEIGHT.load(Ordering::Relaxed);
-    let mut output = vec![vec![' '; screen_width]; screen_height];
-    let mut zbuffer = vec![vec![-f64::INFINITY; screen_width]; screen_height];
+    let charset = charset(config);
+    let emoji = config.emoji && charset == Charset::Unicode && !config.high_contrast;
+    let ramp = &config.theme.ramp;
+    let settings = render::Settings {
+        style: config.render,
+        shading: if config.high_contrast {
+            Shading::Smooth
+        } else {
+            config.shading
+        },
+        ramp: match charset {
+            Charset::Unicode if config.high_contrast => shading::HIGH_CONTRAST.to_vec(),
+            Charset::Ascii if config.high_contrast => shading::ASCII_HIGH_CONTRAST.to_vec(),
+            Charset::Unicode if emoji => shading::EMOJI.to_vec(),
+            Charset::Ascii if !ramp.iter().all(char::is_ascii) => shading::LUMINANCE.to_vec(),
+            _ => ramp.clone(),
+        },
+        dither: if config.high_contrast {
+            Dither::None
+        } else {
+            config.dither
+        },
+        cull: config.cull,
+        projection: Arc::new(render::Perspective),
+        zoom: properties.zoom as Real,
+        bevel: config.bevel as Real,
+        blend: config.blend as Real,
+        debug: DEPTH_VIEW
+            .load(Ordering::Relaxed)
+            .then_some(DebugView::Depth),
+        density: if resizing() { RESIZE_DENSITY } else { 1.0 },
+    };
 
-    for u in (0.0..2.0 * PI).by(0.02) {
-        for v in (0.0..PI).by(0.02) {
-            // Heart parametric equations
-            let x = sin(v) * (15.0 * sin(u) - 4.0 * sin(3.0 * u));
-            let y = 8.0 * cos(v);
-            let z =
-                sin(v) * (15.0 * cos(u) - 5.0 * cos(2.0 * u) - 2.0 * cos(3.0 * u) - cos(4.0 * u));
+    let screen = Viewport::full(screen_width, screen_height);
+    let mut area = screen.inset(config.margin);
+    if config.card {
+        area = area.inset(overlay::CARD_MARGIN);
+    }
+    let viewport = match placement {
+        // Moving around needs some room to move in.
+        Some((across, down)) => area.placed(
+            moving_size(config.width, area.width).max(overlay::MIN_WIDTH),
+            moving_size(config.height, area.height).max(overlay::MIN_HEIGHT),
+            across,
+            down,
+        ),
+        None => area.aligned(
+            config.width.unwrap_or(area.width),
+            config.height.unwrap_or(area.height),
+            config.align,
+        ),
+    };
 
-            // Rotate around Y-axis
-            let x1 = x * cos(b) + z * sin(b);
-            let y1 = y;
-            let z1 = -x * sin(b) + z * cos(b);
+    let (width, height) = (viewport.width, viewport.height);
+    if width < overlay::MIN_WIDTH || height < overlay::MIN_HEIGHT {
+        // Keep asking until the terminal is resized.
+        out.push_str(&terminal().home);
+        let lines = overlay::too_small(screen.width, screen.height, charset);
+        for line in &lines {
+            out.extend(width::printable(line));
+            out.push('\n');
+        }
+        return Picture {
+            cells: lines,
+            colors: None,
+            background: None,
+            bold: false,
+        };
+    }
+    // Emoji mode spreads every cell over two columns, so it draws half as many.
+    let render_width = if emoji { width / 2 } else { width };
+    let mut depth = None;
+    let mut materials = None;
+    // Each scene is drawn in its own columns, with a depth buffer of its own.
+    let views = Viewport::full(render_width, height).columns(scenes.len());
+    let composite = |parts: Vec<Vec<Vec<char>>>| side_by_side(&views, &parts, ' ');
+    let (mut frame, mut colors) = match config.stereo {
+        None => {
+            let (mut frames, mut depths, mut colors) = (Vec::new(), Vec::new(), Vec::new());
+            for (scene, view) in scenes.iter().zip(&views) {
+                let (frame, depth, color) =
+                    render::render_with_colors(scene, &settings, view.width, height);
+                frames.push(frame);
+                depths.push(depth);
+                colors.push(color);
+            }
+            // The depth buffer is drawn in shades of grey, which are its own colours.
+            let colored = settings.debug.is_some()
+                || (scenes.iter()).any(|scene| {
+                    scene.lighting.is_colored()
+                        || (scene.objects.iter()).any(|object| object.material.color.is_some())
+                });
+            materials = colored.then(|| side_by_side(&views, &colors, None));
+            depth = (config.color_by != Coloring::Luminance)
+                .then(|| side_by_side(&views, &depths, Real::NEG_INFINITY));
+            (composite(frames), None)
+        }
+        Some(Stereo::Anaglyph) => {
+            let eye = |eye: fn(&Camera) -> Camera| {
+                let parts = (scenes.iter().zip(&views))
+                    .map(|(scene, view)| {
+                        let scene = Scene {
+                            camera: eye(&scene.camera),
+                            ..scene.clone()
+                        };
+                        render::render(&scene, &settings, view.width, height)
+                    })
+                    .collect();
+                composite(parts)
+            };
+            let left = eye(|camera| stereo::eyes(camera).0);
+            let right = eye(|camera| stereo::eyes(camera).1);
+            let (frame, colors) = stereo::anaglyph(&left, &right);
+            (frame, Some(colors))
+        }
+        Some(Stereo::MagicEye) => {
+            let depths: Vec<_> = (scenes.iter().zip(&views))
+                .map(|(scene, view)| {
+                    render::render_with_depth(scene, &settings, view.width, height).1
+                })
+                .collect();
+            let depth = side_by_side(&views, &depths, Real::NEG_INFINITY);
+            (stereo::magic_eye(&depth), None)
+        }
+    };
+    if let Some(trail) = trail {
+        trail.apply(&mut frame, &settings.ramp);
+    }
+    if let Some(radius) = config.bloom {
+        bloom::bloom(&mut frame, &settings.ramp, radius);
+    }
+    if emoji {
+        frame = (frame.iter())
+            .map(|row| {
+                let mut row = width::widen(row);
+                row.resize(width, ' ');
+                row
+            })
+            .collect();
+        colors = colors.map(|colors| doubled(&colors, width, None));
+        materials = materials.map(|colors| doubled(&colors, width, None));
+        depth = depth.map(|depth| doubled(&depth, width, Real::NEG_INFINITY));
+    }
+    // Cells nothing was drawn in, before the overlays' own spaces go over the objects.
+    let empty: Vec<Vec<bool>> = (frame.iter())
+        .map(|row| row.iter().map(|&c| c == ' ').collect())
+        .collect();
+    if let Some(message) = &config.message {
+        message.draw(&mut frame);
+    }
+    if let Some(marquee) = &config.marquee {
+        marquee.draw(&mut frame, elapsed, charset);
+    }
+    // Colour-blind palettes keep their colours, whatever the timeline does to the hue.
+    let theme = match config.colorblind {
+        Some(kind) => Cow::Owned(config.theme.colorblind(kind)),
+        None if properties.hue != 0.0 => Cow::Owned(config.theme.turned(properties.hue)),
+        None => Cow::Borrowed(&config.theme),
+    };
+    let colors = match (colors, depth) {
+        (None, Some(depth)) => Some(theme.depth_colors(&frame, &depth, config.color_by)),
+        (colors, _) => colors,
+    };
+    // Turning the hue would undo picking colours that can be told apart.
+    let colors = match colors {
+        None if config.rainbow && config.colorblind.is_none() => {
+            Some(theme.rainbow(&frame, elapsed * RAINBOW_SPEED))
+        }
+        None if !theme.gradient.is_empty() => Some(theme.colors(&frame)),
+        colors => colors,
+    };
+    // Objects with colours of their own keep them, whatever colours everything else gets.
+    let colors = match (colors, materials) {
+        (Some(mut colors), Some(materials)) => {
+            color::paint(&mut colors, &materials);
+            Some(colors)
+        }
+        (colors, materials) => colors.or(materials),
+    };
+    let colors = match config.phosphor {
+        true => Some(crt::phosphor(&frame, colors)),
+        false => colors,
+    };
+    // High contrast keeps to the terminal's own colours, which are whatever is most legible to
+    // whoever chose them, except where 3D glasses need colour to work at all.
+    let (mut colors, background) = match config.high_contrast {
+        true if config.stereo == Some(Stereo::Anaglyph) => (colors, None),
+        true => (None, None),
+        false => (colors, config.bg_color.or(theme.background)),
+    };
+    if config.background == Background::Plasma {
+        let colors = colors.get_or_insert_with(|| vec![vec![None; width]; height]);
+        for (y, (row, empty)) in frame.iter_mut().zip(&empty).enumerate() {
+            for (x, (c, &empty)) in row.iter_mut().zip(empty).enumerate() {
+                if empty && *c == ' ' {
+                    let (glyph, rgb) = background::plasma(x, y, elapsed);
+                    *c = glyph;
+                    colors[y][x] = Some(rgb);
+                }
+            }
+        }
+    }
+    for (row, empty) in frame.iter_mut().zip(&empty) {
+        for (c, &empty) in row.iter_mut().zip(empty) {
+            if empty && *c == ' ' {
+                *c = config.bg_char;
+            }
+        }
+    }
+    if config.crt {
+        crt::scanlines(&mut frame, colors.as_mut(), &settings.ramp);
+        if !config.reduced_motion {
+            crt::jitter(&mut frame, colors.as_mut(), &mut Rng::new(frame_number));
+        }
+    }
+    let mut output = if viewport == screen {
+        frame
+    } else {
+        let mut output = vec![vec![config.bg_char; screen.width]; screen.height];
+        viewport.blit(&mut output, &frame);
+        if config.card {
+            overlay::draw_card(&mut output, charset);
+        }
+        output
+    };
 
-            // Rotate around X-axis
-            let x_rot = x1;
-            let y_rot = y1 * cos(a) - z1 * sin(a);
-            let z_rot = y1 * sin(a) + z1 * cos(a);
+    let mut colors = colors.map(|colors| {
+        if viewport == screen {
+            return colors;
+        }
+        let mut screen_colors = vec![vec![None; screen.width]; screen.height];
+        viewport.blit(&mut screen_colors, &colors);
+        screen_colors
+    });
+    if config.insets {
+        draw_insets(&mut output, colors.as_mut(), &scenes[0], &settings);
+    }
+    // The command line goes along the bottom, like vim's, in the terminal's own colours.
+    if let (Some(line), Some(row)) = (PROMPT.lock().unwrap().line(), output.last_mut()) {
+        row.fill(' ');
+        for (cell, c) in row.iter_mut().zip(line.chars()) {
+            *cell = c;
+        }
+        if let Some(row) = colors.as_mut().and_then(|colors| colors.last_mut()) {
+            row.fill(None);
+        }
+    }
+
+    // Overlays are drawn one column at a time, so they can cut wide characters in half.
+    for row in &mut output {
+        width::repair(row);
+    }
+
+    out.push_str(&terminal().home);
+    for (y, line) in output.iter().enumerate() {
+        // Interlaced frames leave the other half of the rows as they were last frame, if the
+        // terminal can skip over them.
+        let start_of_row = config.interlace.then(|| terminal().move_to(y, 0)).flatten();
+        if start_of_row.is_some() && (y as u64 + frame_number) % 2 == 1 {
+            continue;
+        }
+        let line: String = match (&colors, background, config.high_contrast) {
+            (None, None, false) => width::printable(line).collect(),
+            (colors, background, bold) => {
+                let colors = colors.as_ref().map_or(&[][..], |colors| &colors[y]);
+                color::line_on(line, colors, background, bold, terminal().colors)
+            }
+        };
+        match start_of_row {
+            Some(start_of_row) => {
+                out.push_str(&start_of_row);
+                out.push_str(&line);
+            }
+            None => {
+                out.push_str(&line);
+                out.push('\n');
+            }
+        }
+    }
+    Picture {
+        cells: output,
+        colors,
+        background,
+        bold: config.high_contrast,
+    }
+}
 
-            // Projection
-            let z_offset = 70.0;
-            let ooz = 1.0 / (z_rot + z_offset);
-            let width = screen_width as f64;
-            let height = screen_height as f64;
-            let xp = (width / 2.0 + x_rot * ooz * width) as usize;
-            let yp = (height / 2.0 - y_rot * ooz * height) as usize;
+/// Small views of `scene` from above and from the side, in the right-hand corners of `output`.
+/// They're drawn without perspective, so that they show the shape as it really is, and in the
+/// terminal's own colour.
+fn draw_insets(
+    output: &mut [Vec<char>],
+    mut colors: Option<&mut Colors>,
+    scene: &Scene,
+    settings: &render::Settings,
+) {
+    let screen = Viewport::full(output.first().map_or(0, Vec::len), output.len());
+    let (width, height) = (screen.width / INSET_SIZE, screen.height / INSET_SIZE);
+    if width < overlay::MIN_WIDTH || height < overlay::MIN_HEIGHT {
+        return;
+    }
+    let settings = render::Settings {
+        projection: Arc::new(render::Orthographic),
+        // Insets are drawn a column per cell, too small for wide characters.
+        ramp: if settings.ramp.iter().any(|&c| width::width(c) == 2) {
+            shading::LUMINANCE.to_vec()
+        } else {
+            settings.ramp.clone()
+        },
+        ..settings.clone()
+    };
+    let (target, focus) = (scene.camera.target, scene.camera.focus());
+    for (label, from, align) in [
+        ("top", Vec3::Y, Align::TopRight),
+        ("side", Vec3::X, Align::BottomRight),
+    ] {
+        let camera = Camera {
+            position: target + from * focus,
+            target,
+        };
+        let scene = Scene {
+            camera,
+            ..scene.clone()
+        };
+        let mut inset = render::render(&scene, &settings, width, height);
+        for (cell, c) in inset[0].iter_mut().zip(width::columns(label)) {
+            *cell = c;
+        }
+        let view = screen.aligned(width, height, align);
+        view.blit(output, &inset);
+        if let Some(colors) = colors.as_deref_mut() {
+            view.blit(colors, &vec![vec![None; width]; height]);
+        }
+    }
+}
 
-            // Calculate normals
-            let nx = sin(v) * (15.0 * cos(u) - 4.0 * cos(3.0 * u));
-            let ny = 8.0 * -sin(v) * sin(v);
-            let nz =
-                cos(v) * (15.0 * sin(u) - 5.0 * sin(2.0 * u) - 2.0 * sin(3.0 * u) - sin(4.0 * u));
+/// `parts` put together into one grid, each drawn into its view of `views`, and `pad` wherever
+/// none of them covers.
+fn side_by_side<T: Copy>(views: &[Viewport], parts: &[Vec<Vec<T>>], pad: T) -> Vec<Vec<T>> {
+    let width = views
+        .iter()
+        .map(|view| view.x + view.width)
+        .max()
+        .unwrap_or(0);
+    let height = views
+        .iter()
+        .map(|view| view.y + view.height)
+        .max()
+        .unwrap_or(0);
+    let mut grid = vec![vec![pad; width]; height];
+    for (view, part) in views.iter().zip(parts) {
+        view.blit(&mut grid, part);
+    }
+    grid
+}
 
-            // Rotate normals around Y-axis
-            let nx1 = nx * cos(b) + nz * sin(b);
-            let ny1 = ny;
-            let nz1 = -nx * sin(b) + nz * cos(b);
+/// Each cell of `grid` twice over, padded with `pad` to `width` columns, to line up with a frame
+/// spread out with [`width::widen`].
+fn doubled<T: Copy>(grid: &[Vec<T>], width: usize, pad: T) -> Vec<Vec<T>> {
+    (grid.iter())
+        .map(|row| {
+            let mut row: Vec<T> = row.iter().flat_map(|&cell| [cell, cell]).collect();
+            row.resize(width, pad);
+            row
+        })
+        .collect()
+}
 
-            // Rotate normals around X-axis
-            let nx_rot = nx1;
-            let ny_rot = ny1 * cos(a) - nz1 * sin(a);
-            let nz_rot = ny1 * sin(a) + nz1 * cos(a);
+/// Has the terminal deliver each key as it's pressed, without echoing it, until dropped.
+struct RawInput {
+    /// The terminal's settings from before, if it is one.
+    saved: Option<libc::termios>,
+}
 
-            // Normalize normal vector
-            let length = (nx_rot.powi(2) + ny_rot.powi(2) + nz_rot.powi(2)).sqrt();
-            let nx_rot = nx_rot / length;
-            let ny_rot = ny_rot / length;
-            let nz_rot = nz_rot / length;
+impl RawInput {
+    fn start() -> RawInput {
+        // SAFETY: `termios` is plain data, filled in by `tcgetattr` before it's used.
+        let saved = unsafe {
+            let mut termios: libc::termios = std::mem::zeroed();
+            (libc::tcgetattr(libc::STDIN_FILENO, &mut termios) == 0).then_some(termios)
+        };
+        if let Some(mut raw) = saved {
+            raw.c_lflag &= !(libc::ICANON | libc::ECHO);
+            // SAFETY: `raw` is a valid `termios`, modified from the terminal's own.
+            unsafe {
+                libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw);
+            }
+        }
+        RawInput { saved }
+    }
+}
 
-            // Light direction
-            let lx = 0.0;
-            let ly = 0.0;
-            let lz = -1.0;
+impl Drop for RawInput {
+    fn drop(&mut self) {
+        if let Some(saved) = &self.saved {
+            // SAFETY: `saved` came from `tcgetattr`.
+            unsafe {
+                libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, saved);
+            }
+        }
+    }
+}
 
-            // Dot product for luminance
-            let luma = nx_rot * lx + ny_rot * ly + nz_rot * lz;
-            let luminance_index = ((luma + 1.0) * 5.5) as i32;
+/// Writes frames to the terminal on a thread of its own, so that the next frame can be drawn
+/// while a slow terminal is still taking the last one. There are two buffers, which take turns:
+/// one being drawn into while the other is written out.
+struct Output {
+    frames: mpsc::SyncSender<String>,
+    /// Buffers that have been written out, to draw into again.
+    spare: mpsc::Receiver<String>,
+    writer: thread::JoinHandle<()>,
+}
 
-            let within_screen = xp < screen_width && yp < screen_height;
-            let visible = ooz > zbuffer[yp][xp];
-            if within_screen && visible {
-                zbuffer[yp][xp] = ooz;
-                let n_lumas = LUMINANCE.len() - 1;
-                let luminance_index = luminance_index.clamp(0, n_lumas as i32) as usize;
-                output[yp][xp] = LUMINANCE[luminance_index];
+impl Output {
+    fn start() -> Output {
+        // Only one frame waits while another is written, so drawing never gets far ahead.
+        let (frames, waiting) = mpsc::sync_channel::<String>(1);
+        let (written, spare) = mpsc::channel();
+        let writer = thread::spawn(move || {
+            for mut frame in waiting {
+                let mut stdout = io::stdout().lock();
+                let _ = stdout.write_all(frame.as_bytes());
+                let _ = stdout.flush();
+                frame.clear();
+                let _ = written.send(frame);
             }
+        });
+        Output {
+            frames,
+            spare,
+            writer,
         }
     }
 
-    clear_screen();
-    for line in output {
-        let line: String = line.iter().collect();
-        println!("{line}");
+    /// An empty buffer to draw the next frame into.
+    fn buffer(&self) -> String {
+        self.spare.try_recv().unwrap_or_default()
     }
 
-    // I would use f64:: these, but I don't believer it's possible because they're primitives.
-    #[inline(always)]
-    fn sin(x: f64) -> f64 {
-        x.sin()
+    /// Writes `frame` out once the one before it is, waiting if a frame is already waiting.
+    fn show(&self, frame: String) {
+        let _ = self.frames.send(frame);
     }
 
-    #[inline(always)]
-    fn cos(x: f64) -> f64 {
-        x.cos()
+    /// Waits for the last frame to be written.
+    fn finish(self) {
+        drop(self.frames);
+        let _ = self.writer.join();
     }
 }
 
-fn stop_animation() {
-    SHOULD_PLAY.store(false, Ordering::Relaxed)
+/// Takes over the terminal for --screensaver, and gives it back when dropped.
+struct Screensaver {
+    _input: RawInput,
+}
+
+impl Screensaver {
+    /// Switches to the alternate screen, which hides whatever was there before, and stops the
+    /// animation on the first key press, or mouse movement where the terminal reports it.
+    fn start() -> Screensaver {
+        let terminal = terminal();
+        print!(
+            "{}{}{}",
+            terminal.enter_alternate_screen, terminal.clear, terminal.track_mouse
+        );
+        let input = RawInput::start();
+        thread::spawn(|| {
+            let mut byte = [0];
+            // Without a terminal, standard input may never have anything to say.
+            if io::stdin().read(&mut byte).is_ok_and(|read| read > 0) {
+                stop_animation();
+            }
+        });
+        Screensaver { _input: input }
+    }
 }
 
-fn update_screen_dimensions() {
-    let Some((width, height)) = term_size::dimensions() else {
+impl Drop for Screensaver {
+    fn drop(&mut self) {
+        let terminal = terminal();
+        print!(
+            "{}{}",
+            terminal.untrack_mouse, terminal.exit_alternate_screen
+        );
+        let _ = io::stdout().flush();
+    }
+}
+
+/// Listens for keys while the animation plays, until dropped:
+///
+/// - `z` switches between the shaded objects and the depth buffer
+/// - `:` opens the command line, for typing a [command](command::run) to run with Enter, or to
+///   forget with Escape
+/// - `y` copies the frame to the clipboard as plain text, and `Y` with its colours
+/// - `s` saves the frame in --screenshot-dir, as plain text and with its colours
+struct Keys {
+    _input: RawInput,
+}
+
+impl Keys {
+    /// Starts listening, if standard input is a terminal that someone could be typing at.
+    fn start() -> Option<Keys> {
+        // SAFETY: `isatty` only looks at the file descriptor.
+        if unsafe { libc::isatty(libc::STDIN_FILENO) } != 1 {
+            return None;
+        }
+        let input = RawInput::start();
+        thread::spawn(|| {
+            for key in io::stdin().lock().bytes() {
+                let Ok(key) = key else { break };
+                let mut prompt = PROMPT.lock().unwrap();
+                prompt.message = None;
+                let typing = prompt.typing.is_some();
+                match key {
+                    b'z' if !typing => {
+                        DEPTH_VIEW.fetch_xor(true, Ordering::Relaxed);
+                    }
+                    b':' if !typing => prompt.typing = Some(String::new()),
+                    b'y' if !typing => *COPY.lock().unwrap() = Some(Clipboard::Text),
+                    b'Y' if !typing => *COPY.lock().unwrap() = Some(Clipboard::Colored),
+                    b's' if !typing => SCREENSHOT.store(true, Ordering::Relaxed),
+                    // Escape, which also starts the arrow keys and anything else unusual.
+                    0x1b => prompt.typing = None,
+                    b'\r' | b'\n' => {
+                        if let Some(line) = prompt.typing.take() {
+                            prompt.entered.push(Entered { line, reply: None });
+                        }
+                    }
+                    // Backspace, which closes the command line once there's nothing left.
+                    0x7f | 0x08 => {
+                        if let Some(None) = prompt.typing.as_mut().map(String::pop) {
+                            prompt.typing = None;
+                        }
+                    }
+                    b' '..=b'~' => {
+                        if let Some(line) = &mut prompt.typing {
+                            line.push(char::from(key));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        });
+        Some(Keys { _input: input })
+    }
+}
+
+/// What to copy the frame to the clipboard as.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Clipboard {
+    Text,
+    /// With the escape codes for its colours, for pasting back into a terminal.
+    Colored,
+}
+
+/// A [command](command::run) waiting to be run, and where to say how it went, if not on the
+/// command line.
+struct Entered {
+    line: String,
+    reply: Option<mpsc::Sender<Result<(), String>>>,
+}
+
+/// What's been typed on the command line, and what's become of it.
+struct Prompt {
+    /// What's been typed so far, while the command line is open.
+    typing: Option<String>,
+    /// Commands that have been entered, here or over --control, for the next frame to run.
+    entered: Vec<Entered>,
+    /// What happened, like why the last command couldn't be run, until the next key is pressed.
+    message: Option<String>,
+}
+
+impl Prompt {
+    /// What to show along the bottom of the screen, if anything.
+    fn line(&self) -> Option<String> {
+        match (&self.typing, &self.message) {
+            (Some(typing), _) => Some(format!(":{typing}_")),
+            (None, Some(message)) => Some(message.clone()),
+            (None, None) => None,
+        }
+    }
+}
+
+/// Listens on a Unix socket for --control, until dropped, when the socket is removed. Each line
+/// sent is a command, answered with `ok` or `error: ` and why:
+///
+/// - `pause` and `resume` stop time and start it again
+/// - `set-shape NAME` and `set-theme NAME` draw that shape, or in that theme
+/// - `set SETTING VALUE` changes any setting, as on the command line opened with `:`
+/// - `screenshot` saves the next frame in --screenshot-dir
+struct Control {
+    path: PathBuf,
+}
+
+impl Control {
+    fn start(path: &Path) -> io::Result<Control> {
+        let listener = match UnixListener::bind(path) {
+            // Left behind by an instance that didn't get to remove it, if nothing's listening.
+            Err(error) if error.kind() == io::ErrorKind::AddrInUse => {
+                let socket = fs::metadata(path)?.file_type().is_socket();
+                if !socket || UnixStream::connect(path).is_ok() {
+                    return Err(error);
+                }
+                fs::remove_file(path)?;
+                UnixListener::bind(path)?
+            }
+            listener => listener?,
+        };
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                thread::spawn(move || Control::serve(stream));
+            }
+        });
+        Ok(Control {
+            path: path.to_owned(),
+        })
+    }
+
+    /// Answers each line sent over `stream` until it's closed.
+    fn serve(stream: UnixStream) {
+        let Ok(mut replies) = stream.try_clone() else {
+            return;
+        };
+        for line in BufReader::new(stream).lines() {
+            let Ok(line) = line else { break };
+            let reply = match Control::run(line.trim()) {
+                Ok(()) => "ok".to_owned(),
+                Err(error) => format!("error: {error}"),
+            };
+            if writeln!(replies, "{reply}").is_err() {
+                break;
+            }
+        }
+    }
+
+    fn run(line: &str) -> Result<(), String> {
+        let (name, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let command = match name {
+            "pause" | "resume" => {
+                PAUSED.store(name == "pause", Ordering::Relaxed);
+                return Ok(());
+            }
+            "screenshot" => {
+                SCREENSHOT.store(true, Ordering::Relaxed);
+                return Ok(());
+            }
+            "set-shape" => format!("shape {rest}"),
+            "set-theme" => format!("theme {rest}"),
+            "set" => rest.to_owned(),
+            _ => return Err(format!("unknown command {name:?}")),
+        };
+        let (reply, result) = mpsc::channel();
+        PROMPT.lock().unwrap().entered.push(Entered {
+            line: command,
+            reply: Some(reply),
+        });
+        result
+            .recv_timeout(CONTROL_TIMEOUT)
+            .unwrap_or_else(|_| Err("the animation isn't running".to_owned()))
+    }
+}
+
+impl Drop for Control {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Serves the animation over HTTP for --serve, until dropped: streamed a frame at a time to
+/// anyone asking for `/`, for `curl` to play, or the latest frame on its own as plain text at
+/// `/frame.txt`.
+///
+/// Every client is sent the same frames, drawn once each and only shrunk or stretched to the size
+/// each one asks for with `/?width=W&height=H`, so that it takes no more to serve many clients
+/// than one.
+struct Server {
+    clients: Arc<Mutex<Vec<Client>>>,
+    latest: Arc<Mutex<String>>,
+    /// The size set by --width and --height, which frames are always drawn at.
+    fixed: (Option<usize>, Option<usize>),
+}
+
+/// Someone watching the animation served by [`Server`].
+struct Client {
+    /// How many columns and rows they asked for.
+    size: (usize, usize),
+    /// Where to send them frames, which they take as they're ready.
+    frames: mpsc::SyncSender<Arc<String>>,
+}
+
+impl Server {
+    fn start(address: &str, width: Option<usize>, height: Option<usize>) -> io::Result<Server> {
+        let listener = TcpListener::bind(address)?;
+        let server = Server {
+            clients: Arc::default(),
+            latest: Arc::default(),
+            fixed: (width, height),
+        };
+        let (clients, latest) = (server.clients.clone(), server.latest.clone());
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let (clients, latest) = (clients.clone(), latest.clone());
+                thread::spawn(move || Server::serve(stream, &clients, &latest));
+            }
+        });
+        Ok(server)
+    }
+
+    /// Answers a request on `stream`, which for `/` takes until the animation stops or the client
+    /// goes away.
+    fn serve(
+        mut stream: TcpStream,
+        clients: &Mutex<Vec<Client>>,
+        latest: &Mutex<String>,
+    ) -> io::Result<()> {
+        const TEXT: (&str, &str) = ("Content-Type", "text/plain; charset=utf-8");
+        const EMPTY: (&str, &str) = ("Content-Length", "0");
+        stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+        stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+        let Ok(request) = Request::parse(&http::read_head(&mut stream)?) else {
+            return stream.write_all(http::response(400, &[EMPTY]).as_bytes());
+        };
+        log(Event::new("request")
+            .with("method", &request.method)
+            .with("path", &request.path));
+        let body = match request.method.as_str() {
+            "GET" => true,
+            "HEAD" => false,
+            _ => {
+                let head = http::response(405, &[("Allow", "GET, HEAD"), EMPTY]);
+                return stream.write_all(head.as_bytes());
+            }
+        };
+        match request.path.as_str() {
+            "/" => {
+                let side = |name, default, largest| match request.param(name) {
+                    None => Some(default),
+                    Some(side) => side
+                        .parse()
+                        .ok()
+                        .filter(|side| (1..=largest).contains(side)),
+                };
+                let size = match (
+                    side("width", SERVED_SIZE.0, LARGEST_SERVED.0),
+                    side("height", SERVED_SIZE.1, LARGEST_SERVED.1),
+                ) {
+                    (Some(width), Some(height)) => (width, height),
+                    _ => return stream.write_all(http::response(400, &[EMPTY]).as_bytes()),
+                };
+                let head = http::response(200, &[TEXT, ("Cache-Control", "no-store")]);
+                stream.write_all(head.as_bytes())?;
+                if !body {
+                    return Ok(());
+                }
+                let (frames, waiting) = mpsc::sync_channel(1);
+                clients.lock().unwrap().push(Client { size, frames });
+                let start = format!("{}{}", terminal().hide_cursor, terminal().clear);
+                stream.write_all(&http::chunk(start.as_bytes()))?;
+                for frame in waiting {
+                    stream.write_all(&http::chunk(frame.as_bytes()))?;
+                }
+                stream.write_all(&http::chunk(terminal().show_cursor.as_bytes()))?;
+                stream.write_all(&http::chunk(b""))
+            }
+            "/frame.txt" => {
+                let text = latest.lock().unwrap().clone();
+                let length = text.len().to_string();
+                let head = http::response(200, &[TEXT, ("Content-Length", &length)]);
+                stream.write_all(head.as_bytes())?;
+                match body {
+                    true => stream.write_all(text.as_bytes()),
+                    false => Ok(()),
+                }
+            }
+            _ => stream.write_all(http::response(404, &[EMPTY]).as_bytes()),
+        }
+    }
+
+    /// The size to draw frames at: what --width and --height say, or else big enough for
+    /// whoever's asked for the most columns and rows.
+    fn size(&self) -> (usize, usize) {
+        let clients = self.clients.lock().unwrap();
+        let biggest = (clients.iter().map(|client| client.size))
+            .reduce(|(w1, h1), (w2, h2)| (w1.max(w2), h1.max(h2)))
+            .unwrap_or(SERVED_SIZE);
+        (
+            self.fixed.0.unwrap_or(biggest.0),
+            self.fixed.1.unwrap_or(biggest.1),
+        )
+    }
+
+    /// Sends `frame`, which draws `picture`, to everyone watching, fitted to the size they asked
+    /// for, except anyone still taking the last one, who misses it. Keeps the picture for
+    /// `/frame.txt`.
+    fn show(&self, frame: String, picture: &Picture) {
+        let size = (
+            picture.cells.first().map_or(0, Vec::len),
+            picture.cells.len(),
+        );
+        // Each size is only drawn once, however many want it.
+        let mut frames = vec![(size, Arc::new(frame))];
+        self.clients.lock().unwrap().retain(|client| {
+            let frame = match frames.iter().find(|(size, _)| *size == client.size) {
+                Some((_, frame)) => frame.clone(),
+                None => {
+                    let fitted = picture.fitted(client.size.0, client.size.1);
+                    let frame = Arc::new(terminal().home.clone() + &fitted.text(true));
+                    frames.push((client.size, frame.clone()));
+                    frame
+                }
+            };
+            !matches!(
+                client.frames.try_send(frame),
+                Err(mpsc::TrySendError::Disconnected(_))
+            )
+        });
+        *self.latest.lock().unwrap() = picture.text(false);
+    }
+}
+
+impl Drop for Server {
+    /// Lets everyone watching know the animation's over.
+    fn drop(&mut self) {
+        self.clients.lock().unwrap().clear();
+    }
+}
+
+/// Reads the configuration again whenever one of `paths` changes, for --watch, by checking when
+/// they were last modified every so often.
+fn watch(paths: Vec<PathBuf>) {
+    let modified = move || -> Vec<Option<time::SystemTime>> {
+        (paths.iter())
+            .map(|path| {
+                fs::metadata(path)
+                    .and_then(|metadata| metadata.modified())
+                    .ok()
+            })
+            .collect()
+    };
+    thread::spawn(move || {
+        let mut last = modified();
+        while SHOULD_PLAY.load(Ordering::Relaxed) {
+            thread::sleep(WATCH_EVERY);
+            let now = modified();
+            if now != last {
+                RELOAD.store(true, Ordering::Relaxed);
+                last = now;
+            }
+        }
+    });
+}
+
+/// Where --verbose reports what's happening.
+struct Log {
+    start: time::Instant,
+    out: Mutex<Box<dyn Write + Send>>,
+}
+
+/// Opens the log chosen with --verbose or --log-file, if either was.
+fn start_log(options: &Options) -> Result<(), String> {
+    let out: Box<dyn Write + Send> = match &options.log_file {
+        Some(path) => {
+            let file = fs::File::create(path).map_err(|error| format!("--log-file: {error}"))?;
+            Box::new(io::BufWriter::new(file))
+        }
+        None if options.verbose => Box::new(io::stderr()),
+        None => return Ok(()),
+    };
+    let log = Log {
+        start: time::Instant::now(),
+        out: Mutex::new(out),
+    };
+    let _ = LOG.set(log);
+    Ok(())
+}
+
+/// Writes `event` to the log, if there is one, along with how many seconds in it happened.
+/// Never to standard output, which is where the animation goes.
+fn log(event: Event) {
+    let Some(log) = LOG.get() else {
         return;
     };
+    let seconds = log.start.elapsed().as_secs_f64();
+    if let Ok(mut out) = log.out.lock() {
+        let _ = writeln!(out, "t={seconds:.3} {event}");
+        let _ = out.flush();
+    }
+}
+
+/// Whether the terminal's being resized, having just been.
+fn resizing() -> bool {
+    RESIZED
+        .lock()
+        .unwrap()
+        .is_some_and(|resized| resized.elapsed() < RESIZE_SETTLE)
+}
+
+fn stop_animation() {
+    SHOULD_PLAY.store(false, Ordering::Relaxed)
+}
+
+/// Measures the terminal. When it can't be measured, such as when the output is piped, the
+/// screen is exactly the size fixed by --width and --height.
+fn update_screen_dimensions(fixed_width: Option<usize>, fixed_height: Option<usize>) {
+    let measured = terminal_dimensions();
+    set_screen_dimensions(
+        measured.map(|(width, _)| width).or(fixed_width),
+        measured.map(|(_, height)| height).or(fixed_height),
+    );
+}
+
+/// Makes the screen `width` columns by `height` rows, leaving either as it is if it's `None`.
+fn set_screen_dimensions(width: Option<usize>, height: Option<usize>) {
+    if let Some(width) = width {
+        SCREEN_WIDTH.store(width, Ordering::Relaxed);
+    }
+    if let Some(height) = height {
+        SCREEN_HEIGHT.store(height, Ordering::Relaxed);
+    }
+}
 
-    SCREEN_WIDTH.store(width, Ordering::Relaxed);
-    SCREEN_HEIGHT.store(height, Ordering::Relaxed);
+/// The size of the terminal in columns and rows, or what `COLUMNS` and `LINES` say when it
+/// can't be asked, such as when the output is piped.
+fn terminal_dimensions() -> Option<(usize, usize)> {
+    term_size::dimensions().or_else(|| {
+        let variable = |name| {
+            env::var(name)
+                .ok()?
+                .trim()
+                .parse()
+                .ok()
+                .filter(|&size| size > 0)
+        };
+        Some((variable("COLUMNS")?, variable("LINES")?))
+    })
 }
 
-fn clear_screen() {
-    // Cursor Position to top-left
-    print!("\x1b[H");
+/// What the terminal can do, looked up the first time it's needed.
+fn terminal() -> &'static Terminal {
+    TERMINAL.get_or_init(Terminal::detect)
 }
 
 fn show_cursor() {
-    print!("\x1b[?25h");
+    print!("{}", terminal().show_cursor);
 }
 
 fn hide_cursor() {
-    print!("\x1b[?25l");
+    print!("{}", terminal().hide_cursor);
 }
@@ -0,0 +1,278 @@
+//! Small vector, matrix, and rotation helpers, used by the renderer and by shapes.
+
+use std::ops::{Add, Mul, Neg, Sub};
+
+/// The floating-point type that geometry and lighting are worked out in: `f64`, or `f32` with
+/// the `f32` feature, which is faster on boards without double-precision hardware and looks the
+/// same at terminal resolutions.
+#[cfg(not(feature = "f32"))]
+pub type Real = f64;
+#[cfg(feature = "f32")]
+pub type Real = f32;
+
+/// Constants like π, in [`Real`] precision.
+#[cfg(not(feature = "f32"))]
+pub use std::f64::consts;
+
+#[cfg(feature = "f32")]
+pub use std::f32::consts;
+
+/// How close results worked out in [`Real`] can be expected to come to the exact answer.
+#[cfg(test)]
+pub(crate) const TOLERANCE: Real = if cfg!(feature = "f32") { 1e-4 } else { 1e-9 };
+
+/// A vector in 3D space.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Vec3 {
+    pub x: Real,
+    pub y: Real,
+    pub z: Real,
+}
+
+impl Vec3 {
+    pub const X: Vec3 = Vec3::new(1.0, 0.0, 0.0);
+    pub const Y: Vec3 = Vec3::new(0.0, 1.0, 0.0);
+    pub const Z: Vec3 = Vec3::new(0.0, 0.0, 1.0);
+
+    pub const fn new(x: Real, y: Real, z: Real) -> Vec3 {
+        Vec3 { x, y, z }
+    }
+
+    pub fn dot(self, other: Vec3) -> Real {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn cross(self, other: Vec3) -> Vec3 {
+        Vec3 {
+            x: self.y * other.z - self.z * other.y,
+            y: self.z * other.x - self.x * other.z,
+            z: self.x * other.y - self.y * other.x,
+        }
+    }
+
+    pub fn length(self) -> Real {
+        self.dot(self).sqrt()
+    }
+
+    /// Returns a vector pointing in the same direction with a length of 1.
+    pub fn normalized(self) -> Vec3 {
+        self * (1.0 / self.length())
+    }
+}
+
+impl Add for Vec3 {
+    type Output = Vec3;
+
+    fn add(self, other: Vec3) -> Vec3 {
+        Vec3::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl Sub for Vec3 {
+    type Output = Vec3;
+
+    fn sub(self, other: Vec3) -> Vec3 {
+        Vec3::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl Mul<Real> for Vec3 {
+    type Output = Vec3;
+
+    fn mul(self, scale: Real) -> Vec3 {
+        Vec3::new(self.x * scale, self.y * scale, self.z * scale)
+    }
+}
+
+impl Neg for Vec3 {
+    type Output = Vec3;
+
+    fn neg(self) -> Vec3 {
+        Vec3::new(-self.x, -self.y, -self.z)
+    }
+}
+
+/// A unit quaternion representing an orientation in 3D space.
+///
+/// Orientations compose with `*`: `a * b` rotates by `b` first, then by `a`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quat {
+    pub w: Real,
+    pub x: Real,
+    pub y: Real,
+    pub z: Real,
+}
+
+impl Quat {
+    pub const IDENTITY: Quat = Quat {
+        w: 1.0,
+        x: 0.0,
+        y: 0.0,
+        z: 0.0,
+    };
+
+    /// A rotation of `angle` radians around `axis`. The axis need not be normalized.
+    pub fn from_axis_angle(axis: Vec3, angle: Real) -> Quat {
+        let axis = axis.normalized();
+        let (sin, cos) = (angle / 2.0).sin_cos();
+        Quat {
+            w: cos,
+            x: axis.x * sin,
+            y: axis.y * sin,
+            z: axis.z * sin,
+        }
+    }
+
+    pub fn conjugate(self) -> Quat {
+        Quat {
+            w: self.w,
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+        }
+    }
+
+    /// Rescales the quaternion to unit length, undoing drift from repeated composition.
+    pub fn normalized(self) -> Quat {
+        let length = (self.w * self.w + self.x * self.x + self.y * self.y + self.z * self.z).sqrt();
+        Quat {
+            w: self.w / length,
+            x: self.x / length,
+            y: self.y / length,
+            z: self.z / length,
+        }
+    }
+
+    /// Applies this rotation to a vector.
+    pub fn rotate(self, v: Vec3) -> Vec3 {
+        // Equivalent to q * v * q⁻¹, expanded to avoid building intermediate quaternions.
+        let u = Vec3::new(self.x, self.y, self.z);
+        let t = u.cross(v) * 2.0;
+        v + t * self.w + u.cross(t)
+    }
+}
+
+impl Mul for Quat {
+    type Output = Quat;
+
+    fn mul(self, other: Quat) -> Quat {
+        Quat {
+            w: self.w * other.w - self.x * other.x - self.y * other.y - self.z * other.z,
+            x: self.w * other.x + self.x * other.w + self.y * other.z - self.z * other.y,
+            y: self.w * other.y - self.x * other.z + self.y * other.w + self.z * other.x,
+            z: self.w * other.z + self.x * other.y - self.y * other.x + self.z * other.w,
+        }
+    }
+}
+
+/// A 3×3 matrix, such as a rotation or a change of basis.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Mat3 {
+    pub rows: [Vec3; 3],
+}
+
+impl Mat3 {
+    pub const IDENTITY: Mat3 = Mat3::from_rows(Vec3::X, Vec3::Y, Vec3::Z);
+
+    pub const fn from_rows(x: Vec3, y: Vec3, z: Vec3) -> Mat3 {
+        Mat3 { rows: [x, y, z] }
+    }
+
+    /// The matrix that maps X, Y, and Z to `x`, `y`, and `z`.
+    pub fn from_columns(x: Vec3, y: Vec3, z: Vec3) -> Mat3 {
+        Mat3::from_rows(x, y, z).transpose()
+    }
+
+    /// The same rotation as `q`, which should be a unit quaternion.
+    pub fn from_quat(q: Quat) -> Mat3 {
+        Mat3::from_columns(q.rotate(Vec3::X), q.rotate(Vec3::Y), q.rotate(Vec3::Z))
+    }
+
+    pub fn transpose(self) -> Mat3 {
+        let [x, y, z] = self.rows;
+        Mat3::from_rows(
+            Vec3::new(x.x, y.x, z.x),
+            Vec3::new(x.y, y.y, z.y),
+            Vec3::new(x.z, y.z, z.z),
+        )
+    }
+
+    pub fn determinant(self) -> Real {
+        let [x, y, z] = self.rows;
+        x.dot(y.cross(z))
+    }
+}
+
+impl Mul<Vec3> for Mat3 {
+    type Output = Vec3;
+
+    fn mul(self, v: Vec3) -> Vec3 {
+        let [x, y, z] = self.rows;
+        Vec3::new(x.dot(v), y.dot(v), z.dot(v))
+    }
+}
+
+impl Mul for Mat3 {
+    type Output = Mat3;
+
+    /// Like quaternions, `a * b` applies `b` first, then `a`.
+    fn mul(self, other: Mat3) -> Mat3 {
+        let columns = other.transpose().rows;
+        let [x, y, z] = self.rows.map(|row| {
+            Vec3::new(
+                row.dot(columns[0]),
+                row.dot(columns[1]),
+                row.dot(columns[2]),
+            )
+        });
+        Mat3::from_rows(x, y, z)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::consts::FRAC_PI_2;
+    use super::*;
+
+    fn assert_close(expected: Vec3, actual: Vec3) {
+        let error = (expected - actual).length();
+        assert!(error < TOLERANCE, "expected {expected:?}, got {actual:?}");
+    }
+
+    #[test]
+    fn rotate_around_principal_axes() {
+        let quarter_turn = |axis| Quat::from_axis_angle(axis, FRAC_PI_2);
+        assert_close(Vec3::Z, quarter_turn(Vec3::X).rotate(Vec3::Y));
+        assert_close(Vec3::X, quarter_turn(Vec3::Y).rotate(Vec3::Z));
+        assert_close(Vec3::Y, quarter_turn(Vec3::Z).rotate(Vec3::X));
+    }
+
+    #[test]
+    fn composition_applies_right_operand_first() {
+        let x = Quat::from_axis_angle(Vec3::X, FRAC_PI_2);
+        let y = Quat::from_axis_angle(Vec3::Y, FRAC_PI_2);
+        let v = Vec3::new(1.0, 2.0, 3.0);
+        assert_close(x.rotate(y.rotate(v)), (x * y).rotate(v));
+    }
+
+    #[test]
+    fn rotation_preserves_length() {
+        let q = Quat::from_axis_angle(Vec3::new(1.0, 1.0, 0.0), 0.7);
+        let v = Vec3::new(3.0, -4.0, 12.0);
+        assert!((q.rotate(v).length() - 13.0).abs() < TOLERANCE);
+    }
+
+    #[test]
+    fn matrices_rotate_like_quaternions() {
+        let q = Quat::from_axis_angle(Vec3::new(1.0, 2.0, -1.0), 1.1);
+        let p = Quat::from_axis_angle(Vec3::Z, -0.4);
+        let (m, n) = (Mat3::from_quat(q), Mat3::from_quat(p));
+        let v = Vec3::new(3.0, -4.0, 12.0);
+        assert_close(q.rotate(v), m * v);
+        assert_close((q * p).rotate(v), (m * n) * v);
+        // A rotation's transpose undoes it.
+        assert_close(v, m.transpose() * (m * v));
+        assert!((m.determinant() - 1.0).abs() < TOLERANCE);
+        assert_eq!(v, Mat3::IDENTITY * v);
+    }
+}
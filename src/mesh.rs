@@ -0,0 +1,365 @@
+//! Triangle meshes loaded from model files.
+
+mod obj;
+mod ply;
+mod stl;
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::math::{Real, Vec3};
+
+/// How to work out which way the surface of a mesh faces.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Normals {
+    /// Blend normals across each triangle, using the model's own normals where it has them.
+    #[default]
+    Smooth,
+    /// Light each triangle evenly, for a faceted look.
+    Flat,
+}
+
+impl Normals {
+    pub const ALL: [Normals; 2] = [Normals::Smooth, Normals::Flat];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Normals::Smooth => "smooth",
+            Normals::Flat => "flat",
+        }
+    }
+}
+
+impl fmt::Display for Normals {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Normals {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Normals, String> {
+        Normals::ALL
+            .into_iter()
+            .find(|normals| normals.name() == name)
+            .ok_or_else(|| format!("unknown normals {name:?}"))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Triangle {
+    pub corners: [Vec3; 3],
+    /// The surface normal at each corner. Without them, the whole triangle faces the same way.
+    pub normals: Option<[Vec3; 3]>,
+}
+
+impl Triangle {
+    /// Perpendicular to the triangle, facing the side its corners go anticlockwise around. Its
+    /// length is twice the triangle's area.
+    pub fn face_normal(&self) -> Vec3 {
+        let [a, b, c] = self.corners;
+        (b - a).cross(c - a)
+    }
+
+    /// The point and normal at barycentric coordinates `(s, t)`, which weight the second and
+    /// third corners.
+    fn at(&self, s: Real, t: Real) -> (Vec3, Vec3) {
+        let [a, b, c] = self.corners;
+        let r = 1.0 - s - t;
+        let point = a * r + b * s + c * t;
+        let normal = match self.normals {
+            Some([na, nb, nc]) => na * r + nb * s + nc * t,
+            None => self.face_normal(),
+        };
+        (point, normal)
+    }
+}
+
+/// A lone vertex of a point cloud.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Point {
+    pub position: Vec3,
+    /// Which way the scanned surface faced here, if the file says.
+    pub normal: Option<Vec3>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Mesh {
+    pub triangles: Vec<Triangle>,
+    /// Points not joined into triangles, drawn one sample each.
+    pub points: Vec<Point>,
+}
+
+impl Mesh {
+    /// How far apart samples are, in world units, for each radian of sampling step. Chosen so
+    /// that meshes the size of the heart are sampled about as densely.
+    const SPACING: Real = 8.0;
+
+    /// The most pieces [`Mesh::sample`] cuts each edge of a triangle into.
+    const MAX_DIVISIONS: Real = 1000.0;
+
+    /// Loads a model, choosing the format by the file's extension.
+    pub fn load(path: &Path) -> Result<Mesh, MeshError> {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase);
+        let bytes = fs::read(path).map_err(|error| MeshError::Io(path.to_owned(), error))?;
+        let mesh = match extension.as_deref() {
+            Some("obj") => obj::parse(&String::from_utf8_lossy(&bytes))?,
+            Some("ply") => ply::parse(&bytes)?,
+            Some("stl") => stl::parse(&bytes)?,
+            _ => return Err(MeshError::Unsupported(path.to_owned())),
+        };
+        // Coordinates that are each finite can still be too far apart to measure, and then the
+        // mesh can't be fitted to the screen.
+        let (min, max) = mesh.bounds();
+        let extent = max - min;
+        let measurable = extent.x.is_finite() && extent.y.is_finite() && extent.z.is_finite();
+        if !measurable && mesh.corners().next().is_some() {
+            return Err(MeshError::Invalid(
+                "the model is too big to draw".to_owned(),
+            ));
+        }
+        Ok(mesh)
+    }
+
+    /// Whether the mesh is only points, and so is best drawn with [`crate::render::Style::Points`].
+    pub fn is_point_cloud(&self) -> bool {
+        self.triangles.is_empty() && !self.points.is_empty()
+    }
+
+    /// Changes how the mesh is shaded.
+    pub fn with_normals(mut self, normals: Normals) -> Mesh {
+        match normals {
+            Normals::Flat => {
+                for triangle in &mut self.triangles {
+                    triangle.normals = None;
+                }
+            }
+            Normals::Smooth => self.smooth_missing_normals(),
+        }
+        self
+    }
+
+    /// Gives triangles without normals the average normal of the triangles around each of their
+    /// corners.
+    fn smooth_missing_normals(&mut self) {
+        let key = |v: Vec3| [v.x.to_bits(), v.y.to_bits(), v.z.to_bits()];
+        let mut sums: HashMap<_, Vec3> = HashMap::new();
+        for triangle in &self.triangles {
+            // Larger triangles count for more.
+            let normal = triangle.face_normal();
+            for corner in triangle.corners {
+                let sum = sums.entry(key(corner)).or_default();
+                *sum = *sum + normal;
+            }
+        }
+        for triangle in &mut self.triangles {
+            if triangle.normals.is_none() {
+                triangle.normals = Some(triangle.corners.map(|corner| sums[&key(corner)]));
+            }
+        }
+    }
+
+    /// Moves and scales the mesh so that it's centred on the origin and its largest dimension is
+    /// `size`.
+    pub fn fit(mut self, size: Real) -> Mesh {
+        let (min, max) = self.bounds();
+        let extent = max - min;
+        let largest = extent.x.max(extent.y).max(extent.z);
+        if !(largest > 0.0 && largest.is_finite()) {
+            return self;
+        }
+        let centre = (min + max) * 0.5;
+        let scale = size / largest;
+        for triangle in &mut self.triangles {
+            triangle.corners = triangle.corners.map(|corner| (corner - centre) * scale);
+        }
+        for point in &mut self.points {
+            point.position = (point.position - centre) * scale;
+        }
+        self
+    }
+
+    /// Every corner of every triangle, and every point.
+    fn corners(&self) -> impl Iterator<Item = Vec3> + '_ {
+        let points = self.points.iter().map(|point| point.position);
+        (self.triangles.iter())
+            .flat_map(|triangle| triangle.corners)
+            .chain(points)
+    }
+
+    /// The lowest and highest coordinates along each axis, which are infinite for an empty mesh.
+    fn bounds(&self) -> (Vec3, Vec3) {
+        let min = self.corners().fold(
+            Vec3::new(Real::INFINITY, Real::INFINITY, Real::INFINITY),
+            |a, b| Vec3::new(a.x.min(b.x), a.y.min(b.y), a.z.min(b.z)),
+        );
+        let max = self.corners().fold(
+            Vec3::new(Real::NEG_INFINITY, Real::NEG_INFINITY, Real::NEG_INFINITY),
+            |a, b| Vec3::new(a.x.max(b.x), a.y.max(b.y), a.z.max(b.z)),
+        );
+        (min, max)
+    }
+
+    /// Calls `visit` with points covering every triangle and the normals there, roughly as
+    /// densely as a parametric shape sampled every `step` radians, and then with every point.
+    /// Points without normals face away from the centre.
+    pub fn sample(&self, step: Real, mut visit: impl FnMut(Vec3, Vec3)) {
+        let spacing = step * Mesh::SPACING;
+        for triangle in &self.triangles {
+            let [a, b, c] = triangle.corners;
+            let longest = (b - a).length().max((c - b).length()).max((a - c).length());
+            // Fitted meshes need nowhere near the most, which only keeps out ones that aren't.
+            let n = (longest / spacing).ceil().clamp(1.0, Mesh::MAX_DIVISIONS) as usize;
+            for i in 0..=n {
+                for j in 0..=n - i {
+                    let (point, normal) = triangle.at(i as Real / n as Real, j as Real / n as Real);
+                    visit(point, normal);
+                }
+            }
+        }
+        for point in &self.points {
+            visit(point.position, point.normal.unwrap_or(point.position));
+        }
+    }
+
+    /// A point on the surface and the normal there, for `s` and `t` between 0 and 1. Evenly
+    /// distributed `s` and `t` give points evenly distributed over each triangle.
+    pub fn at(&self, s: Real, t: Real) -> (Vec3, Vec3) {
+        if self.triangles.is_empty() {
+            let Some(point) = self
+                .points
+                .get((s.clamp(0.0, 1.0) * self.points.len() as Real) as usize)
+                .or(self.points.last())
+            else {
+                return (Vec3::default(), Vec3::Y);
+            };
+            return (point.position, point.normal.unwrap_or(point.position));
+        }
+        let scaled = s.clamp(0.0, 1.0) * self.triangles.len() as Real;
+        let index = (scaled as usize).min(self.triangles.len() - 1);
+        // Picking uniformly within a triangle needs the square root.
+        let root = (scaled - index as Real).sqrt();
+        self.triangles[index].at(root * (1.0 - t), root * t)
+    }
+}
+
+/// Why a model couldn't be loaded.
+#[derive(Debug)]
+pub enum MeshError {
+    Io(PathBuf, io::Error),
+    /// The file's extension isn't a format that can be loaded.
+    Unsupported(PathBuf),
+    /// The file is malformed.
+    Parse {
+        line: usize,
+        message: String,
+    },
+    /// The file ends before all of the data it says it has.
+    Truncated,
+    /// The file is readable but describes something impossible.
+    Invalid(String),
+}
+
+impl fmt::Display for MeshError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MeshError::Io(path, error) => write!(f, "{}: {error}", path.display()),
+            MeshError::Unsupported(path) => {
+                write!(f, "{}: unsupported model format", path.display())
+            }
+            MeshError::Parse { line, message } => write!(f, "line {line}: {message}"),
+            MeshError::Truncated => f.write_str("file is cut short"),
+            MeshError::Invalid(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for MeshError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MeshError::Io(_, error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    fn square() -> Mesh {
+        let corners = [
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(2.0, 4.0, 0.0),
+            Vec3::new(0.0, 4.0, 0.0),
+        ];
+        let triangle = |a: usize, b: usize, c: usize| Triangle {
+            corners: [corners[a], corners[b], corners[c]],
+            normals: None,
+        };
+        Mesh {
+            triangles: vec![triangle(0, 1, 2), triangle(0, 2, 3)],
+            points: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn fit_centres_and_scales() {
+        let mesh = square().fit(30.0);
+        let corners: Vec<Vec3> = mesh.triangles.iter().flat_map(|t| t.corners).collect();
+        assert!(corners.contains(&Vec3::new(-7.5, -15.0, 0.0)));
+        assert!(corners.contains(&Vec3::new(7.5, 15.0, 0.0)));
+    }
+
+    #[test]
+    fn samples_cover_triangles_with_their_normals() {
+        let mesh = square().with_normals(Normals::Smooth);
+        let mut count = 0;
+        mesh.sample(0.1, |point, normal| {
+            count += 1;
+            assert!((0.0..=2.0).contains(&point.x) && (0.0..=4.0).contains(&point.y));
+            assert!(normal.normalized().z > 0.999);
+        });
+        // Each triangle's longest edge is √20 ≈ 4.5, so 6 subdivisions and 28 samples each.
+        assert_eq!(56, count);
+
+        // Triangles too big to have been fitted are still sampled a bounded number of times.
+        let huge = Mesh {
+            triangles: vec![Triangle {
+                corners: [
+                    Vec3::default(),
+                    Vec3::new(1e30, 0.0, 0.0),
+                    Vec3::new(0.0, 1e30, 0.0),
+                ],
+                normals: None,
+            }],
+            points: Vec::new(),
+        };
+        let mut count = 0;
+        huge.sample(0.1, |_, _| count += 1);
+        assert_eq!(1001 * 1002 / 2, count);
+    }
+
+    #[test]
+    fn loading_refuses_models_too_big_to_measure() {
+        let path = env::temp_dir().join(format!("ascii-love-huge-{}.obj", process::id()));
+        fs::write(&path, "v -1e308 0 0\nv 1e308 0 0\nv 0 1e308 0\nf 1 2 3\n").unwrap();
+        let result = Mesh::load(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(
+            "the model is too big to draw",
+            result.unwrap_err().to_string()
+        );
+    }
+}
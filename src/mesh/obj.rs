@@ -0,0 +1,107 @@
+//! Wavefront OBJ files. Only vertices, vertex normals, and faces are read; materials, texture
+//! coordinates, groups, and the like are ignored.
+
+use super::{Mesh, MeshError, Triangle};
+use crate::math::{Real, Vec3};
+
+pub fn parse(text: &str) -> Result<Mesh, MeshError> {
+    let mut vertices = Vec::new();
+    let mut normals = Vec::new();
+    let mut triangles = Vec::new();
+
+    for (i, line) in text.lines().enumerate() {
+        let error = |message: String| MeshError::Parse {
+            line: i + 1,
+            message,
+        };
+        let line = line.split('#').next().unwrap_or_default();
+        let mut words = line.split_whitespace();
+        match words.next() {
+            Some("v") => vertices.push(vec3(words).map_err(error)?),
+            Some("vn") => normals.push(vec3(words).map_err(error)?),
+            Some("f") => {
+                let corners = words
+                    .map(|word| corner(word, &vertices, &normals))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(error)?;
+                if corners.len() < 3 {
+                    return Err(error("faces need at least three corners".to_owned()));
+                }
+                // Split polygons into a fan of triangles around their first corner.
+                for pair in corners[1..].windows(2) {
+                    let [(a, na), (b, nb), (c, nc)] = [corners[0], pair[0], pair[1]];
+                    triangles.push(Triangle {
+                        corners: [a, b, c],
+                        normals: na.zip(nb).zip(nc).map(|((na, nb), nc)| [na, nb, nc]),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(Mesh {
+        triangles,
+        ..Mesh::default()
+    })
+}
+
+fn vec3<'a>(mut words: impl Iterator<Item = &'a str>) -> Result<Vec3, String> {
+    let mut component = || -> Result<Real, String> {
+        let word = words.next().ok_or("expected three numbers")?;
+        word.parse()
+            .map_err(|_| format!("expected a number, got {word:?}"))
+    };
+    Ok(Vec3::new(component()?, component()?, component()?))
+}
+
+/// A face corner like `3`, `3/1`, `3//2`, or `3/1/2`: a vertex index, optionally followed by
+/// texture coordinate and normal indices. Indices count from 1, or back from the end if negative.
+fn corner(word: &str, vertices: &[Vec3], normals: &[Vec3]) -> Result<(Vec3, Option<Vec3>), String> {
+    let mut indices = word.split('/');
+    let vertex = lookup(indices.next().unwrap_or_default(), vertices)?;
+    let normal = match indices.nth(1) {
+        Some(index) if !index.is_empty() => Some(lookup(index, normals)?),
+        _ => None,
+    };
+    Ok((vertex, normal))
+}
+
+fn lookup(index: &str, items: &[Vec3]) -> Result<Vec3, String> {
+    let number: i64 = index
+        .parse()
+        .map_err(|_| format!("expected an index, got {index:?}"))?;
+    let position = match number {
+        n if n > 0 => n as usize - 1,
+        n if n < 0 => items.len().wrapping_sub(n.unsigned_abs() as usize),
+        _ => usize::MAX,
+    };
+    items
+        .get(position)
+        .copied()
+        .ok_or_else(|| format!("index {number} is out of range"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_faces_with_normals() {
+        let mesh = parse(
+            "# a square\n\
+             v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n\
+             vn 0 0 1\n\
+             f 1//1 2//1 3//1 4//1\n\
+             f -4 -3 -2\n",
+        )
+        .unwrap();
+        assert_eq!(3, mesh.triangles.len());
+        assert_eq!(Some([Vec3::Z; 3]), mesh.triangles[0].normals);
+        assert_eq!(Vec3::new(0.0, 1.0, 0.0), mesh.triangles[1].corners[2]);
+        assert_eq!(None, mesh.triangles[2].normals);
+
+        let error = parse("v 0 0 0\nf 1 2 3").unwrap_err();
+        assert_eq!("line 2: index 2 is out of range", error.to_string());
+    }
+}
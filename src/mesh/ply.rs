@@ -0,0 +1,342 @@
+//! PLY (Stanford polygon) files, in ASCII or either binary byte order. Files with faces become
+//! triangle meshes; files with only vertices, like most scans, become point clouds.
+
+use super::{Mesh, MeshError, Point, Triangle};
+use crate::math::{Real, Vec3};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    Ascii,
+    LittleEndian,
+    BigEndian,
+}
+
+/// The numeric types a property can have, by their size in bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Type {
+    Int(usize),
+    Uint(usize),
+    Float(usize),
+}
+
+impl Type {
+    fn parse(name: &str) -> Result<Type, String> {
+        Ok(match name {
+            "char" | "int8" => Type::Int(1),
+            "uchar" | "uint8" => Type::Uint(1),
+            "short" | "int16" => Type::Int(2),
+            "ushort" | "uint16" => Type::Uint(2),
+            "int" | "int32" => Type::Int(4),
+            "uint" | "uint32" => Type::Uint(4),
+            "float" | "float32" => Type::Float(4),
+            "double" | "float64" => Type::Float(8),
+            _ => return Err(format!("unknown property type {name:?}")),
+        })
+    }
+}
+
+#[derive(Debug)]
+struct Property {
+    name: String,
+    /// For lists, the type of their length. Their items are `ty`.
+    list: Option<Type>,
+    ty: Type,
+}
+
+#[derive(Debug)]
+struct Element {
+    name: String,
+    /// Where the header declares the element.
+    line: usize,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+impl Element {
+    fn index(&self, name: &str) -> Option<usize> {
+        self.properties
+            .iter()
+            .position(|property| property.name == name)
+    }
+}
+
+pub fn parse(bytes: &[u8]) -> Result<Mesh, MeshError> {
+    let (format, elements, header_end, header_lines) = header(bytes)?;
+    let body = &bytes[header_end..];
+    let mut reader = match format {
+        Format::Ascii => Reader::Ascii {
+            words: std::str::from_utf8(body)
+                .unwrap_or_default()
+                .lines()
+                .enumerate()
+                .flat_map(move |(i, line)| {
+                    line.split_whitespace()
+                        .map(move |word| (header_lines + i + 1, word))
+                }),
+        },
+        Format::LittleEndian | Format::BigEndian => Reader::Binary {
+            bytes: body,
+            big_endian: format == Format::BigEndian,
+        },
+    };
+
+    let mut vertices: Vec<Point> = Vec::new();
+    let mut triangles = Vec::new();
+    for element in &elements {
+        let xyz = ["x", "y", "z"].map(|name| element.index(name));
+        let normal = ["nx", "ny", "nz"].map(|name| element.index(name));
+        let indices = element
+            .index("vertex_indices")
+            .or_else(|| element.index("vertex_index"));
+        let missing = match element.name.as_str() {
+            "vertex" if xyz.contains(&None) => Some("vertices need x, y, and z properties"),
+            "face" if indices.is_none() => Some("faces need a vertex_indices property"),
+            _ => None,
+        };
+        if let Some(message) = missing {
+            return Err(MeshError::Parse {
+                line: element.line,
+                message: message.to_owned(),
+            });
+        }
+
+        for n in 0..element.count {
+            let mut values = Vec::with_capacity(element.properties.len());
+            for property in &element.properties {
+                values.push(match property.list {
+                    Some(length) => {
+                        let length = reader.read(length)? as usize;
+                        (0..length)
+                            .map(|_| reader.read(property.ty))
+                            .collect::<Result<_, _>>()?
+                    }
+                    None => vec![reader.read(property.ty)?],
+                });
+            }
+            // Lists have their first value read, and empty ones have none.
+            let scalar = |i: Option<usize>| i.and_then(|i| values[i].first()).map(|&v| v as Real);
+
+            match element.name.as_str() {
+                "vertex" => {
+                    // The properties are known to be there from the check above.
+                    let [Some(x), Some(y), Some(z)] = xyz.map(scalar) else {
+                        return Err(MeshError::Parse {
+                            line: element.line,
+                            message: format!("vertex {n} has an empty list for x, y, or z"),
+                        });
+                    };
+                    let normal = match normal.map(scalar) {
+                        [Some(x), Some(y), Some(z)] => Some(Vec3::new(x, y, z)),
+                        _ => None,
+                    };
+                    vertices.push(Point {
+                        position: Vec3::new(x, y, z),
+                        normal,
+                    });
+                }
+                "face" => {
+                    let corners = values[indices.unwrap()]
+                        .iter()
+                        .map(|&index| {
+                            vertices.get(index as usize).copied().ok_or_else(|| {
+                                MeshError::Invalid(format!("vertex {index} is out of range"))
+                            })
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    // Split polygons into a fan of triangles around their first corner.
+                    for pair in corners.get(1..).unwrap_or_default().windows(2) {
+                        let [a, b, c] = [corners[0], pair[0], pair[1]];
+                        triangles.push(Triangle {
+                            corners: [a.position, b.position, c.position],
+                            normals: a
+                                .normal
+                                .zip(b.normal)
+                                .zip(c.normal)
+                                .map(|((na, nb), nc)| [na, nb, nc]),
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let points = if triangles.is_empty() {
+        vertices
+    } else {
+        Vec::new()
+    };
+    Ok(Mesh { triangles, points })
+}
+
+/// Reads the header, returning the body's format, its elements, where it starts, and how many
+/// lines come before it.
+fn header(bytes: &[u8]) -> Result<(Format, Vec<Element>, usize, usize), MeshError> {
+    let mut format = None;
+    let mut elements: Vec<Element> = Vec::new();
+    let mut offset = 0;
+
+    for i in 1.. {
+        let error = |message: String| MeshError::Parse { line: i, message };
+        let Some(length) = bytes[offset..].iter().position(|&b| b == b'\n') else {
+            return Err(error("expected end_header".to_owned()));
+        };
+        let line = String::from_utf8_lossy(&bytes[offset..offset + length]);
+        offset += length + 1;
+        let words: Vec<&str> = line.split_whitespace().collect();
+
+        match words.as_slice() {
+            ["ply"] if i == 1 => {}
+            _ if i == 1 => return Err(error("not a PLY file".to_owned())),
+            ["format", name, _version] => {
+                format = Some(match *name {
+                    "ascii" => Format::Ascii,
+                    "binary_little_endian" => Format::LittleEndian,
+                    "binary_big_endian" => Format::BigEndian,
+                    _ => return Err(error(format!("unknown format {name:?}"))),
+                })
+            }
+            ["element", name, count] => elements.push(Element {
+                name: name.to_string(),
+                line: i,
+                count: count
+                    .parse()
+                    .map_err(|_| error(format!("expected a count, got {count:?}")))?,
+                properties: Vec::new(),
+            }),
+            ["property", rest @ ..] => {
+                let Some(element) = elements.last_mut() else {
+                    return Err(error("property outside of an element".to_owned()));
+                };
+                let property = match rest {
+                    ["list", length, ty, name] => Property {
+                        name: name.to_string(),
+                        list: Some(Type::parse(length).map_err(error)?),
+                        ty: Type::parse(ty).map_err(error)?,
+                    },
+                    [ty, name] => Property {
+                        name: name.to_string(),
+                        list: None,
+                        ty: Type::parse(ty).map_err(error)?,
+                    },
+                    _ => return Err(error("malformed property".to_owned())),
+                };
+                element.properties.push(property);
+            }
+            ["comment" | "obj_info", ..] | [] => {}
+            ["end_header"] => {
+                let format = format.ok_or_else(|| error("missing format".to_owned()))?;
+                return Ok((format, elements, offset, i));
+            }
+            _ => return Err(error(format!("unexpected {line:?}"))),
+        }
+    }
+    unreachable!()
+}
+
+/// Reads numbers one at a time from the body of the file.
+enum Reader<'a, I> {
+    Ascii { words: I },
+    Binary { bytes: &'a [u8], big_endian: bool },
+}
+
+impl<'a, I: Iterator<Item = (usize, &'a str)>> Reader<'a, I> {
+    fn read(&mut self, ty: Type) -> Result<f64, MeshError> {
+        match self {
+            Reader::Ascii { words } => {
+                let (line, word) = words.next().ok_or(MeshError::Truncated)?;
+                word.parse().map_err(|_| MeshError::Parse {
+                    line,
+                    message: format!("expected a number, got {word:?}"),
+                })
+            }
+            Reader::Binary { bytes, big_endian } => {
+                let size = match ty {
+                    Type::Int(size) | Type::Uint(size) | Type::Float(size) => size,
+                };
+                if bytes.len() < size {
+                    return Err(MeshError::Truncated);
+                }
+                let mut buffer = [0; 8];
+                buffer[..size].copy_from_slice(&bytes[..size]);
+                if *big_endian {
+                    buffer[..size].reverse();
+                }
+                *bytes = &bytes[size..];
+                let raw = u64::from_le_bytes(buffer);
+                Ok(match ty {
+                    Type::Uint(_) => raw as f64,
+                    // Sign-extend by shifting the value into the top bits and back.
+                    Type::Int(size) => ((raw << (64 - 8 * size)) as i64 >> (64 - 8 * size)) as f64,
+                    Type::Float(4) => f64::from(f32::from_bits(raw as u32)),
+                    Type::Float(_) => f64::from_bits(raw),
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ascii_faces() {
+        let mesh = parse(
+            b"ply\nformat ascii 1.0\ncomment a square\n\
+              element vertex 4\nproperty float x\nproperty float y\nproperty float z\n\
+              element face 1\nproperty list uchar int vertex_indices\nend_header\n\
+              0 0 0\n1 0 0\n1 1 0\n0 1 0\n4 0 1 2 3\n",
+        )
+        .unwrap();
+        assert_eq!(2, mesh.triangles.len());
+        assert!(mesh.points.is_empty());
+        assert_eq!(Vec3::new(0.0, 1.0, 0.0), mesh.triangles[1].corners[2]);
+
+        let error =
+            parse(b"ply\nformat ascii 1.0\nelement vertex 1\nproperty float x\n").unwrap_err();
+        assert_eq!("line 5: expected end_header", error.to_string());
+
+        // Coordinates can be lists, but not empty ones.
+        let error = parse(
+            b"ply\nformat ascii 1.0\n\
+              element vertex 2\nproperty float x\nproperty list uchar float y\nproperty float z\n\
+              end_header\n\
+              0 1 0 0\n1 0 0\n",
+        )
+        .unwrap_err();
+        assert_eq!(
+            "line 3: vertex 1 has an empty list for x, y, or z",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn parses_binary_point_clouds() {
+        let mut bytes = b"ply\nformat binary_big_endian 1.0\nelement vertex 2\n\
+                          property float x\nproperty float y\nproperty float z\n\
+                          property uchar red\nproperty short w\nend_header\n"
+            .to_vec();
+        for (x, y, z, red, w) in [
+            (1.0f32, 2.0f32, 3.0f32, 255u8, -2i16),
+            (4.0, 5.0, 6.0, 0, 7),
+        ] {
+            for value in [x, y, z] {
+                bytes.extend(value.to_be_bytes());
+            }
+            bytes.push(red);
+            bytes.extend(w.to_be_bytes());
+        }
+
+        let mesh = parse(&bytes).unwrap();
+        assert!(mesh.triangles.is_empty());
+        let positions: Vec<Vec3> = mesh.points.iter().map(|point| point.position).collect();
+        assert_eq!(
+            vec![Vec3::new(1.0, 2.0, 3.0), Vec3::new(4.0, 5.0, 6.0)],
+            positions
+        );
+
+        bytes.truncate(bytes.len() - 1);
+        assert!(matches!(parse(&bytes), Err(MeshError::Truncated)));
+    }
+}
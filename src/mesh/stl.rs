@@ -0,0 +1,143 @@
+//! STL files, in both their binary and ASCII forms. The normals stored with each facet are
+//! ignored: plenty of exporters leave them zeroed, and the corners' winding says the same thing.
+
+use super::{Mesh, MeshError, Triangle};
+use crate::math::{Real, Vec3};
+
+/// The binary form's header, followed by a little-endian `u32` triangle count.
+const HEADER: usize = 80;
+/// Each binary triangle: a normal, three corners, and two bytes of "attributes", all unused here.
+const RECORD: usize = 50;
+
+pub fn parse(bytes: &[u8]) -> Result<Mesh, MeshError> {
+    // Binary files are allowed to start with "solid" too, so trust the size first.
+    let sized = binary_count(bytes).is_some_and(|count| bytes.len() == HEADER + 4 + count * RECORD);
+    let ascii = bytes.trim_ascii_start().starts_with(b"solid")
+        && bytes.windows(5).any(|word| word == b"facet");
+    if !sized && ascii {
+        parse_ascii(&String::from_utf8_lossy(bytes))
+    } else {
+        parse_binary(bytes)
+    }
+}
+
+fn binary_count(bytes: &[u8]) -> Option<usize> {
+    let count = bytes.get(HEADER..HEADER + 4)?;
+    Some(u32::from_le_bytes(count.try_into().ok()?) as usize)
+}
+
+fn parse_binary(bytes: &[u8]) -> Result<Mesh, MeshError> {
+    let count = binary_count(bytes).ok_or(MeshError::Truncated)?;
+    let records = &bytes[HEADER + 4..];
+    if records.len() < count.saturating_mul(RECORD) {
+        return Err(MeshError::Truncated);
+    }
+    let triangles = records
+        .chunks_exact(RECORD)
+        .take(count)
+        .map(|record| {
+            let float = |i: usize| {
+                let offset = 12 + 4 * i;
+                Real::from(f32::from_le_bytes(
+                    record[offset..offset + 4].try_into().unwrap(),
+                ))
+            };
+            let corner = |i: usize| Vec3::new(float(3 * i), float(3 * i + 1), float(3 * i + 2));
+            Triangle {
+                corners: [corner(0), corner(1), corner(2)],
+                normals: None,
+            }
+        })
+        .collect();
+    Ok(Mesh {
+        triangles,
+        ..Mesh::default()
+    })
+}
+
+fn parse_ascii(text: &str) -> Result<Mesh, MeshError> {
+    let mut triangles = Vec::new();
+    let mut corners = Vec::new();
+
+    for (i, line) in text.lines().enumerate() {
+        let error = |message: String| MeshError::Parse {
+            line: i + 1,
+            message,
+        };
+        let mut words = line.split_whitespace();
+        match words.next() {
+            Some("vertex") => corners.push(vec3(words).map_err(error)?),
+            Some("endloop") => {
+                if corners.len() < 3 {
+                    return Err(error("facets need at least three vertices".to_owned()));
+                }
+                // Facets are meant to be triangles, but split anything bigger into a fan anyway.
+                for pair in corners[1..].windows(2) {
+                    triangles.push(Triangle {
+                        corners: [corners[0], pair[0], pair[1]],
+                        normals: None,
+                    });
+                }
+                corners.clear();
+            }
+            _ => {}
+        }
+    }
+
+    Ok(Mesh {
+        triangles,
+        ..Mesh::default()
+    })
+}
+
+fn vec3<'a>(mut words: impl Iterator<Item = &'a str>) -> Result<Vec3, String> {
+    let mut component = || -> Result<Real, String> {
+        let word = words.next().ok_or("expected three numbers")?;
+        word.parse()
+            .map_err(|_| format!("expected a number, got {word:?}"))
+    };
+    Ok(Vec3::new(component()?, component()?, component()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ascii() {
+        let mesh = parse(
+            b"solid square\n\
+              facet normal 0 0 1\n outer loop\n  vertex 0 0 0\n  vertex 1 0 0\n  vertex 1 1 0\n \
+              endloop\nendfacet\n\
+              facet normal 0 0 0\n outer loop\n  vertex 0 0 0\n  vertex 1 1 0\n  vertex 0 1 0\n \
+              endloop\nendfacet\n\
+              endsolid square\n",
+        )
+        .unwrap();
+        assert_eq!(2, mesh.triangles.len());
+        assert_eq!(Vec3::new(0.0, 1.0, 0.0), mesh.triangles[1].corners[2]);
+
+        let error = parse(b"solid\nfacet normal 0 0 1\nouter loop\nvertex 0 0 q\n").unwrap_err();
+        assert_eq!("line 4: expected a number, got \"q\"", error.to_string());
+    }
+
+    #[test]
+    fn parses_binary_even_with_a_solid_header() {
+        let mut bytes = b"solid but actually binary".to_vec();
+        bytes.resize(HEADER, 0);
+        bytes.extend(1u32.to_le_bytes());
+        for value in [
+            0.0f32, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 2.0, 0.0,
+        ] {
+            bytes.extend(value.to_le_bytes());
+        }
+        bytes.extend([0, 0]);
+
+        let mesh = parse(&bytes).unwrap();
+        assert_eq!(1, mesh.triangles.len());
+        assert_eq!(Vec3::new(0.0, 2.0, 0.0), mesh.triangles[0].corners[2]);
+
+        bytes.truncate(bytes.len() - 10);
+        assert!(matches!(parse(&bytes), Err(MeshError::Truncated)));
+    }
+}
@@ -0,0 +1,162 @@
+//! Ways for the whole animation to move around the screen.
+//!
+//! Positions are fractions of the way across and down the space left over around the animation,
+//! from 0 to 1, so they still fit after the terminal is resized. See [`Viewport::placed`].
+//!
+//! [`Viewport::placed`]: crate::viewport::Viewport::placed
+
+use std::f64::consts::TAU;
+use std::str::FromStr;
+
+/// Bouncing off the edges of the screen, like an old DVD player's logo, but falling as it goes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bounce {
+    pub position: (f64, f64),
+    /// Across and down, in fractions of the space per second.
+    pub velocity: (f64, f64),
+}
+
+impl Bounce {
+    /// How fast it moves across, in fractions of the space per second.
+    pub const SPEED: f64 = 0.15;
+
+    /// How fast it falls faster, in fractions of the space per second per second.
+    pub const GRAVITY: f64 = 0.8;
+
+    /// Moves on by `dt` seconds, bouncing off whichever edges it reaches. Bounces don't lose any
+    /// speed, so it keeps coming back up to where it started.
+    pub fn update(&mut self, dt: f64) {
+        let (x, y) = self.position;
+        let (vx, vy) = self.velocity;
+        let vy = vy + Bounce::GRAVITY * dt;
+        let (x, vx) = bounced(x + vx * dt, vx);
+        let (y, vy) = bounced(y + vy * dt, vy);
+        self.position = (x, y);
+        self.velocity = (vx, vy);
+    }
+}
+
+impl Default for Bounce {
+    /// From the top left, heading right.
+    fn default() -> Bounce {
+        Bounce {
+            position: (0.0, 0.0),
+            velocity: (Bounce::SPEED, 0.0),
+        }
+    }
+}
+
+/// Following a Lissajous curve: swinging back and forth across and down the screen at two
+/// different frequencies, so it traces out loops.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Lissajous {
+    /// How many times it swings across each period.
+    pub across: f64,
+    /// How many times it swings down each period.
+    pub down: f64,
+    /// How far ahead the swing across starts, in radians.
+    pub phase: f64,
+    /// Seconds before the curve starts again, when the frequencies are whole numbers.
+    pub period: f64,
+}
+
+impl Lissajous {
+    pub const DEFAULT_PERIOD: f64 = 20.0;
+
+    /// Where on the curve it is `time` seconds in.
+    pub fn at(&self, time: f64) -> (f64, f64) {
+        let angle = TAU * time / self.period;
+        let swing = |angle: f64| (angle.sin() + 1.0) / 2.0;
+        (
+            swing(self.across * angle + self.phase),
+            swing(self.down * angle),
+        )
+    }
+}
+
+impl Default for Lissajous {
+    /// Three swings across to every two down, which traces a pretzel.
+    fn default() -> Lissajous {
+        Lissajous {
+            across: 3.0,
+            down: 2.0,
+            phase: TAU / 4.0,
+            period: Lissajous::DEFAULT_PERIOD,
+        }
+    }
+}
+
+impl FromStr for Lissajous {
+    type Err = String;
+
+    /// Reads the frequencies across and down, like `3,2`.
+    fn from_str(text: &str) -> Result<Lissajous, String> {
+        let invalid = || format!("expected frequencies like \"3,2\", got {text:?}");
+        let (across, down) = text.split_once(',').ok_or_else(invalid)?;
+        let frequency = |text: &str| match text.trim().parse::<f64>() {
+            Ok(frequency) if frequency > 0.0 && frequency.is_finite() => Ok(frequency),
+            _ => Err(invalid()),
+        };
+        Ok(Lissajous {
+            across: frequency(across)?,
+            down: frequency(down)?,
+            ..Lissajous::default()
+        })
+    }
+}
+
+/// `position` reflected back off either end of the space if it's gone past it, and `velocity`
+/// turned around if it has.
+fn bounced(position: f64, velocity: f64) -> (f64, f64) {
+    if position < 0.0 {
+        ((-position).min(1.0), velocity.abs())
+    } else if position > 1.0 {
+        ((2.0 - position).max(0.0), -velocity.abs())
+    } else {
+        (position, velocity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounces_stay_on_screen() {
+        let mut bounce = Bounce::default();
+        let mut highest_after_landing = f64::INFINITY;
+        let mut landed = false;
+        for _ in 0..2000 {
+            bounce.update(0.05);
+            let (x, y) = bounce.position;
+            assert!((0.0..=1.0).contains(&x) && (0.0..=1.0).contains(&y));
+            landed |= y > 0.99;
+            if landed {
+                highest_after_landing = highest_after_landing.min(y);
+            }
+        }
+        assert!(landed);
+        // It comes back up most of the way after landing.
+        assert!(highest_after_landing < 0.2, "{highest_after_landing}");
+    }
+
+    #[test]
+    fn lissajous_curves_loop() {
+        let curve: Lissajous = "3,2".parse().unwrap();
+        let start = curve.at(0.0);
+        assert!((start.0 - 1.0).abs() < 1e-9 && (start.1 - 0.5).abs() < 1e-9);
+        let later = curve.at(curve.period);
+        assert!((later.0 - start.0).abs() < 1e-9 && (later.1 - start.1).abs() < 1e-9);
+        let (x, y) = curve.at(curve.period / 8.0);
+        assert!((0.0..=1.0).contains(&x) && (0.0..=1.0).contains(&y));
+        assert!("3".parse::<Lissajous>().is_err());
+        assert!("3,-2".parse::<Lissajous>().is_err());
+    }
+
+    #[test]
+    fn turns_around_at_the_edges() {
+        assert_eq!((0.75, -1.0), bounced(1.25, 1.0));
+        assert_eq!((0.25, 2.0), bounced(-0.25, -2.0));
+        assert_eq!((0.5, 1.0), bounced(0.5, 1.0));
+    }
+}
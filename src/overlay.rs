@@ -0,0 +1,329 @@
+//! Text drawn on top of (or behind) a rendered frame.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::terminfo::Charset;
+use crate::viewport::Margin;
+use crate::width;
+
+/// Where a message goes on the screen. It's always centred horizontally.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Placement {
+    Top,
+    #[default]
+    Center,
+    Bottom,
+}
+
+impl Placement {
+    pub const ALL: [Placement; 3] = [Placement::Top, Placement::Center, Placement::Bottom];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Placement::Top => "top",
+            Placement::Center => "center",
+            Placement::Bottom => "bottom",
+        }
+    }
+}
+
+impl fmt::Display for Placement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Placement {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Placement, String> {
+        Placement::ALL
+            .into_iter()
+            .find(|placement| placement.name() == name)
+            .ok_or_else(|| format!("unknown placement {name:?}"))
+    }
+}
+
+/// A banner of one or more lines of text.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Message {
+    pub text: String,
+    pub placement: Placement,
+    /// Only fill empty cells, so the message peeks out from behind the objects.
+    pub under: bool,
+}
+
+impl Message {
+    /// Writes the message into a finished frame. Lines too wide for the frame are cut off at both
+    /// ends, and lines that don't fit below the placement are dropped.
+    pub fn draw(&self, frame: &mut [Vec<char>]) {
+        let lines: Vec<Vec<char>> = self.text.lines().map(width::columns).collect();
+        let height = frame.len();
+        let top = match self.placement {
+            Placement::Top => 0,
+            Placement::Center => height.saturating_sub(lines.len()) / 2,
+            Placement::Bottom => height.saturating_sub(lines.len()),
+        };
+
+        for (row, line) in frame.iter_mut().skip(top).zip(&lines) {
+            let width = row.len() as isize;
+            let left = (width - line.len() as isize) / 2;
+            for (i, &c) in line.iter().enumerate() {
+                let x = left + i as isize;
+                if !(0..width).contains(&x) {
+                    continue;
+                }
+                let cell = &mut row[x as usize];
+                if self.under {
+                    // Spaces in the message are see-through, too.
+                    if *cell == ' ' && c != ' ' {
+                        *cell = c;
+                    }
+                } else {
+                    *cell = c;
+                }
+            }
+        }
+    }
+}
+
+/// Text scrolling from right to left along the bottom row.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Marquee {
+    pub text: String,
+    /// Cells per second.
+    pub speed: f64,
+}
+
+impl Marquee {
+    pub const DEFAULT_SPEED: f64 = 8.0;
+
+    /// Separates the end of the text from the start of its next repeat.
+    const GAP: &'static str = "   ♥   ";
+    const ASCII_GAP: &'static str = "   <3   ";
+
+    pub fn new(text: String) -> Marquee {
+        Marquee {
+            text,
+            speed: Marquee::DEFAULT_SPEED,
+        }
+    }
+
+    /// Replaces the bottom row of a finished frame with the text as it is `time` seconds in.
+    ///
+    /// The text repeats endlessly with a gap in between, and where it is depends only on the time,
+    /// so resizing the terminal reveals or hides text at the right without making it jump.
+    pub fn draw(&self, frame: &mut [Vec<char>], time: f64, charset: Charset) {
+        let Some(row) = frame.last_mut() else {
+            return;
+        };
+        let gap = match charset {
+            Charset::Unicode => Marquee::GAP,
+            Charset::Ascii => Marquee::ASCII_GAP,
+        };
+        let strip = width::columns(&format!("{}{gap}", self.text));
+        let offset = (time * self.speed).floor() as i64;
+        for (x, cell) in row.iter_mut().enumerate() {
+            let i = (x as i64 + offset).rem_euclid(strip.len() as i64);
+            *cell = strip[i as usize];
+        }
+    }
+}
+
+/// How much room the border drawn by [`draw_card`] takes up.
+pub const CARD_MARGIN: Margin = Margin {
+    top: 1,
+    right: 2,
+    bottom: 1,
+    left: 2,
+};
+
+/// The characters a card's border is drawn with.
+struct Border {
+    /// Top-left, top-right, bottom-left, and bottom-right.
+    corners: [char; 4],
+    horizontal: char,
+    vertical: char,
+    /// On either side of each corner.
+    ornament: char,
+}
+
+const CARD: Border = Border {
+    corners: ['╔', '╗', '╚', '╝'],
+    horizontal: '═',
+    vertical: '║',
+    ornament: '♥',
+};
+
+const ASCII_CARD: Border = Border {
+    corners: ['+', '+', '+', '+'],
+    horizontal: '=',
+    vertical: '|',
+    ornament: '*',
+};
+
+/// Draws a greeting-card border around the edge of the screen: a double line with ornaments
+/// flanking each corner, or the nearest ASCII can do. Screens too small for a border are left
+/// alone.
+pub fn draw_card(screen: &mut [Vec<char>], charset: Charset) {
+    let border = match charset {
+        Charset::Unicode => &CARD,
+        Charset::Ascii => &ASCII_CARD,
+    };
+    let [top_left, top_right, bottom_left, bottom_right] = border.corners;
+    let height = screen.len();
+    let width = screen.first().map_or(0, Vec::len);
+    if width < 6 || height < 4 {
+        return;
+    }
+    let (right, bottom) = (width - 1, height - 1);
+
+    for (y, row) in screen.iter_mut().enumerate() {
+        for (x, cell) in row.iter_mut().enumerate() {
+            let edge = match (x, y) {
+                (0, 0) => top_left,
+                (x, 0) if x == right => top_right,
+                (0, y) if y == bottom => bottom_left,
+                (x, y) if x == right && y == bottom => bottom_right,
+                (_, 0) => border.horizontal,
+                (_, y) if y == bottom => border.horizontal,
+                (0, _) => border.vertical,
+                (x, _) if x == right => border.vertical,
+                _ => continue,
+            };
+            let beside_corner = ((x == 1 || x == right - 1) && (y == 0 || y == bottom))
+                || ((y == 1 || y == bottom - 1) && (x == 0 || x == right));
+            *cell = if beside_corner { border.ornament } else { edge };
+        }
+    }
+}
+
+/// The smallest room the animation is drawn in. Any smaller, and shapes are squashed into a
+/// few cells that don't look like anything.
+pub const MIN_WIDTH: usize = 20;
+pub const MIN_HEIGHT: usize = 8;
+
+/// A screen asking for the terminal to be made bigger, with its words wrapped to fit.
+pub fn too_small(width: usize, height: usize, charset: Charset) -> Vec<Vec<char>> {
+    let by = match charset {
+        Charset::Unicode => '×',
+        Charset::Ascii => 'x',
+    };
+    let request = format!("Please enlarge the terminal to at least {MIN_WIDTH}{by}{MIN_HEIGHT}");
+    let mut lines: Vec<String> = Vec::new();
+    for word in request.split(' ') {
+        match lines.last_mut() {
+            Some(line) if line.chars().count() + 1 + word.chars().count() <= width => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.to_owned()),
+        }
+    }
+    let mut screen = vec![vec![' '; width]; height];
+    let message = Message {
+        text: lines.join("\n"),
+        ..Message::default()
+    };
+    message.draw(&mut screen);
+    screen
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(rows: &[&str]) -> Vec<Vec<char>> {
+        rows.iter().map(|row| row.chars().collect()).collect()
+    }
+
+    fn text(frame: &[Vec<char>]) -> Vec<String> {
+        frame.iter().map(|row| row.iter().collect()).collect()
+    }
+
+    #[test]
+    fn message_is_centred_over_the_frame() {
+        let mut output = frame(&["........", "........", "........"]);
+        let message = Message {
+            text: "I ♥ U".to_owned(),
+            ..Message::default()
+        };
+        message.draw(&mut output);
+        assert_eq!(vec!["........", ".I ♥ U..", "........"], text(&output));
+    }
+
+    #[test]
+    fn message_under_only_fills_empty_cells() {
+        let mut output = frame(&["  ##  "]);
+        let message = Message {
+            text: "abcdefgh".to_owned(),
+            placement: Placement::Bottom,
+            under: true,
+        };
+        message.draw(&mut output);
+        assert_eq!(vec!["bc##fg"], text(&output));
+    }
+
+    #[test]
+    fn wide_characters_take_up_two_columns() {
+        let mut output = frame(&["........"]);
+        let message = Message {
+            text: "I💖U".to_owned(),
+            ..Message::default()
+        };
+        message.draw(&mut output);
+        assert_eq!(
+            vec!['.', '.', 'I', '💖', width::CONTINUATION, 'U', '.', '.'],
+            output[0]
+        );
+    }
+
+    #[test]
+    fn card_has_ornamented_corners() {
+        let mut output = frame(&["       ", "       ", "       ", "       "]);
+        draw_card(&mut output, Charset::Unicode);
+        assert_eq!(
+            vec!["╔♥═══♥╗", "♥     ♥", "♥     ♥", "╚♥═══♥╝"],
+            text(&output)
+        );
+        draw_card(&mut output, Charset::Ascii);
+        assert_eq!(
+            vec!["+*===*+", "*     *", "*     *", "+*===*+"],
+            text(&output)
+        );
+    }
+
+    #[test]
+    fn asks_for_more_room_in_whatever_room_there_is() {
+        assert_eq!(
+            vec![
+                "  Please  ",
+                " enlarge  ",
+                "   the    ",
+                " terminal ",
+                "  to at   ",
+                "least 20×8",
+                "          ",
+            ],
+            text(&too_small(10, 7, Charset::Unicode))
+        );
+        assert_eq!("least 20x8", text(&too_small(10, 7, Charset::Ascii))[5]);
+        assert!(too_small(0, 0, Charset::Unicode).is_empty());
+    }
+
+    #[test]
+    fn marquee_scrolls_left_along_the_bottom() {
+        let marquee = Marquee {
+            text: "HELLO".to_owned(),
+            speed: 2.0,
+        };
+        let mut output = frame(&["......", "......"]);
+        marquee.draw(&mut output, 0.0, Charset::Unicode);
+        assert_eq!(vec!["......", "HELLO "], text(&output));
+        marquee.draw(&mut output, 1.0, Charset::Unicode);
+        assert_eq!(vec!["......", "LLO   "], text(&output));
+        marquee.draw(&mut output, 3.0, Charset::Ascii);
+        assert_eq!(vec!["......", "  <3  "], text(&output));
+    }
+}
@@ -0,0 +1,56 @@
+//! Keeping the objects turning at the same speed however long frames take to draw, so that they
+//! keep time with everything that follows the clock, like the camera, the timeline, and the
+//! heartbeat.
+
+use std::time::Duration;
+
+/// How far the animation is behind the clock, in frames.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Pace {
+    /// The part of a frame there hasn't been a whole frame's turning for yet.
+    lag: f64,
+}
+
+impl Pace {
+    /// How long to wait after a frame that took `drew` to draw, for frames to start `pause`
+    /// apart. Drawing counts towards the pause, so slow frames don't wait for a whole one too.
+    pub fn wait(pause: Duration, drew: Duration) -> Duration {
+        pause.saturating_sub(drew)
+    }
+
+    /// How many frames' turning to do after a frame that took `took` from start to start, with
+    /// frames meant to be `pause` apart: one for a frame on time, and more for slower ones. What
+    /// isn't a whole frame is carried on to the next, so that frames taking one and a half
+    /// pauses take turns turning one frame and two.
+    pub fn step(&mut self, took: Duration, pause: Duration) -> usize {
+        self.lag += took.as_secs_f64() / pause.as_secs_f64().max(f64::EPSILON);
+        // Give or take rounding, which would otherwise leave a frame just short of whole.
+        let frames = (self.lag + 1e-9).floor();
+        self.lag = (self.lag - frames).max(0.0);
+        frames as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAUSE: Duration = Duration::from_millis(40);
+
+    #[test]
+    fn slow_frames_catch_up() {
+        // A frame that takes most of the pause to draw waits for the rest, and then turns once.
+        let drew = PAUSE.mul_f64(0.9);
+        assert_eq!(PAUSE.mul_f64(0.1), Pace::wait(PAUSE, drew));
+        let mut pace = Pace::default();
+        assert_eq!(1, pace.step(drew + Pace::wait(PAUSE, drew), PAUSE));
+        // Frames too slow to wait at all turn as far as the clock has gone, over the frames.
+        assert_eq!(Duration::ZERO, Pace::wait(PAUSE, PAUSE * 2));
+        let slow = PAUSE.mul_f64(1.5);
+        let steps: Vec<usize> = (0..4).map(|_| pace.step(slow, PAUSE)).collect();
+        assert_eq!(vec![1, 2, 1, 2], steps);
+        let mut pace = Pace::default();
+        let turned: usize = (0..100).map(|_| pace.step(PAUSE.mul_f64(2.3), PAUSE)).sum();
+        assert_eq!(230, turned);
+    }
+}
@@ -0,0 +1,73 @@
+//! Takes turns showing different shapes, for leaving the animation running on a display.
+
+use crate::easing::{ease_in_back, ease_out_back};
+use crate::scene::Shape;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Playlist {
+    /// The shapes to show, in order. Starts over after the last one.
+    pub shapes: Vec<Shape>,
+    /// How many seconds each shape is shown for, including its transitions.
+    pub every: f64,
+}
+
+impl Playlist {
+    /// Seconds each shape spends shrinking away or growing in.
+    pub const TRANSITION: f64 = 0.6;
+
+    /// How long each shape is shown for when the configuration doesn't say.
+    pub const DEFAULT_EVERY: f64 = 10.0;
+
+    /// Cycles through every built-in shape.
+    pub fn new(every: f64) -> Playlist {
+        Playlist {
+            shapes: Shape::BUILT_IN.to_vec(),
+            every,
+        }
+    }
+
+    /// The shape showing at `time`, and how much to scale it by while it transitions in or out.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are no shapes.
+    pub fn at(&self, time: f64) -> (Shape, f64) {
+        assert!(
+            !self.shapes.is_empty(),
+            "a playlist needs at least one shape"
+        );
+        let turn = (time / self.every).floor();
+        let shape = self.shapes[turn.rem_euclid(self.shapes.len() as f64) as usize].clone();
+        if self.shapes.len() == 1 {
+            return (shape, 1.0);
+        }
+
+        (shape, transition(time - turn * self.every, self.every))
+    }
+}
+
+/// How much to scale something by `into_turn` seconds into its turn of `every` seconds, as it
+/// grows in at the start and shrinks away at the end.
+pub(crate) fn transition(into_turn: f64, every: f64) -> f64 {
+    let transition = Playlist::TRANSITION.min(every / 2.0);
+    let growing = ease_out_back((into_turn / transition).min(1.0));
+    // Swells slightly before collapsing.
+    let shrinking = 1.0 - ease_in_back(((into_turn - (every - transition)) / transition).max(0.0));
+    growing.min(shrinking).max(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shapes_take_turns_and_grow_in() {
+        let playlist = Playlist::new(10.0);
+        assert_eq!(Shape::Heart, playlist.at(0.0).0);
+        assert!(playlist.at(0.0).1 < 1e-9);
+        assert_eq!((Shape::Heart, 1.0), playlist.at(5.0));
+        assert_eq!(Shape::BUILT_IN[1], playlist.at(15.0).0);
+        assert_eq!((Shape::Heart, 1.0), playlist.at(35.0));
+        assert!(playlist.at(9.99).1 < 0.1);
+    }
+}
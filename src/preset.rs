@@ -0,0 +1,61 @@
+//! Ready-made scenes that need more than a couple of flags to set up.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::config::Config;
+use crate::math::consts::PI;
+use crate::math::{Quat, Real, Vec3};
+use crate::scene::{Object, Transform};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Preset {
+    /// Two hearts orbiting each other, each spinning on its own.
+    Duet,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 1] = [Preset::Duet];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Preset::Duet => "duet",
+        }
+    }
+
+    /// Changes the settings the preset is made of, leaving the rest as they were.
+    pub fn apply(self, config: &mut Config) {
+        match self {
+            Preset::Duet => {
+                let heart = |x: Real, turned: Real| Object {
+                    transform: Transform {
+                        position: Vec3::new(x, 0.0, 0.0),
+                        // Face different ways, so the hearts don't spin in lockstep.
+                        orientation: Quat::from_axis_angle(Vec3::Y, turned),
+                        scale: 0.5,
+                    },
+                    ..Object::default()
+                };
+                config.objects = vec![heart(-14.0, 0.0), heart(14.0, PI)];
+                config.orbit = 0.03;
+            }
+        }
+    }
+}
+
+impl fmt::Display for Preset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Preset {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Preset, String> {
+        Preset::ALL
+            .into_iter()
+            .find(|preset| preset.name() == name)
+            .ok_or_else(|| format!("unknown preset {name:?}"))
+    }
+}
@@ -0,0 +1,36 @@
+//! Periodic pulses, for things that throb rather than move from one value to another.
+
+/// How strongly a heart is contracting at `phase` (in beats; only the fractional part matters),
+/// from 0 at rest to 1 at the peak of the first beat.
+///
+/// Each heartbeat is a strong "lub" followed closely by a softer "dub", then a rest until the
+/// next one. Both rise quickly and relax slowly.
+pub fn heartbeat(phase: f64) -> f64 {
+    let phase = phase.rem_euclid(1.0);
+    let beat = |start: f64, length: f64| {
+        let t = (phase - start) / length;
+        if (0.0..1.0).contains(&t) {
+            // Peaks at 1 when t = 1/3.
+            27.0 / 4.0 * t * (1.0 - t) * (1.0 - t)
+        } else {
+            0.0
+        }
+    };
+    beat(0.0, 0.25) + 0.6 * beat(0.25, 0.25)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lub_then_dub_then_rest() {
+        let lub = heartbeat(0.25 / 3.0);
+        let dub = heartbeat(0.25 + 0.25 / 3.0);
+        assert!((lub - 1.0).abs() < 1e-9);
+        assert!((dub - 0.6).abs() < 1e-9);
+        assert_eq!(0.0, heartbeat(0.0));
+        assert_eq!(0.0, heartbeat(0.75));
+        assert!((heartbeat(0.1) - heartbeat(3.1)).abs() < 1e-9);
+    }
+}
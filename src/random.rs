@@ -0,0 +1,100 @@
+//! Reproducible pseudo-random numbers, and random starting scenes built from them.
+
+use std::f64::consts::PI;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::Config;
+use crate::math::{Quat, Real, Vec3};
+use crate::scene::Shape;
+use crate::shading::Shading;
+
+/// A small, fast generator (SplitMix64). Not suitable for anything secret, but the same seed
+/// always gives the same numbers.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    /// A seed that differs from run to run.
+    pub fn entropy() -> u64 {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_nanos() as u64);
+        nanos ^ (u64::from(std::process::id()) << 32)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0.0..1.0`.
+    pub fn next_f64(&mut self) -> f64 {
+        // The top 53 bits fill a double's mantissa exactly.
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// A number in `low..high`.
+    pub fn range(&mut self, low: f64, high: f64) -> f64 {
+        low + (high - low) * self.next_f64()
+    }
+
+    /// An index in `0..len`.
+    pub fn below(&mut self, len: usize) -> usize {
+        (self.next_f64() * len as f64) as usize
+    }
+
+    /// A direction, equally likely to point anywhere.
+    pub fn unit_vector(&mut self) -> Vec3 {
+        let z = self.range(-1.0, 1.0);
+        let angle = self.range(0.0, 2.0 * PI);
+        let r = (1.0 - z * z).sqrt();
+        Vec3::new(
+            (r * angle.cos()) as Real,
+            (r * angle.sin()) as Real,
+            z as Real,
+        )
+    }
+}
+
+/// Picks a shape, look, rotation axis, and starting orientation, leaving everything else as it
+/// was.
+pub fn randomize(config: &mut Config, seed: u64) {
+    let mut rng = Rng::new(seed);
+    let shape = Shape::BUILT_IN[rng.below(Shape::BUILT_IN.len())].clone();
+    for object in &mut config.objects {
+        object.shape = shape.clone();
+    }
+    config.shading = Shading::ALL[rng.below(Shading::ALL.len())];
+    config.axis = Some(rng.unit_vector());
+    config.orientation = Quat::from_axis_angle(rng.unit_vector(), rng.range(0.0, 2.0 * PI) as Real);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::TOLERANCE;
+
+    #[test]
+    fn same_seed_same_scene() {
+        let (mut a, mut b) = (Config::default(), Config::default());
+        randomize(&mut a, 42);
+        randomize(&mut b, 42);
+        assert_eq!(a, b);
+
+        let mut rng = Rng::new(7);
+        for _ in 0..1000 {
+            let x = rng.next_f64();
+            assert!((0.0..1.0).contains(&x));
+            assert!((rng.unit_vector().length() - 1.0).abs() < TOLERANCE);
+        }
+    }
+}
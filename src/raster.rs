@@ -0,0 +1,930 @@
+//! Draws frames of characters as pixels, with a small bitmap font built in, for saving them as
+//! pictures.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::color::Colors;
+use crate::image::Image;
+use crate::width;
+
+/// How many font pixels wide and tall each cell is, around a 5×7 glyph: one pixel apart across,
+/// and twice as tall as it's wide, like a terminal's cells.
+pub const CELL_WIDTH: usize = 6;
+pub const CELL_HEIGHT: usize = 12;
+
+/// How far down its cell each glyph starts, in font pixels.
+const GLYPH_TOP: usize = 3;
+
+/// The colour characters are drawn in when they don't have one of their own, and the one behind
+/// them, as on a terminal with the usual light-on-dark colours.
+pub const FOREGROUND: [u8; 3] = [204, 204, 204];
+pub const BACKGROUND: [u8; 3] = [0, 0, 0];
+
+/// Each glyph is the line with its character, then 7 rows of 5 pixels, `#` for lit. Spaces are
+/// blank and `█` fills its whole cell, so neither needs one. Characters with no glyph are drawn
+/// as the box at the end.
+const FONT: &str = r##"!
+..#..
+..#..
+..#..
+..#..
+..#..
+.....
+..#..
+"
+.#.#.
+.#.#.
+.#.#.
+.....
+.....
+.....
+.....
+#
+.#.#.
+.#.#.
+#####
+.#.#.
+#####
+.#.#.
+.#.#.
+$
+..#..
+.####
+#.#..
+.###.
+..#.#
+####.
+..#..
+%
+##...
+##..#
+...#.
+..#..
+.#...
+#..##
+...##
+&
+.##..
+#..#.
+#.#..
+.#...
+#.#.#
+#..#.
+.##.#
+'
+..#..
+..#..
+.#...
+.....
+.....
+.....
+.....
+(
+...#.
+..#..
+.#...
+.#...
+.#...
+..#..
+...#.
+)
+.#...
+..#..
+...#.
+...#.
+...#.
+..#..
+.#...
+*
+.....
+..#..
+#.#.#
+.###.
+#.#.#
+..#..
+.....
++
+.....
+..#..
+..#..
+#####
+..#..
+..#..
+.....
+,
+.....
+.....
+.....
+.....
+.##..
+..#..
+.#...
+-
+.....
+.....
+.....
+#####
+.....
+.....
+.....
+.
+.....
+.....
+.....
+.....
+.....
+.##..
+.##..
+/
+.....
+....#
+...#.
+..#..
+.#...
+#....
+.....
+0
+.###.
+#...#
+#..##
+#.#.#
+##..#
+#...#
+.###.
+1
+..#..
+.##..
+..#..
+..#..
+..#..
+..#..
+.###.
+2
+.###.
+#...#
+....#
+...#.
+..#..
+.#...
+#####
+3
+#####
+...#.
+..#..
+...#.
+....#
+#...#
+.###.
+4
+...#.
+..##.
+.#.#.
+#..#.
+#####
+...#.
+...#.
+5
+#####
+#....
+####.
+....#
+....#
+#...#
+.###.
+6
+..##.
+.#...
+#....
+####.
+#...#
+#...#
+.###.
+7
+#####
+....#
+...#.
+..#..
+.#...
+.#...
+.#...
+8
+.###.
+#...#
+#...#
+.###.
+#...#
+#...#
+.###.
+9
+.###.
+#...#
+#...#
+.####
+....#
+...#.
+.##..
+:
+.....
+.##..
+.##..
+.....
+.##..
+.##..
+.....
+;
+.....
+.##..
+.##..
+.....
+.##..
+..#..
+.#...
+<
+...#.
+..#..
+.#...
+#....
+.#...
+..#..
+...#.
+=
+.....
+.....
+#####
+.....
+#####
+.....
+.....
+>
+.#...
+..#..
+...#.
+....#
+...#.
+..#..
+.#...
+?
+.###.
+#...#
+....#
+...#.
+..#..
+.....
+..#..
+@
+.###.
+#...#
+....#
+.##.#
+#.#.#
+#.#.#
+.###.
+A
+.###.
+#...#
+#...#
+#...#
+#####
+#...#
+#...#
+B
+####.
+#...#
+#...#
+####.
+#...#
+#...#
+####.
+C
+.###.
+#...#
+#....
+#....
+#....
+#...#
+.###.
+D
+###..
+#..#.
+#...#
+#...#
+#...#
+#..#.
+###..
+E
+#####
+#....
+#....
+####.
+#....
+#....
+#####
+F
+#####
+#....
+#....
+####.
+#....
+#....
+#....
+G
+.###.
+#...#
+#....
+#.###
+#...#
+#...#
+.####
+H
+#...#
+#...#
+#...#
+#####
+#...#
+#...#
+#...#
+I
+.###.
+..#..
+..#..
+..#..
+..#..
+..#..
+.###.
+J
+..###
+...#.
+...#.
+...#.
+...#.
+#..#.
+.##..
+K
+#...#
+#..#.
+#.#..
+##...
+#.#..
+#..#.
+#...#
+L
+#....
+#....
+#....
+#....
+#....
+#....
+#####
+M
+#...#
+##.##
+#.#.#
+#.#.#
+#...#
+#...#
+#...#
+N
+#...#
+#...#
+##..#
+#.#.#
+#..##
+#...#
+#...#
+O
+.###.
+#...#
+#...#
+#...#
+#...#
+#...#
+.###.
+P
+####.
+#...#
+#...#
+####.
+#....
+#....
+#....
+Q
+.###.
+#...#
+#...#
+#...#
+#.#.#
+#..#.
+.##.#
+R
+####.
+#...#
+#...#
+####.
+#.#..
+#..#.
+#...#
+S
+.####
+#....
+#....
+.###.
+....#
+....#
+####.
+T
+#####
+..#..
+..#..
+..#..
+..#..
+..#..
+..#..
+U
+#...#
+#...#
+#...#
+#...#
+#...#
+#...#
+.###.
+V
+#...#
+#...#
+#...#
+#...#
+#...#
+.#.#.
+..#..
+W
+#...#
+#...#
+#...#
+#.#.#
+#.#.#
+#.#.#
+.#.#.
+X
+#...#
+#...#
+.#.#.
+..#..
+.#.#.
+#...#
+#...#
+Y
+#...#
+#...#
+#...#
+.#.#.
+..#..
+..#..
+..#..
+Z
+#####
+....#
+...#.
+..#..
+.#...
+#....
+#####
+[
+.###.
+.#...
+.#...
+.#...
+.#...
+.#...
+.###.
+\
+.....
+#....
+.#...
+..#..
+...#.
+....#
+.....
+]
+.###.
+...#.
+...#.
+...#.
+...#.
+...#.
+.###.
+^
+..#..
+.#.#.
+#...#
+.....
+.....
+.....
+.....
+_
+.....
+.....
+.....
+.....
+.....
+.....
+#####
+`
+.#...
+..#..
+...#.
+.....
+.....
+.....
+.....
+a
+.....
+.....
+.###.
+....#
+.####
+#...#
+.####
+b
+#....
+#....
+#.##.
+##..#
+#...#
+#...#
+####.
+c
+.....
+.....
+.###.
+#....
+#....
+#...#
+.###.
+d
+....#
+....#
+.##.#
+#..##
+#...#
+#...#
+.####
+e
+.....
+.....
+.###.
+#...#
+#####
+#....
+.###.
+f
+..##.
+.#..#
+.#...
+###..
+.#...
+.#...
+.#...
+g
+.....
+.####
+#...#
+#...#
+.####
+....#
+.###.
+h
+#....
+#....
+#.##.
+##..#
+#...#
+#...#
+#...#
+i
+..#..
+.....
+.##..
+..#..
+..#..
+..#..
+.###.
+j
+...#.
+.....
+..##.
+...#.
+...#.
+#..#.
+.##..
+k
+#....
+#....
+#..#.
+#.#..
+##...
+#.#..
+#..#.
+l
+.##..
+..#..
+..#..
+..#..
+..#..
+..#..
+.###.
+m
+.....
+.....
+##.#.
+#.#.#
+#.#.#
+#...#
+#...#
+n
+.....
+.....
+#.##.
+##..#
+#...#
+#...#
+#...#
+o
+.....
+.....
+.###.
+#...#
+#...#
+#...#
+.###.
+p
+.....
+.....
+####.
+#...#
+####.
+#....
+#....
+q
+.....
+.....
+.##.#
+#..##
+.####
+....#
+....#
+r
+.....
+.....
+#.##.
+##..#
+#....
+#....
+#....
+s
+.....
+.....
+.###.
+#....
+.###.
+....#
+####.
+t
+.#...
+.#...
+###..
+.#...
+.#...
+.#..#
+..##.
+u
+.....
+.....
+#...#
+#...#
+#...#
+#..##
+.##.#
+v
+.....
+.....
+#...#
+#...#
+#...#
+.#.#.
+..#..
+w
+.....
+.....
+#...#
+#...#
+#.#.#
+#.#.#
+.#.#.
+x
+.....
+.....
+#...#
+.#.#.
+..#..
+.#.#.
+#...#
+y
+.....
+.....
+#...#
+#...#
+.####
+....#
+.###.
+z
+.....
+.....
+#####
+...#.
+..#..
+.#...
+#####
+{
+...#.
+..#..
+..#..
+.#...
+..#..
+..#..
+...#.
+|
+..#..
+..#..
+..#..
+..#..
+..#..
+..#..
+..#..
+}
+.#...
+..#..
+..#..
+...#.
+..#..
+..#..
+.#...
+~
+.....
+.....
+.#...
+#.#.#
+...#.
+.....
+.....
+·
+.....
+.....
+.....
+..#..
+.....
+.....
+.....
+•
+.....
+.....
+.###.
+.###.
+.###.
+.....
+.....
+♡
+.....
+.#.#.
+#.#.#
+#...#
+.#.#.
+..#..
+.....
+♥
+.....
+.#.#.
+#####
+#####
+.###.
+..#..
+.....
+💖
+.....
+.#.#.
+#####
+#####
+.###.
+..#..
+.....
+"##;
+
+/// Drawn for characters the font doesn't have.
+const MISSING: [u8; 7] = [
+    0b11111, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b11111,
+];
+
+/// The rows of `c`'s glyph, from the top, with the leftmost pixel in the highest of 5 bits.
+fn glyph(c: char) -> [u8; 7] {
+    static GLYPHS: OnceLock<HashMap<char, [u8; 7]>> = OnceLock::new();
+    let glyphs = GLYPHS.get_or_init(|| {
+        let lines: Vec<&str> = FONT.lines().collect();
+        (lines.chunks(8))
+            .filter_map(|glyph| {
+                let c = glyph[0].chars().next()?;
+                let mut rows = [0; 7];
+                for (row, line) in rows.iter_mut().zip(&glyph[1..]) {
+                    *row = line
+                        .chars()
+                        .fold(0, |bits, pixel| bits << 1 | u8::from(pixel == '#'));
+                }
+                Some((c, rows))
+            })
+            .collect()
+    });
+    glyphs.get(&c).copied().unwrap_or(MISSING)
+}
+
+/// Draws `frame` as a picture, each font pixel `scale` pixels square, in `colors` where it has
+/// them and [`FOREGROUND`] where it doesn't, on `background` or [`BACKGROUND`].
+pub fn rasterize(
+    frame: &[Vec<char>],
+    colors: Option<&Colors>,
+    background: Option<[u8; 3]>,
+    scale: usize,
+) -> Image {
+    let columns = frame.iter().map(Vec::len).max().unwrap_or(0);
+    let (cell_width, cell_height) = (CELL_WIDTH * scale, CELL_HEIGHT * scale);
+    let (width, height) = (columns * cell_width, frame.len() * cell_height);
+    let mut pixels = vec![background.unwrap_or(BACKGROUND); width * height];
+    for (y, row) in frame.iter().enumerate() {
+        for (x, &c) in row.iter().enumerate() {
+            if c == ' ' || c == width::CONTINUATION {
+                continue;
+            }
+            let color = colors.and_then(|colors| colors[y][x]).unwrap_or(FOREGROUND);
+            let (left, top) = (x * cell_width, y * cell_height);
+            let mut fill = |px: usize, py: usize| {
+                for row in pixels[(top + py) * width..].chunks_mut(width).take(scale) {
+                    row[left + px..left + px + scale].fill(color);
+                }
+            };
+            if c == '█' {
+                for py in (0..cell_height).step_by(scale) {
+                    for px in (0..cell_width).step_by(scale) {
+                        fill(px, py);
+                    }
+                }
+                continue;
+            }
+            for (dy, bits) in glyph(c).into_iter().enumerate() {
+                for dx in (0..5).filter(|dx| bits & (0b10000 >> dx) != 0) {
+                    fill(dx * scale, (GLYPH_TOP + dy) * scale);
+                }
+            }
+        }
+    }
+    Image {
+        width,
+        height,
+        pixels,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_glyph_is_five_by_seven() {
+        let lines: Vec<&str> = FONT.lines().collect();
+        assert_eq!(0, lines.len() % 8);
+        for glyph in lines.chunks(8) {
+            assert_eq!(1, glyph[0].chars().count(), "{glyph:?}");
+            for row in &glyph[1..] {
+                assert!(row.len() == 5 && row.chars().all(|c| c == '#' || c == '.'));
+            }
+        }
+        // All of printable ASCII but the space, which is blank.
+        for c in '!'..='~' {
+            assert_ne!(MISSING, glyph(c), "{c:?}");
+        }
+        assert_eq!([0b01110, 0b10001], glyph('O')[..2]);
+    }
+
+    #[test]
+    fn cells_are_drawn_in_their_colours() {
+        let frame = vec![vec!['█', ' ', '|']];
+        let colors = vec![vec![Some([255, 0, 0]), None, None]];
+        let image = rasterize(&frame, Some(&colors), None, 2);
+        assert_eq!(
+            (3 * CELL_WIDTH * 2, CELL_HEIGHT * 2),
+            (image.width, image.height)
+        );
+        assert_eq!([255, 0, 0], image.pixel(0, 0));
+        assert_eq!(BACKGROUND, image.pixel(CELL_WIDTH * 2, 0));
+        // The middle column of the bar, in the third cell.
+        let middle = 2 * CELL_WIDTH * 2 + 2 * 2;
+        assert_eq!(FOREGROUND, image.pixel(middle, GLYPH_TOP * 2));
+        assert_eq!(BACKGROUND, image.pixel(middle, 0));
+    }
+}
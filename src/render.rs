@@ -0,0 +1,994 @@
+//! How sampled surface points become cells on the screen.
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::color::Colors;
+use crate::dither::Dither;
+use crate::image::Image;
+use crate::math::{Real, Vec3};
+use crate::scene::{Material, Scene};
+use crate::sdf::{self, Sdf};
+use crate::shading::{self, Shading};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Style {
+    /// Samples the surface densely enough to fill it in, lit by the scene's lights.
+    #[default]
+    Surface,
+    /// Plots a sparse subsample of the surface as particles, brighter the closer they are. Much
+    /// cheaper to draw, especially on slow terminals.
+    Points,
+    /// Casts a ray through every cell into the shapes' signed distance functions, which can be
+    /// rounded off and blended together. Slower, and skips meshes and expression surfaces.
+    Raymarch,
+    /// Like [`Style::Raymarch`], with a floor under the objects and shadows cast onto it. Slower
+    /// still.
+    Raytrace,
+}
+
+impl Style {
+    pub const ALL: [Style; 4] = [
+        Style::Surface,
+        Style::Points,
+        Style::Raymarch,
+        Style::Raytrace,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Style::Surface => "surface",
+            Style::Points => "points",
+            Style::Raymarch => "raymarch",
+            Style::Raytrace => "raytrace",
+        }
+    }
+
+    /// Distance between neighbouring samples of the surface's parameters, in radians.
+    pub fn step(self) -> Real {
+        match self {
+            Style::Surface | Style::Raymarch | Style::Raytrace => 0.02,
+            Style::Points => 0.1,
+        }
+    }
+}
+
+impl fmt::Display for Style {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Style {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Style, String> {
+        Style::ALL
+            .into_iter()
+            .find(|style| style.name() == name)
+            .ok_or_else(|| format!("unknown render style {name:?}"))
+    }
+}
+
+/// Ways to see what the renderer is working with, instead of the finished picture.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DebugView {
+    /// The depth buffer, in shades of grey from the nearest surface to the furthest.
+    Depth,
+}
+
+impl DebugView {
+    pub const ALL: [DebugView; 1] = [DebugView::Depth];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DebugView::Depth => "depth",
+        }
+    }
+}
+
+impl fmt::Display for DebugView {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for DebugView {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<DebugView, String> {
+        DebugView::ALL
+            .into_iter()
+            .find(|view| view.name() == name)
+            .ok_or_else(|| format!("unknown debug view {name:?}"))
+    }
+}
+
+/// How points in front of the camera are flattened onto the screen, and the other way round.
+///
+/// Positions on the screen are measured from the middle, with +y up, in units where the edges
+/// of the screen are ±0.5 before zooming. Points are in the camera's space, always in front of
+/// it (+z), and `focus` is how far the camera is from what it's looking at.
+pub trait Projection: fmt::Debug + Send + Sync {
+    /// Where `point` lands on the screen, or `None` if it can't be seen at all.
+    fn project(&self, point: Vec3, focus: Real) -> Option<(Real, Real)>;
+
+    /// The ray that lands on `screen`, for raymarching: where it starts and which way it goes,
+    /// as a unit vector. `None` if nothing lands there.
+    fn ray(&self, screen: (Real, Real), focus: Real) -> Option<(Vec3, Vec3)>;
+}
+
+/// Further away is smaller, as the eye sees it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Perspective;
+
+impl Projection for Perspective {
+    fn project(&self, point: Vec3, _: Real) -> Option<(Real, Real)> {
+        let scale = 1.0 / point.z;
+        Some((point.x * scale, point.y * scale))
+    }
+
+    fn ray(&self, (x, y): (Real, Real), _: Real) -> Option<(Vec3, Vec3)> {
+        Some((Vec3::default(), Vec3::new(x, y, 1.0).normalized()))
+    }
+}
+
+/// Everything the same size however far away it is: the size it would be where the camera is
+/// looking. Good for seeing how a shape is really made.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Orthographic;
+
+impl Projection for Orthographic {
+    fn project(&self, point: Vec3, focus: Real) -> Option<(Real, Real)> {
+        Some((point.x / focus, point.y / focus))
+    }
+
+    fn ray(&self, (x, y): (Real, Real), focus: Real) -> Option<(Vec3, Vec3)> {
+        Some((Vec3::new(x, y, 0.0) * focus, Vec3::Z))
+    }
+}
+
+/// A fisheye lens, which sees everything in front of the camera: how far a point lands from
+/// the middle of the screen is how far it is from straight ahead, in radians. Straight lines
+/// bow outwards, more and more towards the edges.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Fisheye;
+
+impl Projection for Fisheye {
+    fn project(&self, point: Vec3, _: Real) -> Option<(Real, Real)> {
+        let off_axis = point.x.hypot(point.y);
+        if off_axis == 0.0 {
+            return Some((0.0, 0.0));
+        }
+        let angle = off_axis.atan2(point.z);
+        Some((point.x * angle / off_axis, point.y * angle / off_axis))
+    }
+
+    fn ray(&self, (x, y): (Real, Real), _: Real) -> Option<(Vec3, Vec3)> {
+        let angle = x.hypot(y);
+        if angle >= std::f64::consts::FRAC_PI_2 as Real {
+            return None;
+        }
+        let spread = if angle == 0.0 {
+            0.0
+        } else {
+            angle.sin() / angle
+        };
+        let direction = Vec3::new(x * spread, y * spread, angle.cos());
+        Some((Vec3::default(), direction))
+    }
+}
+
+/// Everything about drawing a frame that isn't part of the scene itself.
+#[derive(Clone, Debug)]
+pub struct Settings {
+    pub style: Style,
+    pub shading: Shading,
+    /// Characters from darkest to brightest, for smooth shading.
+    pub ramp: Vec<char>,
+    pub dither: Dither,
+    /// Skip surfaces facing away from the camera.
+    pub cull: bool,
+    pub projection: Arc<dyn Projection>,
+    /// Magnifies the projection; 2.0 draws everything twice as large.
+    pub zoom: Real,
+    /// When raymarching, how much to round off every shape, in world units.
+    pub bevel: Real,
+    /// When raymarching, how far apart objects start to melt into each other, in world units.
+    pub blend: Real,
+    /// What to draw instead of the shaded surfaces, if anything.
+    pub debug: Option<DebugView>,
+    /// How densely surfaces and points are sampled, as a fraction of what the style samples at:
+    /// 0.5 takes half as many samples along each of the surface's parameters.
+    pub density: Real,
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            style: Style::default(),
+            shading: Shading::default(),
+            ramp: shading::LUMINANCE.to_vec(),
+            dither: Dither::default(),
+            cull: false,
+            projection: Arc::new(Perspective),
+            zoom: 1.0,
+            bevel: 0.0,
+            blend: 0.0,
+            debug: None,
+            density: 1.0,
+        }
+    }
+}
+
+/// A finished picture: rows of characters, all the same width.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Frame {
+    pub rows: Vec<Vec<char>>,
+}
+
+impl Frame {
+    /// Draws the scene as `height` rows of `width` characters. Nothing but the arguments goes
+    /// into it, so the same scene always gives the same frame.
+    pub fn render(scene: &Scene, settings: &Settings, width: usize, height: usize) -> Frame {
+        Frame {
+            rows: render(scene, settings, width, height),
+        }
+    }
+}
+
+impl fmt::Display for Frame {
+    /// Each row on a line of its own.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in &self.rows {
+            writeln!(f, "{}", row.iter().collect::<String>())?;
+        }
+        Ok(())
+    }
+}
+
+/// Draws the scene as `height` rows of `width` characters.
+pub fn render(scene: &Scene, settings: &Settings, width: usize, height: usize) -> Vec<Vec<char>> {
+    render_with_depth(scene, settings, width, height).0
+}
+
+/// Like [`render`], but also returns the depth buffer: the reciprocal of each cell's distance
+/// from the camera, or −∞ where nothing was drawn.
+pub fn render_with_depth(
+    scene: &Scene,
+    settings: &Settings,
+    width: usize,
+    height: usize,
+) -> (Vec<Vec<char>>, Vec<Vec<Real>>) {
+    let (output, zbuffer, _) = draw(scene, settings, width, height);
+    (output, zbuffer)
+}
+
+/// Like [`render_with_depth`], but also returns each cell's colour: the colour of the material
+/// of the object drawn there, times the colours of the lights on it. `None` where neither has a
+/// colour.
+pub fn render_with_colors(
+    scene: &Scene,
+    settings: &Settings,
+    width: usize,
+    height: usize,
+) -> (Vec<Vec<char>>, Vec<Vec<Real>>, Colors) {
+    draw(scene, settings, width, height)
+}
+
+/// Everything a fragment function knows about the surface in a cell. See
+/// [`render_with_fragment`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShadeInput {
+    /// Which way the surface faces, in the camera's space: +Z points away from the camera.
+    pub normal: Vec3,
+    /// How far the surface is from the camera.
+    pub depth: Real,
+    /// Where the cell is on the screen, from (0, 0) at the top left to (1, 1) at the bottom
+    /// right.
+    pub uv: (Real, Real),
+    /// The time passed to [`render_with_fragment`], in seconds.
+    pub time: f64,
+    /// How brightly the lights shine on the surface, from −1 to 1: what the built-in shading
+    /// picks a character by.
+    pub luminance: Real,
+    /// The material's colour times the colours of the lights on it, if either has one.
+    pub color: Option<[u8; 3]>,
+    /// Which of the scene's objects it is, or `None` for the ground when ray tracing.
+    pub object: Option<usize>,
+}
+
+/// What a fragment function draws in a cell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cell {
+    pub glyph: char,
+    /// `None` for the terminal's own colour.
+    pub color: Option<[u8; 3]>,
+}
+
+impl From<char> for Cell {
+    fn from(glyph: char) -> Cell {
+        Cell { glyph, color: None }
+    }
+}
+
+/// Like [`render_with_colors`], but `fragment` decides what every cell with a surface in it
+/// looks like, instead of the ramp and shading in `settings`. `time` is passed along to it, for
+/// looks that change as the animation goes. Particles are drawn the same as ever.
+pub fn render_with_fragment(
+    scene: &Scene,
+    settings: &Settings,
+    width: usize,
+    height: usize,
+    time: f64,
+    fragment: impl Fn(ShadeInput) -> Cell,
+) -> (Vec<Vec<char>>, Vec<Vec<Real>>, Colors) {
+    let Raster {
+        luminance,
+        mut zbuffer,
+        owners,
+        mut colors,
+        normals,
+    } = rasterize(scene, settings, width, height);
+    let mut output = vec![vec![' '; width]; height];
+    for (y, row) in output.iter_mut().enumerate() {
+        for (x, c) in row.iter_mut().enumerate() {
+            if zbuffer[y][x] == Real::NEG_INFINITY {
+                continue;
+            }
+            let cell = fragment(ShadeInput {
+                normal: normals[y][x],
+                depth: 1.0 / zbuffer[y][x],
+                uv: (
+                    (x as Real + 0.5) / width as Real,
+                    (y as Real + 0.5) / height as Real,
+                ),
+                time,
+                luminance: luminance[y][x],
+                color: colors[y][x],
+                object: owners[y][x],
+            });
+            *c = cell.glyph;
+            colors[y][x] = cell.color;
+        }
+    }
+    draw_particles(scene, settings, &mut output, &mut zbuffer, &mut colors);
+    (output, zbuffer, colors)
+}
+
+/// Which of the scene's objects each cell shows, if any, the colour it shows it in, and which
+/// way the surface there faces.
+struct Cells<'a> {
+    owners: &'a mut [Vec<Option<usize>>],
+    colors: &'a mut Colors,
+    normals: &'a mut [Vec<Vec3>],
+}
+
+impl Cells<'_> {
+    fn set(
+        &mut self,
+        (x, y): (usize, usize),
+        owner: Option<usize>,
+        color: Option<[u8; 3]>,
+        normal: Vec3,
+    ) {
+        self.owners[y][x] = owner;
+        self.colors[y][x] = color;
+        self.normals[y][x] = normal;
+    }
+}
+
+/// Everything known about each cell once the scene's surfaces are drawn, before they're shaded.
+struct Raster {
+    luminance: Vec<Vec<Real>>,
+    zbuffer: Vec<Vec<Real>>,
+    owners: Vec<Vec<Option<usize>>>,
+    colors: Colors,
+    normals: Vec<Vec<Vec3>>,
+}
+
+fn rasterize(scene: &Scene, settings: &Settings, width: usize, height: usize) -> Raster {
+    let mut luminance = vec![vec![0.0; width]; height];
+    let mut zbuffer = vec![vec![-Real::INFINITY; width]; height];
+    let mut owners = vec![vec![None; width]; height];
+    let mut colors = vec![vec![None; width]; height];
+    let mut normals = vec![vec![Vec3::default(); width]; height];
+
+    let cells = Cells {
+        owners: &mut owners,
+        colors: &mut colors,
+        normals: &mut normals,
+    };
+    match settings.style {
+        Style::Surface | Style::Points => {
+            sample(scene, settings, &mut luminance, &mut zbuffer, cells)
+        }
+        Style::Raymarch | Style::Raytrace => {
+            raymarch(scene, settings, &mut luminance, &mut zbuffer, cells)
+        }
+    }
+
+    if settings.style == Style::Points {
+        depth_luminance(&mut luminance, &zbuffer);
+    }
+    Raster {
+        luminance,
+        zbuffer,
+        owners,
+        colors,
+        normals,
+    }
+}
+
+fn draw(
+    scene: &Scene,
+    settings: &Settings,
+    width: usize,
+    height: usize,
+) -> (Vec<Vec<char>>, Vec<Vec<Real>>, Colors) {
+    let Raster {
+        mut luminance,
+        mut zbuffer,
+        owners,
+        mut colors,
+        ..
+    } = rasterize(scene, settings, width, height);
+    if settings.debug == Some(DebugView::Depth) {
+        // Nearer is brighter, with every step of the ramp and nothing else in the way.
+        depth_luminance(&mut luminance, &zbuffer);
+        let mut output = shading::shade(
+            &luminance,
+            &zbuffer,
+            Shading::Smooth,
+            &settings.ramp,
+            Dither::None,
+        );
+        let mut colors = (luminance.iter().zip(&zbuffer))
+            .map(|(luma_row, depth_row)| {
+                (luma_row.iter().zip(depth_row))
+                    .map(|(&luma, &depth)| {
+                        (depth > Real::NEG_INFINITY)
+                            .then(|| [((luma + 1.0) / 2.0 * 255.0).round() as u8; 3])
+                    })
+                    .collect()
+            })
+            .collect();
+        draw_particles(scene, settings, &mut output, &mut zbuffer, &mut colors);
+        return (output, zbuffer, colors);
+    }
+    let mut output = shading::shade(
+        &luminance,
+        &zbuffer,
+        settings.shading,
+        &settings.ramp,
+        settings.dither,
+    );
+    // Objects with their own ramp are shaded again with it, as if nothing else were there.
+    if settings.shading == Shading::Smooth {
+        for (i, object) in scene.objects.iter().enumerate() {
+            let Some(ramp) = &object.material.ramp else {
+                continue;
+            };
+            let own = |y: usize, x: usize| owners[y][x] == Some(i);
+            let depth: Vec<Vec<Real>> = zbuffer
+                .iter()
+                .enumerate()
+                .map(|(y, row)| {
+                    row.iter()
+                        .enumerate()
+                        .map(|(x, &depth)| if own(y, x) { depth } else { -Real::INFINITY })
+                        .collect()
+                })
+                .collect();
+            let shaded =
+                shading::shade(&luminance, &depth, settings.shading, ramp, settings.dither);
+            for (y, row) in shaded.into_iter().enumerate() {
+                for (x, c) in row.into_iter().enumerate() {
+                    if own(y, x) {
+                        output[y][x] = c;
+                    }
+                }
+            }
+        }
+    }
+
+    draw_particles(scene, settings, &mut output, &mut zbuffer, &mut colors);
+    (output, zbuffer, colors)
+}
+
+/// Particles are too small to shade, but still hide behind surfaces.
+fn draw_particles(
+    scene: &Scene,
+    settings: &Settings,
+    output: &mut [Vec<char>],
+    zbuffer: &mut [Vec<Real>],
+    colors: &mut Colors,
+) {
+    let (width, height) = (zbuffer.first().map_or(0, Vec::len), zbuffer.len());
+    let camera = &scene.camera;
+    let focus = camera.focus();
+    for particle in &scene.particles {
+        let point = camera.to_view(particle.position);
+        let Some((xp, yp, ooz)) = project(point, width, height, settings, focus) else {
+            continue;
+        };
+        if ooz > zbuffer[yp][xp] {
+            zbuffer[yp][xp] = ooz;
+            colors[yp][xp] = None;
+            output[yp][xp] = particle.glyph;
+        }
+    }
+}
+
+/// Plots points sampled from each object's surface.
+fn sample(
+    scene: &Scene,
+    settings: &Settings,
+    luminance: &mut [Vec<Real>],
+    zbuffer: &mut [Vec<Real>],
+    mut cells: Cells,
+) {
+    let (width, height) = (zbuffer.first().map_or(0, Vec::len), zbuffer.len());
+    let camera = &scene.camera;
+    let focus = camera.focus();
+
+    for (i, object) in scene.objects.iter().enumerate() {
+        let lighting = object.material.lighting(&scene.lighting);
+        object
+            .shape
+            .sample(settings.style.step() / settings.density, |point, normal| {
+                // Move from the object's space, to the world, to the camera
+                let point = camera.to_view(object.transform.apply(point));
+
+                // Rotate and normalize normal vector
+                let normal = camera
+                    .to_view_direction(object.transform.apply_normal(normal))
+                    .normalized();
+
+                // Back-face culling: skip surfaces facing away from the camera, which is at the origin
+                if settings.cull && normal.dot(point) > 0.0 {
+                    return;
+                }
+
+                let Some((xp, yp, ooz)) = project(point, width, height, settings, focus) else {
+                    return;
+                };
+                let visible = ooz > zbuffer[yp][xp];
+                if visible {
+                    zbuffer[yp][xp] = ooz;
+                    let material = &object.material;
+                    let color = if material.emissive {
+                        material.color
+                    } else {
+                        lighting.color(material.color, point, normal)
+                    };
+                    cells.set((xp, yp), Some(i), color, normal);
+                    // Sum of dot products with each light for luminance. Points are shaded by depth
+                    // instead, once every point has been plotted.
+                    luminance[yp][xp] = match settings.style {
+                        _ if object.material.emissive => 1.0,
+                        Style::Surface | Style::Raymarch | Style::Raytrace => {
+                            lighting.luminance(point, normal)
+                        }
+                        Style::Points => 0.0,
+                    };
+                }
+            });
+    }
+}
+
+/// Steps along a ray through the middle of each cell, each time by the distance to the nearest
+/// surface, which can't overshoot, until it's close enough to count as a hit. When ray tracing,
+/// there's a ground plane too, and rays from each hit towards each light check for shadows.
+fn raymarch(
+    scene: &Scene,
+    settings: &Settings,
+    luminance: &mut [Vec<Real>],
+    zbuffer: &mut [Vec<Real>],
+    mut cells: Cells,
+) {
+    const MAX_STEPS: usize = 128;
+    const MAX_DISTANCE: Real = 400.0;
+    const HIT: Real = 0.01;
+    /// Far enough below the origin to clear the heart however it's turned.
+    const GROUND: Real = -24.0;
+
+    let camera = &scene.camera;
+    let traced = settings.style == Style::Raytrace;
+    let objects: Vec<_> = scene
+        .objects
+        .iter()
+        .enumerate()
+        .filter_map(|(i, object)| {
+            let mut sdf = object.shape.sdf()?;
+            if settings.bevel > 0.0 {
+                sdf = Sdf::Round(Box::new(sdf), settings.bevel / object.transform.scale);
+            }
+            Some((i, object, sdf, object.material.lighting(&scene.lighting)))
+        })
+        .collect();
+    if objects.is_empty() && !traced {
+        return;
+    }
+
+    // The distance from a point in the world to the nearest surface, and which object's it is.
+    // The ground is the object after the last one.
+    let nearest = |point: Vec3| {
+        let mut distance = Real::INFINITY;
+        let mut closest = 0;
+        let mut closest_distance = Real::INFINITY;
+        for (i, (_, object, sdf, _)) in objects.iter().enumerate() {
+            let local = object.transform.unapply(point);
+            let d = sdf.distance(local) * object.transform.scale;
+            if d < closest_distance {
+                closest = i;
+                closest_distance = d;
+            }
+            distance = sdf::smooth_min(distance, d, settings.blend);
+        }
+        if traced && point.y - GROUND < distance {
+            return (point.y - GROUND, objects.len());
+        }
+        (distance, closest)
+    };
+
+    // How far along the ray from `origin` the first surface is, if there's one within `limit`.
+    let march = |origin: Vec3, direction: Vec3, limit: Real| {
+        let mut travelled = 0.0;
+        for _ in 0..MAX_STEPS {
+            let (distance, closest) = nearest(origin + direction * travelled);
+            if distance < HIT {
+                return Some((travelled, closest));
+            }
+            travelled += distance;
+            if travelled > limit {
+                break;
+            }
+        }
+        None
+    };
+
+    let (width, height) = (zbuffer.first().map_or(0, Vec::len), zbuffer.len());
+    for (yp, (luma_row, depth_row)) in luminance.iter_mut().zip(zbuffer.iter_mut()).enumerate() {
+        for (xp, (luma, depth)) in luma_row.iter_mut().zip(depth_row.iter_mut()).enumerate() {
+            // The inverse of `project`, for the middle of the cell.
+            let scale = settings.zoom;
+            let screen = (
+                (xp as Real + 0.5 - width as Real / 2.0) / (scale * width as Real),
+                -(yp as Real + 0.5 - height as Real / 2.0) / (scale * height as Real),
+            );
+            let Some((origin, direction)) = settings.projection.ray(screen, camera.focus()) else {
+                continue;
+            };
+
+            let ray = camera.to_world_direction(direction);
+            let start = camera.to_world(origin);
+            let Some((travelled, closest)) = march(start, ray, MAX_DISTANCE) else {
+                continue;
+            };
+            let point = origin + direction * travelled;
+            let world = start + ray * travelled;
+            let world_normal = sdf::normal(|point| nearest(point).0, world);
+            let normal = camera.to_view_direction(world_normal);
+            let object = objects.get(closest);
+            let owner = object.map(|object| object.0);
+            let lighting = object.map_or(&scene.lighting, |object| &object.3);
+
+            // The ground is plain, like an object made of the default material.
+            let plain = Material::default();
+            let material = object.map_or(&plain, |object| &object.1.material);
+
+            *depth = 1.0 / point.z;
+            if material.emissive {
+                cells.set((xp, yp), owner, material.color, normal);
+                *luma = 1.0;
+                continue;
+            }
+            let color = lighting.color(material.color, point, normal);
+            cells.set((xp, yp), owner, color, normal);
+            *luma = if traced {
+                // Start a little way off the surface, so the ray doesn't hit it straight away.
+                let start = world + world_normal * (HIT * 4.0);
+                lighting.shadowed_luminance(point, normal, |light| {
+                    let (towards, _) = light.incident(point);
+                    let limit = light.distance(point).min(MAX_DISTANCE);
+                    march(start, camera.to_world_direction(towards), limit).is_some()
+                })
+            } else {
+                lighting.luminance(point, normal)
+            };
+        }
+    }
+}
+
+/// The cell a point in camera space lands on, and the reciprocal of its depth, or `None` if it's
+/// off the screen or behind the camera. `focus` is how far the camera is from what it's looking
+/// at.
+fn project(
+    point: Vec3,
+    width: usize,
+    height: usize,
+    settings: &Settings,
+    focus: Real,
+) -> Option<(usize, usize, Real)> {
+    if point.z <= 0.0 {
+        // Behind the camera
+        return None;
+    }
+    let ooz = 1.0 / point.z;
+    let width = width as Real;
+    let height = height as Real;
+    let (x, y) = settings.projection.project(point, focus)?;
+    let xp = width / 2.0 + x * settings.zoom * width;
+    let yp = height / 2.0 - y * settings.zoom * height;
+
+    let within_screen = xp >= 0.0 && xp < width && yp >= 0.0 && yp < height;
+    within_screen.then_some((xp as usize, yp as usize, ooz))
+}
+
+/// Draws a picture one character per pixel, with the same characters a lit surface would get,
+/// so black pixels look like surfaces facing away from the light and white ones like those
+/// facing it head-on.
+pub fn image(image: &Image, shading: Shading, dither: Dither) -> Vec<Vec<char>> {
+    let luma: Vec<Vec<Real>> = (0..image.height)
+        .map(|y| {
+            (0..image.width)
+                .map(|x| 2.0 * image.luminance(x, y) - 1.0)
+                .collect()
+        })
+        .collect();
+    // Every cell is covered, all at the same depth.
+    let depth = vec![vec![1.0; image.width]; image.height];
+    shading::shade(&luma, &depth, shading, &shading::LUMINANCE, dither)
+}
+
+/// Replaces the luminance of every covered cell with its depth, scaled so the nearest cell is 1
+/// and the furthest is −1.
+///
+/// `depth` holds the reciprocal of each cell's distance from the camera, or −∞ where nothing was
+/// drawn, as passed to [`crate::shading::shade`].
+pub fn depth_luminance(luma: &mut [Vec<Real>], depth: &[Vec<Real>]) {
+    let distances = || {
+        depth
+            .iter()
+            .flatten()
+            .filter(|&&depth| depth > Real::NEG_INFINITY)
+            .map(|depth| 1.0 / depth)
+    };
+    let near = distances().fold(Real::INFINITY, Real::min);
+    let far = distances().fold(0.0, Real::max);
+    let range = far - near;
+
+    for (luma_row, depth_row) in luma.iter_mut().zip(depth) {
+        for (luma, &depth) in luma_row.iter_mut().zip(depth_row) {
+            if depth == Real::NEG_INFINITY {
+                continue;
+            }
+            *luma = if range > 0.0 {
+                1.0 - 2.0 * (1.0 / depth - near) / range
+            } else {
+                1.0
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::TOLERANCE;
+
+    #[test]
+    fn nearest_points_are_brightest() {
+        let empty = Real::NEG_INFINITY;
+        let depth = vec![vec![1.0 / 50.0, empty, 1.0 / 60.0, 1.0 / 70.0]];
+        let mut luma = vec![vec![0.5; 4]];
+        depth_luminance(&mut luma, &depth);
+        for (expected, actual) in [1.0, 0.5, 0.0, -1.0].iter().zip(&luma[0]) {
+            assert!((expected - actual).abs() < TOLERANCE, "{luma:?}");
+        }
+    }
+
+    #[test]
+    fn objects_are_drawn_in_their_materials() {
+        use crate::scene::{Material, Object, Shape};
+
+        let pink = [255, 105, 180];
+        let scene = Scene {
+            objects: vec![Object {
+                shape: Shape::Sphere { radius: 10.0 },
+                material: Material {
+                    color: Some(pink),
+                    ramp: Some(vec!['o', 'O']),
+                    emissive: true,
+                    ..Material::default()
+                },
+                ..Object::default()
+            }],
+            ..Scene::default()
+        };
+        let (frame, depth, colors) = render_with_colors(&scene, &Settings::default(), 40, 20);
+        let mut covered = 0;
+        for y in 0..20 {
+            for x in 0..40 {
+                if depth[y][x] == Real::NEG_INFINITY {
+                    assert_eq!((' ', None), (frame[y][x], colors[y][x]));
+                } else {
+                    assert_eq!(('O', Some(pink)), (frame[y][x], colors[y][x]));
+                    covered += 1;
+                }
+            }
+        }
+        assert!(covered > 0);
+    }
+
+    #[test]
+    fn sparser_samples_cover_about_the_same_cells() {
+        use crate::scene::{Object, Shape};
+
+        let scene = Scene {
+            objects: vec![Object {
+                shape: Shape::Sphere { radius: 10.0 },
+                ..Object::default()
+            }],
+            ..Scene::default()
+        };
+        let covered = |density| {
+            let settings = Settings {
+                density,
+                ..Settings::default()
+            };
+            let (_, depth) = render_with_depth(&scene, &settings, 40, 20);
+            (depth.iter().flatten())
+                .filter(|&&depth| depth > Real::NEG_INFINITY)
+                .count()
+        };
+        let (full, sparse) = (covered(1.0), covered(0.5));
+        assert!(full > 0 && sparse <= full && sparse * 10 >= full * 9);
+    }
+
+    #[test]
+    fn fragments_replace_the_shading() {
+        use crate::scene::{Object, Shape};
+
+        let scene = Scene {
+            objects: vec![Object {
+                shape: Shape::Sphere { radius: 10.0 },
+                ..Object::default()
+            }],
+            ..Scene::default()
+        };
+        for style in [Style::Surface, Style::Raymarch] {
+            let settings = Settings {
+                style,
+                ..Settings::default()
+            };
+            let (shaded, _) = render_with_depth(&scene, &settings, 40, 20);
+            let (frame, _, colors) =
+                render_with_fragment(&scene, &settings, 40, 20, 1.5, |input| {
+                    assert_eq!((1.5, Some(0)), (input.time, input.object));
+                    assert!(input.depth > 0.0 && (input.normal.length() - 1.0).abs() < 1e-3);
+                    let (u, v) = input.uv;
+                    assert!((0.0..1.0).contains(&u) && (0.0..1.0).contains(&v));
+                    // Facing the camera, or facing away.
+                    if input.normal.z < 0.0 {
+                        Cell {
+                            glyph: 'o',
+                            color: Some([255, 0, 0]),
+                        }
+                    } else {
+                        Cell::from('.')
+                    }
+                });
+            assert!(frame.iter().flatten().any(|&c| c == 'o'));
+            for (y, row) in frame.iter().enumerate() {
+                for (x, &c) in row.iter().enumerate() {
+                    assert_eq!(shaded[y][x] == ' ', c == ' ');
+                    assert_eq!(c == 'o', colors[y][x] == Some([255, 0, 0]));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn depth_views_are_grey_from_near_to_far() {
+        use crate::scene::{Object, Shape};
+
+        let scene = Scene {
+            objects: vec![Object {
+                shape: Shape::Sphere { radius: 10.0 },
+                ..Object::default()
+            }],
+            ..Scene::default()
+        };
+        let settings = Settings {
+            debug: Some(DebugView::Depth),
+            ..Settings::default()
+        };
+        let (frame, depth, colors) = render_with_colors(&scene, &settings, 40, 20);
+        let (nearest, _) = (depth.iter().flatten().enumerate())
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .unwrap();
+        let (y, x) = (nearest / 40, nearest % 40);
+        assert_eq!(
+            (shading::LUMINANCE[11], Some([255; 3])),
+            (frame[y][x], colors[y][x])
+        );
+        for (y, row) in colors.iter().enumerate() {
+            for (x, color) in row.iter().enumerate() {
+                let covered = depth[y][x] > Real::NEG_INFINITY;
+                assert_eq!(covered, color.is_some_and(|[r, g, b]| r == g && g == b));
+            }
+        }
+        assert_eq!(Ok(DebugView::Depth), "depth".parse());
+    }
+
+    #[test]
+    fn orthographic_views_dont_shrink_into_the_distance() {
+        use crate::scene::{Object, Shape, Transform};
+
+        // How many cells a sphere covers, `z` further away than where the camera is looking.
+        let covered = |(style, projection): (Style, &Arc<dyn Projection>), z| {
+            let scene = Scene {
+                objects: vec![Object {
+                    shape: Shape::Sphere { radius: 10.0 },
+                    transform: Transform {
+                        position: Vec3::new(0.0, 0.0, z),
+                        ..Transform::default()
+                    },
+                    ..Object::default()
+                }],
+                ..Scene::default()
+            };
+            let settings = Settings {
+                style,
+                projection: projection.clone(),
+                ..Settings::default()
+            };
+            let (_, depth) = render_with_depth(&scene, &settings, 60, 30);
+            depth
+                .iter()
+                .flatten()
+                .filter(|&&d| d > Real::NEG_INFINITY)
+                .count()
+        };
+        let (perspective, orthographic): (Arc<dyn Projection>, Arc<dyn Projection>) =
+            (Arc::new(Perspective), Arc::new(Orthographic));
+        for style in [Style::Surface, Style::Raymarch] {
+            let perspective = (style, &perspective);
+            assert!(covered(perspective, 30.0) < covered(perspective, 0.0));
+            let orthographic = (style, &orthographic);
+            assert_eq!(covered(orthographic, 0.0), covered(orthographic, 30.0));
+        }
+    }
+
+    #[test]
+    fn rays_land_where_points_are_projected() {
+        let projections: [&dyn Projection; 3] = [&Perspective, &Orthographic, &Fisheye];
+        for projection in projections {
+            for screen in [(0.0, 0.0), (0.3, -0.2), (-0.45, 0.1)] {
+                let (origin, direction) = projection.ray(screen, 50.0).unwrap();
+                assert!((direction.length() - 1.0).abs() < 1e-4, "{projection:?}");
+                let (x, y) = projection.project(origin + direction * 20.0, 50.0).unwrap();
+                let off = (x - screen.0).hypot(y - screen.1);
+                assert!(off < 1e-4, "{projection:?} missed {screen:?} by {off}");
+            }
+        }
+
+        // Fisheyes see nearly all the way round to the side, where perspective can't.
+        let aside = Vec3::new(10.0, 0.0, 1.0);
+        assert!(Perspective.project(aside, 50.0).unwrap().0 > 5.0);
+        let (x, _) = Fisheye.project(aside, 50.0).unwrap();
+        assert!(x > 1.4 && x < 1.5, "{x}");
+        assert_eq!(None, Fisheye.ray((1.6, 0.0), 50.0));
+    }
+
+    #[test]
+    fn images_use_the_whole_ramp() {
+        let picture = Image::parse(b"P2 3 1 255 0 128 255").unwrap();
+        let text = image(&picture, Shading::Smooth, Dither::None);
+        let [darkest, .., brightest] = shading::LUMINANCE;
+        assert_eq!(vec![vec![darkest, ';', brightest]], text);
+    }
+}
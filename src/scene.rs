@@ -0,0 +1,300 @@
+//! Shapes placed in the world, and everything needed to draw them.
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::camera::Camera;
+use crate::heightmap::Heightmap;
+use crate::light::Lighting;
+use crate::math::{consts::PI, Quat, Real, Vec3};
+use crate::mesh::Mesh;
+use crate::sdf::Sdf;
+use crate::surface::Surface;
+use crate::ToFloatRangeIter;
+
+/// A parametric surface, a mesh, or a heightmap.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Shape {
+    /// About 30 units wide, centred on the origin.
+    #[default]
+    Heart,
+    Sphere {
+        radius: Real,
+    },
+    /// A ring lying flat, around the Y axis.
+    Torus {
+        /// From the centre of the ring to the middle of its tube.
+        major_radius: Real,
+        /// The radius of the tube.
+        minor_radius: Real,
+    },
+    /// A model loaded from a file.
+    Mesh(Arc<Mesh>),
+    /// A surface given by expressions.
+    Surface(Arc<Surface>),
+    /// Terrain raised from an image.
+    Heightmap(Arc<Heightmap>),
+}
+
+impl Shape {
+    /// Every kind of shape, at about the same size as the heart.
+    pub const BUILT_IN: [Shape; 3] = [
+        Shape::Heart,
+        Shape::Sphere { radius: 10.0 },
+        Shape::Torus {
+            major_radius: 11.0,
+            minor_radius: 4.0,
+        },
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Shape::Heart => "heart",
+            Shape::Sphere { .. } => "sphere",
+            Shape::Torus { .. } => "torus",
+            Shape::Mesh(_) => "model",
+            Shape::Surface(_) => "surface",
+            Shape::Heightmap(_) => "heightmap",
+        }
+    }
+
+    /// The shape as a signed distance function, for shapes that have one.
+    pub fn sdf(&self) -> Option<Sdf> {
+        match *self {
+            Shape::Heart => Some(Sdf::Heart),
+            Shape::Sphere { radius } => Some(Sdf::Sphere { radius }),
+            Shape::Torus {
+                major_radius,
+                minor_radius,
+            } => Some(Sdf::Torus {
+                major_radius,
+                minor_radius,
+            }),
+            Shape::Mesh(_) | Shape::Surface(_) | Shape::Heightmap(_) => None,
+        }
+    }
+
+    /// Calls `visit` with points on the surface and their normals (not necessarily of unit
+    /// length), at parameters `step` radians apart.
+    pub fn sample(&self, step: Real, mut visit: impl FnMut(Vec3, Vec3)) {
+        if let Shape::Mesh(mesh) = self {
+            return mesh.sample(step, visit);
+        }
+        for u in (0.0..2.0 * PI).by(step) {
+            for v in (0.0..PI).by(step) {
+                let (point, normal) = self.at(u, v);
+                visit(point, normal);
+            }
+        }
+    }
+
+    /// The point on the surface at parameters `u` (from 0 to 2π) and `v` (from 0 to π), and the
+    /// surface's normal there (not necessarily of unit length).
+    pub fn at(&self, u: Real, v: Real) -> (Vec3, Vec3) {
+        match *self {
+            Shape::Mesh(ref mesh) => mesh.at(u / (2.0 * PI), v / PI),
+            Shape::Surface(ref surface) => surface.at(u, v),
+            Shape::Heightmap(ref heightmap) => heightmap.at(u / (2.0 * PI), v / PI),
+            Shape::Heart => {
+                // Heart parametric equations
+                let x = sin(v) * (15.0 * sin(u) - 4.0 * sin(3.0 * u));
+                let y = 8.0 * cos(v);
+                let z = sin(v)
+                    * (15.0 * cos(u) - 5.0 * cos(2.0 * u) - 2.0 * cos(3.0 * u) - cos(4.0 * u));
+
+                // Calculate normals
+                let nx = sin(v) * (15.0 * cos(u) - 4.0 * cos(3.0 * u));
+                let ny = 8.0 * -sin(v) * sin(v);
+                let nz = cos(v)
+                    * (15.0 * sin(u) - 5.0 * sin(2.0 * u) - 2.0 * sin(3.0 * u) - sin(4.0 * u));
+
+                (Vec3::new(x, y, z), Vec3::new(nx, ny, nz))
+            }
+            Shape::Sphere { radius } => {
+                let normal = Vec3::new(sin(v) * cos(u), cos(v), sin(v) * sin(u));
+                (normal * radius, normal)
+            }
+            Shape::Torus {
+                major_radius,
+                minor_radius,
+            } => {
+                // v only covers half a turn, but the tube needs a whole one.
+                let around = 2.0 * v;
+                let normal = Vec3::new(cos(around) * cos(u), sin(around), cos(around) * sin(u));
+                let centre = Vec3::new(cos(u), 0.0, sin(u)) * major_radius;
+                (centre + normal * minor_radius, normal)
+            }
+        }
+    }
+}
+
+// I would use f64:: these, but I don't believer it's possible because they're primitives.
+#[inline(always)]
+fn sin(x: Real) -> Real {
+    x.sin()
+}
+
+#[inline(always)]
+fn cos(x: Real) -> Real {
+    x.cos()
+}
+
+impl fmt::Display for Shape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Shape {
+    type Err = String;
+
+    /// One of the [built-in](Shape::BUILT_IN) shapes.
+    fn from_str(name: &str) -> Result<Shape, String> {
+        Shape::BUILT_IN
+            .into_iter()
+            .find(|shape| shape.name() == name)
+            .ok_or_else(|| format!("unknown shape {name:?}"))
+    }
+}
+
+/// Places a shape in the world: scaled, then rotated, then moved.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+    pub position: Vec3,
+    pub orientation: Quat,
+    pub scale: Real,
+}
+
+impl Default for Transform {
+    fn default() -> Transform {
+        Transform {
+            position: Vec3::default(),
+            orientation: Quat::IDENTITY,
+            scale: 1.0,
+        }
+    }
+}
+
+impl Transform {
+    /// Transforms a point from the shape's own space into world space.
+    pub fn apply(&self, point: Vec3) -> Vec3 {
+        self.position + self.orientation.rotate(point * self.scale)
+    }
+
+    /// The inverse of [`Transform::apply`]: transforms a point from world space into the shape's
+    /// own space.
+    pub fn unapply(&self, point: Vec3) -> Vec3 {
+        self.orientation.conjugate().rotate(point - self.position) * (1.0 / self.scale)
+    }
+
+    /// Like [`Transform::apply`], but for normals, which only need rotating.
+    pub fn apply_normal(&self, normal: Vec3) -> Vec3 {
+        self.orientation.rotate(normal)
+    }
+
+    /// The same transform with the shape turned around its own centre.
+    pub fn spun(&self, rotation: Quat) -> Transform {
+        Transform {
+            orientation: rotation * self.orientation,
+            ..*self
+        }
+    }
+
+    /// The same transform followed by a rotation around the world's origin, so that objects
+    /// away from the origin orbit it.
+    pub fn rotated(&self, rotation: Quat) -> Transform {
+        Transform {
+            position: rotation.rotate(self.position),
+            orientation: rotation * self.orientation,
+            scale: self.scale,
+        }
+    }
+}
+
+/// How an object responds to light, and how it's drawn.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Material {
+    /// The object's colour, on terminals that can show it.
+    pub color: Option<[u8; 3]>,
+    /// Replaces the scene's shininess for this object.
+    pub shininess: Option<Real>,
+    /// Replaces the characters smooth shading draws this object with, from darkest to brightest.
+    pub ramp: Option<Vec<char>>,
+    /// Glows evenly at full brightness, whatever the lights are doing.
+    pub emissive: bool,
+}
+
+impl Material {
+    /// The scene's lighting, as it applies to objects made of this material.
+    pub fn lighting(&self, lighting: &Lighting) -> Lighting {
+        Lighting {
+            shininess: self.shininess.or(lighting.shininess),
+            ..lighting.clone()
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Object {
+    pub shape: Shape,
+    pub transform: Transform,
+    pub material: Material,
+}
+
+/// A single character floating in the world, like a sparkle.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Particle {
+    pub position: Vec3,
+    pub glyph: char,
+}
+
+/// Everything that appears in a frame. Objects and particles share one depth buffer, so they hide
+/// each other correctly wherever they overlap.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Scene {
+    pub objects: Vec<Object>,
+    pub particles: Vec<Particle>,
+    pub lighting: Lighting,
+    pub camera: Camera,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::TOLERANCE;
+
+    #[test]
+    fn transform_scales_rotates_then_moves() {
+        let transform = Transform {
+            position: Vec3::new(10.0, 0.0, 0.0),
+            orientation: Quat::from_axis_angle(Vec3::Z, PI / 2.0),
+            scale: 2.0,
+        };
+        let point = transform.apply(Vec3::X);
+        assert!((point - Vec3::new(10.0, 2.0, 0.0)).length() < TOLERANCE);
+        assert!((transform.unapply(point) - Vec3::X).length() < TOLERANCE);
+
+        // Orbiting a quarter turn around Y carries the object from +X to −Z.
+        let orbited = transform.rotated(Quat::from_axis_angle(Vec3::Y, PI / 2.0));
+        assert!((orbited.position - Vec3::new(0.0, 0.0, -10.0)).length() < TOLERANCE);
+    }
+
+    #[test]
+    fn sphere_normals_point_outwards() {
+        Shape::Sphere { radius: 3.0 }.sample(0.5, |point, normal| {
+            assert!((point.length() - 3.0).abs() < TOLERANCE);
+            assert!((point - normal * 3.0).length() < TOLERANCE);
+        });
+    }
+
+    #[test]
+    fn torus_stays_within_its_tube() {
+        let torus = "torus".parse::<Shape>().unwrap();
+        torus.sample(0.3, |point, normal| {
+            let ring = Vec3::new(point.x, 0.0, point.z).normalized() * 11.0;
+            assert!(((point - ring).length() - 4.0).abs() < TOLERANCE);
+            assert!((normal.length() - 1.0).abs() < TOLERANCE);
+        });
+    }
+}
@@ -0,0 +1,62 @@
+//! Saving frames as files when s is pressed, named after when they were taken. Screenshots taken
+//! in the same second get a number after the time, rather than writing over each other.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::clock;
+
+/// Saves a frame in `dir` as `text`, and as a `.ansi` file of `colored`, the same frame with its
+/// colours. Returns the path of the text file.
+pub fn save(dir: &Path, text: &str, colored: &str) -> io::Result<PathBuf> {
+    save_stamped(dir, &clock::timestamp(), text, colored)
+}
+
+/// Saves a frame like [`save`], as taken at `stamp`.
+fn save_stamped(dir: &Path, stamp: &str, text: &str, colored: &str) -> io::Result<PathBuf> {
+    for n in 0.. {
+        let name = match n {
+            0 => format!("ascii-love-{stamp}.txt"),
+            n => format!("ascii-love-{stamp}-{n}.txt"),
+        };
+        let path = dir.join(name);
+        // Creating the file, rather than checking for it first, so that two at once can't both
+        // take the same name.
+        let mut file = match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Err(error) if error.kind() == io::ErrorKind::AlreadyExists => continue,
+            file => file?,
+        };
+        file.write_all(text.as_bytes())?;
+        fs::write(path.with_extension("ansi"), colored)?;
+        return Ok(path);
+    }
+    unreachable!("ran out of numbers for screenshots")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    #[test]
+    fn screenshots_in_the_same_second_are_kept() {
+        let dir = env::temp_dir().join(format!("ascii-love-screenshots-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let stamp = "2026-02-14-120000";
+        let first = save_stamped(&dir, stamp, "first\n", "\x1b[31mfirst\n").unwrap();
+        let second = save_stamped(&dir, stamp, "second\n", "\x1b[31msecond\n").unwrap();
+        let read = |path: &Path| fs::read_to_string(path).unwrap();
+        let saved = [
+            read(&first),
+            read(&second),
+            read(&first.with_extension("ansi")),
+        ];
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(dir.join("ascii-love-2026-02-14-120000.txt"), first);
+        assert_eq!(dir.join("ascii-love-2026-02-14-120000-1.txt"), second);
+        assert_eq!(["first\n", "second\n", "\x1b[31mfirst\n"], saved);
+    }
+}
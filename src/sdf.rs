@@ -0,0 +1,149 @@
+//! Signed distance functions: shapes described by how far each point is from their surface,
+//! negative inside. Unlike parametric surfaces, they combine easily.
+
+use crate::math::{Real, Vec3};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Sdf {
+    Sphere {
+        radius: Real,
+    },
+    /// A ring lying flat, around the Y axis, like [`crate::scene::Shape::Torus`].
+    Torus {
+        major_radius: Real,
+        minor_radius: Real,
+    },
+    /// The classic heart surface, about the size of [`crate::scene::Shape::Heart`].
+    Heart,
+    /// The shape grown outwards by `radius`, rounding off its edges and points.
+    Round(Box<Sdf>, Real),
+    /// Everything in either shape.
+    Union(Box<Sdf>, Box<Sdf>),
+    /// Everything in both shapes.
+    Intersection(Box<Sdf>, Box<Sdf>),
+    /// Everything in the first shape but not the second.
+    Difference(Box<Sdf>, Box<Sdf>),
+    /// Like a union, but filleted where the shapes meet, over about `radius`.
+    SmoothUnion(Box<Sdf>, Box<Sdf>, Real),
+}
+
+impl Sdf {
+    /// Scales the heart surface's equation, which is about 2 units tall, up to the parametric
+    /// heart's size.
+    const HEART_SCALE: Real = 15.0;
+    /// Lines the heart's point up with the parametric heart's.
+    const HEART_OFFSET: Vec3 = Vec3::new(0.0, 0.0, -4.0);
+
+    /// The distance from `point` to the surface: positive outside the shape and negative inside.
+    ///
+    /// For the heart, this is only an estimate: near the surface it's between about a quarter
+    /// and one and a half times the real distance, and further away it's much too small. That's
+    /// fine for raymarching, but rounds the heart off unevenly.
+    pub fn distance(&self, point: Vec3) -> Real {
+        match self {
+            Sdf::Sphere { radius } => point.length() - radius,
+            Sdf::Torus {
+                major_radius,
+                minor_radius,
+            } => {
+                let ring = (point.x * point.x + point.z * point.z).sqrt() - major_radius;
+                (ring * ring + point.y * point.y).sqrt() - minor_radius
+            }
+            Sdf::Heart => {
+                let p = (point - Sdf::HEART_OFFSET) * (1.0 / Sdf::HEART_SCALE);
+                let (value, gradient) = heart(p);
+                // The value divided by how quickly it changes.
+                value / gradient.length().max(1e-9) * Sdf::HEART_SCALE
+            }
+            Sdf::Round(sdf, radius) => sdf.distance(point) - radius,
+            Sdf::Union(a, b) => a.distance(point).min(b.distance(point)),
+            Sdf::Intersection(a, b) => a.distance(point).max(b.distance(point)),
+            Sdf::Difference(a, b) => a.distance(point).max(-b.distance(point)),
+            Sdf::SmoothUnion(a, b, radius) => {
+                smooth_min(a.distance(point), b.distance(point), *radius)
+            }
+        }
+    }
+
+    /// The direction the surface faces at `point`.
+    pub fn normal(&self, point: Vec3) -> Vec3 {
+        normal(|point| self.distance(point), point)
+    }
+}
+
+/// The direction the surface of any distance function faces at `point`, from how the distance
+/// changes around it.
+pub fn normal(distance: impl Fn(Vec3) -> Real, point: Vec3) -> Vec3 {
+    let h = 1e-3;
+    // Four samples at the corners of a tetrahedron are enough for the gradient.
+    let corners = [
+        Vec3::new(1.0, -1.0, -1.0),
+        Vec3::new(-1.0, -1.0, 1.0),
+        Vec3::new(-1.0, 1.0, -1.0),
+        Vec3::new(1.0, 1.0, 1.0),
+    ];
+    corners
+        .into_iter()
+        .fold(Vec3::default(), |sum, corner| {
+            sum + corner * distance(point + corner * h)
+        })
+        .normalized()
+}
+
+/// Blends two distances like [`Sdf::SmoothUnion`] does.
+pub fn smooth_min(a: Real, b: Real, radius: Real) -> Real {
+    if radius <= 0.0 {
+        return a.min(b);
+    }
+    // The polynomial smooth minimum.
+    let h = (radius - (a - b).abs()).max(0.0) / radius;
+    a.min(b) - h * h * radius * 0.25
+}
+
+/// The heart surface `(x² + 9/4 y² + z² − 1)³ − x² z³ − 9/80 y² z³ = 0`, pointing down −Z and
+/// thinnest along Y, and its gradient.
+fn heart(p: Vec3) -> (Real, Vec3) {
+    let (x, y, z) = (p.x, p.y, p.z);
+    let a = x * x + 2.25 * y * y + z * z - 1.0;
+    let (z2, z3) = (z * z, z * z * z);
+    let value = a * a * a - x * x * z3 - 0.1125 * y * y * z3;
+    let gradient = Vec3::new(
+        6.0 * a * a * x - 2.0 * x * z3,
+        13.5 * a * a * y - 0.225 * y * z3,
+        6.0 * a * a * z - 3.0 * x * x * z2 - 0.3375 * y * y * z2,
+    );
+    (value, gradient)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combines_distances() {
+        let sphere = |radius| Box::new(Sdf::Sphere { radius });
+        let point = Vec3::new(0.0, 0.0, 3.0);
+        assert_eq!(1.0, sphere(2.0).distance(point));
+        assert_eq!(0.0, Sdf::Round(sphere(2.0), 1.0).distance(point));
+        assert_eq!(-2.0, Sdf::Union(sphere(1.0), sphere(5.0)).distance(point));
+        assert_eq!(
+            2.0,
+            Sdf::Intersection(sphere(1.0), sphere(5.0)).distance(point)
+        );
+        assert_eq!(
+            1.0,
+            Sdf::Difference(sphere(5.0), sphere(4.0)).distance(point)
+        );
+        assert!(Sdf::SmoothUnion(sphere(2.0), sphere(2.0), 1.0).distance(point) < 1.0);
+        assert!(sphere(2.0).normal(point).dot(Vec3::Z) > 0.999);
+    }
+
+    #[test]
+    fn heart_lines_up_with_the_parametric_heart() {
+        // The bottom point is on the surface, the middle is inside, and above the lobes is out.
+        let point = Vec3::new(0.0, 0.0, -19.0);
+        assert!(Sdf::Heart.distance(point).abs() < 0.5);
+        assert!(Sdf::Heart.distance(Vec3::default()) < 0.0);
+        assert!(Sdf::Heart.distance(Vec3::new(0.0, 0.0, 20.0)) > 0.0);
+    }
+}
@@ -0,0 +1,205 @@
+//! Turns lit surfaces into characters.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::dither::{self, Dither};
+use crate::math::Real;
+
+/// Characters from darkest to brightest.
+pub const LUMINANCE: [char; 12] = ['.', ',', '-', '~', ':', ';', '=', '!', '*', '#', '$', '@'];
+
+/// Characters for emoji mode, from darkest to brightest, to be spread over two columns each with
+/// [`crate::width::widen`]. Only the brightest is an emoji.
+pub const EMOJI: [char; 6] = ['.', '·', '•', '♡', '♥', '💖'];
+
+/// Characters for high contrast: nothing at all, or as much as a cell can hold. The full end is
+/// doubled so that every surface turned towards the light is drawn full, not just those facing it
+/// head-on.
+pub const HIGH_CONTRAST: [char; 3] = [' ', '█', '█'];
+pub const ASCII_HIGH_CONTRAST: [char; 3] = [' ', '@', '@'];
+
+/// Hard bands used by [`Shading::Toon`], from darkest to brightest.
+pub const TOON_BANDS: [char; 4] = ['.', '+', '*', '@'];
+
+/// Drawn around the outline of shapes by [`Shading::Toon`].
+pub const TOON_EDGE: char = '#';
+
+/// How far apart (in camera-space units) neighbouring samples must be for the nearer one to be
+/// drawn as an edge, like where the lobes of the heart overlap.
+const EDGE_DEPTH: Real = 6.0;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Shading {
+    /// Every luminance level gets its own character.
+    #[default]
+    Smooth,
+    /// A few hard bands with outlines, for a cartoon look.
+    Toon,
+}
+
+impl Shading {
+    pub const ALL: [Shading; 2] = [Shading::Smooth, Shading::Toon];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Shading::Smooth => "smooth",
+            Shading::Toon => "toon",
+        }
+    }
+}
+
+impl fmt::Display for Shading {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Shading {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Shading, String> {
+        Shading::ALL
+            .into_iter()
+            .find(|shading| shading.name() == name)
+            .ok_or_else(|| format!("unknown shading {name:?}"))
+    }
+}
+
+/// Picks the character for `luma`, a luminance between −1 and 1 (as computed by
+/// [`crate::light::Lighting::luminance`]). Values out of that range are clamped.
+///
+/// The brightest character is reserved for surfaces facing the light head-on.
+pub fn ramp(luma: Real, ramp: &[char]) -> char {
+    pick(ramp, ramp_level(luma, ramp.len()))
+}
+
+/// Like [`ramp`], but splits luminance into evenly sized bands, one per character.
+pub fn band(luma: Real, bands: &[char]) -> char {
+    pick(bands, band_level(luma, bands.len()))
+}
+
+fn ramp_level(luma: Real, len: usize) -> Real {
+    (luma + 1.0) * (len as Real - 1.0) / 2.0
+}
+
+fn band_level(luma: Real, len: usize) -> Real {
+    (luma + 1.0) * len as Real / 2.0
+}
+
+fn pick(chars: &[char], level: Real) -> char {
+    chars[(level as i64).clamp(0, chars.len() as i64 - 1) as usize]
+}
+
+/// Converts per-cell luminance into characters, from `ramp` for [`Shading::Smooth`].
+///
+/// `depth` holds the reciprocal of each cell's distance from the camera, or −∞ where nothing was
+/// drawn; those cells become spaces.
+pub fn shade(
+    luma: &[Vec<Real>],
+    depth: &[Vec<Real>],
+    shading: Shading,
+    ramp: &[char],
+    dither: Dither,
+) -> Vec<Vec<char>> {
+    let covered = |x: usize, y: usize| depth[y][x] > Real::NEG_INFINITY;
+    let chars: &[char] = match shading {
+        Shading::Smooth => ramp,
+        Shading::Toon => &TOON_BANDS,
+    };
+    let level = |luma| match shading {
+        Shading::Smooth => ramp_level(luma, chars.len()),
+        Shading::Toon => band_level(luma, chars.len()),
+    };
+
+    let levels: Vec<Vec<Option<Real>>> = luma
+        .iter()
+        .enumerate()
+        .map(|(y, row)| {
+            row.iter()
+                .enumerate()
+                .map(|(x, &luma)| covered(x, y).then(|| level(luma)))
+                .collect()
+        })
+        .collect();
+
+    let mut output: Vec<Vec<char>> = dither::quantize(&levels, chars.len() - 1, dither)
+        .into_iter()
+        .map(|row| {
+            row.into_iter()
+                .map(|level| level.map_or(' ', |level| chars[level]))
+                .collect()
+        })
+        .collect();
+
+    if shading == Shading::Toon {
+        for (y, row) in output.iter_mut().enumerate() {
+            for (x, cell) in row.iter_mut().enumerate() {
+                if covered(x, y) && is_edge(depth, x, y) {
+                    *cell = TOON_EDGE;
+                }
+            }
+        }
+    }
+
+    output
+}
+
+/// Whether a covered cell is on an outline: next to empty space, or in front of a surface much
+/// further away.
+fn is_edge(depth: &[Vec<Real>], x: usize, y: usize) -> bool {
+    let distance = 1.0 / depth[y][x];
+    let neighbours = [
+        (x.wrapping_sub(1), y),
+        (x + 1, y),
+        (x, y.wrapping_sub(1)),
+        (x, y + 1),
+    ];
+    neighbours.into_iter().any(|(nx, ny)| {
+        match depth.get(ny).and_then(|row| row.get(nx)) {
+            // The edge of the screen isn't an outline.
+            None => false,
+            Some(&Real::NEG_INFINITY) => true,
+            Some(&neighbour) => 1.0 / neighbour - distance > EDGE_DEPTH,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ramp_matches_classic_mapping() {
+        assert_eq!('.', ramp(-1.0, &LUMINANCE));
+        assert_eq!('=', ramp(0.1, &LUMINANCE));
+        assert_eq!('@', ramp(1.0, &LUMINANCE));
+        assert_eq!('@', ramp(3.0, &LUMINANCE));
+    }
+
+    #[test]
+    fn high_contrast_fills_everything_lit() {
+        assert_eq!(' ', ramp(-0.1, &HIGH_CONTRAST));
+        assert_eq!('█', ramp(0.1, &HIGH_CONTRAST));
+        assert_eq!('█', ramp(1.0, &HIGH_CONTRAST));
+    }
+
+    #[test]
+    fn toon_outlines_shapes() {
+        let empty = Real::NEG_INFINITY;
+        let near = 1.0 / 50.0;
+        let far = 1.0 / 70.0;
+        let depth = vec![
+            vec![empty, empty, empty, empty, empty],
+            vec![empty, near, near, near, far],
+            vec![empty, near, near, near, far],
+            vec![empty, near, near, near, far],
+        ];
+        let luma = vec![vec![1.0; 5]; 4];
+        let output: Vec<String> = shade(&luma, &depth, Shading::Toon, &LUMINANCE, Dither::None)
+            .iter()
+            .map(|row| row.iter().collect())
+            .collect();
+        assert_eq!(vec!["     ", " ####", " #@#@", " #@#@"], output);
+    }
+}
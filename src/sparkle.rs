@@ -0,0 +1,119 @@
+//! Twinkling sparkles that drift away from the surface of shapes and fade out.
+
+use std::f64::consts::PI;
+
+use crate::math::{Real, Vec3};
+use crate::random::Rng;
+use crate::scene::{Object, Particle};
+
+/// Glyphs for a sparkle as it fades, from brightest to dimmest.
+const FADE: [char; 4] = ['*', '+', ':', '.'];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Sparkle {
+    position: Vec3,
+    velocity: Vec3,
+    /// Seconds since it appeared.
+    age: f64,
+}
+
+/// Every sparkle currently alive, and where new ones come from.
+#[derive(Clone, Debug)]
+pub struct Sparkles {
+    sparkles: Vec<Sparkle>,
+    rng: Rng,
+    /// How many sparkles appear per second.
+    pub rate: f64,
+}
+
+impl Sparkles {
+    /// Seconds a sparkle lasts.
+    pub const LIFETIME: f64 = 1.5;
+
+    /// How fast sparkles drift away from the surface, in world units per second.
+    pub const SPEED: Real = 5.0;
+
+    pub fn new(rate: f64, seed: u64) -> Sparkles {
+        Sparkles {
+            sparkles: Vec::new(),
+            rng: Rng::new(seed),
+            rate,
+        }
+    }
+
+    /// Moves time on by `dt` seconds: sparkles drift and age, the oldest disappear, and new ones
+    /// appear on the surfaces of `objects`, which should already be posed for this frame.
+    pub fn update(&mut self, dt: f64, objects: &[Object]) {
+        for sparkle in &mut self.sparkles {
+            sparkle.position = sparkle.position + sparkle.velocity * dt as Real;
+            sparkle.age += dt;
+        }
+        self.sparkles
+            .retain(|sparkle| sparkle.age < Sparkles::LIFETIME);
+
+        if objects.is_empty() {
+            return;
+        }
+        // Round randomly, so low rates at high frame rates still spawn some.
+        let count = (self.rate * dt + self.rng.next_f64()) as usize;
+        for _ in 0..count {
+            let object = &objects[self.rng.below(objects.len())];
+            let (u, v) = (
+                self.rng.range(0.0, 2.0 * PI) as Real,
+                self.rng.range(0.0, PI) as Real,
+            );
+            let (point, normal) = object.shape.at(u, v);
+            let normal = object.transform.apply_normal(normal);
+            if normal.length() == 0.0 {
+                continue;
+            }
+            self.sparkles.push(Sparkle {
+                position: object.transform.apply(point),
+                velocity: normal.normalized() * Sparkles::SPEED,
+                age: 0.0,
+            });
+        }
+    }
+
+    /// The sparkles as they should be drawn this frame.
+    pub fn particles(&mut self) -> Vec<Particle> {
+        let rng = &mut self.rng;
+        self.sparkles
+            .iter()
+            .map(|sparkle| {
+                let faded = sparkle.age / Sparkles::LIFETIME;
+                let mut level = (faded * FADE.len() as f64) as usize;
+                // Twinkle by flickering a level dimmer now and then.
+                if rng.next_f64() < 0.3 {
+                    level += 1;
+                }
+                Particle {
+                    position: sparkle.position,
+                    glyph: FADE[level.min(FADE.len() - 1)],
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sparkles_drift_outward_and_expire() {
+        let sphere = Object {
+            shape: crate::scene::Shape::Sphere { radius: 10.0 },
+            ..Object::default()
+        };
+        let mut sparkles = Sparkles::new(100.0, 1);
+        sparkles.update(0.5, &[sphere]);
+        assert!(!sparkles.sparkles.is_empty());
+        sparkles.update(0.5, &[]);
+        for sparkle in &sparkles.sparkles {
+            assert!(sparkle.position.length() > 10.0);
+        }
+        sparkles.update(Sparkles::LIFETIME, &[]);
+        assert!(sparkles.particles().is_empty());
+    }
+}
@@ -0,0 +1,171 @@
+//! Drawing the scene once for each eye, so that it looks solid.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::camera::Camera;
+use crate::color::Colors;
+use crate::math::Real;
+use crate::random::Rng;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stereo {
+    /// Red for the left eye and cyan for the right, for red–cyan glasses.
+    Anaglyph,
+    /// A random-dot autostereogram, which shows the shape when you look through the screen.
+    MagicEye,
+}
+
+impl Stereo {
+    pub const ALL: [Stereo; 2] = [Stereo::Anaglyph, Stereo::MagicEye];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Stereo::Anaglyph => "anaglyph",
+            Stereo::MagicEye => "magic-eye",
+        }
+    }
+}
+
+impl fmt::Display for Stereo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Stereo {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Stereo, String> {
+        Stereo::ALL
+            .into_iter()
+            .find(|stereo| stereo.name() == name)
+            .ok_or_else(|| format!("unknown stereo mode {name:?}"))
+    }
+}
+
+/// How far apart the eyes are, in world units. Wider than real eyes, since there are so few
+/// columns to show the difference between the views in.
+const EYE_SEPARATION: Real = 6.0;
+
+const LEFT: [u8; 3] = [255, 0, 0];
+const RIGHT: [u8; 3] = [0, 255, 255];
+const BOTH: [u8; 3] = [255, 255, 255];
+
+/// The left and right eyes, either side of `camera` and looking at the same point, so that
+/// whatever is there appears at the depth of the screen.
+pub fn eyes(camera: &Camera) -> (Camera, Camera) {
+    (
+        camera.shifted(-EYE_SEPARATION / 2.0),
+        camera.shifted(EYE_SEPARATION / 2.0),
+    )
+}
+
+/// Overlays the views from each eye. Cells only the left eye sees are red, those only the right
+/// eye sees are cyan, and those both see are white, so that each lens lets through its own view.
+pub fn anaglyph(left: &[Vec<char>], right: &[Vec<char>]) -> (Vec<Vec<char>>, Colors) {
+    left.iter()
+        .zip(right)
+        .map(|(left, right)| {
+            left.iter()
+                .zip(right)
+                .map(|(&l, &r)| match (l != ' ', r != ' ') {
+                    (true, true) => (l, Some(BOTH)),
+                    (true, false) => (l, Some(LEFT)),
+                    (false, true) => (r, Some(RIGHT)),
+                    (false, false) => (' ', None),
+                })
+                .unzip()
+        })
+        .unzip()
+}
+
+/// How many columns apart the pattern of a magic-eye picture repeats, for the background.
+const PATTERN_WIDTH: usize = 20;
+/// How many columns closer together the pattern repeats for the nearest parts of the scene.
+/// Each column is a step in depth, so there are this many steps.
+const DEPTH_STEPS: usize = 8;
+/// Random dots are made of these.
+const DOTS: [char; 10] = ['#', '@', '%', '&', '*', '+', '=', '-', ':', '.'];
+
+/// Draws the depth buffer as an autostereogram for looking at wall-eyed, focusing behind the
+/// screen. Each row repeats a pattern of random dots, more closely where the scene is nearer.
+///
+/// The dots are the same from one frame to the next, so that only the shape appears to move.
+pub fn magic_eye(depth: &[Vec<Real>]) -> Vec<Vec<char>> {
+    let distances = || {
+        depth
+            .iter()
+            .flatten()
+            .filter(|&&depth| depth > Real::NEG_INFINITY)
+            .map(|depth| 1.0 / depth)
+    };
+    let near = distances().fold(Real::INFINITY, Real::min);
+    let far = distances().fold(0.0, Real::max);
+
+    // The nearest surfaces are the furthest steps forward, and even the furthest are a step in
+    // front of the background, so that shapes stand out from it.
+    let step = |depth: Real| -> usize {
+        if depth == Real::NEG_INFINITY {
+            return 0;
+        }
+        let nearness = if far > near {
+            (far - 1.0 / depth) / (far - near)
+        } else {
+            1.0
+        };
+        1 + (nearness * (DEPTH_STEPS - 1) as Real).round() as usize
+    };
+
+    let mut rng = Rng::new(0);
+    depth
+        .iter()
+        .map(|row| {
+            let mut line: Vec<char> = Vec::with_capacity(row.len());
+            for (x, &depth) in row.iter().enumerate() {
+                // Random dots until the pattern starts repeating, then copies from a column back.
+                let dot = DOTS[rng.below(DOTS.len())];
+                let separation = PATTERN_WIDTH - step(depth);
+                line.push(if x < separation {
+                    dot
+                } else {
+                    line[x - separation]
+                });
+            }
+            line
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_eye_gets_its_own_colour() {
+        let (frame, colors) = anaglyph(&[vec!['a', 'b', ' ', ' ']], &[vec![' ', 'c', 'd', ' ']]);
+        assert_eq!(vec![vec!['a', 'b', 'd', ' ']], frame);
+        assert_eq!(
+            vec![vec![Some(LEFT), Some(BOTH), Some(RIGHT), None]],
+            colors
+        );
+
+        let (left, right) = eyes(&Camera::default());
+        assert!(left.position.x < 0.0 && right.position.x > 0.0);
+        assert_eq!(left.target, right.target);
+    }
+
+    #[test]
+    fn nearer_surfaces_repeat_sooner() {
+        let empty = Real::NEG_INFINITY;
+        let mut depth = vec![vec![empty; 60]];
+        depth[0][40..50].fill(1.0 / 50.0);
+        let picture = magic_eye(&depth);
+        let row = &picture[0];
+        // The background repeats every pattern width, and the shape a full depth range sooner.
+        assert_eq!(row[5], row[5 + PATTERN_WIDTH]);
+        let separation = PATTERN_WIDTH - DEPTH_STEPS;
+        assert!((40..50).all(|x| row[x] == row[x - separation]));
+        assert_eq!(picture, magic_eye(&depth));
+    }
+}
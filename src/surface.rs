@@ -0,0 +1,70 @@
+//! Parametric surfaces written as expressions, for shapes that aren't built in.
+
+mod expr;
+
+pub use expr::Expr;
+
+use crate::math::{Real, Vec3};
+
+/// A surface given by an expression for each coordinate, in terms of `u` (from 0 to 2π) and `v`
+/// (from 0 to π), like the built-in shapes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Surface {
+    pub x: Expr,
+    pub y: Expr,
+    pub z: Expr,
+}
+
+impl Surface {
+    /// How far apart the points used to estimate normals are, in radians.
+    const DELTA: Real = if cfg!(feature = "f32") { 1e-3 } else { 1e-5 };
+
+    pub fn point(&self, u: Real, v: Real) -> Vec3 {
+        Vec3::new(self.x.eval(u, v), self.y.eval(u, v), self.z.eval(u, v))
+    }
+
+    /// The point at `(u, v)` and the normal there, worked out from how the point moves as `u`
+    /// and `v` change. The normal faces the same way as the built-in shapes' for surfaces that
+    /// go around the same way, such as a sphere written like [`crate::scene::Shape::Sphere`].
+    pub fn at(&self, u: Real, v: Real) -> (Vec3, Vec3) {
+        let point = self.point(u, v);
+        let mut normal = self.normal(u, v);
+        // At poles, where changing `u` doesn't move the point, look a little way off instead.
+        let mut offset = Surface::DELTA;
+        while normal.length() < 1e-12 && offset < 0.1 {
+            normal = self.normal(u, v + offset);
+            offset *= 10.0;
+        }
+        (point, normal)
+    }
+
+    fn normal(&self, u: Real, v: Real) -> Vec3 {
+        let h = Surface::DELTA;
+        let du = self.point(u + h, v) - self.point(u - h, v);
+        let dv = self.point(u, v + h) - self.point(u, v - h);
+        du.cross(dv)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::TOLERANCE;
+    use crate::scene::Shape;
+
+    #[test]
+    fn sphere_matches_the_built_in_one() {
+        let surface = Surface {
+            x: "10 * sin(v) * cos(u)".parse().unwrap(),
+            y: "10 * cos(v)".parse().unwrap(),
+            z: "10 * sin(v) * sin(u)".parse().unwrap(),
+        };
+        let sphere = Shape::Sphere { radius: 10.0 };
+        for (u, v) in [(0.0, 0.0), (1.0, 0.5), (4.0, 2.0), (6.0, 3.1)] {
+            let (point, normal) = surface.at(u, v);
+            let (expected_point, expected_normal) = sphere.at(u, v);
+            assert!((point - expected_point).length() < TOLERANCE);
+            assert!(normal.normalized().dot(expected_normal) > 0.999);
+        }
+    }
+}
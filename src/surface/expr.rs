@@ -0,0 +1,383 @@
+//! Arithmetic expressions of the surface parameters `u` and `v`, like `sin(v) * cos(u) * 10`.
+
+use std::str::FromStr;
+
+use crate::math::consts::{E, PI, TAU};
+use crate::math::Real;
+
+/// A parsed expression, ready to be evaluated.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    Number(Real),
+    U,
+    V,
+    Neg(Box<Expr>),
+    Binary(Op, Box<Expr>, Box<Expr>),
+    Call(Function, Vec<Expr>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Pow,
+}
+
+/// Functions that can be called by name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Function {
+    Sin,
+    Cos,
+    Tan,
+    Asin,
+    Acos,
+    Atan,
+    Sinh,
+    Cosh,
+    Tanh,
+    Sqrt,
+    Abs,
+    Exp,
+    Ln,
+    Floor,
+    Ceil,
+    Sign,
+    Atan2,
+    Pow,
+    Min,
+    Max,
+}
+
+impl Function {
+    const ALL: [(&'static str, Function); 20] = [
+        ("sin", Function::Sin),
+        ("cos", Function::Cos),
+        ("tan", Function::Tan),
+        ("asin", Function::Asin),
+        ("acos", Function::Acos),
+        ("atan", Function::Atan),
+        ("sinh", Function::Sinh),
+        ("cosh", Function::Cosh),
+        ("tanh", Function::Tanh),
+        ("sqrt", Function::Sqrt),
+        ("abs", Function::Abs),
+        ("exp", Function::Exp),
+        ("ln", Function::Ln),
+        ("floor", Function::Floor),
+        ("ceil", Function::Ceil),
+        ("sign", Function::Sign),
+        ("atan2", Function::Atan2),
+        ("pow", Function::Pow),
+        ("min", Function::Min),
+        ("max", Function::Max),
+    ];
+
+    fn arity(self) -> usize {
+        match self {
+            Function::Atan2 | Function::Pow | Function::Min | Function::Max => 2,
+            _ => 1,
+        }
+    }
+
+    fn call(self, args: &[Real]) -> Real {
+        let a = args[0];
+        match self {
+            Function::Sin => a.sin(),
+            Function::Cos => a.cos(),
+            Function::Tan => a.tan(),
+            Function::Asin => a.asin(),
+            Function::Acos => a.acos(),
+            Function::Atan => a.atan(),
+            Function::Sinh => a.sinh(),
+            Function::Cosh => a.cosh(),
+            Function::Tanh => a.tanh(),
+            Function::Sqrt => a.sqrt(),
+            Function::Abs => a.abs(),
+            Function::Exp => a.exp(),
+            Function::Ln => a.ln(),
+            Function::Floor => a.floor(),
+            Function::Ceil => a.ceil(),
+            Function::Sign => a.signum(),
+            Function::Atan2 => a.atan2(args[1]),
+            Function::Pow => a.powf(args[1]),
+            Function::Min => a.min(args[1]),
+            Function::Max => a.max(args[1]),
+        }
+    }
+}
+
+impl Expr {
+    pub fn eval(&self, u: Real, v: Real) -> Real {
+        match self {
+            Expr::Number(n) => *n,
+            Expr::U => u,
+            Expr::V => v,
+            Expr::Neg(a) => -a.eval(u, v),
+            Expr::Binary(op, a, b) => {
+                let (a, b) = (a.eval(u, v), b.eval(u, v));
+                match op {
+                    Op::Add => a + b,
+                    Op::Sub => a - b,
+                    Op::Mul => a * b,
+                    Op::Div => a / b,
+                    Op::Rem => a.rem_euclid(b),
+                    Op::Pow => a.powf(b),
+                }
+            }
+            Expr::Call(function, args) => {
+                let mut values = [0.0; 2];
+                for (value, arg) in values.iter_mut().zip(args) {
+                    *value = arg.eval(u, v);
+                }
+                function.call(&values)
+            }
+        }
+    }
+}
+
+impl FromStr for Expr {
+    type Err = String;
+
+    /// Parses the usual infix arithmetic: `+ - * / % ^`, parentheses, the variables `u` and `v`,
+    /// the constants `pi`, `tau`, and `e`, and calls to functions like `sin(u)` and `max(a, b)`.
+    fn from_str(text: &str) -> Result<Expr, String> {
+        let mut parser = Parser {
+            tokens: tokenize(text)?,
+            next: 0,
+        };
+        let expr = parser.sum()?;
+        match parser.peek() {
+            None => Ok(expr),
+            Some((column, token)) => Err(format!("unexpected {token} at column {column}")),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(Real),
+    Name(String),
+    Symbol(char),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Number(n) => write!(f, "{n}"),
+            Token::Name(name) => write!(f, "{name:?}"),
+            Token::Symbol(c) => write!(f, "'{c}'"),
+        }
+    }
+}
+
+/// Splits the text into tokens, each with the column it starts at, counting from 1.
+fn tokenize(text: &str) -> Result<Vec<(usize, Token)>, String> {
+    let mut tokens = Vec::new();
+    let chars: Vec<char> = text.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        let token = if c.is_ascii_digit() || c == '.' {
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            // An exponent, like the 3 in 1e3 or 1e-3.
+            if i < chars.len() && matches!(chars[i], 'e' | 'E') {
+                let sign = usize::from(matches!(chars.get(i + 1), Some('+' | '-')));
+                if chars.get(i + 1 + sign).is_some_and(char::is_ascii_digit) {
+                    i += 1 + sign;
+                    while i < chars.len() && chars[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+            }
+            let number: String = chars[start..i].iter().collect();
+            Token::Number(
+                number
+                    .parse()
+                    .map_err(|_| format!("bad number {number:?} at column {}", start + 1))?,
+            )
+        } else if c.is_alphabetic() || c == '_' {
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            Token::Name(chars[start..i].iter().collect())
+        } else if "+-*/%^(),".contains(c) {
+            i += 1;
+            Token::Symbol(c)
+        } else {
+            return Err(format!("unexpected {c:?} at column {}", start + 1));
+        };
+        tokens.push((start + 1, token));
+    }
+    Ok(tokens)
+}
+
+/// A recursive descent parser, with one method for each level of precedence.
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    next: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&(usize, Token)> {
+        self.tokens.get(self.next)
+    }
+
+    /// Moves past the next token if it's `symbol`.
+    fn eat(&mut self, symbol: char) -> bool {
+        let found = matches!(self.peek(), Some((_, Token::Symbol(c))) if *c == symbol);
+        self.next += usize::from(found);
+        found
+    }
+
+    fn expect(&mut self, symbol: char) -> Result<(), String> {
+        if self.eat(symbol) {
+            return Ok(());
+        }
+        match self.peek() {
+            Some((column, token)) => Err(format!(
+                "expected '{symbol}' at column {column}, got {token}"
+            )),
+            None => Err(format!("expected '{symbol}' at the end")),
+        }
+    }
+
+    /// Terms added or subtracted.
+    fn sum(&mut self) -> Result<Expr, String> {
+        let mut expr = self.product()?;
+        loop {
+            let op = if self.eat('+') {
+                Op::Add
+            } else if self.eat('-') {
+                Op::Sub
+            } else {
+                return Ok(expr);
+            };
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.product()?));
+        }
+    }
+
+    /// Factors multiplied, divided, or taken the remainder of.
+    fn product(&mut self) -> Result<Expr, String> {
+        let mut expr = self.unary()?;
+        loop {
+            let op = if self.eat('*') {
+                Op::Mul
+            } else if self.eat('/') {
+                Op::Div
+            } else if self.eat('%') {
+                Op::Rem
+            } else {
+                return Ok(expr);
+            };
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.unary()?));
+        }
+    }
+
+    /// Negation binds less tightly than powers, so `-2^2` is −4.
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat('-') {
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        if self.eat('+') {
+            return self.unary();
+        }
+        self.power()
+    }
+
+    /// Powers group to the right, so `2^3^2` is 2⁹.
+    fn power(&mut self) -> Result<Expr, String> {
+        let base = self.atom()?;
+        if self.eat('^') {
+            return Ok(Expr::Binary(
+                Op::Pow,
+                Box::new(base),
+                Box::new(self.unary()?),
+            ));
+        }
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<Expr, String> {
+        let Some((column, token)) = self.peek().cloned() else {
+            return Err("expected a number, variable, or '(' at the end".to_owned());
+        };
+        self.next += 1;
+        match token {
+            Token::Number(n) => Ok(Expr::Number(n)),
+            Token::Symbol('(') => {
+                let expr = self.sum()?;
+                self.expect(')')?;
+                Ok(expr)
+            }
+            Token::Name(name) => match name.as_str() {
+                "u" => Ok(Expr::U),
+                "v" => Ok(Expr::V),
+                "pi" => Ok(Expr::Number(PI)),
+                "tau" => Ok(Expr::Number(TAU)),
+                "e" => Ok(Expr::Number(E)),
+                _ => {
+                    let Some(&(_, function)) = Function::ALL.iter().find(|(n, _)| *n == name)
+                    else {
+                        return Err(format!("unknown name {name:?} at column {column}"));
+                    };
+                    self.expect('(')?;
+                    let mut args = vec![self.sum()?];
+                    while self.eat(',') {
+                        args.push(self.sum()?);
+                    }
+                    self.expect(')')?;
+                    if args.len() != function.arity() {
+                        return Err(format!(
+                            "{name} takes {} argument(s), not {}",
+                            function.arity(),
+                            args.len()
+                        ));
+                    }
+                    Ok(Expr::Call(function, args))
+                }
+            },
+            token => Err(format!(
+                "expected a number, variable, or '(' at column {column}, got {token}"
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(text: &str, u: Real, v: Real) -> Real {
+        text.parse::<Expr>().unwrap().eval(u, v)
+    }
+
+    #[test]
+    fn follows_precedence() {
+        assert_eq!(7.0, eval("1 + 2 * 3", 0.0, 0.0));
+        assert_eq!(-4.0, eval("-2^2", 0.0, 0.0));
+        assert_eq!(512.0, eval("2^3^2", 0.0, 0.0));
+        assert_eq!(0.5, eval("(u - v) / 4", 5.0, 3.0));
+        assert_eq!(3.0, eval("max(u, 3) % 4", 1.0, 0.0));
+        assert!((eval("sin(v)*cos(u)*10", 0.0, PI / 2.0) - 10.0).abs() < 1e-12);
+        assert_eq!(1500.0, eval("1.5e3", 0.0, 0.0));
+    }
+
+    #[test]
+    fn reports_where_it_went_wrong() {
+        let error = |text: &str| text.parse::<Expr>().unwrap_err();
+        assert_eq!("unknown name \"w\" at column 5", error("u + w"));
+        assert_eq!("expected ')' at the end", error("(u + v"));
+        assert_eq!("unexpected \"u\" at column 3", error("v u"));
+        assert_eq!("pow takes 2 argument(s), not 1", error("pow(u)"));
+    }
+}
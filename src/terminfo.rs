@@ -0,0 +1,540 @@
+//! What the terminal can do, from its terminfo entry, so that the animation only sends escape
+//! codes the terminal understands, and from the locale, so that it only draws characters the
+//! terminal can show.
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::color::ColorMode;
+
+/// The escape codes for the features the animation uses. Features the terminal doesn't have are
+/// empty, so that printing them does nothing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Terminal {
+    /// The richest colours the terminal can show.
+    pub colors: ColorMode,
+    pub charset: Charset,
+    /// Moves the cursor to the top-left corner.
+    pub home: String,
+    /// Clears the screen, leaving the cursor in the top-left corner.
+    pub clear: String,
+    pub hide_cursor: String,
+    pub show_cursor: String,
+    /// Switches to the alternate screen, leaving what was on screen to come back to.
+    pub enter_alternate_screen: String,
+    pub exit_alternate_screen: String,
+    /// Remembers the window title, so that it can be put back with `restore_title`.
+    pub save_title: String,
+    pub restore_title: String,
+    /// Reports every mouse movement as input, for terminals that say they report the mouse the
+    /// way xterm does, and stops again.
+    pub track_mouse: String,
+    pub untrack_mouse: String,
+    /// Moves the cursor to a row and column, as a parameterized string.
+    cursor_address: Option<String>,
+    /// What goes before and after a new window title.
+    title: Option<(String, String)>,
+}
+
+/// Which characters the terminal can show.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Charset {
+    /// Only ASCII.
+    Ascii,
+    /// Anything in Unicode, encoded as UTF-8.
+    #[default]
+    Unicode,
+}
+
+impl Charset {
+    /// The charset of the locale, which is the first of `$LC_ALL`, `$LC_CTYPE`, and `$LANG`
+    /// that's set, as `var` reads them. Locales that don't say they're UTF-8, like `C`, are taken
+    /// to be ASCII.
+    pub fn from_locale(var: impl Fn(&str) -> Option<String>) -> Charset {
+        let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+            .into_iter()
+            .find_map(|name| var(name).filter(|value| !value.is_empty()))
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        if locale.contains("utf-8") || locale.contains("utf8") {
+            Charset::Unicode
+        } else {
+            Charset::Ascii
+        }
+    }
+}
+
+/// Where the capabilities are in a compiled terminfo entry.
+const HAS_STATUS_LINE: usize = 9;
+const MAX_COLORS: usize = 13;
+const CLEAR_SCREEN: usize = 5;
+const CURSOR_ADDRESS: usize = 10;
+const CURSOR_HOME: usize = 12;
+const CURSOR_INVISIBLE: usize = 13;
+const CURSOR_NORMAL: usize = 16;
+const ENTER_CA_MODE: usize = 28;
+const EXIT_CA_MODE: usize = 40;
+const FROM_STATUS_LINE: usize = 47;
+const TO_STATUS_LINE: usize = 135;
+const KEY_MOUSE: usize = 355;
+
+/// The xterm codes for the window title, which most terminals that switch screens the way xterm
+/// does also understand, even though their entries don't say so.
+const XTERM_TITLE: (&str, &str) = ("\x1b]0;", "\x07");
+const XTERM_SAVE_TITLE: &str = "\x1b[22;0t";
+const XTERM_RESTORE_TITLE: &str = "\x1b[23;0t";
+const XTERM_ENTER_CA_MODE: &str = "\x1b[?1049h";
+const XTERM_TRACK_MOUSE: (&str, &str) = ("\x1b[?1003h", "\x1b[?1003l");
+
+/// Magic numbers at the start of compiled entries, with 16-bit and 32-bit numbers.
+const MAGIC: u16 = 0o432;
+const MAGIC_32_BIT: u16 = 0o1036;
+
+impl Terminal {
+    /// The terminal named by `$TERM`, with truecolor if `$COLORTERM` says so, and the charset of
+    /// the locale.
+    pub fn detect() -> Terminal {
+        let truecolor = env::var("COLORTERM").is_ok_and(|c| c == "truecolor" || c == "24bit");
+        let mut terminal = Terminal::named(env::var("TERM").ok().as_deref());
+        if truecolor && terminal.colors != ColorMode::None {
+            terminal.colors = ColorMode::Truecolor;
+        }
+        terminal.charset = Charset::from_locale(|name| env::var(name).ok());
+        terminal
+    }
+
+    /// The terminal with the terminfo entry `name`. Terminals without one, or without a name,
+    /// are only trusted with the cursor codes that nearly all terminals understand: no colours,
+    /// and no window title, since there's no knowing what they'd make of them.
+    pub fn named(name: Option<&str>) -> Terminal {
+        name.filter(|name| !name.is_empty())
+            .and_then(|name| {
+                let bytes = entry_paths(name).find_map(|path| fs::read(path).ok())?;
+                Terminal::parse(&bytes).ok()
+            })
+            .unwrap_or_else(|| Terminal {
+                colors: ColorMode::None,
+                save_title: String::new(),
+                restore_title: String::new(),
+                track_mouse: String::new(),
+                untrack_mouse: String::new(),
+                title: None,
+                ..Terminal::ansi()
+            })
+    }
+
+    /// An xterm-compatible terminal with 256 colours.
+    pub fn ansi() -> Terminal {
+        Terminal {
+            colors: ColorMode::Ansi256,
+            charset: Charset::Unicode,
+            home: "\x1b[H".to_owned(),
+            clear: "\x1b[H\x1b[2J".to_owned(),
+            hide_cursor: "\x1b[?25l".to_owned(),
+            show_cursor: "\x1b[?25h".to_owned(),
+            enter_alternate_screen: "\x1b[?1049h".to_owned(),
+            exit_alternate_screen: "\x1b[?1049l".to_owned(),
+            save_title: XTERM_SAVE_TITLE.to_owned(),
+            restore_title: XTERM_RESTORE_TITLE.to_owned(),
+            track_mouse: XTERM_TRACK_MOUSE.0.to_owned(),
+            untrack_mouse: XTERM_TRACK_MOUSE.1.to_owned(),
+            cursor_address: Some("\x1b[%i%p1%d;%p2%dH".to_owned()),
+            title: Some((XTERM_TITLE.0.to_owned(), XTERM_TITLE.1.to_owned())),
+        }
+    }
+
+    /// Reads a compiled terminfo entry, as written by `tic`.
+    pub fn parse(bytes: &[u8]) -> Result<Terminal, String> {
+        let header: Vec<usize> = (0..6)
+            .map(|i| i16_at(bytes, 2 * i).map(|n| n.max(0) as usize))
+            .collect::<Option<_>>()
+            .ok_or("terminfo entry is too short")?;
+        let number_size = match header[0] as u16 {
+            MAGIC => 2,
+            MAGIC_32_BIT => 4,
+            _ => return Err("not a terminfo entry".to_owned()),
+        };
+        let [names, booleans, numbers, strings, table] =
+            [header[1], header[2], header[3], header[4], header[5]];
+
+        // Numbers start on an even byte.
+        let numbers_at = (12 + names + booleans).next_multiple_of(2);
+        let strings_at = numbers_at + numbers * number_size;
+        let table_at = strings_at + strings * 2;
+        let table = bytes
+            .get(table_at..table_at + table)
+            .ok_or("terminfo entry is truncated")?;
+
+        let boolean = |index: usize| index < booleans && bytes.get(12 + names + index) == Some(&1);
+        let number = |index: usize| -> Option<i32> {
+            if index >= numbers {
+                return None;
+            }
+            let at = numbers_at + index * number_size;
+            let n = match number_size {
+                2 => i32::from(i16_at(bytes, at)?),
+                _ => i32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?),
+            };
+            (n >= 0).then_some(n)
+        };
+        let string = |index: usize| -> Option<String> {
+            if index >= strings {
+                return None;
+            }
+            // Missing and cancelled capabilities have negative offsets.
+            let offset = usize::try_from(i16_at(bytes, strings_at + index * 2)?).ok()?;
+            let rest = table.get(offset..)?;
+            let end = rest.iter().position(|&b| b == 0)?;
+            Some(without_padding(&String::from_utf8_lossy(&rest[..end])))
+        };
+
+        let colors = match number(MAX_COLORS) {
+            Some(256..) => ColorMode::Ansi256,
+            Some(8..) => ColorMode::Ansi16,
+            _ => ColorMode::None,
+        };
+        let enter_alternate_screen = string(ENTER_CA_MODE).unwrap_or_default();
+        let xterm_like = enter_alternate_screen.starts_with(XTERM_ENTER_CA_MODE);
+        // Terminals with a status line in a title bar say how to write to it.
+        let title = match (string(TO_STATUS_LINE), string(FROM_STATUS_LINE)) {
+            (Some(start), Some(end)) if boolean(HAS_STATUS_LINE) => Some((start, end)),
+            _ if xterm_like => Some((XTERM_TITLE.0.to_owned(), XTERM_TITLE.1.to_owned())),
+            _ => None,
+        };
+        let (save_title, restore_title) = match xterm_like {
+            true => (XTERM_SAVE_TITLE.to_owned(), XTERM_RESTORE_TITLE.to_owned()),
+            false => (String::new(), String::new()),
+        };
+        // What a mouse event starts with, in xterm's original or its SGR encoding.
+        let (track_mouse, untrack_mouse) = match string(KEY_MOUSE).as_deref() {
+            Some("\x1b[M" | "\x1b[<") => XTERM_TRACK_MOUSE,
+            _ => ("", ""),
+        };
+        Ok(Terminal {
+            colors,
+            charset: Charset::Unicode,
+            home: string(CURSOR_HOME).unwrap_or_default(),
+            clear: string(CLEAR_SCREEN).unwrap_or_default(),
+            hide_cursor: string(CURSOR_INVISIBLE).unwrap_or_default(),
+            show_cursor: string(CURSOR_NORMAL).unwrap_or_default(),
+            enter_alternate_screen,
+            exit_alternate_screen: string(EXIT_CA_MODE).unwrap_or_default(),
+            save_title,
+            restore_title,
+            track_mouse: track_mouse.to_owned(),
+            untrack_mouse: untrack_mouse.to_owned(),
+            cursor_address: string(CURSOR_ADDRESS),
+            title,
+        })
+    }
+
+    /// Sets the window title, or `None` if the terminal doesn't have one. Control characters are
+    /// left out, so that they can't end the title early.
+    pub fn set_title(&self, title: &str) -> Option<String> {
+        let (start, end) = self.title.as_ref()?;
+        let title: String = title.chars().filter(|c| !c.is_control()).collect();
+        Some(format!("{start}{title}{end}"))
+    }
+
+    /// Moves the cursor to `row` and `column`, counting from 0, or `None` if the terminal can't.
+    pub fn move_to(&self, row: usize, column: usize) -> Option<String> {
+        expand(
+            self.cursor_address.as_deref()?,
+            &[row as i64, column as i64],
+        )
+    }
+}
+
+/// Puts `text` on the clipboard with OSC 52, which even copies to the clipboard of the computer
+/// at the other end of an SSH connection. There's no terminfo capability for it that terminals
+/// agree on, but those that can't do it ignore it.
+pub fn copy_to_clipboard(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", base64(text.as_bytes()))
+}
+
+/// Encodes `bytes` with the standard base64 alphabet, padded.
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::new();
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &byte)| n | u32::from(byte) << (16 - 8 * i));
+        for i in 0..4 {
+            encoded.push(match i <= chunk.len() {
+                true => char::from(ALPHABET[(n >> (18 - 6 * i) & 63) as usize]),
+                false => '=',
+            });
+        }
+    }
+    encoded
+}
+
+/// The files a terminal's entry might be in, in the order ncurses looks.
+fn entry_paths(name: &str) -> impl Iterator<Item = PathBuf> + '_ {
+    let mut directories: Vec<PathBuf> = Vec::new();
+    directories.extend(env::var_os("TERMINFO").map(PathBuf::from));
+    directories.extend(env::var_os("HOME").map(|home| PathBuf::from(home).join(".terminfo")));
+    if let Some(list) = env::var_os("TERMINFO_DIRS") {
+        directories.extend(env::split_paths(&list));
+    }
+    for directory in ["/etc/terminfo", "/lib/terminfo", "/usr/share/terminfo"] {
+        directories.push(PathBuf::from(directory));
+    }
+    let first = name.chars().next().unwrap_or('_');
+    directories.into_iter().flat_map(move |directory| {
+        // Entries are filed under their first letter, or its hex code on some systems.
+        [
+            directory.join(first.to_string()).join(name),
+            directory.join(format!("{:x}", u32::from(first))).join(name),
+        ]
+    })
+}
+
+fn i16_at(bytes: &[u8], at: usize) -> Option<i16> {
+    Some(i16::from_le_bytes([*bytes.get(at)?, *bytes.get(at + 1)?]))
+}
+
+/// Removes delays like `$<5>`, which only matter to real terminals on slow lines.
+fn without_padding(capability: &str) -> String {
+    let mut result = String::new();
+    let mut rest = capability;
+    while let Some(start) = rest.find("$<") {
+        result.push_str(&rest[..start]);
+        match rest[start..].find('>') {
+            Some(end) => rest = &rest[start + end + 1..],
+            None => {
+                rest = &rest[start..];
+                break;
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Fills in the parameters of a capability like `\E[%i%p1%d;%p2%dH`. Handles the arithmetic
+/// that positioning the cursor needs, but not conditionals, which give `None`.
+fn expand(capability: &str, parameters: &[i64]) -> Option<String> {
+    let mut parameters = parameters.to_vec();
+    let mut stack: Vec<i64> = Vec::new();
+    let mut result = String::new();
+    let mut chars = capability.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            result.push(c);
+            continue;
+        }
+        match chars.next()? {
+            '%' => result.push('%'),
+            'i' => parameters.iter_mut().take(2).for_each(|p| *p += 1),
+            'p' => {
+                let index = chars.next()?.to_digit(10)? as usize;
+                stack.push(parameters.get(index.checked_sub(1)?).copied().unwrap_or(0));
+            }
+            'd' => result.push_str(&stack.pop()?.to_string()),
+            'c' => result.push(char::from_u32(u32::try_from(stack.pop()?).ok()?)?),
+            '{' => {
+                let digits: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                stack.push(digits.parse().ok()?);
+            }
+            '\'' => {
+                stack.push(i64::from(u32::from(chars.next()?)));
+                chars.next()?;
+            }
+            operator @ ('+' | '-' | '*' | '/' | 'm') => {
+                let (b, a) = (stack.pop()?, stack.pop()?);
+                stack.push(match operator {
+                    '+' => a + b,
+                    '-' => a - b,
+                    '*' => a * b,
+                    '/' => a.checked_div(b)?,
+                    _ => a.checked_rem(b)?,
+                });
+            }
+            _ => return None,
+        }
+    }
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A compiled entry with the given booleans set, `colors`, and the given strings, laid out
+    /// the way `tic` does.
+    fn entry(flags: &[usize], colors: i16, strings: &[(usize, &str)]) -> Vec<u8> {
+        let names = b"test|a terminal\0";
+        let booleans = flags.iter().map(|&index| index + 1).max().unwrap_or(0);
+        let count = strings
+            .iter()
+            .map(|&(index, _)| index + 1)
+            .max()
+            .unwrap_or(0);
+        let mut offsets = vec![-1i16; count];
+        let mut table = Vec::new();
+        for &(index, string) in strings {
+            offsets[index] = table.len() as i16;
+            table.extend(string.bytes());
+            table.push(0);
+        }
+        let mut bytes = Vec::new();
+        for n in [
+            MAGIC as i16,
+            names.len() as i16,
+            booleans as i16,
+            MAX_COLORS as i16 + 1,
+            count as i16,
+            table.len() as i16,
+        ] {
+            bytes.extend(n.to_le_bytes());
+        }
+        bytes.extend(names);
+        bytes.extend((0..booleans).map(|index| u8::from(flags.contains(&index))));
+        if bytes.len() % 2 == 1 {
+            bytes.push(0);
+        }
+        for index in 0..=MAX_COLORS {
+            let n: i16 = if index == MAX_COLORS { colors } else { -1 };
+            bytes.extend(n.to_le_bytes());
+        }
+        for offset in offsets {
+            bytes.extend(offset.to_le_bytes());
+        }
+        bytes.extend(table);
+        bytes
+    }
+
+    #[test]
+    fn reads_capabilities_from_compiled_entries() {
+        let bytes = entry(
+            &[],
+            256,
+            &[
+                (CURSOR_HOME, "\x1b[H"),
+                (CLEAR_SCREEN, "\x1b[H\x1b[2J$<50>"),
+                (CURSOR_INVISIBLE, "\x1b[?25l$<2>"),
+                (CURSOR_ADDRESS, "\x1b[%i%p1%d;%p2%dH"),
+            ],
+        );
+        let terminal = Terminal::parse(&bytes).unwrap();
+        assert_eq!(ColorMode::Ansi256, terminal.colors);
+        assert_eq!("\x1b[H", terminal.home);
+        assert_eq!("\x1b[H\x1b[2J", terminal.clear);
+        assert_eq!("\x1b[?25l", terminal.hide_cursor);
+        assert_eq!("", terminal.enter_alternate_screen);
+        assert_eq!(Some("\x1b[3;1H".to_owned()), terminal.move_to(2, 0));
+
+        let dumb = Terminal::parse(&entry(&[], -1, &[])).unwrap();
+        assert_eq!(ColorMode::None, dumb.colors);
+        assert_eq!(None, dumb.move_to(0, 0));
+        assert!(Terminal::parse(b"not terminfo").is_err());
+    }
+
+    #[test]
+    fn eight_colour_terminals_get_sixteen() {
+        // Like xterm, linux, and screen, which have the bright colours too.
+        for colors in [8, 16] {
+            let terminal = Terminal::parse(&entry(&[], colors, &[])).unwrap();
+            assert_eq!(ColorMode::Ansi16, terminal.colors, "colors#{colors}");
+        }
+        let mono = Terminal::parse(&entry(&[], 2, &[])).unwrap();
+        assert_eq!(ColorMode::None, mono.colors);
+    }
+
+    #[test]
+    fn terminals_without_entries_get_no_colour_or_title() {
+        for name in [None, Some(""), Some("no-such-terminal-for-ascii-love")] {
+            let terminal = Terminal::named(name);
+            assert_eq!(ColorMode::None, terminal.colors, "{name:?}");
+            assert_eq!(None, terminal.set_title("hi"));
+            assert_eq!(("", ""), (&*terminal.save_title, &*terminal.restore_title));
+            assert_eq!("", terminal.track_mouse);
+            assert_eq!(Terminal::ansi().home, terminal.home);
+        }
+    }
+
+    #[test]
+    fn sets_titles_where_the_terminal_has_them() {
+        let status_line = entry(
+            &[HAS_STATUS_LINE],
+            256,
+            &[(TO_STATUS_LINE, "\x1b]2;"), (FROM_STATUS_LINE, "\x07")],
+        );
+        assert_eq!(
+            Some("\x1b]2;ascii-love ♥\x07".to_owned()),
+            Terminal::parse(&status_line)
+                .unwrap()
+                .set_title("ascii-love\x1b ♥")
+        );
+
+        let xterm = Terminal::parse(&entry(&[], 256, &[(ENTER_CA_MODE, "\x1b[?1049h")])).unwrap();
+        assert_eq!(Some("\x1b]0;hi\x07".to_owned()), xterm.set_title("hi"));
+        assert_eq!("\x1b[22;0t", xterm.save_title);
+
+        let dumb = Terminal::parse(&entry(&[], -1, &[])).unwrap();
+        assert_eq!(None, dumb.set_title("hi"));
+        assert_eq!("", dumb.restore_title);
+    }
+
+    #[test]
+    fn tracks_the_mouse_where_the_terminal_reports_it() {
+        for key_mouse in ["\x1b[M", "\x1b[<"] {
+            let xterm = Terminal::parse(&entry(&[], 256, &[(KEY_MOUSE, key_mouse)])).unwrap();
+            assert_eq!(
+                ("\x1b[?1003h", "\x1b[?1003l"),
+                (&*xterm.track_mouse, &*xterm.untrack_mouse)
+            );
+        }
+        let vt100 = Terminal::parse(&entry(&[], -1, &[(ENTER_CA_MODE, "\x1b7\x1b[?47h")])).unwrap();
+        assert_eq!(("", ""), (&*vt100.track_mouse, &*vt100.untrack_mouse));
+    }
+
+    #[test]
+    fn copies_to_the_clipboard() {
+        assert_eq!("", base64(b""));
+        assert_eq!("Zg==", base64(b"f"));
+        assert_eq!("Zm8=", base64(b"fo"));
+        assert_eq!("Zm9vYmFy", base64(b"foobar"));
+        assert_eq!("4pml", base64("♥".as_bytes()));
+        assert_eq!("\x1b]52;c;aGk=\x07", copy_to_clipboard("hi"));
+    }
+
+    #[test]
+    fn expands_parameters() {
+        assert_eq!(
+            Some("\x1b[5;10H".to_owned()),
+            Terminal::ansi().move_to(4, 9)
+        );
+        // The VT52 way: row and column as characters offset by a space.
+        assert_eq!(
+            Some("\x1bY# ".to_owned()),
+            expand("\x1bY%p1%' '%+%c%p2%{32}%+%c", &[3, 0])
+        );
+        assert_eq!(None, expand("%?%p1%t;%;", &[1]));
+    }
+
+    #[test]
+    fn reads_the_charset_from_the_locale() {
+        let locale = |vars: &'static [(&str, &str)]| {
+            Charset::from_locale(move |name| {
+                let value = vars.iter().find(|(var, _)| *var == name)?.1;
+                Some(value.to_owned())
+            })
+        };
+        assert_eq!(Charset::Unicode, locale(&[("LANG", "en_CA.UTF-8")]));
+        assert_eq!(Charset::Unicode, locale(&[("LC_CTYPE", "C.utf8")]));
+        assert_eq!(
+            Charset::Ascii,
+            locale(&[("LC_ALL", "C"), ("LANG", "en_CA.UTF-8")])
+        );
+        assert_eq!(
+            Charset::Unicode,
+            locale(&[("LC_ALL", ""), ("LANG", "en_CA.UTF-8")])
+        );
+        assert_eq!(Charset::Ascii, locale(&[]));
+    }
+}
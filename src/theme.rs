@@ -0,0 +1,599 @@
+//! Named looks that go together: the characters surfaces are shaded with, the colours they're
+//! drawn in, and the colour behind them.
+//!
+//! Besides the built-in themes, a theme can be read from a TOML file, so that it can be shared:
+//!
+//! ```toml
+//! ramp = ".:-=+*#%@"
+//! gradient = ["#400010", "#ff6040", "#ffe0a0"]
+//! background = "#100008"
+//! ```
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::color::{self, Colors};
+use crate::config::{ConfigError, Section};
+use crate::math::Real;
+use crate::render;
+use crate::shading::LUMINANCE;
+use crate::toml;
+
+/// A character ramp with colours to match.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Theme {
+    pub name: String,
+    /// Characters from darkest to brightest, used instead of [`LUMINANCE`].
+    pub ramp: Vec<char>,
+    /// Colours spread evenly along the ramp, from darkest to brightest, blended in between.
+    /// Empty for the terminal's own colour.
+    pub gradient: Vec<[u8; 3]>,
+    /// Behind every cell, or `None` for the terminal's own background.
+    pub background: Option<[u8; 3]>,
+    /// The colours of the furthest and nearest surfaces for [`Coloring::Depth`], or `None` for
+    /// cool and warm.
+    pub depth: Option<[[u8; 3]; 2]>,
+}
+
+/// What decides the colour of each cell.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Coloring {
+    /// How bright it is, along the theme's gradient.
+    #[default]
+    Luminance,
+    /// How near it is: warm up close and cool further away.
+    Depth,
+    /// How near it is, darkened where it's dim.
+    Both,
+}
+
+impl Coloring {
+    pub const ALL: [Coloring; 3] = [Coloring::Luminance, Coloring::Depth, Coloring::Both];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Coloring::Luminance => "luminance",
+            Coloring::Depth => "depth",
+            Coloring::Both => "both",
+        }
+    }
+}
+
+impl fmt::Display for Coloring {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Coloring {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Coloring, String> {
+        Coloring::ALL
+            .into_iter()
+            .find(|coloring| coloring.name() == name)
+            .ok_or_else(|| format!("unknown coloring {name:?}"))
+    }
+}
+
+/// A kind of colour blindness, for picking colours that people with it can tell apart.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Colorblind {
+    /// Green looks like red.
+    Deuteranopia,
+    /// Red looks like green, and darker.
+    Protanopia,
+    /// Blue looks like green, and yellow like pink.
+    Tritanopia,
+}
+
+impl Colorblind {
+    pub const ALL: [Colorblind; 3] = [
+        Colorblind::Deuteranopia,
+        Colorblind::Protanopia,
+        Colorblind::Tritanopia,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Colorblind::Deuteranopia => "deuteranopia",
+            Colorblind::Protanopia => "protanopia",
+            Colorblind::Tritanopia => "tritanopia",
+        }
+    }
+
+    /// A gradient from dark to bright along hues that don't get mixed up, so that it's how bright
+    /// a colour is that says how bright the surface is: from blue to yellow without red or green
+    /// to tell apart, or from red to cyan without blue and yellow.
+    pub fn gradient(self) -> &'static [[u8; 3]] {
+        match self {
+            Colorblind::Deuteranopia => &[
+                [0, 32, 77],
+                [65, 77, 107],
+                [124, 123, 120],
+                [188, 175, 111],
+                [255, 234, 70],
+            ],
+            Colorblind::Protanopia => &[
+                [10, 25, 90],
+                [40, 85, 170],
+                [130, 150, 200],
+                [235, 225, 150],
+            ],
+            Colorblind::Tritanopia => {
+                &[[70, 0, 20], [180, 50, 60], [240, 140, 150], [210, 245, 250]]
+            }
+        }
+    }
+}
+
+impl fmt::Display for Colorblind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Colorblind {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Colorblind, String> {
+        Colorblind::ALL
+            .into_iter()
+            .find(|kind| kind.name() == name)
+            .ok_or_else(|| format!("unknown colour blindness {name:?}"))
+    }
+}
+
+/// The colours of the nearest and furthest surfaces, for [`Coloring::Depth`].
+const NEAR: [u8; 3] = [255, 140, 50];
+const FAR: [u8; 3] = [50, 110, 255];
+
+/// How dark [`Coloring::Both`] makes the darkest character on the ramp.
+const DIMMEST: Real = 0.3;
+
+/// What [`Theme::rainbow`] turns the hue of, for themes without colours of their own.
+const RAINBOW: [[u8; 3]; 3] = [[90, 0, 20], [255, 30, 60], [255, 200, 210]];
+
+/// A built-in theme, before it's turned into a [`Theme`].
+struct BuiltIn {
+    name: &'static str,
+    ramp: &'static str,
+    gradient: &'static [[u8; 3]],
+    background: Option<[u8; 3]>,
+}
+
+/// Every built-in theme, found by name with [`Theme::from_str`].
+const BUILT_IN: [BuiltIn; 6] = [
+    BuiltIn {
+        name: "valentine",
+        ramp: ".,-~:;=!*#$@",
+        gradient: &[[90, 0, 30], [220, 20, 60], [255, 105, 180], [255, 220, 235]],
+        background: None,
+    },
+    BuiltIn {
+        name: "matrix",
+        ramp: ".:-=+*xX#%@",
+        gradient: &[[0, 60, 0], [0, 200, 60], [170, 255, 170]],
+        background: Some([0, 0, 0]),
+    },
+    BuiltIn {
+        name: "fire",
+        ramp: ".,:;^*%#&@",
+        gradient: &[[80, 0, 0], [230, 50, 0], [255, 170, 0], [255, 250, 200]],
+        background: Some([15, 0, 0]),
+    },
+    BuiltIn {
+        name: "ocean",
+        ramp: "._-~=oO0@",
+        gradient: &[[0, 30, 80], [0, 110, 190], [120, 220, 255]],
+        background: Some([0, 10, 30]),
+    },
+    BuiltIn {
+        name: "mono",
+        ramp: ".,-~:;=!*#$@",
+        gradient: &[[90, 90, 90], [255, 255, 255]],
+        background: None,
+    },
+    BuiltIn {
+        name: "halloween",
+        ramp: ".:-=+*#%@",
+        gradient: &[[70, 0, 90], [210, 70, 0], [255, 150, 20], [255, 230, 150]],
+        background: Some([10, 0, 15]),
+    },
+];
+
+impl Theme {
+    /// Reads a theme file, named after the file unless it says otherwise.
+    pub fn load(path: &Path) -> Result<Theme, ConfigError> {
+        let text =
+            fs::read_to_string(path).map_err(|error| ConfigError::Io(path.to_owned(), error))?;
+        let name = path.file_stem().unwrap_or_default().to_string_lossy();
+        Theme::parse(&name, &text)
+    }
+
+    /// Parses a theme file. The ramp defaults to [`LUMINANCE`], and the colours to the
+    /// terminal's own.
+    pub fn parse(name: &str, text: &str) -> Result<Theme, ConfigError> {
+        let table = toml::parse(text).map_err(|error| ConfigError::Syntax {
+            line: error.line,
+            message: error.message,
+        })?;
+        let root = Section::root(&table);
+        root.allow_keys(&["name", "ramp", "gradient", "background"])?;
+        Ok(Theme {
+            name: root.str("name")?.unwrap_or(name).to_owned(),
+            ramp: root.ramp("ramp")?.unwrap_or_else(|| LUMINANCE.to_vec()),
+            gradient: root.colors("gradient")?.unwrap_or_default(),
+            background: root.color("background")?,
+            depth: None,
+        })
+    }
+
+    /// This theme's ramp and background, in colours that people with `kind` of colour blindness
+    /// can tell apart, for surfaces' brightness and for how near they are alike.
+    pub fn colorblind(&self, kind: Colorblind) -> Theme {
+        let gradient = kind.gradient();
+        Theme {
+            gradient: gradient.to_vec(),
+            depth: Some([gradient[0], gradient[gradient.len() - 1]]),
+            ..self.clone()
+        }
+    }
+
+    /// This theme with the hue of all its colours turned `degrees` around the colour wheel.
+    pub fn turned(&self, degrees: f64) -> Theme {
+        let turn = |rgb| color::rotate_hue(rgb, degrees);
+        Theme {
+            gradient: self.gradient.iter().copied().map(turn).collect(),
+            background: self.background.map(turn),
+            depth: Some(self.depth.unwrap_or([FAR, NEAR]).map(turn)),
+            ..self.clone()
+        }
+    }
+
+    /// The names of the built-in themes.
+    pub fn built_in() -> impl Iterator<Item = &'static str> {
+        BUILT_IN.iter().map(|theme| theme.name)
+    }
+
+    /// The colour for each cell of `frame`: from the gradient for characters on the ramp, and the
+    /// terminal's own for anything else, like text drawn over the top.
+    pub fn colors(&self, frame: &[Vec<char>]) -> Colors {
+        frame
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|c| {
+                        let level = self.ramp.iter().position(|r| r == c)?;
+                        self.color(level as f64 / (self.ramp.len().max(2) - 1) as f64)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Like [`Theme::colors`], but with the hue turned `degrees` around the colour wheel, and
+    /// further still down the frame, so that stripes of colour roll down it as `degrees` grows.
+    pub fn rainbow(&self, frame: &[Vec<char>], degrees: f64) -> Colors {
+        let mut theme = self.clone();
+        if theme.gradient.is_empty() {
+            theme.gradient = RAINBOW.to_vec();
+        }
+        let rows = frame.len().max(1) as f64;
+        let mut colors = theme.colors(frame);
+        for (y, row) in colors.iter_mut().enumerate() {
+            let turn = degrees + 360.0 * y as f64 / rows;
+            for cell in row.iter_mut().flatten() {
+                *cell = color::rotate_hue(*cell, turn);
+            }
+        }
+        colors
+    }
+
+    /// The colour for each cell of `frame` by how near it is, with `depth` as from
+    /// [`render::render_with_depth`]. With [`Coloring::Both`], cells are darker the further down
+    /// the ramp their character is. [`Coloring::Luminance`] is the same as [`Theme::colors`].
+    pub fn depth_colors(
+        &self,
+        frame: &[Vec<char>],
+        depth: &[Vec<Real>],
+        coloring: Coloring,
+    ) -> Colors {
+        if coloring == Coloring::Luminance {
+            return self.colors(frame);
+        }
+        let [far, near] = self.depth.unwrap_or([FAR, NEAR]);
+        // From 1 for the nearest cell to −1 for the furthest.
+        let mut nearness: Vec<Vec<Real>> = depth.iter().map(|row| vec![0.0; row.len()]).collect();
+        render::depth_luminance(&mut nearness, depth);
+        let top = (self.ramp.len().max(2) - 1) as Real;
+        frame
+            .iter()
+            .zip(depth.iter().zip(&nearness))
+            .map(|(row, (depth_row, nearness_row))| {
+                row.iter()
+                    .zip(depth_row.iter().zip(nearness_row))
+                    .map(|(c, (&depth, &nearness))| {
+                        if depth == Real::NEG_INFINITY {
+                            return None;
+                        }
+                        let t = (nearness + 1.0) / 2.0;
+                        let brightness = match (coloring, self.ramp.iter().position(|r| r == c)) {
+                            (Coloring::Both, Some(level)) => {
+                                DIMMEST + (1.0 - DIMMEST) * level as Real / top
+                            }
+                            _ => 1.0,
+                        };
+                        Some([0, 1, 2].map(|i| {
+                            let blend = Real::from(far[i]) * (1.0 - t) + Real::from(near[i]) * t;
+                            (blend * brightness).round() as u8
+                        }))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// The gradient's colour `t` of the way along, from 0 to 1.
+    fn color(&self, t: f64) -> Option<[u8; 3]> {
+        let last = self.gradient.len().checked_sub(1)?;
+        let position = t.clamp(0.0, 1.0) * last as f64;
+        let i = (position as usize).min(last.saturating_sub(1));
+        let (from, to) = (self.gradient[i], self.gradient[(i + 1).min(last)]);
+        let f = position - i as f64;
+        Some([0, 1, 2].map(|c| {
+            (f64::from(from[c]) + (f64::from(to[c]) - f64::from(from[c])) * f).round() as u8
+        }))
+    }
+}
+
+impl Default for Theme {
+    /// The classic ramp, in the terminal's own colours.
+    fn default() -> Theme {
+        Theme {
+            name: "default".to_owned(),
+            ramp: LUMINANCE.to_vec(),
+            gradient: Vec::new(),
+            background: None,
+            depth: None,
+        }
+    }
+}
+
+impl fmt::Display for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+impl FromStr for Theme {
+    type Err = String;
+
+    /// One of the built-in themes.
+    fn from_str(name: &str) -> Result<Theme, String> {
+        BUILT_IN
+            .iter()
+            .find(|theme| theme.name == name)
+            .map(|theme| Theme {
+                name: theme.name.to_owned(),
+                ramp: theme.ramp.chars().collect(),
+                gradient: theme.gradient.to_vec(),
+                background: theme.background,
+                depth: None,
+            })
+            .ok_or_else(|| format!("unknown theme {name:?}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn built_in_themes_can_be_found_by_name() {
+        for name in Theme::built_in() {
+            let theme: Theme = name.parse().unwrap();
+            assert_eq!(name, theme.to_string());
+            assert!(theme.ramp.len() >= 2);
+        }
+        assert!("plaid".parse::<Theme>().is_err());
+    }
+
+    #[test]
+    fn colours_follow_the_ramp() {
+        let theme = Theme {
+            name: "test".to_owned(),
+            ramp: vec!['.', ':', '#'],
+            gradient: vec![[0, 0, 0], [200, 100, 0]],
+            background: None,
+            depth: None,
+        };
+        let colors = theme.colors(&[vec!['.', ':', '#', 'A', ' ']]);
+        assert_eq!(
+            vec![vec![
+                Some([0, 0, 0]),
+                Some([100, 50, 0]),
+                Some([200, 100, 0]),
+                None,
+                None
+            ]],
+            colors
+        );
+        assert_eq!(vec![vec![None]], Theme::default().colors(&[vec!['@']]));
+    }
+
+    #[test]
+    fn rainbows_turn_down_the_frame() {
+        let frame = vec![vec!['@'], vec!['@'], vec![' ']];
+        let red = Theme {
+            gradient: vec![[255, 0, 0]],
+            ..Theme::default()
+        };
+        assert_eq!(
+            vec![vec![Some([0, 255, 0])], vec![Some([0, 0, 255])], vec![None]],
+            red.rainbow(&frame, 120.0)
+        );
+        // Themes without colours still get a rainbow.
+        assert!(Theme::default().rainbow(&frame, 0.0)[0][0].is_some());
+    }
+
+    #[test]
+    fn turning_the_hue_turns_every_colour() {
+        let red = Theme {
+            gradient: vec![[255, 0, 0]],
+            background: Some([40, 0, 0]),
+            ..Theme::default()
+        };
+        let turned = red.turned(120.0);
+        assert_eq!(vec![[0, 255, 0]], turned.gradient);
+        assert_eq!(Some([0, 40, 0]), turned.background);
+        assert_eq!(
+            Some([FAR, NEAR].map(|rgb| color::rotate_hue(rgb, 120.0))),
+            turned.depth
+        );
+        assert_eq!(red.gradient, red.turned(360.0).gradient);
+    }
+
+    #[test]
+    fn near_cells_are_warm_and_far_ones_cool() {
+        let empty = Real::NEG_INFINITY;
+        let frame = vec![vec!['@', '.', ' ']];
+        let depth = vec![vec![1.0 / 50.0, 1.0 / 70.0, empty]];
+        let theme = Theme::default();
+        assert_eq!(
+            vec![vec![Some(NEAR), Some(FAR), None]],
+            theme.depth_colors(&frame, &depth, Coloring::Depth)
+        );
+        let dim = FAR.map(|c| (Real::from(c) * DIMMEST).round() as u8);
+        assert_eq!(
+            vec![vec![Some(NEAR), Some(dim), None]],
+            theme.depth_colors(&frame, &depth, Coloring::Both)
+        );
+        assert_eq!("both".parse(), Ok(Coloring::Both));
+    }
+
+    /// How `rgb` looks to someone with `kind` of colour blindness, in linear RGB, from Machado,
+    /// Oliveira and Fernandes's simulation at full severity.
+    fn simulated(rgb: [u8; 3], kind: Colorblind) -> [f64; 3] {
+        let matrix = match kind {
+            Colorblind::Deuteranopia => [
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881],
+            ],
+            Colorblind::Protanopia => [
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998],
+            ],
+            Colorblind::Tritanopia => [
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900],
+            ],
+        };
+        let linear = rgb.map(|c| {
+            let c = f64::from(c) / 255.0;
+            match c <= 0.04045 {
+                true => c / 12.92,
+                false => ((c + 0.055) / 1.055).powf(2.4),
+            }
+        });
+        matrix.map(|row| {
+            (0..3)
+                .map(|i| row[i] * linear[i])
+                .sum::<f64>()
+                .clamp(0.0, 1.0)
+        })
+    }
+
+    /// How light a colour in linear RGB looks, as CIE L*, from 0 for black to 100 for white.
+    fn lightness([r, g, b]: [f64; 3]) -> f64 {
+        let y = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+        match y > 216.0 / 24389.0 {
+            true => 116.0 * y.cbrt() - 16.0,
+            false => 24389.0 / 27.0 * y,
+        }
+    }
+
+    /// Whether each character along the classic ramp in `theme` looks clearly lighter than the
+    /// one before to someone with `kind` of colour blindness.
+    fn brightens(theme: &Theme, kind: Colorblind) -> bool {
+        let ramp = LUMINANCE.to_vec();
+        let theme = Theme {
+            ramp: ramp.clone(),
+            ..theme.clone()
+        };
+        let colors = theme.colors(&[ramp]);
+        let lightness: Vec<f64> = (colors[0].iter())
+            .map(|color| lightness(simulated(color.unwrap(), kind)))
+            .collect();
+        lightness.windows(2).all(|pair| pair[1] - pair[0] >= 5.0)
+    }
+
+    #[test]
+    fn colorblind_gradients_brighten_whatever_the_colour_blindness() {
+        for kind in Colorblind::ALL {
+            let theme = Theme::default().colorblind(kind);
+            for seen_by in Colorblind::ALL {
+                assert!(brightens(&theme, seen_by), "{kind} seen with {seen_by}");
+            }
+            assert_eq!(Ok(kind), kind.name().parse());
+        }
+        // Red to green, as bright as each other, is no good to someone who can't tell them apart.
+        let traffic = Theme {
+            gradient: vec![[200, 40, 40], [40, 130, 40]],
+            ..Theme::default()
+        };
+        assert!(!brightens(&traffic, Colorblind::Deuteranopia));
+        assert!("colourful".parse::<Colorblind>().is_err());
+    }
+
+    #[test]
+    fn colorblind_depth_goes_from_dark_to_bright() {
+        let frame = vec![vec!['@', '.']];
+        let depth = vec![vec![1.0 / 50.0, 1.0 / 70.0]];
+        let fire: Theme = "fire".parse().unwrap();
+        let theme = fire.colorblind(Colorblind::Tritanopia);
+        assert_eq!(
+            (fire.ramp, fire.background),
+            (theme.ramp.clone(), theme.background)
+        );
+        assert_eq!(
+            vec![vec![Some([210, 245, 250]), Some([70, 0, 20])]],
+            theme.depth_colors(&frame, &depth, Coloring::Depth)
+        );
+    }
+
+    #[test]
+    fn parses_theme_files() {
+        let text = r##"
+            ramp = ".:#"
+            gradient = ["#000000", "#c86400"]
+            background = "#100008"
+        "##;
+        let theme = Theme::parse("sunset", text).unwrap();
+        assert_eq!("sunset", theme.name);
+        assert_eq!(vec!['.', ':', '#'], theme.ramp);
+        assert_eq!(vec![[0, 0, 0], [200, 100, 0]], theme.gradient);
+        assert_eq!(Some([16, 0, 8]), theme.background);
+
+        let named = Theme::parse("file", "name = \"dusk\"").unwrap();
+        assert_eq!("dusk", named.name);
+        assert_eq!(LUMINANCE.to_vec(), named.ramp);
+
+        for text in [
+            "ramp = \"@\"",
+            "gradient = [\"red\"]",
+            "background = \"#12345\"",
+            "colour = \"#123456\"",
+        ] {
+            assert!(
+                matches!(Theme::parse("bad", text), Err(ConfigError::Invalid { .. })),
+                "{text}"
+            );
+        }
+    }
+}
@@ -0,0 +1,215 @@
+//! Animates named properties over keyframes, so a scene can be choreographed rather than spinning
+//! uniformly forever.
+
+use crate::easing::Easing;
+use crate::math::{Real, Vec3};
+
+/// Values that can be interpolated between keyframes.
+pub trait Lerp: Copy {
+    /// Blends from `self` (at `t = 0`) to `other` (at `t = 1`).
+    fn lerp(self, other: Self, t: f64) -> Self;
+}
+
+impl Lerp for f64 {
+    fn lerp(self, other: f64, t: f64) -> f64 {
+        self + (other - self) * t
+    }
+}
+
+#[cfg(feature = "f32")]
+impl Lerp for f32 {
+    fn lerp(self, other: f32, t: f64) -> f32 {
+        self + (other - self) * t as f32
+    }
+}
+
+impl Lerp for Vec3 {
+    fn lerp(self, other: Vec3, t: f64) -> Vec3 {
+        self + (other - self) * t as Real
+    }
+}
+
+/// A value at a point in time.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Key<T> {
+    /// Seconds since the start of the animation.
+    pub time: f64,
+    pub value: T,
+    /// How to move from the previous key to this one.
+    pub easing: Easing,
+}
+
+/// The keys for one property, sorted by time.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Track<T> {
+    keys: Vec<Key<T>>,
+}
+
+impl<T: Lerp> Track<T> {
+    /// # Panics
+    ///
+    /// Panics if there are no keys.
+    pub fn new(mut keys: Vec<Key<T>>) -> Track<T> {
+        assert!(!keys.is_empty(), "a track needs at least one key");
+        keys.sort_by(|a, b| a.time.total_cmp(&b.time));
+        Track { keys }
+    }
+
+    pub fn keys(&self) -> &[Key<T>] {
+        &self.keys
+    }
+
+    pub fn start_time(&self) -> f64 {
+        self.keys[0].time
+    }
+
+    pub fn end_time(&self) -> f64 {
+        self.keys[self.keys.len() - 1].time
+    }
+
+    /// The value at `time`. Before the first key and after the last, the nearest key is held.
+    pub fn at(&self, time: f64) -> T {
+        let next = self.keys.partition_point(|key| key.time <= time);
+        match next {
+            0 => self.keys[0].value,
+            n if n == self.keys.len() => self.keys[n - 1].value,
+            n => {
+                let (before, after) = (&self.keys[n - 1], &self.keys[n]);
+                let t = (time - before.time) / (after.time - before.time);
+                before.value.lerp(after.value, after.easing.apply(t))
+            }
+        }
+    }
+}
+
+/// Maps `time` into `start..end`, for animations that start over when they finish.
+pub(crate) fn wrap_time(time: f64, start: f64, end: f64) -> f64 {
+    let duration = end - start;
+    if duration > 0.0 {
+        start + (time - start).rem_euclid(duration)
+    } else {
+        time
+    }
+}
+
+/// The values of every animated property at one moment.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Properties {
+    /// Multiplies the rotation speed.
+    pub speed: f64,
+    /// Magnifies the projection; 2.0 draws everything twice as large.
+    pub zoom: f64,
+    /// Replaces the direction of every directional light, if the timeline animates it.
+    pub light: Option<Vec3>,
+    /// Degrees to turn the hue of the theme's colours around the colour wheel.
+    pub hue: f64,
+}
+
+impl Default for Properties {
+    fn default() -> Properties {
+        Properties {
+            speed: 1.0,
+            zoom: 1.0,
+            light: None,
+            hue: 0.0,
+        }
+    }
+}
+
+/// Tracks for each animatable property. Properties without a track keep their default value.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Timeline {
+    pub speed: Option<Track<f64>>,
+    pub zoom: Option<Track<f64>>,
+    pub light: Option<Track<Vec3>>,
+    pub hue: Option<Track<f64>>,
+    /// Whether to start over after the last key of the longest track.
+    pub looping: bool,
+}
+
+impl Timeline {
+    /// When the last key of any track happens.
+    pub fn duration(&self) -> f64 {
+        [
+            self.speed.as_ref().map(Track::end_time),
+            self.zoom.as_ref().map(Track::end_time),
+            self.light.as_ref().map(Track::end_time),
+            self.hue.as_ref().map(Track::end_time),
+        ]
+        .into_iter()
+        .flatten()
+        .fold(0.0, f64::max)
+    }
+
+    pub fn at(&self, time: f64) -> Properties {
+        let time = if self.looping {
+            wrap_time(time, 0.0, self.duration())
+        } else {
+            time
+        };
+        let defaults = Properties::default();
+        Properties {
+            speed: self.speed.as_ref().map_or(defaults.speed, |t| t.at(time)),
+            zoom: self.zoom.as_ref().map_or(defaults.zoom, |t| t.at(time)),
+            light: self.light.as_ref().map(|t| t.at(time)),
+            hue: self.hue.as_ref().map_or(defaults.hue, |t| t.at(time)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key<T>(time: f64, value: T) -> Key<T> {
+        Key {
+            time,
+            value,
+            easing: Easing::Linear,
+        }
+    }
+
+    #[test]
+    fn track_interpolates_and_holds_ends() {
+        let track = Track::new(vec![key(4.0, 10.0), key(2.0, 0.0)]);
+        assert_eq!(0.0, track.at(0.0));
+        assert_eq!(5.0, track.at(3.0));
+        assert_eq!(10.0, track.at(100.0));
+    }
+
+    #[test]
+    fn track_applies_easing_of_destination_key() {
+        let track = Track::new(vec![
+            key(0.0, 0.0),
+            Key {
+                easing: Easing::EaseInQuad,
+                ..key(1.0, 1.0)
+            },
+        ]);
+        assert_eq!(0.25, track.at(0.5));
+    }
+
+    #[test]
+    fn timeline_loops_over_longest_track() {
+        let timeline = Timeline {
+            speed: Some(Track::new(vec![key(0.0, 0.0), key(2.0, 2.0)])),
+            zoom: Some(Track::new(vec![key(0.0, 1.0), key(4.0, 3.0)])),
+            looping: true,
+            ..Timeline::default()
+        };
+        assert_eq!(4.0, timeline.duration());
+        assert_eq!(timeline.at(1.0), timeline.at(5.0));
+        assert_eq!(Properties::default().light, timeline.at(1.0).light);
+    }
+
+    #[test]
+    fn hue_turns_from_none() {
+        let timeline = Timeline {
+            hue: Some(Track::new(vec![key(0.0, 0.0), key(10.0, 360.0)])),
+            ..Timeline::default()
+        };
+        assert_eq!(10.0, timeline.duration());
+        assert_eq!(90.0, timeline.at(2.5).hue);
+        assert_eq!(0.0, Timeline::default().at(2.5).hue);
+    }
+}
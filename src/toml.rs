@@ -0,0 +1,450 @@
+//! A parser for the subset of TOML used by configuration and theme files.
+//!
+//! Supports tables, arrays of tables, dotted keys, basic and literal strings, integers, floats,
+//! booleans, (multi-line) arrays, and inline tables. Dates and multi-line strings are not
+//! supported.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+pub(crate) type Table = BTreeMap<String, Value>;
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Value {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    Array(Vec<Value>),
+    Table(Table),
+}
+
+impl Value {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::String(_) => "a string",
+            Value::Integer(_) => "an integer",
+            Value::Float(_) => "a float",
+            Value::Boolean(_) => "a boolean",
+            Value::Array(_) => "an array",
+            Value::Table(_) => "a table",
+        }
+    }
+
+    /// Integers are accepted wherever a float is expected.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Value::Integer(n) => Some(n as f64),
+            Value::Float(n) => Some(n),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+pub(crate) fn parse(text: &str) -> Result<Table, ParseError> {
+    Parser {
+        chars: text.chars().collect(),
+        position: 0,
+        line: 1,
+    }
+    .document()
+}
+
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+    line: usize,
+}
+
+type Result<T, E = ParseError> = std::result::Result<T, E>;
+
+impl Parser {
+    fn document(&mut self) -> Result<Table> {
+        let mut root = Table::new();
+        // Path of the table that `key = value` lines are inserted into.
+        let mut current: Vec<String> = Vec::new();
+
+        loop {
+            self.skip_whitespace_and_comments(true);
+            match self.peek() {
+                None => return Ok(root),
+                Some('[') => {
+                    self.bump();
+                    let is_array = self.eat('[');
+                    self.skip_whitespace();
+                    let path = self.key_path()?;
+                    self.skip_whitespace();
+                    self.expect(']')?;
+                    if is_array {
+                        self.expect(']')?;
+                        self.push_array_table(&mut root, &path)?;
+                    } else {
+                        self.table_at(&mut root, &path)?;
+                    }
+                    current = path;
+                }
+                Some(_) => {
+                    let path = self.key_path()?;
+                    self.skip_whitespace();
+                    self.expect('=')?;
+                    self.skip_whitespace();
+                    let value = self.value()?;
+                    let table = self.table_at(&mut root, &current)?;
+                    self.insert(table, &path, value)?;
+                }
+            }
+            self.end_of_line()?;
+        }
+    }
+
+    /// Finds the table at `path`, creating empty tables as needed. For arrays of tables, the
+    /// most recently added table is used.
+    fn table_at<'t>(&self, mut table: &'t mut Table, path: &[String]) -> Result<&'t mut Table> {
+        for key in path {
+            let entry = table
+                .entry(key.clone())
+                .or_insert_with(|| Value::Table(Table::new()));
+            table = match entry {
+                Value::Table(table) => table,
+                Value::Array(array) => match array.last_mut() {
+                    Some(Value::Table(table)) => table,
+                    _ => return Err(self.error(format!("{key} is not a table"))),
+                },
+                _ => return Err(self.error(format!("{key} is not a table"))),
+            };
+        }
+        Ok(table)
+    }
+
+    fn push_array_table(&self, root: &mut Table, path: &[String]) -> Result<()> {
+        let (last, parents) = path.split_last().expect("key paths are never empty");
+        let parent = self.table_at(root, parents)?;
+        let entry = parent
+            .entry(last.clone())
+            .or_insert_with(|| Value::Array(Vec::new()));
+        match entry {
+            Value::Array(array) => {
+                array.push(Value::Table(Table::new()));
+                Ok(())
+            }
+            _ => Err(self.error(format!("{last} is not an array of tables"))),
+        }
+    }
+
+    fn insert(&self, table: &mut Table, path: &[String], value: Value) -> Result<()> {
+        let (last, parents) = path.split_last().expect("key paths are never empty");
+        let table = self.table_at(table, parents)?;
+        if table.contains_key(last) {
+            return Err(self.error(format!("duplicate key {last}")));
+        }
+        table.insert(last.clone(), value);
+        Ok(())
+    }
+
+    fn key_path(&mut self) -> Result<Vec<String>> {
+        let mut path = vec![self.key()?];
+        loop {
+            self.skip_whitespace();
+            if !self.eat('.') {
+                return Ok(path);
+            }
+            self.skip_whitespace();
+            path.push(self.key()?);
+        }
+    }
+
+    fn key(&mut self) -> Result<String> {
+        match self.peek() {
+            Some('"') => self.basic_string(),
+            Some('\'') => self.literal_string(),
+            _ => {
+                let key = self.take_while(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+                if key.is_empty() {
+                    Err(self.error("expected a key".to_owned()))
+                } else {
+                    Ok(key)
+                }
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Value> {
+        match self.peek() {
+            Some('"') => self.basic_string().map(Value::String),
+            Some('\'') => self.literal_string().map(Value::String),
+            Some('[') => self.array(),
+            Some('{') => self.inline_table(),
+            Some(_) => {
+                let word = self.take_while(|c| {
+                    c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.' | '_')
+                });
+                self.scalar(&word)
+            }
+            None => Err(self.error("expected a value".to_owned())),
+        }
+    }
+
+    fn scalar(&self, word: &str) -> Result<Value> {
+        match word {
+            "true" => return Ok(Value::Boolean(true)),
+            "false" => return Ok(Value::Boolean(false)),
+            "inf" | "+inf" => return Ok(Value::Float(f64::INFINITY)),
+            "-inf" => return Ok(Value::Float(f64::NEG_INFINITY)),
+            "nan" | "+nan" | "-nan" => return Ok(Value::Float(f64::NAN)),
+            _ => {}
+        }
+
+        let digits = word.replace('_', "");
+        let looks_numeric = digits
+            .trim_start_matches(['+', '-'])
+            .starts_with(|c: char| c.is_ascii_digit());
+        if looks_numeric {
+            if let Ok(n) = digits.parse::<i64>() {
+                return Ok(Value::Integer(n));
+            }
+            if let Ok(n) = digits.parse::<f64>() {
+                return Ok(Value::Float(n));
+            }
+        }
+        Err(self.error(format!("invalid value {word:?}")))
+    }
+
+    fn array(&mut self) -> Result<Value> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+        loop {
+            self.skip_whitespace_and_comments(true);
+            if self.eat(']') {
+                return Ok(Value::Array(items));
+            }
+            items.push(self.value()?);
+            self.skip_whitespace_and_comments(true);
+            if !self.eat(',') {
+                self.skip_whitespace_and_comments(true);
+                self.expect(']')?;
+                return Ok(Value::Array(items));
+            }
+        }
+    }
+
+    fn inline_table(&mut self) -> Result<Value> {
+        self.expect('{')?;
+        let mut table = Table::new();
+        self.skip_whitespace();
+        if self.eat('}') {
+            return Ok(Value::Table(table));
+        }
+        loop {
+            self.skip_whitespace();
+            let path = self.key_path()?;
+            self.skip_whitespace();
+            self.expect('=')?;
+            self.skip_whitespace();
+            let value = self.value()?;
+            self.insert(&mut table, &path, value)?;
+            self.skip_whitespace();
+            if !self.eat(',') {
+                self.expect('}')?;
+                return Ok(Value::Table(table));
+            }
+        }
+    }
+
+    fn basic_string(&mut self) -> Result<String> {
+        self.expect('"')?;
+        let mut string = String::new();
+        loop {
+            match self.bump() {
+                Some('"') => return Ok(string),
+                Some('\\') => {
+                    let escaped = match self.bump() {
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('r') => '\r',
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('u') => self.unicode_escape(4)?,
+                        Some('U') => self.unicode_escape(8)?,
+                        _ => return Err(self.error("invalid escape sequence".to_owned())),
+                    };
+                    string.push(escaped);
+                }
+                Some('\n') | None => return Err(self.error("unterminated string".to_owned())),
+                Some(c) => string.push(c),
+            }
+        }
+    }
+
+    fn unicode_escape(&mut self, length: usize) -> Result<char> {
+        let hex: String = (0..length).filter_map(|_| self.bump()).collect();
+        u32::from_str_radix(&hex, 16)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or_else(|| self.error(format!("invalid unicode escape \\u{hex}")))
+    }
+
+    fn literal_string(&mut self) -> Result<String> {
+        self.expect('\'')?;
+        let string = self.take_while(|c| c != '\'' && c != '\n');
+        self.expect('\'')?;
+        Ok(string)
+    }
+
+    fn end_of_line(&mut self) -> Result<()> {
+        self.skip_whitespace_and_comments(false);
+        match self.peek() {
+            None | Some('\n') => Ok(()),
+            Some(c) => Err(self.error(format!("unexpected {c:?}"))),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.take_while(|c| c == ' ' || c == '\t' || c == '\r');
+    }
+
+    fn skip_whitespace_and_comments(&mut self, newlines: bool) {
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some('#') => {
+                    self.take_while(|c| c != '\n');
+                }
+                Some('\n') if newlines => {
+                    self.bump();
+                }
+                _ => return,
+            }
+        }
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> String {
+        let mut taken = String::new();
+        while let Some(c) = self.peek().filter(|&c| predicate(c)) {
+            taken.push(c);
+            self.bump();
+        }
+        taken
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += 1;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.bump();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<()> {
+        if self.eat(expected) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected {expected:?}")))
+        }
+    }
+
+    fn error(&self, message: String) -> ParseError {
+        ParseError {
+            line: self.line,
+            message,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scalars_and_arrays() {
+        let table = parse(
+            r#"
+            # A comment
+            name = "heart" # trailing comment
+            speed = 0.05
+            frames = 1_000
+            loop = true
+            axis = [1, 1.5, -2]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(Value::String("heart".into()), table["name"]);
+        assert_eq!(Value::Float(0.05), table["speed"]);
+        assert_eq!(Value::Integer(1000), table["frames"]);
+        assert_eq!(Value::Boolean(true), table["loop"]);
+        assert_eq!(
+            Value::Array(vec![
+                Value::Integer(1),
+                Value::Float(1.5),
+                Value::Integer(-2)
+            ]),
+            table["axis"]
+        );
+    }
+
+    #[test]
+    fn tables_and_arrays_of_tables() {
+        let table = parse(
+            r#"
+            [camera]
+            loop = false
+
+            [[camera.keyframe]]
+            time = 0
+
+            [[camera.keyframe]]
+            time = 10
+            target = { x = 1, y = 2 }
+            "#,
+        )
+        .unwrap();
+
+        let Value::Table(camera) = &table["camera"] else {
+            panic!("camera should be a table");
+        };
+        let Value::Array(keyframes) = &camera["keyframe"] else {
+            panic!("keyframe should be an array");
+        };
+        assert_eq!(2, keyframes.len());
+        let Value::Table(second) = &keyframes[1] else {
+            panic!("keyframes should be tables");
+        };
+        assert_eq!(Value::Integer(10), second["time"]);
+        assert!(matches!(second["target"], Value::Table(_)));
+    }
+
+    #[test]
+    fn errors_report_line_numbers() {
+        let error = parse("a = 1\nb = = 2\n").unwrap_err();
+        assert_eq!(2, error.line);
+
+        let error = parse("a = 1\na = 2\n").unwrap_err();
+        assert_eq!("duplicate key a", error.message);
+    }
+}
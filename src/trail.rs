@@ -0,0 +1,80 @@
+//! Fading trails left behind by whatever moves, from frames that decay instead of being cleared.
+
+/// How bright each cell of the last few frames still is, to draw the next one over.
+#[derive(Clone, Debug)]
+pub struct Trail {
+    /// How much of its brightness a cell keeps from one frame to the next, from 0 to 1.
+    pub decay: f64,
+    /// For each cell, how far up the ramp it still is, counting empty cells as 0 and the ramp's
+    /// first character as 1.
+    levels: Vec<Vec<f64>>,
+}
+
+impl Trail {
+    pub fn new(decay: f64) -> Trail {
+        Trail {
+            decay,
+            levels: Vec::new(),
+        }
+    }
+
+    /// Draws what's left of earlier frames into the empty cells of `frame`, as characters further
+    /// down `ramp`, and remembers how bright every cell is for next time. Characters that aren't
+    /// on the ramp count as its brightest. Starts over whenever the frame changes size.
+    pub fn apply(&mut self, frame: &mut [Vec<char>], ramp: &[char]) {
+        let resized = self.levels.len() != frame.len()
+            || (self.levels.iter().zip(frame.iter()))
+                .any(|(levels, row)| levels.len() != row.len());
+        if resized {
+            self.levels = frame.iter().map(|row| vec![0.0; row.len()]).collect();
+        }
+        for (levels, row) in self.levels.iter_mut().zip(frame.iter_mut()) {
+            for (level, c) in levels.iter_mut().zip(row.iter_mut()) {
+                let faded = *level * self.decay;
+                if *c != ' ' {
+                    let drawn = ramp.iter().position(|r| r == c).unwrap_or(ramp.len()) + 1;
+                    *level = faded.max(drawn as f64);
+                    continue;
+                }
+                *level = faded;
+                // Only whole steps up the ramp show; anything less has faded away.
+                if let Some(&faded) = (faded as usize).checked_sub(1).and_then(|i| ramp.get(i)) {
+                    *c = faded;
+                } else if faded >= ramp.len() as f64 {
+                    *c = ramp[ramp.len() - 1];
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trails_fade_down_the_ramp() {
+        let ramp = ['.', ':', '#'];
+        let mut trail = Trail::new(0.5);
+        let mut frame = vec![vec!['#', ' ']];
+        trail.apply(&mut frame, &ramp);
+        assert_eq!(vec![vec!['#', ' ']], frame);
+
+        // '#' is 3 steps up, which halves to 1.5, the first step, and then to nothing.
+        let mut frame = vec![vec![' ', ':']];
+        trail.apply(&mut frame, &ramp);
+        assert_eq!(vec![vec!['.', ':']], frame);
+        let mut frame = vec![vec![' ', ' ']];
+        trail.apply(&mut frame, &ramp);
+        assert_eq!(vec![vec![' ', '.']], frame);
+    }
+
+    #[test]
+    fn starts_over_when_resized() {
+        let mut trail = Trail::new(0.9);
+        trail.apply(&mut [vec!['@']], &['.', '@']);
+        let mut frame = vec![vec![' ', ' ']];
+        trail.apply(&mut frame, &['.', '@']);
+        assert_eq!(vec![vec![' ', ' ']], frame);
+    }
+}
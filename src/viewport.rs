@@ -0,0 +1,300 @@
+//! The part of the screen that the animation is drawn into.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Space to leave empty on each side, in cells.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Margin {
+    pub top: usize,
+    pub right: usize,
+    pub bottom: usize,
+    pub left: usize,
+}
+
+impl Margin {
+    /// The same space on every side.
+    pub fn uniform(size: usize) -> Margin {
+        Margin {
+            top: size,
+            right: size,
+            bottom: size,
+            left: size,
+        }
+    }
+}
+
+/// Where a viewport smaller than the space for it goes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Align {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    #[default]
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Align {
+    pub const ALL: [Align; 9] = [
+        Align::TopLeft,
+        Align::Top,
+        Align::TopRight,
+        Align::Left,
+        Align::Center,
+        Align::Right,
+        Align::BottomLeft,
+        Align::Bottom,
+        Align::BottomRight,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Align::TopLeft => "top-left",
+            Align::Top => "top",
+            Align::TopRight => "top-right",
+            Align::Left => "left",
+            Align::Center => "center",
+            Align::Right => "right",
+            Align::BottomLeft => "bottom-left",
+            Align::Bottom => "bottom",
+            Align::BottomRight => "bottom-right",
+        }
+    }
+
+    /// How far across and down the leftover space the viewport goes: 0 for the start, 2 for the
+    /// end, and 1 for the middle.
+    fn halves(self) -> (usize, usize) {
+        let i = Align::ALL
+            .iter()
+            .position(|&align| align == self)
+            .unwrap_or(4);
+        (i % 3, i / 3)
+    }
+}
+
+impl fmt::Display for Align {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Align {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Align, String> {
+        Align::ALL
+            .into_iter()
+            .find(|align| align.name() == name)
+            .ok_or_else(|| format!("unknown alignment {name:?}"))
+    }
+}
+
+/// A rectangle of the screen, in cells.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Viewport {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Viewport {
+    /// The whole of a screen this size.
+    pub fn full(width: usize, height: usize) -> Viewport {
+        Viewport {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        }
+    }
+
+    /// The viewport shrunk by `margin`, down to nothing if the margin doesn't fit.
+    pub fn inset(&self, margin: Margin) -> Viewport {
+        let width = self.width.saturating_sub(margin.left + margin.right);
+        let height = self.height.saturating_sub(margin.top + margin.bottom);
+        Viewport {
+            x: self.x + margin.left.min(self.width),
+            y: self.y + margin.top.min(self.height),
+            width,
+            height,
+        }
+    }
+
+    /// A `width` by `height` part of the viewport, placed according to `align`. Sizes bigger
+    /// than the viewport are cut down to fit.
+    pub fn aligned(&self, width: usize, height: usize, align: Align) -> Viewport {
+        let (width, height) = (width.min(self.width), height.min(self.height));
+        let (across, down) = align.halves();
+        Viewport {
+            x: self.x + (self.width - width) * across / 2,
+            y: self.y + (self.height - height) * down / 2,
+            width,
+            height,
+        }
+    }
+
+    /// Like [`Viewport::aligned`], but placed `across` and `down` the leftover space, each from 0
+    /// to 1, rather than at one of its edges or in the middle of it.
+    pub fn placed(&self, width: usize, height: usize, across: f64, down: f64) -> Viewport {
+        let (width, height) = (width.min(self.width), height.min(self.height));
+        let offset =
+            |space: usize, fraction: f64| (space as f64 * fraction.clamp(0.0, 1.0)).round();
+        Viewport {
+            x: self.x + offset(self.width - width, across) as usize,
+            y: self.y + offset(self.height - height, down) as usize,
+            width,
+            height,
+        }
+    }
+
+    /// The biggest part of the viewport, in the middle of it, that has the proportions of
+    /// `width` by `height`.
+    pub fn fitted(&self, width: usize, height: usize) -> Viewport {
+        let (width, height) = (width.max(1), height.max(1));
+        // Whichever side runs out of room first sets the scale.
+        let (width, height) = if self.width * height <= self.height * width {
+            (self.width, height * self.width / width)
+        } else {
+            (width * self.height / height, self.height)
+        };
+        self.aligned(width, height, Align::Center)
+    }
+
+    /// The viewport cut into `count` columns side by side, as evenly as they'll go.
+    pub fn columns(&self, count: usize) -> Vec<Viewport> {
+        let edge = |i: usize| self.width * i / count.max(1);
+        (0..count)
+            .map(|i| Viewport {
+                x: self.x + edge(i),
+                width: edge(i + 1) - edge(i),
+                ..*self
+            })
+            .collect()
+    }
+
+    /// Copies `frame`, which should be the size of the viewport, onto the screen.
+    pub fn blit<T: Copy>(&self, screen: &mut [Vec<T>], frame: &[Vec<T>]) {
+        for (row, line) in screen.iter_mut().skip(self.y).zip(frame) {
+            for (cell, &c) in row.iter_mut().skip(self.x).zip(line) {
+                *cell = c;
+            }
+        }
+    }
+}
+
+/// `frame` stretched or squashed to `width` by `height`, taking each cell from the nearest one.
+pub fn resample<T: Copy>(frame: &[Vec<T>], width: usize, height: usize) -> Vec<Vec<T>> {
+    let (rows, columns) = (frame.len(), frame.first().map_or(0, Vec::len));
+    if rows == 0 || columns == 0 {
+        return Vec::new();
+    }
+    // From the middle of each new cell.
+    let nearest = |i: usize, to: usize, from: usize| ((2 * i + 1) * from / (2 * to)).min(from - 1);
+    (0..height)
+        .map(|y| {
+            let row = &frame[nearest(y, height, rows)];
+            (0..width)
+                .map(|x| row[nearest(x, width, columns).min(row.len() - 1)])
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inset_frame_lands_inside_margin() {
+        let viewport = Viewport::full(6, 4).inset(Margin {
+            top: 1,
+            right: 2,
+            bottom: 1,
+            left: 2,
+        });
+        assert_eq!(
+            Viewport {
+                x: 2,
+                y: 1,
+                width: 2,
+                height: 2
+            },
+            viewport
+        );
+
+        let mut screen = vec![vec!['.'; 6]; 4];
+        viewport.blit(&mut screen, &[vec!['a', 'b'], vec!['c', 'd']]);
+        let screen: Vec<String> = screen.iter().map(|row| row.iter().collect()).collect();
+        assert_eq!(vec!["......", "..ab..", "..cd..", "......"], screen);
+    }
+
+    #[test]
+    fn aligned_viewports_go_in_the_leftover_space() {
+        let area = Viewport::full(10, 5).inset(Margin::uniform(1));
+        assert_eq!(
+            Viewport {
+                x: 1,
+                y: 1,
+                width: 4,
+                height: 3
+            },
+            area.aligned(4, 20, Align::TopLeft)
+        );
+        assert_eq!(3, area.aligned(4, 3, Align::Center).x);
+        assert_eq!(5, area.aligned(4, 3, Align::BottomRight).x);
+        assert_eq!(Ok(Align::BottomRight), "bottom-right".parse());
+    }
+
+    #[test]
+    fn columns_share_out_the_width() {
+        let columns = Viewport::full(10, 3).inset(Margin::uniform(1)).columns(3);
+        let spans: Vec<_> = columns.iter().map(|view| (view.x, view.width)).collect();
+        assert_eq!(vec![(1, 2), (3, 3), (6, 3)], spans);
+        assert!(columns.iter().all(|view| (view.y, view.height) == (1, 1)));
+    }
+
+    #[test]
+    fn placed_viewports_go_anywhere_in_the_leftover_space() {
+        let area = Viewport::full(10, 5).inset(Margin::uniform(1));
+        assert_eq!(
+            area.aligned(4, 3, Align::TopLeft),
+            area.placed(4, 3, 0.0, 0.0)
+        );
+        assert_eq!(
+            area.aligned(4, 3, Align::BottomRight),
+            area.placed(4, 3, 1.0, 1.0)
+        );
+        assert_eq!(3, area.placed(4, 3, 0.5, 0.0).x);
+        assert_eq!(5, area.placed(4, 3, 7.0, 0.0).x);
+    }
+
+    #[test]
+    fn frames_can_be_resampled_to_fit() {
+        let fitted = Viewport::full(40, 10).fitted(80, 40);
+        assert_eq!(
+            (10, 0, 20, 10),
+            (fitted.x, fitted.y, fitted.width, fitted.height)
+        );
+        let fitted = Viewport::full(40, 10).fitted(80, 10);
+        assert_eq!(
+            (0, 2, 40, 5),
+            (fitted.x, fitted.y, fitted.width, fitted.height)
+        );
+        assert_eq!(Viewport::full(8, 6), Viewport::full(8, 6).fitted(4, 3));
+
+        let frame = vec![vec!['a', 'b'], vec!['c', 'd']];
+        let rows = |frame: Vec<Vec<char>>| -> Vec<String> {
+            frame.iter().map(|row| row.iter().collect()).collect()
+        };
+        assert_eq!(vec!["aabb", "ccdd", "ccdd"], rows(resample(&frame, 4, 3)));
+        assert_eq!(vec!["d"], rows(resample(&frame, 1, 1)));
+        assert_eq!(frame, resample(&frame, 2, 2));
+        assert!(resample::<char>(&[], 4, 3).is_empty());
+    }
+}
@@ -0,0 +1,129 @@
+//! How many columns characters take up on the terminal.
+//!
+//! Frames are grids of one character per column. A wide character, like most emoji, fills its
+//! own cell and the [`CONTINUATION`] after it, which isn't printed, so every row still lines up
+//! with the columns it's drawn in. Text is laid out that way with [`columns`], and anything drawn
+//! over half of a wide character is tidied up with [`repair`] before the row is printed.
+
+/// Stands in for the column a wide character spills into.
+pub const CONTINUATION: char = '\0';
+
+/// Ranges of characters most terminals draw two columns wide: East Asian wide and fullwidth
+/// characters, and emoji.
+const WIDE: [(char, char); 12] = [
+    ('\u{1100}', '\u{115f}'),
+    ('\u{2e80}', '\u{303e}'),
+    ('\u{3041}', '\u{33ff}'),
+    ('\u{3400}', '\u{4dbf}'),
+    ('\u{4e00}', '\u{9fff}'),
+    ('\u{a000}', '\u{a4cf}'),
+    ('\u{ac00}', '\u{d7a3}'),
+    ('\u{f900}', '\u{faff}'),
+    ('\u{fe30}', '\u{fe4f}'),
+    ('\u{ff00}', '\u{ff60}'),
+    ('\u{1f300}', '\u{1faff}'),
+    ('\u{20000}', '\u{3fffd}'),
+];
+
+/// Ranges of characters that don't take up a column of their own, like combining accents and
+/// variation selectors.
+const ZERO_WIDTH: [(char, char); 5] = [
+    ('\u{0300}', '\u{036f}'),
+    ('\u{200b}', '\u{200f}'),
+    ('\u{20d0}', '\u{20ff}'),
+    ('\u{fe00}', '\u{fe0f}'),
+    ('\u{fe20}', '\u{fe2f}'),
+];
+
+/// How many columns `c` takes up: 0, 1, or 2.
+pub fn width(c: char) -> usize {
+    let within =
+        |ranges: &[(char, char)]| ranges.iter().any(|&(low, high)| (low..=high).contains(&c));
+    if c.is_control() || within(&ZERO_WIDTH) {
+        0
+    } else if within(&WIDE) {
+        2
+    } else {
+        1
+    }
+}
+
+/// Lays out `text` one column per cell, with a [`CONTINUATION`] after each wide character.
+/// Characters that don't take up a column of their own, like combining accents, are left out,
+/// since each cell holds just one character.
+pub fn columns(text: &str) -> Vec<char> {
+    text.chars()
+        .flat_map(|c| match width(c) {
+            0 => [None, None],
+            1 => [Some(c), None],
+            _ => [Some(c), Some(CONTINUATION)],
+        })
+        .flatten()
+        .collect()
+}
+
+/// Spreads each character of `row` over two columns, so that wide and narrow characters line up
+/// alike: wide ones fill both, and narrow ones are followed by a space.
+pub fn widen(row: &[char]) -> Vec<char> {
+    row.iter()
+        .flat_map(|&c| [c, if width(c) == 2 { CONTINUATION } else { ' ' }])
+        .collect()
+}
+
+/// Blanks out wide characters whose second column has been drawn over, and second columns whose
+/// wide character has, so that nothing after them in the row is pushed out of line.
+pub fn repair(row: &mut [char]) {
+    for x in 0..row.len() {
+        let wide = width(row[x]) == 2;
+        let continued = row.get(x + 1) == Some(&CONTINUATION);
+        if wide && !continued {
+            row[x] = ' ';
+        }
+        let orphaned = row[x] == CONTINUATION && (x == 0 || width(row[x - 1]) != 2);
+        if orphaned {
+            row[x] = ' ';
+        }
+    }
+}
+
+/// The characters of `row` to print, leaving out [`CONTINUATION`]s.
+pub fn printable(row: &[char]) -> impl Iterator<Item = char> + '_ {
+    row.iter().copied().filter(|&c| c != CONTINUATION)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn knows_wide_characters() {
+        assert_eq!(1, width('a'));
+        assert_eq!(1, width('♥'));
+        assert_eq!(2, width('💖'));
+        assert_eq!(2, width('愛'));
+        assert_eq!(0, width('\u{301}'));
+        assert_eq!(0, width(CONTINUATION));
+    }
+
+    #[test]
+    fn lays_text_out_in_columns() {
+        assert_eq!(vec!['I', ' ', '💖', CONTINUATION], columns("I 💖"));
+        assert_eq!(vec!['e', '❤'], columns("e\u{301}❤\u{fe0f}"));
+    }
+
+    #[test]
+    fn widened_rows_keep_their_columns() {
+        let row = widen(&['💖', '♥', '.']);
+        assert_eq!(vec!['💖', CONTINUATION, '♥', ' ', '.', ' '], row);
+        assert_eq!("💖♥ . ", printable(&row).collect::<String>());
+    }
+
+    #[test]
+    fn repairs_half_overwritten_wide_characters() {
+        let mut row = vec!['💖', CONTINUATION, '💖', CONTINUATION, 'a'];
+        row[1] = 'I';
+        row[2] = ' ';
+        repair(&mut row);
+        assert_eq!(vec![' ', 'I', ' ', ' ', 'a'], row);
+    }
+}
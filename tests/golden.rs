@@ -0,0 +1,112 @@
+//! Renders fixed scenes and compares them with the frames in `tests/golden`, so that any change
+//! to shading or projection shows up as a difference in the text.
+//!
+//! After a deliberate change, rewrite the frames with `UPDATE_GOLDEN=1 cargo test` and review
+//! the diff.
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use ascii_love::dither::Dither;
+use ascii_love::math::{Quat, Vec3};
+use ascii_love::render::{Frame, Settings, Style};
+use ascii_love::scene::{Object, Scene, Shape, Transform};
+use ascii_love::shading::Shading;
+
+const WIDTH: usize = 80;
+const HEIGHT: usize = 24;
+
+/// A scene with one shape, turned a little so that its sides show.
+fn scene(shape: Shape) -> Scene {
+    let orientation = Quat::from_axis_angle(Vec3::Y, 0.6) * Quat::from_axis_angle(Vec3::X, 0.3);
+    Scene {
+        objects: vec![Object {
+            shape,
+            transform: Transform {
+                orientation,
+                ..Transform::default()
+            },
+            ..Object::default()
+        }],
+        ..Scene::default()
+    }
+}
+
+fn assert_golden(name: &str, scene: &Scene, settings: &Settings) {
+    let frame = Frame::render(scene, settings, WIDTH, HEIGHT).to_string();
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{name}.txt"));
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&path, &frame).unwrap();
+        return;
+    }
+    let golden =
+        fs::read_to_string(&path).unwrap_or_else(|error| panic!("{}: {error}", path.display()));
+    // Rounding in f32 can tip a cell at the edge of a band over to the next character.
+    let allowed = if cfg!(feature = "f32") {
+        WIDTH * HEIGHT / 100
+    } else {
+        0
+    };
+    let differences = frame
+        .chars()
+        .zip(golden.chars())
+        .filter(|(a, b)| a != b)
+        .count();
+    assert!(
+        frame.len() == golden.len() && differences <= allowed,
+        "{name} has changed.\nExpected:\n{golden}\nGot:\n{frame}\nRun with UPDATE_GOLDEN=1 to accept it."
+    );
+}
+
+#[test]
+fn heart() {
+    assert_golden("heart", &scene(Shape::Heart), &Settings::default());
+}
+
+#[test]
+fn heart_toon() {
+    let settings = Settings {
+        shading: Shading::Toon,
+        ..Settings::default()
+    };
+    assert_golden("heart_toon", &scene(Shape::Heart), &settings);
+}
+
+#[test]
+fn heart_dithered() {
+    let settings = Settings {
+        dither: Dither::Bayer,
+        ..Settings::default()
+    };
+    assert_golden("heart_dithered", &scene(Shape::Heart), &settings);
+}
+
+#[test]
+fn torus_points() {
+    let torus = Shape::Torus {
+        major_radius: 10.0,
+        minor_radius: 4.0,
+    };
+    let settings = Settings {
+        style: Style::Points,
+        ..Settings::default()
+    };
+    assert_golden("torus_points", &scene(torus), &settings);
+}
+
+#[test]
+fn sphere_raymarched() {
+    let settings = Settings {
+        style: Style::Raymarch,
+        zoom: 1.5,
+        ..Settings::default()
+    };
+    assert_golden(
+        "sphere_raymarched",
+        &scene(Shape::Sphere { radius: 8.0 }),
+        &settings,
+    );
+}
@@ -0,0 +1,56 @@
+//! Runs the program with `--checksum`, which draws frames on a screen exactly `--width` by
+//! `--height` rather than on the terminal, to check what flags make of that screen.
+
+use std::env;
+use std::fs;
+use std::process::{self, Command, Output};
+
+/// Runs the program with `args`, and waits for it to finish.
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ascii-love"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+/// The checksum of all the frames drawn with `args`.
+fn checksum(args: &[&str]) -> String {
+    let size = ["--frames", "8", "--width", "60", "--height", "20"];
+    let output = run(&[&["--checksum"][..], &size, args].concat());
+    assert!(output.status.success(), "{args:?} failed");
+    let text = String::from_utf8(output.stdout).unwrap();
+    let total = text.lines().find_map(|line| line.strip_prefix("total "));
+    total.expect("a total checksum").to_owned()
+}
+
+#[test]
+fn bouncing_leaves_room_to_move() {
+    assert_ne!(checksum(&[]), checksum(&["--bounce"]));
+}
+
+#[test]
+fn looping_leaves_room_to_move() {
+    assert_ne!(checksum(&[]), checksum(&["--lissajous", "3,2"]));
+}
+
+#[test]
+fn frame_rates_too_slow_to_pause_for_are_refused() {
+    let output = run(&["--checksum", "--fps", "1e-300"]);
+    let error = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(Some(2), output.status.code());
+    assert!(error.starts_with("ascii-love: --fps must be between 0.01 and 1000\n"));
+}
+
+#[test]
+fn models_that_cant_be_loaded_arent_usage_mistakes() {
+    let path = env::temp_dir().join(format!("ascii-love-bad-{}.obj", process::id()));
+    fs::write(&path, "v 0 0 0\nf 1 2 3\n").unwrap();
+    let output = run(&["--checksum", "--model", path.to_str().unwrap()]);
+    fs::remove_file(&path).unwrap();
+    let error = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(Some(1), output.status.code());
+    assert_eq!(
+        "ascii-love: --model: line 2: index 2 is out of range\n",
+        error
+    );
+}